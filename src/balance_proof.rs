@@ -0,0 +1,118 @@
+use anyhow::{Context, Result};
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use serde::{Deserialize, Serialize};
+use spl_token_2022::solana_zk_sdk::{
+    encryption::{
+        elgamal::{ElGamalCiphertext, ElGamalKeypair},
+        pedersen::{Pedersen, PedersenOpening},
+        pod::{elgamal::{PodElGamalCiphertext, PodElGamalPubkey}, pedersen::PodPedersenCommitment},
+    },
+    zk_elgamal_proof_program::proof_data::{CiphertextCommitmentEqualityProofData, ZkProofData},
+};
+use std::path::Path;
+use std::str::FromStr;
+use crate::utils;
+
+/// Identifies balance proofs written by `prove-balance`.
+pub const BALANCE_PROOF_FORMAT: &str = "confidential-balance-proof";
+
+/// Bumped whenever a field is removed, renamed or changes meaning.
+pub const BALANCE_PROOF_VERSION: u32 = 1;
+
+/// A disclosure of an account's available balance at `slot`: `amount` and `opening` open
+/// `commitment`, and `proof` shows `available_balance`, the on-chain ciphertext, encrypts
+/// the same amount. Checking it needs neither the owner's keys nor the chain, though the
+/// ciphertext can be compared with the account at that slot.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BalanceProof {
+    pub format: String,
+    pub version: u32,
+    pub created_at: String,
+    pub account: String,
+    pub mint: String,
+    pub owner: String,
+    pub slot: u64,
+    pub amount: u64,
+    pub elgamal_pubkey: String,
+    pub available_balance: String,
+    pub commitment: String,
+    pub opening: String,
+    /// `CiphertextCommitmentEqualityProofData`, base64
+    pub proof: String,
+}
+
+impl BalanceProof {
+    /// Proves that `available_balance` encrypts `amount` under `elgamal_keypair`.
+    pub fn prove(
+        elgamal_keypair: &ElGamalKeypair,
+        available_balance: &PodElGamalCiphertext,
+        amount: u64,
+    ) -> Result<(PodPedersenCommitment, PedersenOpening, CiphertextCommitmentEqualityProofData)> {
+        let ciphertext: ElGamalCiphertext = (*available_balance).try_into()?;
+        let opening = PedersenOpening::new_rand();
+        let commitment = Pedersen::with(amount, &opening);
+        let proof = CiphertextCommitmentEqualityProofData::new(elgamal_keypair, &ciphertext, &commitment, &opening, amount)
+            .map_err(|e| anyhow::anyhow!("Failed to generate the equality proof: {:?}", e))?;
+        Ok((commitment.into(), opening, proof))
+    }
+
+    /// Checks the proof and that it is about this file's key, ciphertext and commitment,
+    /// and that the revealed amount and opening open the commitment.
+    pub fn verify(&self) -> Result<()> {
+        let bytes = BASE64.decode(&self.proof).context("Proof is not base64")?;
+        let proof = bytemuck::try_pod_read_unaligned::<CiphertextCommitmentEqualityProofData>(&bytes)
+            .map_err(|_| anyhow::anyhow!("Proof is not ciphertext-commitment equality proof data"))?;
+        proof.verify_proof()
+            .map_err(|e| anyhow::anyhow!("The equality proof does not verify: {}", e))?;
+
+        let context = proof.context_data();
+        if context.pubkey != parse(&self.elgamal_pubkey, "elgamal_pubkey", PodElGamalPubkey::from_str)? {
+            anyhow::bail!("The proof is for another ElGamal key than elgamal_pubkey");
+        }
+        if context.ciphertext != parse(&self.available_balance, "available_balance", PodElGamalCiphertext::from_str)? {
+            anyhow::bail!("The proof is about another ciphertext than available_balance");
+        }
+        let commitment = parse(&self.commitment, "commitment", PodPedersenCommitment::from_str)?;
+        if context.commitment != commitment {
+            anyhow::bail!("The proof is about another commitment than commitment");
+        }
+
+        let opening = BASE64.decode(&self.opening).ok()
+            .and_then(|bytes| PedersenOpening::from_bytes(&bytes))
+            .ok_or_else(|| anyhow::anyhow!("opening is not a base64 Pedersen opening"))?;
+        if PodPedersenCommitment::from(Pedersen::with(self.amount, &opening)) != commitment {
+            anyhow::bail!("amount and opening do not open the commitment, so the amount is not the proven one");
+        }
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read balance proof {}", path.display()))?;
+        let proof: Self = serde_json::from_str(&contents)
+            .with_context(|| format!("Invalid balance proof {}", path.display()))?;
+
+        if proof.format != BALANCE_PROOF_FORMAT {
+            anyhow::bail!("{} is not a balance proof written by prove-balance", path.display());
+        }
+        if proof.version > BALANCE_PROOF_VERSION {
+            anyhow::bail!(
+                "Balance proof {} uses format version {}, this CLI understands up to {}",
+                path.display(),
+                proof.version,
+                BALANCE_PROOF_VERSION
+            );
+        }
+        Ok(proof)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let contents = serde_json::to_string_pretty(self)?;
+        utils::write_atomic(path, contents)
+            .with_context(|| format!("Failed to write balance proof {}", path.display()))
+    }
+}
+
+fn parse<T, E>(value: &str, field: &str, from_str: impl Fn(&str) -> Result<T, E>) -> Result<T> {
+    from_str(value).map_err(|_| anyhow::anyhow!("{} is not valid base64 of the right length", field))
+}
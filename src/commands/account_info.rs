@@ -0,0 +1,100 @@
+use anyhow::Result;
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use spl_token_2022::{
+    extension::{
+        confidential_transfer::ConfidentialTransferAccount,
+        BaseStateWithExtensions,
+        StateWithExtensions,
+    },
+    solana_zk_sdk::encryption::pod::elgamal::PodElGamalCiphertext,
+};
+use bytemuck::Zeroable;
+use serde_json::json;
+use crate::{config::ReadOnlyConfig, output::{self, say}, utils};
+
+/// Dumps every `ConfidentialTransferAccount` field without decrypting anything; needs no keypair.
+pub async fn execute(account: String) -> Result<()> {
+    say!("🔬 Account Info...\n");
+
+    let config = ReadOnlyConfig::new()?;
+    let account_pubkey = utils::parse_pubkey(&account)?;
+
+    let account_data = config.rpc_client.get_account(&account_pubkey).await?;
+    let token_account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data.data)?;
+    let ct_account = token_account.get_extension::<ConfidentialTransferAccount>()
+        .map_err(|_| anyhow::anyhow!("Account {} is not configured for confidential transfers", account_pubkey))?;
+
+    say!("📋 Account: {}", account_pubkey);
+    say!("  Mint: {}", token_account.base.mint);
+    say!("  Owner: {}", token_account.base.owner);
+    say!("  Public Balance: {}", token_account.base.amount);
+
+    say!("\n🔐 ConfidentialTransferAccount:");
+    say!("  approved: {}", bool::from(ct_account.approved));
+    say!("  elgamal_pubkey:");
+    say!("    base64: {}", ct_account.elgamal_pubkey);
+    say!("    hex:    {}", hex(bytemuck::bytes_of(&ct_account.elgamal_pubkey)));
+    for (name, ciphertext) in [
+        ("pending_balance_lo", &ct_account.pending_balance_lo),
+        ("pending_balance_hi", &ct_account.pending_balance_hi),
+        ("available_balance", &ct_account.available_balance),
+    ] {
+        say_ciphertext(name, ciphertext);
+    }
+    // The AES ciphertext is a 12-byte nonce followed by the encrypted amount
+    let decryptable = bytemuck::bytes_of(&ct_account.decryptable_available_balance);
+    say!("  decryptable_available_balance:");
+    say!("    base64: {}", ct_account.decryptable_available_balance);
+    say!("    nonce:  {}", hex(&decryptable[..12]));
+    say!("    body:   {}", hex(&decryptable[12..]));
+    say!("  allow_confidential_credits: {}", bool::from(ct_account.allow_confidential_credits));
+    say!("  allow_non_confidential_credits: {}", bool::from(ct_account.allow_non_confidential_credits));
+    say!("  pending_balance_credit_counter: {}", u64::from(ct_account.pending_balance_credit_counter));
+    say!("  maximum_pending_balance_credit_counter: {}", u64::from(ct_account.maximum_pending_balance_credit_counter));
+    say!("  expected_pending_balance_credit_counter: {}", u64::from(ct_account.expected_pending_balance_credit_counter));
+    say!("  actual_pending_balance_credit_counter: {}", u64::from(ct_account.actual_pending_balance_credit_counter));
+
+    let raw = BASE64.encode(bytemuck::bytes_of(ct_account));
+    say!("\n📦 Raw extension ({} bytes, base64):", bytemuck::bytes_of(ct_account).len());
+    say!("  {}", raw);
+
+    output::emit("account-info", json!({
+        "account": account_pubkey.to_string(),
+        "mint": token_account.base.mint.to_string(),
+        "owner": token_account.base.owner.to_string(),
+        "approved": bool::from(ct_account.approved),
+        "elgamal_pubkey": ct_account.elgamal_pubkey.to_string(),
+        "pending_balance_lo": ct_account.pending_balance_lo.to_string(),
+        "pending_balance_hi": ct_account.pending_balance_hi.to_string(),
+        "available_balance": ct_account.available_balance.to_string(),
+        "decryptable_available_balance": ct_account.decryptable_available_balance.to_string(),
+        "allow_confidential_credits": bool::from(ct_account.allow_confidential_credits),
+        "allow_non_confidential_credits": bool::from(ct_account.allow_non_confidential_credits),
+        "pending_balance_credit_counter": u64::from(ct_account.pending_balance_credit_counter),
+        "maximum_pending_balance_credit_counter": u64::from(ct_account.maximum_pending_balance_credit_counter),
+        "expected_pending_balance_credit_counter": u64::from(ct_account.expected_pending_balance_credit_counter),
+        "actual_pending_balance_credit_counter": u64::from(ct_account.actual_pending_balance_credit_counter),
+        "raw": raw,
+    }));
+
+    say!("\n💡 Run 'balance' with the owner's keypair to decrypt these ciphertexts");
+
+    Ok(())
+}
+
+/// A twisted ElGamal ciphertext is a Pedersen commitment followed by a decrypt handle.
+fn say_ciphertext(name: &str, ciphertext: &PodElGamalCiphertext) {
+    let bytes = bytemuck::bytes_of(ciphertext);
+    say!("  {}:", name);
+    say!("    base64:     {}", ciphertext);
+    if *ciphertext == PodElGamalCiphertext::zeroed() {
+        say!("    (all zero, the initial ciphertext of 0)");
+        return;
+    }
+    say!("    commitment: {}", hex(&bytes[..32]));
+    say!("    handle:     {}", hex(&bytes[32..]));
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
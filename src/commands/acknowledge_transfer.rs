@@ -0,0 +1,296 @@
+use anyhow::{Context, Result};
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use serde_json::{json, Value};
+use solana_client::{nonblocking::rpc_client::RpcClient, rpc_client::GetConfirmedSignaturesForAddress2Config};
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use spl_token_2022::{
+    extension::{confidential_transfer::instruction::ConfidentialTransferInstruction, StateWithExtensions},
+    instruction::{decode_instruction_type, TokenInstruction},
+    solana_zk_sdk::zk_elgamal_proof_program::proof_data::ZeroCiphertextProofData,
+};
+use std::path::PathBuf;
+use std::str::FromStr;
+use crate::{
+    commands::audit_session::{self, FetchedTransaction},
+    config::{AppConfig, ReadOnlyConfig},
+    crypto,
+    output::{self, say},
+    rpc,
+    utils,
+};
+
+const SIGNATURES_PER_PAGE: usize = 1000;
+
+/// Identifies acknowledgments written by `acknowledge-transfer`.
+pub const ACKNOWLEDGMENT_FORMAT: &str = "confidential-transfer-acknowledgment";
+
+/// Bumped whenever a field is removed, renamed or changes meaning.
+pub const ACKNOWLEDGMENT_VERSION: u32 = 1;
+
+/// Writes the recipient's signed statement that the confidential transfer in `signature`
+/// reached `account` and was applied to its available balance. With `prove_amount` it
+/// also carries the decrypted amount and a zero-ciphertext proof of it, as audit sessions do.
+pub async fn execute(account: String, signature: String, owner: Option<String>, prove_amount: bool, out: PathBuf) -> Result<()> {
+    say!("📨 Acknowledging Transfer...\n");
+
+    let config = AppConfig::new()?.with_owner(owner)?;
+    let account_pubkey = utils::parse_pubkey(&account)?;
+    let transfer_signature = Signature::from_str(&signature)
+        .with_context(|| format!("Invalid transaction signature: {}", signature))?;
+
+    say!("📋 Acknowledgment:");
+    say!("  Account: {}", account_pubkey);
+    say!("  Transfer: {}", transfer_signature);
+
+    let transaction = audit_session::fetch_transaction(&config.scan_client, &transfer_signature).await?;
+    let movement = audit_session::movements(&transaction, &account_pubkey)
+        .into_iter()
+        .find(|movement| movement.kind == "transfer-in")
+        .ok_or_else(|| anyhow::anyhow!("{} is not a confidential transfer into {}", transfer_signature, account_pubkey))?;
+
+    let (applied_signature, applied_slot) = applied_after(&config.scan_client, &account_pubkey, &transfer_signature).await?
+        .ok_or_else(|| anyhow::anyhow!("The transfer is still pending; run 'apply-balance --account {}' first", account_pubkey))?;
+    say!("  Applied in: {} (slot {})", applied_signature, applied_slot);
+
+    let mut transfer = json!({
+        "account": account_pubkey.to_string(),
+        "signature": transfer_signature.to_string(),
+        "instruction_index": movement.instruction_index,
+        "kind": movement.kind,
+    });
+    if prove_amount {
+        let (lo, hi, pubkey) = audit_session::transfer_ciphertexts(&config.scan_client, &movement).await?
+            .ok_or_else(|| anyhow::anyhow!("The transfer's amount proof could not be found or decoded"))?;
+        let elgamal_keypair = crypto::derive_elgamal_keypair(config.owner(), &account_pubkey)?;
+        let amount = crypto::decrypt_pending_balance(&elgamal_keypair, &lo, &hi)
+            .context("Failed to decrypt the transfer amount")?;
+        let difference = audit_session::combine(&lo, &hi)?.subtract_amount(amount);
+        let proof = ZeroCiphertextProofData::new(&elgamal_keypair, &difference)
+            .map_err(|e| anyhow::anyhow!("Failed to prove the amount: {:?}", e))?;
+        say!("  Amount: {} base units (proven)", amount);
+        transfer["amount"] = json!(amount);
+        transfer["elgamal_pubkey"] = json!(pubkey.to_string());
+        transfer["ciphertext_lo"] = json!(lo.to_string());
+        transfer["ciphertext_hi"] = json!(hi.to_string());
+        transfer["proof"] = json!(BASE64.encode(bytemuck::bytes_of(&proof)));
+    }
+
+    let body = json!({
+        "cli_version": env!("CARGO_PKG_VERSION"),
+        "created_at": chrono::Utc::now().to_rfc3339(),
+        "transfer": transfer,
+        "applied_signature": applied_signature.to_string(),
+        "applied_slot": applied_slot,
+    });
+    let owner_signature = config.owner().sign_message(serde_json::to_string(&body)?.as_bytes());
+    let acknowledgment = json!({
+        "format": ACKNOWLEDGMENT_FORMAT,
+        "version": ACKNOWLEDGMENT_VERSION,
+        "body": body,
+        "signer": config.owner().pubkey().to_string(),
+        "signature": owner_signature.to_string(),
+    });
+    utils::write_atomic(&out, serde_json::to_string_pretty(&acknowledgment)?)
+        .with_context(|| format!("Failed to write acknowledgment {}", out.display()))?;
+
+    say!("\n✅ Acknowledgment written to {}", out.display());
+
+    output::emit("acknowledge-transfer", json!({
+        "path": out.display().to_string(),
+        "account": account_pubkey.to_string(),
+        "transfer_signature": transfer_signature.to_string(),
+        "applied_signature": applied_signature.to_string(),
+        "signer": config.owner().pubkey().to_string(),
+        "amount_proven": prove_amount,
+    }));
+
+    say!("\n💡 Send the file to the sender; they check it with 'verify-receipt --file {}'", out.display());
+
+    Ok(())
+}
+
+/// Checks an acknowledgment's signature, that its signer owns the account, and that the
+/// transfer and the apply after it are on chain; with `amount`, that it is the proven one.
+pub async fn verify(file: PathBuf, amount: Option<String>) -> Result<()> {
+    say!("🔎 Verifying Transfer Receipt...\n");
+
+    let contents = std::fs::read_to_string(&file)
+        .with_context(|| format!("Failed to read acknowledgment {}", file.display()))?;
+    let acknowledgment: Value = serde_json::from_str(&contents)
+        .with_context(|| format!("{} is not a transfer acknowledgment", file.display()))?;
+    if acknowledgment["format"] != ACKNOWLEDGMENT_FORMAT {
+        anyhow::bail!("{} is not a transfer acknowledgment", file.display());
+    }
+    let version = acknowledgment["version"].as_u64().unwrap_or(0);
+    if version > u64::from(ACKNOWLEDGMENT_VERSION) {
+        anyhow::bail!(
+            "Acknowledgment {} uses format version {}, this CLI understands up to {}",
+            file.display(),
+            version,
+            ACKNOWLEDGMENT_VERSION
+        );
+    }
+    let body = &acknowledgment["body"];
+    let transfer = &body["transfer"];
+    let signer = utils::parse_pubkey(acknowledgment["signer"].as_str().unwrap_or_default())?;
+    let signature = Signature::from_str(acknowledgment["signature"].as_str().unwrap_or_default())
+        .context("Acknowledgment signature is not valid base58")?;
+    let account_pubkey = utils::parse_pubkey(transfer["account"].as_str().unwrap_or_default())?;
+    let transfer_signature = Signature::from_str(transfer["signature"].as_str().unwrap_or_default())
+        .context("Transfer signature is not valid base58")?;
+    let applied_signature = Signature::from_str(body["applied_signature"].as_str().unwrap_or_default())
+        .context("Apply signature is not valid base58")?;
+
+    let config = ReadOnlyConfig::new()?;
+    say!("📋 Receipt:");
+    say!("  Account: {}", account_pubkey);
+    say!("  Transfer: {}", transfer_signature);
+    say!("  Signer: {}", signer);
+
+    let mut checks = Vec::new();
+    let signed = signature.verify(signer.as_ref(), serde_json::to_string(body)?.as_bytes());
+    checks.push(("signature", if signed { Ok(()) } else { Err(anyhow::anyhow!("not the signer's signature over the body")) }));
+    checks.push(("signer owns the account", check_owner(&config, &account_pubkey, &signer).await));
+    // A proven amount is checked against the chain along with its proof
+    let transfer_check = if transfer["proof"].is_null() {
+        check_transfer(&config, &account_pubkey, transfer).await
+    } else {
+        audit_session::verify_entry(transfer, Some(&config)).await
+    };
+    checks.push(("transfer on chain", transfer_check));
+    checks.push(("applied after the transfer", check_applied(&config, &account_pubkey, &transfer_signature, &applied_signature).await));
+    if let Some(amount) = &amount {
+        checks.push(("amount", check_amount(&config, &account_pubkey, transfer, amount).await));
+    }
+
+    let mut failures = Vec::new();
+    for (check, result) in &checks {
+        match result {
+            Ok(()) => say!("  ✅ {}", check),
+            Err(e) => {
+                say!("  ❌ {}  {:#}", check, e);
+                failures.push(json!({ "check": check, "error": format!("{:#}", e) }));
+            }
+        }
+    }
+
+    output::emit("verify-receipt", json!({
+        "account": account_pubkey.to_string(),
+        "transfer_signature": transfer_signature.to_string(),
+        "applied_signature": applied_signature.to_string(),
+        "signer": signer.to_string(),
+        "amount": transfer["amount"].as_u64(),
+        "failures": failures,
+    }));
+
+    if !failures.is_empty() {
+        anyhow::bail!("Transfer receipt failed verification");
+    }
+    say!("\n✅ {} acknowledged and applied the transfer", signer);
+    if amount.is_none() && transfer["amount"].is_null() {
+        say!("   The receipt carries no amount; ask for one made with --prove-amount to check it");
+    }
+
+    Ok(())
+}
+
+/// The first successful transaction after `transfer` that applied `account`'s pending
+/// balance. Any apply moves the whole pending balance, this credit included.
+async fn applied_after(rpc_client: &RpcClient, account: &Pubkey, transfer: &Signature) -> Result<Option<(Signature, u64)>> {
+    let mut newer = Vec::new();
+    let mut before = None;
+    loop {
+        let page = rpc_client
+            .get_signatures_for_address_with_config(account, GetConfirmedSignaturesForAddress2Config {
+                before,
+                until: Some(*transfer),
+                limit: Some(SIGNATURES_PER_PAGE),
+                ..GetConfirmedSignaturesForAddress2Config::default()
+            })
+            .await
+            .map_err(anyhow::Error::from);
+        let page = rpc::with_scan_hint(page, "getSignaturesForAddress")?;
+        let Some(last) = page.last() else {
+            break;
+        };
+        before = Some(Signature::from_str(&last.signature)?);
+        let full = page.len() == SIGNATURES_PER_PAGE;
+        newer.extend(page.into_iter().filter(|status| status.err.is_none()));
+        if !full {
+            break;
+        }
+    }
+
+    // Newest first, so the earliest apply is found from the back
+    for status in newer.iter().rev() {
+        let signature = Signature::from_str(&status.signature)?;
+        let transaction = audit_session::fetch_transaction(rpc_client, &signature).await?;
+        if applies_pending_balance(&transaction, account) {
+            return Ok(Some((signature, status.slot)));
+        }
+    }
+    Ok(None)
+}
+
+fn applies_pending_balance(transaction: &FetchedTransaction, account: &Pubkey) -> bool {
+    transaction.instructions.iter().any(|instruction| {
+        transaction.account_keys.get(instruction.program_id_index as usize) == Some(&spl_token_2022::id())
+            && matches!(TokenInstruction::unpack(&instruction.data), Ok(TokenInstruction::ConfidentialTransferExtension))
+            && matches!(
+                decode_instruction_type::<ConfidentialTransferInstruction>(&instruction.data[1..]),
+                Ok(ConfidentialTransferInstruction::ApplyPendingBalance)
+            )
+            && instruction.accounts.first().and_then(|index| transaction.account_keys.get(*index as usize)) == Some(account)
+    })
+}
+
+async fn check_owner(config: &ReadOnlyConfig, account: &Pubkey, signer: &Pubkey) -> Result<()> {
+    let account_data = config.rpc_client.get_account(account).await?;
+    let token_account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data.data)?;
+    if token_account.base.owner != *signer {
+        anyhow::bail!("{} is owned by {}", account, token_account.base.owner);
+    }
+    Ok(())
+}
+
+async fn check_transfer(config: &ReadOnlyConfig, account: &Pubkey, transfer: &Value) -> Result<()> {
+    let signature = Signature::from_str(transfer["signature"].as_str().unwrap_or_default())?;
+    let instruction_index = transfer["instruction_index"].as_u64().unwrap_or(u64::MAX) as usize;
+    let transaction = audit_session::fetch_transaction(&config.scan_client, &signature).await?;
+    audit_session::movements(&transaction, account)
+        .into_iter()
+        .any(|movement| movement.instruction_index == instruction_index && movement.kind == "transfer-in")
+        .then_some(())
+        .ok_or_else(|| anyhow::anyhow!("No confidential transfer into {} at instruction {}", account, instruction_index))
+}
+
+async fn check_applied(config: &ReadOnlyConfig, account: &Pubkey, transfer: &Signature, applied: &Signature) -> Result<()> {
+    let transaction = audit_session::fetch_transaction(&config.scan_client, applied).await?;
+    if !applies_pending_balance(&transaction, account) {
+        anyhow::bail!("{} does not apply {}'s pending balance", applied, account);
+    }
+    let statuses = config.scan_client
+        .get_signature_statuses_with_history(&[*transfer, *applied])
+        .await?
+        .value;
+    let slot = |index: usize| statuses.get(index).and_then(|status| status.as_ref()).map(|status| status.slot);
+    match (slot(0), slot(1)) {
+        (Some(transfer_slot), Some(applied_slot)) if applied_slot >= transfer_slot => Ok(()),
+        (Some(_), Some(_)) => anyhow::bail!("The apply landed before the transfer"),
+        _ => anyhow::bail!("The RPC node no longer knows one of the transactions"),
+    }
+}
+
+async fn check_amount(config: &ReadOnlyConfig, account: &Pubkey, transfer: &Value, amount: &str) -> Result<()> {
+    let proven = transfer["amount"].as_u64()
+        .ok_or_else(|| anyhow::anyhow!("the receipt carries no proven amount (made without --prove-amount)"))?;
+    let account_data = config.rpc_client.get_account(account).await?;
+    let token_account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data.data)?;
+    let mint_data = config.rpc_client.get_account(&token_account.base.mint).await?;
+    let decimals = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data.data)?.base.decimals;
+    let expected = utils::parse_amount(amount, decimals)?;
+    if proven != expected {
+        anyhow::bail!("the recipient proved {}, not {}", utils::format_amount(proven, decimals), utils::format_amount(expected, decimals));
+    }
+    Ok(())
+}
@@ -1,5 +1,7 @@
 use anyhow::{Result, Context};
 use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
     signature::Signer,
     transaction::Transaction,
 };
@@ -8,20 +10,45 @@ use spl_token_2022::{
         confidential_transfer::ConfidentialTransferAccount,
         StateWithExtensions,
     },
-    solana_zk_sdk::encryption::pod::elgamal::PodElGamalCiphertext,
+    solana_zk_sdk::encryption::pod::auth_encryption::PodAeCiphertext,
 };
 use spl_token_2022::extension::BaseStateWithExtensions;
-use bytemuck::Zeroable;
-use crate::{config::AppConfig, crypto, utils};
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::Path;
+use crate::{config::{self, AppConfig}, cpi, crypto, intent::{self, TransferIntent}, multisig, output::{self, say}, proof_context, rpc, utils};
 
-pub async fn execute(account: String) -> Result<()> {
-    println!("🔄 Applying Pending Balance...\n");
+// Credits landing mid-decryption restart the flow; more than this and the account is too busy
+const APPLY_ATTEMPTS: u32 = 3;
+
+enum ApplyOutcome {
+    Done,
+    CounterMoved { from: u64, to: u64 },
+}
+
+pub async fn execute(account: String, owner: Option<String>) -> Result<()> {
+    say!("🔄 Applying Pending Balance...\n");
     
-    let config = AppConfig::new()?;
+    let config = AppConfig::new()?.with_owner(owner)?;
     let account_pubkey = utils::parse_pubkey(&account)?;
     
+    for attempt in 1..=APPLY_ATTEMPTS {
+        match apply_once(&config, &account_pubkey).await? {
+            ApplyOutcome::Done => return Ok(()),
+            ApplyOutcome::CounterMoved { from, to } => {
+                say!("\n⚠️  A credit arrived while decrypting (counter {} → {}), starting over ({}/{})", from, to, attempt, APPLY_ATTEMPTS);
+            }
+        }
+    }
+    anyhow::bail!("Credits kept arriving on {} during {} attempts; retry once transfers into it pause", account_pubkey, APPLY_ATTEMPTS)
+}
+
+/// One pass of decrypt-and-apply. The pending balance and credit counter are read from the
+/// same account snapshot; if the counter has moved by the time the transaction is ready,
+/// the decryption is stale and nothing is sent.
+async fn apply_once(config: &AppConfig, account_pubkey: &Pubkey) -> Result<ApplyOutcome> {
     // Fetch account data
-    let account_data = config.rpc_client.get_account(&account_pubkey).await?;
+    let account_data = config.rpc_client.get_account(account_pubkey).await?;
     let token_account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data.data)?;
     let ct_account = token_account.get_extension::<ConfidentialTransferAccount>()?;
     
@@ -30,49 +57,63 @@ pub async fn execute(account: String) -> Result<()> {
     let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data.data)?;
     let decimals = mint.base.decimals;
     
-    println!("📋 Account: {}", account_pubkey);
-    println!("  Mint: {}", token_account.base.mint);
+    say!("📋 Account: {}", account_pubkey);
+    say!("  Mint: {}", token_account.base.mint);
     
     // Derive encryption keys
-    let elgamal_keypair = crypto::derive_elgamal_keypair(&config.payer);
-    let aes_key = crypto::derive_aes_key(&config.payer);
+    let elgamal_keypair = crypto::derive_elgamal_keypair(config.owner(), account_pubkey)?;
+    crypto::warn_derivation_scheme(config.owner(), account_pubkey, &elgamal_keypair, &ct_account.elgamal_pubkey);
+    let aes_key = crypto::derive_aes_key(config.owner(), account_pubkey)?;
+    let expected_counter = u64::from(ct_account.pending_balance_credit_counter);
     
-    println!("\n🔓 Decrypting balances...");
+    say!("\n🔓 Decrypting balances...");
     
     // Decrypt current available balance
-    let current_available_balance = if ct_account.available_balance == PodElGamalCiphertext::zeroed() {
-        0u64
-    } else {
-        aes_key.decrypt(&ct_account.decryptable_available_balance.try_into()?)
-            .ok_or_else(|| anyhow::anyhow!("Failed to decrypt available balance"))?
-    };
+    let current_available_balance = crypto::decrypt_available_balance(
+        &elgamal_keypair,
+        &aes_key,
+        &ct_account.available_balance,
+        &ct_account.decryptable_available_balance,
+    )?;
     
-    println!("  Current Available Balance: {}", utils::format_amount(current_available_balance, decimals));
+    say!("  Current Available Balance: {}", utils::format_amount(current_available_balance, decimals));
     
     // Decrypt pending balance (this can be slow if many transfers)
-    println!("  Decrypting pending balance (may take time)...");
+    say!("  Decrypting pending balance (may take time)...");
     
-    let pending_balance_lo = elgamal_keypair.secret().decrypt_u32(&ct_account.pending_balance_lo.try_into()?)
-        .ok_or_else(|| anyhow::anyhow!("Failed to decrypt pending balance lo"))?;
-    
-    let pending_balance_hi = elgamal_keypair.secret().decrypt_u32(&ct_account.pending_balance_hi.try_into()?)
-        .ok_or_else(|| anyhow::anyhow!("Failed to decrypt pending balance hi"))?;
+    let pending_balance = crypto::decrypt_pending_balance(
+        &elgamal_keypair,
+        &ct_account.pending_balance_lo,
+        &ct_account.pending_balance_hi,
+    )?;
     
-    // Combine lo (16-bit) and hi (32-bit) parts
-    let pending_balance = (pending_balance_lo as u64) + ((pending_balance_hi as u64) << 16);
+    say!("  Pending Balance: {}", utils::format_amount(pending_balance, decimals));
     
-    println!("  Pending Balance: {}", utils::format_amount(pending_balance, decimals));
+    // Applying rewrites the decryptable balance, so a missing one is repaired even with nothing pending
+    let repair = crypto::decryptable_balance_missing(&ct_account.available_balance, &ct_account.decryptable_available_balance);
+    if repair {
+        say!("\n🔧 Rewriting the missing decryptable available balance");
+    }
     
-    if pending_balance == 0 {
-        println!("\n⚠️  No pending balance to apply!");
-        return Ok(());
+    if pending_balance == 0 && !repair {
+        say!("\n⚠️  No pending balance to apply!");
+        output::emit("apply-balance", json!({
+            "account": account_pubkey.to_string(),
+            "mint": token_account.base.mint.to_string(),
+            "previous_available_balance": current_available_balance,
+            "pending_balance": 0,
+            "new_available_balance": current_available_balance,
+            "signature": null,
+            "stale_credits": 0,
+        }));
+        return Ok(ApplyOutcome::Done);
     }
     
     // Calculate new available balance after applying pending
     let new_available_balance = current_available_balance + pending_balance;
     
-    println!("\n💡 After applying:");
-    println!("  New Available Balance: {}", utils::format_amount(new_available_balance, decimals));
+    say!("\n💡 After applying:");
+    say!("  New Available Balance: {}", utils::format_amount(new_available_balance, decimals));
     
     // Encrypt the new available balance
     let new_decryptable_balance = aes_key.encrypt(new_available_balance);
@@ -82,44 +123,404 @@ pub async fn execute(account: String) -> Result<()> {
     let pod_decryptable_balance: spl_token_2022::solana_zk_sdk::encryption::pod::auth_encryption::PodAeCiphertext 
         = new_decryptable_balance.into();
     
+    // Program-owned and multisig-owned accounts are authorized by their owner, not by the payer
+    let multisig_signers = multisig::signer_pubkeys(config, &token_account.base.owner).await?;
+    let authority = if cpi::is_program_owned(&token_account.base.owner) || !multisig_signers.is_empty() {
+        token_account.base.owner
+    } else {
+        config.owner().pubkey()
+    };
+    
     let apply_ix = spl_token_2022::extension::confidential_transfer::instruction::apply_pending_balance(
         &spl_token_2022::id(),
-        &account_pubkey,
-        ct_account.pending_balance_credit_counter.into(),
+        account_pubkey,
+        expected_counter,
         &pod_decryptable_balance,
-        &config.payer.pubkey(),
-        &[], // No multisig signers
+        &authority,
+        &multisig_signers.iter().collect::<Vec<_>>(),
     )?;
     
+    if cpi::is_program_owned(&authority) {
+        cpi::emit_instructions("apply-balance", &authority, &[apply_ix])?;
+        return Ok(ApplyOutcome::Done);
+    }
+    
     let mut transaction = Transaction::new_with_payer(
         &[apply_ix],
         Some(&config.payer.pubkey()),
     );
     
     let recent_blockhash = config.rpc_client.get_latest_blockhash().await?;
-    transaction.sign(&[&config.payer], recent_blockhash);
+
+    if let Some(path) = config::global_options().export_intent {
+        transaction.message.recent_blockhash = recent_blockhash;
+        let intent = TransferIntent::new("apply-balance", &transaction)?
+            .account("account", account_pubkey)
+            .amount(pending_balance)
+            .ciphertext("new_decryptable_available_balance", pod_decryptable_balance);
+        intent::export(&intent, &path)?;
+        return Ok(ApplyOutcome::Done);
+    }
+
+    // Decryption can take a while; make sure no credit landed in the meantime
+    let current_counter = credit_counter(config, account_pubkey).await?;
+    if current_counter != expected_counter {
+        return Ok(ApplyOutcome::CounterMoved { from: expected_counter, to: current_counter });
+    }
+
+    config.sign(&mut transaction, recent_blockhash)?;
     
-    println!("\n📤 Sending transaction...");
-    let signature = config.rpc_client
-        .send_and_confirm_transaction(&transaction)
+    say!("\n📤 Sending transaction...");
+    let signature = rpc::send_once(config, &transaction)
         .await
         .context("Failed to apply pending balance")?;
     
-    println!("✅ Pending balance applied successfully!");
-    println!("   Signature: {}", signature);
+    say!("✅ Pending balance applied successfully!");
+    say!("   Signature: {}", signature);
+    
+    // A credit between the check and execution is applied too, but is missing from the
+    // decryptable balance; the program records both counters so this can be caught
+    let account_data = config.rpc_client.get_account(account_pubkey).await?;
+    let token_account_after = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data.data)?;
+    let ct_account_after = token_account_after.get_extension::<ConfidentialTransferAccount>()?;
+    let actual_counter = u64::from(ct_account_after.actual_pending_balance_credit_counter);
+    let stale_credits = actual_counter.saturating_sub(expected_counter);
     
-    println!("\n📚 What just happened:");
-    println!("   1. Decrypted your pending balance: {}", utils::format_amount(pending_balance, decimals));
-    println!("   2. Added to available balance: {}", utils::format_amount(current_available_balance, decimals));
-    println!("   3. New available balance: {}", utils::format_amount(new_available_balance, decimals));
-    println!("   4. Pending balance reset to 0");
-    println!("   5. Updated decryptable balance for instant access");
+    // Emitted either way: the transaction landed, so callers need its signature
+    output::emit("apply-balance", json!({
+        "account": account_pubkey.to_string(),
+        "mint": token_account.base.mint.to_string(),
+        "previous_available_balance": current_available_balance,
+        "pending_balance": pending_balance,
+        "new_available_balance": new_available_balance,
+        "signature": signature.to_string(),
+        "stale_credits": stale_credits,
+    }));
+    
+    if actual_counter != expected_counter {
+        anyhow::bail!(
+            "{} credit(s) arrived between the counter check and execution of {}; they were applied, but the \
+             decryptable balance does not include them ('health' will report it as stale)",
+            stale_credits,
+            signature
+        );
+    }
     
-    println!("\n💡 Key Concepts:");
-    println!("   • Pending balance: Accumulated incoming transfers (encrypted)");
-    println!("   • Available balance: Spendable balance (encrypted)");
-    println!("   • This operation merges pending -> available");
-    println!("   • Required before spending newly received tokens");
+    say!("\n📚 What just happened:");
+    say!("   1. Decrypted your pending balance: {}", utils::format_amount(pending_balance, decimals));
+    say!("   2. Added to available balance: {}", utils::format_amount(current_available_balance, decimals));
+    say!("   3. New available balance: {}", utils::format_amount(new_available_balance, decimals));
+    say!("   4. Pending balance reset to 0");
+    say!("   5. Updated decryptable balance for instant access");
     
+    say!("\n💡 Key Concepts:");
+    say!("   • Pending balance: Accumulated incoming transfers (encrypted)");
+    say!("   • Available balance: Spendable balance (encrypted)");
+    say!("   • This operation merges pending -> available");
+    say!("   • Required before spending newly received tokens");
+    
+    Ok(ApplyOutcome::Done)
+}
+
+/// The pending and actual pending balance credit counters of a confidential account.
+fn ct_counters(data: &[u8]) -> Option<(u64, u64)> {
+    let token_account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(data).ok()?;
+    let ct_account = token_account.get_extension::<ConfidentialTransferAccount>().ok()?;
+    Some((ct_account.pending_balance_credit_counter.into(), ct_account.actual_pending_balance_credit_counter.into()))
+}
+
+fn pubkeys_of(batch: &[PendingApply]) -> Vec<Pubkey> {
+    batch.iter().map(|apply| apply.account).collect()
+}
+
+async fn credit_counter(config: &AppConfig, account: &Pubkey) -> Result<u64> {
+    let account_data = config.rpc_client.get_account(account).await?;
+    let token_account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data.data)?;
+    Ok(token_account.get_extension::<ConfidentialTransferAccount>()?.pending_balance_credit_counter.into())
+}
+
+struct PendingApply {
+    account: Pubkey,
+    decimals: u8,
+    pending_balance: u64,
+    credit_counter: u64,
+    instruction: Instruction,
+}
+
+/// Applies pending balances across many accounts, packing as many applies into each
+/// transaction as fit. `accounts` may also be a single file with one address per line.
+pub async fn execute_many(accounts: Vec<String>, all_mine: bool, owner: Option<String>) -> Result<()> {
+    say!("🔄 Applying Pending Balances...\n");
+
+    if config::global_options().export_intent.is_some() {
+        anyhow::bail!("--export-intent takes a single --account");
+    }
+
+    let config = AppConfig::new()?.with_owner(owner)?;
+    let payer = config.payer.pubkey();
+
+    let pubkeys = if all_mine {
+        rpc::get_token_accounts_by_owner(&config.scan_client, &config.owner().pubkey()).await?
+            .into_iter()
+            .filter(|(_, account)| StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account.data)
+                .is_ok_and(|token_account| token_account.get_extension::<ConfidentialTransferAccount>().is_ok()))
+            .map(|(pubkey, _)| pubkey)
+            .collect()
+    } else {
+        read_account_list(accounts)?
+            .iter()
+            .map(|account| utils::parse_pubkey(account))
+            .collect::<Result<Vec<_>>>()?
+    };
+    say!("📋 Accounts: {}", pubkeys.len());
+
+    let mut decimals_by_mint = HashMap::new();
+    let mut reports = Vec::new();
+    let mut ready = Vec::new();
+    for pubkey in &pubkeys {
+        match prepare_apply(&config, &mut decimals_by_mint, pubkey).await {
+            Ok(Some(apply)) => {
+                say!("  📥 {}  pending {}", pubkey, utils::format_amount(apply.pending_balance, apply.decimals));
+                ready.push(apply);
+            }
+            Ok(None) => {
+                say!("  ✅ {}  nothing pending", pubkey);
+                reports.push(json!({ "account": pubkey.to_string(), "status": "nothing-pending", "pending_balance": 0, "signature": null, "error": null }));
+            }
+            Err(e) => {
+                say!("  ⚠️  {}  {:#}", pubkey, e);
+                reports.push(json!({ "account": pubkey.to_string(), "status": "error", "pending_balance": null, "signature": null, "error": format!("{:#}", e) }));
+            }
+        }
+    }
+
+    // Applies are signed by the payer and at most the owner and --multisig-signers, so any of them can share a transaction
+    let mut batches: Vec<Vec<PendingApply>> = Vec::new();
+    for apply in ready {
+        let fits = match batches.last() {
+            Some(batch) => {
+                let instructions: Vec<Instruction> = batch.iter()
+                    .chain(std::iter::once(&apply))
+                    .map(|apply| apply.instruction.clone())
+                    .collect();
+                proof_context::transaction_fits(&instructions, &payer)?
+            }
+            None => false,
+        };
+        match batches.last_mut() {
+            Some(batch) if fits => batch.push(apply),
+            _ => batches.push(vec![apply]),
+        }
+    }
+
+    let mut signatures = Vec::new();
+    let batch_count = batches.len();
+    for (batch_index, batch) in batches.iter_mut().enumerate() {
+        // Accounts credited since they were decrypted are decrypted again, once
+        // Those that can't be are dropped: sent stale, the apply would still execute and leave
+        // the decryptable balance out of date
+        let current = config.rpc_client.get_multiple_accounts(&pubkeys_of(batch)).await?;
+        let mut refreshed_batch = Vec::with_capacity(batch.len());
+        for (apply, account) in std::mem::take(batch).into_iter().zip(current) {
+            let moved = account.as_ref()
+                .and_then(|account| ct_counters(&account.data))
+                .is_some_and(|(counter, _)| counter != apply.credit_counter);
+            if !moved {
+                refreshed_batch.push(apply);
+                continue;
+            }
+            say!("  🔁 {}  credited since decryption, decrypting again", apply.account);
+            let error = match prepare_apply(&config, &mut decimals_by_mint, &apply.account).await {
+                Ok(Some(refreshed)) => {
+                    refreshed_batch.push(refreshed);
+                    continue;
+                }
+                Ok(None) => anyhow::anyhow!("Credited since decryption, but has nothing pending any more; check it with 'balance'"),
+                Err(e) => e,
+            };
+            say!("  ⚠️  {}  {:#}", apply.account, error);
+            reports.push(json!({
+                "account": apply.account.to_string(),
+                "status": "error",
+                "pending_balance": null,
+                "signature": null,
+                "error": format!("{:#}", error),
+            }));
+        }
+        *batch = refreshed_batch;
+        if batch.is_empty() {
+            continue;
+        }
+
+        let instructions: Vec<Instruction> = batch.iter().map(|apply| apply.instruction.clone()).collect();
+        let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer));
+        let recent_blockhash = config.rpc_client.get_latest_blockhash().await?;
+        config.sign(&mut transaction, recent_blockhash)?;
+
+        say!("\n📤 Sending apply batch {}/{} ({} accounts)...", batch_index + 1, batch_count, batch.len());
+        let result = rpc::send_once(&config, &transaction)
+            .await
+            .context("Failed to apply pending balances");
+        match &result {
+            Ok(signature) => {
+                say!("✅ Batch confirmed");
+                say!("   Signature: {}", signature);
+                signatures.push(signature.to_string());
+            }
+            Err(e) => say!("❌ Batch failed: {:#}", e),
+        }
+
+        // As in a single apply, credits landing between the check and execution are applied
+        // but missing from the decryptable balance
+        let actual_counters: Vec<Option<u64>> = match &result {
+            Ok(_) => match config.rpc_client.get_multiple_accounts(&pubkeys_of(batch)).await {
+                Ok(accounts) => accounts.iter()
+                    .map(|account| account.as_ref().and_then(|account| ct_counters(&account.data)).map(|(_, actual)| actual))
+                    .collect(),
+                Err(e) => {
+                    say!("⚠️  Could not re-read the batch to check for credits during execution: {:#}", e);
+                    vec![None; batch.len()]
+                }
+            },
+            Err(_) => vec![None; batch.len()],
+        };
+        for (apply, actual_counter) in batch.iter().zip(actual_counters) {
+            let stale_credits = actual_counter.map_or(0, |actual| actual.saturating_sub(apply.credit_counter));
+            reports.push(match &result {
+                Ok(signature) if stale_credits > 0 => {
+                    say!("  ⚠️  {}  {} credit(s) arrived before execution; applied, but the decryptable balance does not include them",
+                        apply.account, stale_credits);
+                    json!({
+                        "account": apply.account.to_string(),
+                        "status": "error",
+                        "pending_balance": apply.pending_balance,
+                        "signature": signature.to_string(),
+                        "stale_credits": stale_credits,
+                        "error": format!(
+                            "{} credit(s) arrived between the counter check and execution; they were applied, but the \
+                             decryptable balance does not include them ('health' will report it as stale)",
+                            stale_credits
+                        ),
+                    })
+                }
+                Ok(signature) => json!({
+                    "account": apply.account.to_string(),
+                    "status": "applied",
+                    "pending_balance": apply.pending_balance,
+                    "signature": signature.to_string(),
+                    "stale_credits": 0,
+                    "error": null,
+                }),
+                Err(e) => json!({
+                    "account": apply.account.to_string(),
+                    "status": "error",
+                    "pending_balance": apply.pending_balance,
+                    "signature": null,
+                    "error": format!("{:#}", e),
+                }),
+            });
+        }
+    }
+
+    let applied = reports.iter().filter(|report| report["status"] == "applied").count();
+    let errors = reports.iter().filter(|report| report["status"] == "error").count();
+    say!("\n📊 Summary: {} account(s), {} applied, {} error(s)", pubkeys.len(), applied, errors);
+
+    output::emit("apply-balance-batch", json!({
+        "accounts": reports,
+        "applied": applied,
+        "errors": errors,
+        "signatures": signatures,
+    }));
+
+    if errors > 0 {
+        anyhow::bail!("{} account(s) could not be applied cleanly; see the errors above", errors);
+    }
+
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Builds the apply instruction for `account`, or `None` when nothing is pending.
+async fn prepare_apply(
+    config: &AppConfig,
+    decimals_by_mint: &mut HashMap<Pubkey, u8>,
+    account: &Pubkey,
+) -> Result<Option<PendingApply>> {
+    let account_data = config.rpc_client.get_account(account).await?;
+    let token_account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data.data)?;
+    let ct_account = token_account.get_extension::<ConfidentialTransferAccount>()?;
+    let mint = token_account.base.mint;
+
+    let owner = config.owner().pubkey();
+    let multisig_signers = if token_account.base.owner == owner {
+        Vec::new()
+    } else {
+        multisig::signer_pubkeys(config, &token_account.base.owner).await?
+    };
+    if token_account.base.owner != owner && multisig_signers.is_empty() {
+        anyhow::bail!("Owned by {}, not {}; pass its --owner or apply it with 'apply-balance --account'", token_account.base.owner, owner);
+    }
+
+    let authority = if multisig_signers.is_empty() { owner } else { token_account.base.owner };
+
+    let decimals = match decimals_by_mint.get(&mint) {
+        Some(decimals) => *decimals,
+        None => {
+            let mint_data = config.rpc_client.get_account(&mint).await?;
+            let decimals = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data.data)?.base.decimals;
+            decimals_by_mint.insert(mint, decimals);
+            decimals
+        }
+    };
+
+    let elgamal_keypair = crypto::derive_elgamal_keypair(config.owner(), account)?;
+    let aes_key = crypto::derive_aes_key(config.owner(), account)?;
+
+    let current_available_balance = crypto::decrypt_available_balance(
+        &elgamal_keypair,
+        &aes_key,
+        &ct_account.available_balance,
+        &ct_account.decryptable_available_balance,
+    )?;
+    let pending_balance = crypto::decrypt_pending_balance(
+        &elgamal_keypair,
+        &ct_account.pending_balance_lo,
+        &ct_account.pending_balance_hi,
+    )?;
+    if pending_balance == 0
+        && !crypto::decryptable_balance_missing(&ct_account.available_balance, &ct_account.decryptable_available_balance)
+    {
+        return Ok(None);
+    }
+
+    let credit_counter = u64::from(ct_account.pending_balance_credit_counter);
+    let new_decryptable_balance: PodAeCiphertext = aes_key.encrypt(current_available_balance + pending_balance).into();
+    let instruction = spl_token_2022::extension::confidential_transfer::instruction::apply_pending_balance(
+        &spl_token_2022::id(),
+        account,
+        credit_counter,
+        &new_decryptable_balance,
+        &authority,
+        &multisig_signers.iter().collect::<Vec<_>>(),
+    )?;
+
+    Ok(Some(PendingApply { account: *account, decimals, pending_balance, credit_counter, instruction }))
+}
+
+/// A single existing file stands for the addresses listed in it, one per line.
+fn read_account_list(accounts: Vec<String>) -> Result<Vec<String>> {
+    let [path] = accounts.as_slice() else {
+        return Ok(accounts);
+    };
+    if !Path::new(path).is_file() {
+        return Ok(accounts);
+    }
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read account list {}", path))?;
+    Ok(contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
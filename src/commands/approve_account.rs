@@ -0,0 +1,91 @@
+use anyhow::{Result, Context};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::Signer,
+    transaction::Transaction,
+};
+use spl_token_2022::extension::{
+    confidential_transfer::{ConfidentialTransferAccount, ConfidentialTransferMint},
+    BaseStateWithExtensions,
+    StateWithExtensions,
+};
+use serde_json::json;
+use crate::{config::{self, AppConfig}, intent::{self, TransferIntent}, output::{self, say}, rpc, utils};
+
+pub async fn execute(account: String) -> Result<()> {
+    say!("✅ Approving Confidential Account...\n");
+
+    let config = AppConfig::new()?;
+    let account_pubkey = utils::parse_pubkey(&account)?;
+
+    // Fetch the account and its mint
+    let account_data = config.rpc_client.get_account(&account_pubkey).await?;
+    let token_account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data.data)?;
+    let ct_account = token_account.get_extension::<ConfidentialTransferAccount>()
+        .map_err(|_| anyhow::anyhow!("Account {} is not configured for confidential transfers; run 'create-account' first", account_pubkey))?;
+
+    let mint_pubkey = token_account.base.mint;
+    let mint_data = config.rpc_client.get_account(&mint_pubkey).await?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data.data)?;
+    let ct_mint = mint.get_extension::<ConfidentialTransferMint>()?;
+
+    say!("📋 Approval Details:");
+    say!("  Account: {}", account_pubkey);
+    say!("  Owner: {}", token_account.base.owner);
+    say!("  Mint: {}", mint_pubkey);
+
+    if bool::from(ct_account.approved) {
+        say!("\n💡 Account is already approved, nothing to do");
+        return Ok(());
+    }
+
+    let authority = Option::<Pubkey>::from(ct_mint.authority)
+        .ok_or_else(|| anyhow::anyhow!("Mint {} has no confidential transfer authority, so accounts cannot be approved", mint_pubkey))?;
+    if authority != config.owner().pubkey() {
+        anyhow::bail!("The keypair {} is not the mint's confidential transfer authority ({})", config.owner().pubkey(), authority);
+    }
+
+    let approve_ix = spl_token_2022::extension::confidential_transfer::instruction::approve_account(
+        &spl_token_2022::id(),
+        &account_pubkey,
+        &mint_pubkey,
+        &authority,
+        &[], // No multisig
+    )?;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[approve_ix],
+        Some(&config.payer.pubkey()),
+    );
+
+    let recent_blockhash = config.rpc_client.get_latest_blockhash().await?;
+
+    if let Some(path) = config::global_options().export_intent {
+        transaction.message.recent_blockhash = recent_blockhash;
+        let intent = TransferIntent::new("approve-account", &transaction)?
+            .account("account", &account_pubkey)
+            .account("mint", &mint_pubkey);
+        return intent::export(&intent, &path);
+    }
+
+    config.sign(&mut transaction, recent_blockhash)?;
+
+    say!("\n📤 Sending approval transaction...");
+    let signature = rpc::send_once(&config, &transaction)
+        .await
+        .context("Failed to approve account")?;
+
+    say!("✅ Account approved!");
+    say!("   Signature: {}", signature);
+
+    output::emit("approve-account", json!({
+        "account": account_pubkey.to_string(),
+        "owner": token_account.base.owner.to_string(),
+        "mint": mint_pubkey.to_string(),
+        "signature": signature.to_string(),
+    }));
+
+    say!("\n💡 The owner can now deposit, transfer and withdraw confidentially");
+
+    Ok(())
+}
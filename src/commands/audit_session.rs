@@ -0,0 +1,516 @@
+use anyhow::{Context, Result};
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use serde_json::{json, Value};
+use solana_client::{rpc_client::GetConfirmedSignaturesForAddress2Config, rpc_request::RpcRequest};
+use solana_sdk::{
+    instruction::CompiledInstruction,
+    pubkey::Pubkey,
+    signature::Signature,
+    transaction::VersionedTransaction,
+};
+use spl_token_2022::{
+    extension::confidential_transfer::instruction::{
+        ConfidentialTransferInstruction,
+        DepositInstructionData,
+        TransferInstructionData,
+        TransferWithFeeInstructionData,
+        WithdrawInstructionData,
+    },
+    instruction::{decode_instruction_data, decode_instruction_type, TokenInstruction},
+    solana_zk_sdk::{
+        encryption::{elgamal::ElGamalCiphertext, pod::elgamal::{PodElGamalCiphertext, PodElGamalPubkey}},
+        zk_elgamal_proof_program::{
+            instruction::ProofInstruction,
+            proof_data::{
+                BatchedGroupedCiphertext3HandlesValidityProofContext,
+                BatchedGroupedCiphertext3HandlesValidityProofData,
+                ZeroCiphertextProofData,
+                ZkProofData,
+            },
+        },
+    },
+};
+use spl_token_confidential_transfer_proof_generation::{try_combine_lo_hi_ciphertexts, TRANSFER_AMOUNT_LO_BITS};
+use std::path::PathBuf;
+use std::str::FromStr;
+use crate::{config::{AppConfig, ReadOnlyConfig}, crypto, output::{self, say}, utils};
+
+const SIGNATURES_PER_PAGE: usize = 1000;
+
+/// Identifies audit session files; the first sessions had no `format` or `version`
+/// and count as version 0.
+pub const AUDIT_SESSION_FORMAT: &str = "confidential-audit-session";
+
+/// Bumped whenever a field is removed, renamed or changes meaning.
+pub const AUDIT_SESSION_VERSION: u32 = 1;
+
+const VERIFICATION_STEPS: &[&str] = &[
+    "Check `signature` is the signer's ed25519 signature over the compact JSON of `body`",
+    "For each transfer entry, combine ciphertext_lo + ciphertext_hi * 2^16 and subtract amount",
+    "The result must equal the ciphertext in the entry's zero-ciphertext proof, whose pubkey must be elgamal_pubkey",
+    "Verify the proof; a valid proof shows the ciphertext encrypts exactly amount, without revealing any key",
+    "Fetch each transaction by signature and compare ciphertexts, pubkeys and public amounts with the instruction at instruction_index",
+    "`confidential-cli verify-audit-session --file <FILE> --check-chain` runs all of these checks",
+];
+
+/// A confidential transfer extension instruction touching an audited account.
+pub struct Movement {
+    pub instruction_index: usize,
+    pub kind: &'static str,
+    /// Public amount, for deposits and withdrawals
+    pub amount: Option<u64>,
+    /// lo/hi ciphertexts under the account's key and that key, for transfers
+    pub ciphertexts: Option<TransferCiphertexts>,
+    /// For a transfer whose amount proof sits in a context account: the decrypt handle
+    /// to extract and the accounts that may hold it
+    pub context_lookup: Option<(usize, Vec<Pubkey>)>,
+}
+
+pub type TransferCiphertexts = (PodElGamalCiphertext, PodElGamalCiphertext, PodElGamalPubkey);
+
+/// Exports every confidential movement of `accounts` between `from` and `to`, with a
+/// zero-knowledge proof per transfer amount, signed by the payer.
+pub async fn execute(accounts: Vec<String>, from: String, to: String, out: PathBuf) -> Result<()> {
+    say!("🕵️  Exporting Audit Session...\n");
+
+    let config = AppConfig::new()?;
+    let from_time = chrono::DateTime::parse_from_rfc3339(&from)
+        .with_context(|| format!("Invalid RFC3339 time: {}", from))?;
+    let to_time = chrono::DateTime::parse_from_rfc3339(&to)
+        .with_context(|| format!("Invalid RFC3339 time: {}", to))?;
+    if to_time < from_time {
+        anyhow::bail!("--to ({}) is before --from ({})", to, from);
+    }
+    let pubkeys = accounts
+        .iter()
+        .map(|account| utils::parse_pubkey(account))
+        .collect::<Result<Vec<_>>>()?;
+
+    say!("📋 Session:");
+    say!("  Accounts: {}", pubkeys.len());
+    say!("  Period: {} → {}", from_time.to_rfc3339(), to_time.to_rfc3339());
+
+    let mut entries = Vec::new();
+    let mut unresolved = Vec::new();
+    for account in &pubkeys {
+        say!("\n━━━ {} ━━━", account);
+        let elgamal_keypair = crypto::derive_elgamal_keypair(config.owner(), account)?;
+        let signatures = signatures_in_period(&config, account, from_time.timestamp(), to_time.timestamp()).await?;
+        say!("  {} transaction(s) in period", signatures.len());
+
+        // Gather every movement first, so the account's transfer amounts decrypt as one batch
+        let mut found = Vec::new();
+        for (signature, slot, block_time) in signatures {
+            let transaction = fetch_transaction(&config.scan_client, &signature).await?;
+            for movement in movements(&transaction, account) {
+                let ciphertexts = transfer_ciphertexts(&config.scan_client, &movement).await?;
+                found.push((signature, slot, block_time, movement, ciphertexts));
+            }
+        }
+        let pairs: Vec<_> = found.iter()
+            .filter_map(|(.., ciphertexts)| ciphertexts.as_ref().map(|(lo, hi, _)| (*lo, *hi)))
+            .collect();
+        let mut amounts = crypto::decrypt_pending_balances(&elgamal_keypair, &pairs).into_iter();
+
+        for (signature, slot, block_time, movement, ciphertexts) in found {
+            let is_transfer = movement.amount.is_none();
+            let base = json!({
+                "account": account.to_string(),
+                "signature": signature.to_string(),
+                "slot": slot,
+                "block_time": block_time,
+                "instruction_index": movement.instruction_index,
+                "kind": movement.kind,
+            });
+            if is_transfer && ciphertexts.is_none() {
+                let reason = "amount proof could not be found or decoded";
+                say!("  ⚠️  {} {}: {}", signature, movement.kind, reason);
+                let mut entry = base;
+                entry["reason"] = json!(reason);
+                unresolved.push(entry);
+                continue;
+            }
+
+            let mut entry = base;
+            match ciphertexts {
+                Some((lo, hi, pubkey)) => {
+                    let amount = amounts.next()
+                        .expect("one result per ciphertext pair")
+                        .with_context(|| format!("Failed to decrypt the amount of {}", signature))?;
+                    let difference = combine(&lo, &hi)?.subtract_amount(amount);
+                    let proof = ZeroCiphertextProofData::new(&elgamal_keypair, &difference)
+                        .map_err(|e| anyhow::anyhow!("Failed to prove the amount of {}: {:?}", signature, e))?;
+                    say!("  🔐 {} {}: {}", signature, movement.kind, amount);
+                    entry["amount"] = json!(amount);
+                    entry["elgamal_pubkey"] = json!(pubkey.to_string());
+                    entry["ciphertext_lo"] = json!(lo.to_string());
+                    entry["ciphertext_hi"] = json!(hi.to_string());
+                    entry["proof"] = json!(BASE64.encode(bytemuck::bytes_of(&proof)));
+                }
+                None => {
+                    let amount = movement.amount.unwrap_or_default();
+                    say!("  📄 {} {}: {}", signature, movement.kind, amount);
+                    entry["amount"] = json!(amount);
+                }
+            }
+            entries.push(entry);
+        }
+    }
+
+    let body = json!({
+        "cli_version": env!("CARGO_PKG_VERSION"),
+        "created_at": chrono::Utc::now().to_rfc3339(),
+        "from": from_time.to_rfc3339(),
+        "to": to_time.to_rfc3339(),
+        "accounts": pubkeys.iter().map(|pubkey| pubkey.to_string()).collect::<Vec<_>>(),
+        "entries": entries,
+        "unresolved": unresolved,
+        "verification": VERIFICATION_STEPS,
+    });
+    let signature = config.owner().sign_message(serde_json::to_string(&body)?.as_bytes());
+    let session = json!({
+        "format": AUDIT_SESSION_FORMAT,
+        "version": AUDIT_SESSION_VERSION,
+        "body": body,
+        "signer": config.owner().pubkey().to_string(),
+        "signature": signature.to_string(),
+    });
+    utils::write_atomic(&out, serde_json::to_string_pretty(&session)?)
+        .with_context(|| format!("Failed to write audit session {}", out.display()))?;
+
+    say!("\n✅ Audit session written to {}", out.display());
+    say!("   Entries: {}, unresolved: {}", entries.len(), unresolved.len());
+
+    output::emit("audit-session", json!({
+        "path": out.display().to_string(),
+        "signer": config.owner().pubkey().to_string(),
+        "entries": entries.len(),
+        "unresolved": unresolved.len(),
+    }));
+
+    say!("\n📚 What just happened:");
+    say!("   1. Collected the confidential transfers, deposits and withdrawals in the period");
+    say!("   2. Decrypted each transfer amount and proved it with a zero-ciphertext proof");
+    say!("   3. Signed the session so any change to it is detectable");
+    say!("\n💡 The auditor checks it with 'verify-audit-session'; no keys leave this machine");
+
+    Ok(())
+}
+
+/// Checks a session's signature and proofs, and with `check_chain` its ciphertexts and
+/// public amounts against the transactions themselves.
+pub async fn verify(file: PathBuf, check_chain: bool) -> Result<()> {
+    say!("🔎 Verifying Audit Session...\n");
+
+    let contents = std::fs::read_to_string(&file)
+        .with_context(|| format!("Failed to read audit session {}", file.display()))?;
+    let session: Value = serde_json::from_str(&contents)
+        .with_context(|| format!("{} is not an audit session", file.display()))?;
+    let version = session["version"].as_u64().unwrap_or(0);
+    if !session["format"].is_null() && session["format"] != AUDIT_SESSION_FORMAT {
+        anyhow::bail!("{} is not an audit session", file.display());
+    }
+    if version > u64::from(AUDIT_SESSION_VERSION) {
+        anyhow::bail!(
+            "Audit session {} uses format version {}, this CLI understands up to {}",
+            file.display(),
+            version,
+            AUDIT_SESSION_VERSION
+        );
+    }
+    let body = &session["body"];
+    let signer = utils::parse_pubkey(session["signer"].as_str().unwrap_or_default())?;
+    let signature = Signature::from_str(session["signature"].as_str().unwrap_or_default())
+        .context("Audit session signature is not valid base58")?;
+
+    let signed = signature.verify(signer.as_ref(), serde_json::to_string(body)?.as_bytes());
+    say!("📋 Session: {} → {}", body["from"].as_str().unwrap_or("?"), body["to"].as_str().unwrap_or("?"));
+    say!("  Signer: {}", signer);
+    say!("  {} Signature", if signed { "✅" } else { "❌" });
+
+    let read_only = check_chain.then(ReadOnlyConfig::new).transpose()?;
+    let mut failures = Vec::new();
+    let empty = Vec::new();
+    let entries = body["entries"].as_array().unwrap_or(&empty);
+    for entry in entries {
+        let label = format!("{} #{}", entry["signature"].as_str().unwrap_or("?"), entry["instruction_index"]);
+        match verify_entry(entry, read_only.as_ref()).await {
+            Ok(()) => say!("  ✅ {}", label),
+            Err(e) => {
+                say!("  ❌ {}  {:#}", label, e);
+                failures.push(json!({ "entry": label, "error": format!("{:#}", e) }));
+            }
+        }
+    }
+
+    say!("\n📊 Summary: {} entr(ies), {} failure(s){}", entries.len(), failures.len(),
+        if check_chain { "" } else { " (not compared with the chain; pass --check-chain)" });
+
+    output::emit("verify-audit-session", json!({
+        "signer": signer.to_string(),
+        "signature_valid": signed,
+        "entries": entries.len(),
+        "checked_chain": check_chain,
+        "failures": failures,
+    }));
+
+    if !signed || !failures.is_empty() {
+        anyhow::bail!("Audit session failed verification");
+    }
+
+    Ok(())
+}
+
+/// Checks one entry's proof, and with `read_only` that it matches the transaction on chain.
+pub async fn verify_entry(entry: &Value, read_only: Option<&ReadOnlyConfig>) -> Result<()> {
+    let amount = entry["amount"].as_u64().ok_or_else(|| anyhow::anyhow!("Entry has no amount"))?;
+    let account = utils::parse_pubkey(entry["account"].as_str().unwrap_or_default())?;
+    let proof = match entry["proof"].as_str() {
+        Some(proof) => {
+            let lo = parse_ciphertext(&entry["ciphertext_lo"])?;
+            let hi = parse_ciphertext(&entry["ciphertext_hi"])?;
+            let pubkey = PodElGamalPubkey::from_str(entry["elgamal_pubkey"].as_str().unwrap_or_default())
+                .map_err(|_| anyhow::anyhow!("Entry ElGamal pubkey is not valid base64"))?;
+            let bytes = BASE64.decode(proof).context("Entry proof is not valid base64")?;
+            let proof = *bytemuck::try_from_bytes::<ZeroCiphertextProofData>(&bytes)
+                .map_err(|_| anyhow::anyhow!("Entry proof has the wrong length"))?;
+
+            let context = proof.context_data();
+            if context.pubkey != pubkey {
+                anyhow::bail!("Proof is for a different ElGamal key");
+            }
+            if context.ciphertext != PodElGamalCiphertext::from(combine(&lo, &hi)?.subtract_amount(amount)) {
+                anyhow::bail!("Proof does not match the ciphertexts and amount");
+            }
+            proof.verify_proof().map_err(|e| anyhow::anyhow!("Proof does not verify: {:?}", e))?;
+            Some((lo, hi, pubkey))
+        }
+        None => None,
+    };
+
+    let Some(read_only) = read_only else {
+        return Ok(());
+    };
+    let signature = Signature::from_str(entry["signature"].as_str().unwrap_or_default())
+        .context("Entry signature is not valid base58")?;
+    let instruction_index = entry["instruction_index"].as_u64().unwrap_or(u64::MAX) as usize;
+    let transaction = fetch_transaction(&read_only.scan_client, &signature).await?;
+    let movement = movements(&transaction, &account)
+        .into_iter()
+        .find(|movement| movement.instruction_index == instruction_index)
+        .ok_or_else(|| anyhow::anyhow!("No movement of {} at instruction {} on chain", account, instruction_index))?;
+    let on_chain = transfer_ciphertexts(&read_only.scan_client, &movement).await?;
+    match (proof, on_chain, movement.amount) {
+        (Some(claimed), Some(on_chain), _) if claimed == on_chain => Ok(()),
+        (None, None, Some(on_chain)) if on_chain == amount => Ok(()),
+        _ => anyhow::bail!("Entry does not match the transaction on chain"),
+    }
+}
+
+fn parse_ciphertext(value: &Value) -> Result<PodElGamalCiphertext> {
+    PodElGamalCiphertext::from_str(value.as_str().unwrap_or_default())
+        .map_err(|_| anyhow::anyhow!("Entry ciphertext is not valid base64"))
+}
+
+pub fn combine(lo: &PodElGamalCiphertext, hi: &PodElGamalCiphertext) -> Result<ElGamalCiphertext> {
+    let lo: ElGamalCiphertext = (*lo).try_into()?;
+    let hi: ElGamalCiphertext = (*hi).try_into()?;
+    try_combine_lo_hi_ciphertexts(&lo, &hi, TRANSFER_AMOUNT_LO_BITS)
+        .ok_or_else(|| anyhow::anyhow!("Failed to combine lo and hi ciphertexts"))
+}
+
+/// Successful transactions touching `account` with a block time in `from..=to`, oldest first.
+async fn signatures_in_period(config: &AppConfig, account: &Pubkey, from: i64, to: i64) -> Result<Vec<(Signature, u64, i64)>> {
+    let mut found = Vec::new();
+    let mut before = None;
+    loop {
+        let page = config.scan_client
+            .get_signatures_for_address_with_config(account, GetConfirmedSignaturesForAddress2Config {
+                before,
+                limit: Some(SIGNATURES_PER_PAGE),
+                ..GetConfirmedSignaturesForAddress2Config::default()
+            })
+            .await
+            .map_err(anyhow::Error::from);
+        let page = crate::rpc::with_scan_hint(page, "getSignaturesForAddress")?;
+        let Some(last) = page.last() else {
+            break;
+        };
+        let reached_start = last.block_time.is_some_and(|block_time| block_time < from);
+        before = Some(Signature::from_str(&last.signature)?);
+
+        for status in &page {
+            let Some(block_time) = status.block_time else {
+                continue;
+            };
+            if status.err.is_none() && (from..=to).contains(&block_time) {
+                found.push((Signature::from_str(&status.signature)?, status.slot, block_time));
+            }
+        }
+        if reached_start || page.len() < SIGNATURES_PER_PAGE {
+            break;
+        }
+    }
+    found.reverse();
+    Ok(found)
+}
+
+pub struct FetchedTransaction {
+    pub account_keys: Vec<Pubkey>,
+    pub instructions: Vec<CompiledInstruction>,
+}
+
+pub async fn fetch_transaction(rpc_client: &solana_client::nonblocking::rpc_client::RpcClient, signature: &Signature) -> Result<FetchedTransaction> {
+    let params = json!([signature.to_string(), { "encoding": "base64", "maxSupportedTransactionVersion": 0 }]);
+    let response: Value = rpc_client
+        .send(RpcRequest::GetTransaction, params)
+        .await
+        .with_context(|| format!("Failed to fetch transaction {}", signature))?;
+    let encoded = response["transaction"][0].as_str()
+        .ok_or_else(|| anyhow::anyhow!("Transaction {} not found", signature))?;
+    let transaction: VersionedTransaction = bincode::deserialize(&BASE64.decode(encoded)?)
+        .with_context(|| format!("Failed to decode transaction {}", signature))?;
+
+    // Lookup table addresses follow the static keys, writable ones first
+    let mut account_keys = transaction.message.static_account_keys().to_vec();
+    for kind in ["writable", "readonly"] {
+        if let Some(addresses) = response["meta"]["loadedAddresses"][kind].as_array() {
+            for address in addresses {
+                account_keys.push(utils::parse_pubkey(address.as_str().unwrap_or_default())?);
+            }
+        }
+    }
+
+    Ok(FetchedTransaction {
+        account_keys,
+        instructions: transaction.message.instructions().to_vec(),
+    })
+}
+
+pub fn movements(transaction: &FetchedTransaction, account: &Pubkey) -> Vec<Movement> {
+    let key = |instruction: &CompiledInstruction, position: usize| instruction.accounts.get(position)
+        .and_then(|index| transaction.account_keys.get(*index as usize))
+        .copied();
+
+    let mut found = Vec::new();
+    for (instruction_index, instruction) in transaction.instructions.iter().enumerate() {
+        let program_id = transaction.account_keys.get(instruction.program_id_index as usize);
+        if program_id != Some(&spl_token_2022::id()) {
+            continue;
+        }
+        if !matches!(TokenInstruction::unpack(&instruction.data), Ok(TokenInstruction::ConfidentialTransferExtension)) {
+            continue;
+        }
+        let data = &instruction.data[1..];
+        let movement = |kind, amount, ciphertexts, context_lookup| Movement { instruction_index, kind, amount, ciphertexts, context_lookup };
+
+        match decode_instruction_type::<ConfidentialTransferInstruction>(data) {
+            Ok(ConfidentialTransferInstruction::Deposit) if key(instruction, 0) == Some(*account) => {
+                if let Ok(deposit) = decode_instruction_data::<DepositInstructionData>(data) {
+                    found.push(movement("deposit", Some(u64::from(deposit.amount)), None, None));
+                }
+            }
+            Ok(ConfidentialTransferInstruction::Withdraw) if key(instruction, 0) == Some(*account) => {
+                if let Ok(withdraw) = decode_instruction_data::<WithdrawInstructionData>(data) {
+                    found.push(movement("withdraw", Some(u64::from(withdraw.amount)), None, None));
+                }
+            }
+            Ok(kind @ (ConfidentialTransferInstruction::Transfer | ConfidentialTransferInstruction::TransferWithFee)) => {
+                // Transfers list the source first and the destination third
+                let direction = if key(instruction, 0) == Some(*account) {
+                    ("transfer-out", 0)
+                } else if key(instruction, 2) == Some(*account) {
+                    ("transfer-in", 1)
+                } else {
+                    continue;
+                };
+                let offset = match kind {
+                    ConfidentialTransferInstruction::Transfer => decode_instruction_data::<TransferInstructionData>(data)
+                        .map(|transfer| transfer.ciphertext_validity_proof_instruction_offset),
+                    _ => decode_instruction_data::<TransferWithFeeInstructionData>(data)
+                        .map(|transfer| transfer.transfer_amount_ciphertext_validity_proof_instruction_offset),
+                };
+                let Ok(offset) = offset else {
+                    continue;
+                };
+                let ciphertexts = (offset != 0)
+                    .then(|| instruction_index.checked_add_signed(offset as isize))
+                    .flatten()
+                    .and_then(|index| transaction.instructions.get(index))
+                    .and_then(|proof_instruction| validity_context(&proof_instruction.data))
+                    .and_then(|context| extract(&context, direction.1));
+                // Between the destination and the trailing authority sit the sysvar and
+                // any context accounts
+                let context_lookup = (offset == 0).then(|| {
+                    let candidates = (3..instruction.accounts.len().saturating_sub(1))
+                        .filter_map(|position| key(instruction, position))
+                        .collect();
+                    (direction.1, candidates)
+                });
+                found.push(movement(direction.0, None, ciphertexts, context_lookup));
+            }
+            _ => {}
+        }
+    }
+    found
+}
+
+fn validity_context(data: &[u8]) -> Option<BatchedGroupedCiphertext3HandlesValidityProofContext> {
+    if ProofInstruction::instruction_type(data) != Some(ProofInstruction::VerifyBatchedGroupedCiphertext3HandlesValidity) {
+        return None;
+    }
+    ProofInstruction::proof_data::<
+        BatchedGroupedCiphertext3HandlesValidityProofData,
+        BatchedGroupedCiphertext3HandlesValidityProofContext,
+    >(data).map(|proof_data| proof_data.context)
+}
+
+/// Handle 0 is the source's, 1 the destination's
+fn extract(context: &BatchedGroupedCiphertext3HandlesValidityProofContext, handle: usize) -> Option<TransferCiphertexts> {
+    let pubkey = if handle == 0 { context.first_pubkey } else { context.second_pubkey };
+    Some((
+        context.grouped_ciphertext_lo.try_extract_ciphertext(handle).ok()?,
+        context.grouped_ciphertext_hi.try_extract_ciphertext(handle).ok()?,
+        pubkey,
+    ))
+}
+
+/// The transfer's amount ciphertexts, looking up the transaction that verified its
+/// proof into a context account when the proof was not inline.
+pub async fn transfer_ciphertexts(
+    rpc_client: &solana_client::nonblocking::rpc_client::RpcClient,
+    movement: &Movement,
+) -> Result<Option<TransferCiphertexts>> {
+    if movement.ciphertexts.is_some() {
+        return Ok(movement.ciphertexts);
+    }
+    let Some((handle, candidates)) = &movement.context_lookup else {
+        return Ok(None);
+    };
+    let proof_program = spl_token_2022::solana_zk_sdk::zk_elgamal_proof_program::id();
+    for candidate in candidates {
+        // Context accounts are fresh keypairs, so their history is a handful of transactions
+        let history = rpc_client
+            .get_signatures_for_address_with_config(candidate, GetConfirmedSignaturesForAddress2Config {
+                limit: Some(10),
+                ..GetConfirmedSignaturesForAddress2Config::default()
+            })
+            .await
+            .map_err(anyhow::Error::from);
+        let Ok(history) = crate::rpc::with_scan_hint(history, "getSignaturesForAddress") else {
+            continue;
+        };
+        for status in history.iter().filter(|status| status.err.is_none()) {
+            let transaction = fetch_transaction(rpc_client, &Signature::from_str(&status.signature)?).await?;
+            let verified = transaction.instructions.iter().find_map(|instruction| {
+                let targets_candidate = transaction.account_keys.get(instruction.program_id_index as usize) == Some(&proof_program)
+                    && instruction.accounts.first().and_then(|index| transaction.account_keys.get(*index as usize)) == Some(candidate);
+                targets_candidate.then(|| validity_context(&instruction.data)).flatten()
+            });
+            if let Some(context) = verified {
+                return Ok(extract(&context, *handle));
+            }
+        }
+    }
+    Ok(None)
+}
@@ -1,109 +1,156 @@
 use anyhow::{Result, Context};
-use solana_sdk::signature::Signer;
 use spl_token_2022::{
     extension::{
         confidential_transfer::ConfidentialTransferAccount,
         StateWithExtensions,
     },
-    solana_zk_sdk::encryption::pod::elgamal::PodElGamalCiphertext,
 };
-use crate::{config::AppConfig, crypto, utils};
+use std::path::PathBuf;
+use serde_json::json;
+use crate::{config::AppConfig, cpi, crypto, output::{self, say}, price, rpc, snapshot::AccountSnapshot, utils};
 use spl_token_2022::extension::BaseStateWithExtensions;
-use bytemuck::Zeroable;
 
-pub async fn execute(account: String) -> Result<()> {
-    println!("💼 Checking Confidential Balance...\n");
-    
-    let config = AppConfig::new()?;
+pub async fn execute(
+    account: String,
+    snapshot: Option<PathBuf>,
+    owner: Option<String>,
+) -> Result<()> {
+    say!("💼 Checking Confidential Balance...\n");
+    
+    let config = AppConfig::new()?.with_owner(owner)?;
     let account_pubkey = utils::parse_pubkey(&account)?;
     
     // Fetch account data
-    let account_data = config.rpc_client.get_account(&account_pubkey).await
+    let (account_data, context_slot) = rpc::get_account_with_slot(&config.rpc_client, &account_pubkey).await
         .context("Failed to fetch account")?;
     
     let token_account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data.data)?;
     let ct_account = token_account.get_extension::<ConfidentialTransferAccount>()?;
     
     // Fetch mint to get decimals
-    let mint_data = config.rpc_client.get_account(&token_account.base.mint).await?;
+    let (mint_data, _) = rpc::get_account_with_slot(&config.rpc_client, &token_account.base.mint).await?;
     let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data.data)?;
     let decimals = mint.base.decimals;
     
-    println!("📋 Account Information:");
-    println!("  Address: {}", account_pubkey);
-    println!("  Mint: {}", token_account.base.mint);
-    println!("  Owner: {}", token_account.base.owner);
-    println!("  Decimals: {}", decimals);
+    say!("📋 Account Information:");
+    say!("  Address: {}", account_pubkey);
+    say!("  Mint: {}", token_account.base.mint);
+    say!("  Owner: {}", token_account.base.owner);
+    say!("  Decimals: {}", decimals);
+    say!("  Context Slot: {}", context_slot);
     
     // Derive encryption keys
-    let elgamal_keypair = crypto::derive_elgamal_keypair(&config.payer);
-    let aes_key = crypto::derive_aes_key(&config.payer);
+    let elgamal_keypair = crypto::derive_elgamal_keypair(config.owner(), &account_pubkey)?;
+    let aes_key = crypto::derive_aes_key(config.owner(), &account_pubkey)?;
+    crypto::warn_derivation_scheme(config.owner(), &account_pubkey, &elgamal_keypair, &ct_account.elgamal_pubkey);
+    if token_account.base.owner != config.owner().pubkey() && !cpi::is_program_owned(&token_account.base.owner) {
+        say!("\n⚠️  Owned by {}, but keys are derived from {}; pass --owner if decryption fails",
+            token_account.base.owner, config.owner().pubkey());
+    }
     
-    println!("\n🔐 Encryption Keys:");
-    println!("  ElGamal Public Key: {:?}", ct_account.elgamal_pubkey);
-    println!("  Approved: {}", bool::from(ct_account.approved));
+    say!("\n🔐 Encryption Keys:");
+    say!("  ElGamal Public Key: {:?}", ct_account.elgamal_pubkey);
+    say!("  Approved: {}", bool::from(ct_account.approved));
+    say!("  Confidential Credits: {}", if bool::from(ct_account.allow_confidential_credits) { "allowed" } else { "blocked" });
+    say!("  Non-Confidential Credits: {}", if bool::from(ct_account.allow_non_confidential_credits) { "allowed" } else { "blocked" });
     
-    println!("\n🔓 Decrypting Balances...");
+    say!("\n🔓 Decrypting Balances...");
     
     // Decrypt available balance (fast - uses AES)
-    let available_balance = if ct_account.available_balance == PodElGamalCiphertext::zeroed() {
-        0u64
-    } else {
-        aes_key.decrypt(&ct_account.decryptable_available_balance.try_into()?)
-            .ok_or_else(|| anyhow::anyhow!("Failed to decrypt available balance"))?
-    };
-    
-    println!("  ✅ Available Balance (spendable): {}", 
+    let available_balance = crypto::decrypt_available_balance(
+        &elgamal_keypair,
+        &aes_key,
+        &ct_account.available_balance,
+        &ct_account.decryptable_available_balance,
+    )?;
+    
+    say!("  ✅ Available Balance (spendable): {}", 
         utils::format_amount(available_balance, decimals));
     
     // Decrypt pending balance (can be slow)
-    println!("\n  Decrypting pending balance...");
-    
-    let pending_balance_lo = elgamal_keypair.secret().decrypt_u32(&ct_account.pending_balance_lo.try_into()?)
-        .ok_or_else(|| anyhow::anyhow!("Failed to decrypt pending balance lo"))?;
-    
-    let pending_balance_hi = elgamal_keypair.secret().decrypt_u32(&ct_account.pending_balance_hi.try_into()?)
-        .ok_or_else(|| anyhow::anyhow!("Failed to decrypt pending balance hi"))?;
+    say!("\n  Decrypting pending balance...");
     
-    // Combine lo (16-bit) and hi (32-bit)
-    let pending_balance = (pending_balance_lo as u64) + ((pending_balance_hi as u64) << 16);
+    let pending_balance = crypto::decrypt_pending_balance(
+        &elgamal_keypair,
+        &ct_account.pending_balance_lo,
+        &ct_account.pending_balance_hi,
+    )?;
     
-    println!("  ✅ Pending Balance (incoming): {}", 
+    say!("  ✅ Pending Balance (incoming): {}", 
         utils::format_amount(pending_balance, decimals));
     
-    // Total balance
-    let total_balance = available_balance + pending_balance;
+    // Total balance, in u128 since the two halves together may not fit a u64
+    let total_balance = u128::from(available_balance) + u128::from(pending_balance);
     
-    println!("\n💰 Balance Summary:");
-    println!("  ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-    println!("  Available (spendable):  {}", utils::format_amount(available_balance, decimals));
-    println!("  Pending (incoming):     {}", utils::format_amount(pending_balance, decimals));
-    println!("  ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-    println!("  Total:                  {}", utils::format_amount(total_balance, decimals));
+    say!("\n💰 Balance Summary:");
+    say!("  ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    say!("  Available (spendable):  {}", utils::format_amount(available_balance, decimals));
+    say!("  Pending (incoming):     {}", utils::format_amount(pending_balance, decimals));
+    say!("  ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    say!("  Total:                  {}", utils::format_amount(total_balance, decimals));
     
-    println!("\n📊 Pending Balance Counter:");
-    println!("  Current: {}", u64::from(ct_account.pending_balance_credit_counter));
-    println!("  Maximum: {}", u64::from(ct_account.maximum_pending_balance_credit_counter));
+    let price = price::fetch(&token_account.base.mint).await;
+    let fiat_estimate = price.as_ref().map(|price| price.estimate(total_balance, decimals));
+    if let (Some(price), Some(estimate)) = (&price, &fiat_estimate) {
+        say!("  ≈ {} {} (estimate from --price-url, not a quote)", estimate, price.currency);
+    }
+    
+    output::emit("balance", json!({
+        "account": account_pubkey.to_string(),
+        "mint": token_account.base.mint.to_string(),
+        "owner": token_account.base.owner.to_string(),
+        "decimals": decimals,
+        "slot": context_slot,
+        "elgamal_pubkey": ct_account.elgamal_pubkey.to_string(),
+        "approved": bool::from(ct_account.approved),
+        "allow_confidential_credits": bool::from(ct_account.allow_confidential_credits),
+        "allow_non_confidential_credits": bool::from(ct_account.allow_non_confidential_credits),
+        "public_balance": token_account.base.amount,
+        "available_balance": available_balance,
+        "pending_balance": pending_balance,
+        "pending_balance_credit_counter": u64::from(ct_account.pending_balance_credit_counter),
+        "maximum_pending_balance_credit_counter": u64::from(ct_account.maximum_pending_balance_credit_counter),
+        "fiat_estimate": fiat_estimate,
+        "fiat_currency": price.map(|price| price.currency),
+    }));
+    
+    say!("\n📊 Pending Balance Counter:");
+    say!("  Current: {}", u64::from(ct_account.pending_balance_credit_counter));
+    say!("  Maximum: {}", u64::from(ct_account.maximum_pending_balance_credit_counter));
+    
+    if let Some(path) = snapshot {
+        AccountSnapshot::capture(
+            &account_pubkey,
+            context_slot,
+            &token_account.base,
+            ct_account,
+            decimals,
+            &elgamal_keypair,
+            &aes_key,
+        ).save(&path)?;
+        say!("\n📸 Snapshot saved to {}", path.display());
+        say!("  Compare later with: confidential-cli diff --account {} --from-snapshot {}", account_pubkey, path.display());
+    }
     
     if pending_balance > 0 {
-        println!("\n⚠️  Action Required:");
-        println!("  You have pending balance! Run:");
-        println!("  $ confidential-cli apply-balance --account {}", account_pubkey);
-        println!("  This will move pending balance -> available balance");
+        say!("\n⚠️  Action Required:");
+        say!("  You have pending balance! Run:");
+        say!("  $ confidential-cli apply-balance --account {}", account_pubkey);
+        say!("  This will move pending balance -> available balance");
     }
     
     if u64::from(ct_account.pending_balance_credit_counter) > u64::from(ct_account.maximum_pending_balance_credit_counter) / 2 {
-        println!("\n⚠️  Warning:");
-        println!("  Pending balance counter is over 50% of maximum");
-        println!("  Consider running 'apply-balance' soon to prevent overflow");
+        say!("\n⚠️  Warning:");
+        say!("  Pending balance counter is over 50% of maximum");
+        say!("  Consider running 'apply-balance' soon to prevent overflow");
     }
     
-    println!("\n💡 Understanding Your Balance:");
-    println!("  • Available Balance: Ready to spend immediately");
-    println!("  • Pending Balance: Received transfers not yet applied");
-    println!("  • Run 'apply-balance' to merge pending -> available");
-    println!("  • All balances are encrypted on-chain");
-    println!("  • Only you can decrypt with your ElGamal secret key");
+    say!("\n💡 Understanding Your Balance:");
+    say!("  • Available Balance: Ready to spend immediately");
+    say!("  • Pending Balance: Received transfers not yet applied");
+    say!("  • Run 'apply-balance' to merge pending -> available");
+    say!("  • All balances are encrypted on-chain");
+    say!("  • Only you can decrypt with your ElGamal secret key");
     
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,207 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::json;
+use solana_sdk::pubkey::Pubkey;
+use std::path::{Path, PathBuf};
+use crate::{
+    commands::{approve_account, confidential_mint_to, create_account, create_mint, deposit, mint_to},
+    output::{self, say},
+    utils,
+};
+
+/// Identifies reports written by `bootstrap --report`.
+pub const BOOTSTRAP_REPORT_FORMAT: &str = "confidential-bootstrap-report";
+
+/// Bumped whenever a field is removed, renamed or changes meaning.
+pub const BOOTSTRAP_REPORT_VERSION: u32 = 1;
+
+/// What `bootstrap` sets up. Only `decimals` is required; the fields mirror the flags of
+/// the commands it runs, so a manifest reads like the commands it replaces.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct Manifest {
+    decimals: u8,
+    /// Mint authority (a signer source or bare address); defaults to --keypair
+    authority: Option<String>,
+    #[serde(default)]
+    confidential_mint_burn: bool,
+    #[serde(default)]
+    require_approval: bool,
+    auditor_pubkey: Option<String>,
+    auditor_keypair: Option<String>,
+    /// Owner of the treasury account; defaults to --keypair
+    treasury_owner: Option<String>,
+    /// Minted into the treasury, in tokens like `mint-to --amount`
+    mint_amount: Option<String>,
+    /// Moved from the treasury's public balance into its pending confidential balance
+    deposit_amount: Option<String>,
+}
+
+impl Manifest {
+    fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read manifest {}", path.display()))?;
+        let manifest: Self = serde_json::from_str(&contents)
+            .with_context(|| format!("Invalid manifest {}", path.display()))?;
+        manifest.check()?;
+        Ok(manifest)
+    }
+
+    /// Catches the combinations that would only fail halfway, after the mint exists.
+    fn check(&self) -> Result<()> {
+        if self.auditor_pubkey.is_some() && self.auditor_keypair.is_some() {
+            anyhow::bail!("Give either auditor_pubkey or auditor_keypair, not both");
+        }
+        // mint-to and approve-account sign as the mint authority with --keypair
+        if self.authority.is_some() && (self.mint_amount.is_some() || self.require_approval) {
+            anyhow::bail!("mint_amount and require_approval need --keypair as the mint authority; leave out authority");
+        }
+        if self.confidential_mint_burn && self.deposit_amount.is_some() {
+            anyhow::bail!("A confidential_mint_burn mint mints straight into the pending balance; leave out deposit_amount");
+        }
+        let mint_amount = self.mint_amount.as_deref()
+            .map(|amount| utils::parse_amount(amount, self.decimals))
+            .transpose()?;
+        let deposit_amount = self.deposit_amount.as_deref()
+            .map(|amount| utils::parse_amount(amount, self.decimals))
+            .transpose()?;
+        if let Some(deposit_amount) = deposit_amount
+            && deposit_amount > mint_amount.unwrap_or_default()
+        {
+            anyhow::bail!("deposit_amount exceeds mint_amount, which is all the new treasury will hold");
+        }
+        Ok(())
+    }
+
+    fn steps(&self) -> Vec<&'static str> {
+        let mut steps = vec!["create-mint", "create-account"];
+        if self.require_approval {
+            steps.push("approve-account");
+        }
+        if self.mint_amount.is_some() {
+            steps.push(if self.confidential_mint_burn { "confidential-mint-to" } else { "mint-to" });
+        }
+        if self.deposit_amount.is_some() {
+            steps.push("deposit");
+        }
+        steps
+    }
+}
+
+/// What got created before `run` finished or stopped.
+#[derive(Default)]
+struct Progress {
+    mint: Option<Pubkey>,
+    treasury: Option<Pubkey>,
+    done: Vec<&'static str>,
+}
+
+/// Sets up a new project from one manifest: the mint, its treasury confidential account,
+/// the initial supply and deposit. Each step needs the one before it, so they run in order.
+pub async fn execute(manifest_path: PathBuf, report: Option<PathBuf>, dry_run: bool) -> Result<()> {
+    say!("🏗️  Bootstrapping Confidential Token...\n");
+
+    let manifest = Manifest::load(&manifest_path)?;
+    let steps = manifest.steps();
+    say!("📋 Plan from {}:", manifest_path.display());
+    for (index, step) in steps.iter().enumerate() {
+        say!("  {}. {}", index + 1, step);
+    }
+    if dry_run {
+        say!("\n💡 Dry run: nothing was created; run without --dry-run to go ahead");
+        return Ok(());
+    }
+
+    let mut progress = Progress::default();
+    let failure = run(&manifest, &mut progress).await.err();
+
+    say!("\n📊 Completed {}/{} step(s)", progress.done.len(), steps.len());
+    if let Some(mint) = progress.mint {
+        say!("  Mint: {}", mint);
+    }
+    if let Some(treasury) = progress.treasury {
+        say!("  Treasury: {}", treasury);
+    }
+
+    // Written even after a failure, so whatever was created isn't lost track of
+    if let Some(path) = &report {
+        let contents = json!({
+            "format": BOOTSTRAP_REPORT_FORMAT,
+            "version": BOOTSTRAP_REPORT_VERSION,
+            "manifest": manifest_path.display().to_string(),
+            "created_at": chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            "mint": progress.mint.map(|mint| mint.to_string()),
+            "treasury": progress.treasury.map(|treasury| treasury.to_string()),
+            "decimals": manifest.decimals,
+            "planned": steps,
+            "completed": progress.done,
+            "error": failure.as_ref().map(|e| format!("{:#}", e)),
+        });
+        utils::write_atomic(path, serde_json::to_string_pretty(&contents)?)
+            .with_context(|| format!("Failed to write bootstrap report {}", path.display()))?;
+        say!("\n📝 Report written to {}", path.display());
+    }
+
+    output::emit("bootstrap", json!({
+        "mint": progress.mint.map(|mint| mint.to_string()),
+        "treasury": progress.treasury.map(|treasury| treasury.to_string()),
+        "planned": steps,
+        "completed": progress.done,
+        "report": report.as_ref().map(|path| path.display().to_string()),
+    }));
+
+    if let Some(e) = failure {
+        return Err(e.context(format!("Stopped after {} of {} steps", progress.done.len(), steps.len())));
+    }
+
+    if let Some(treasury) = progress.treasury {
+        say!("\n💡 Next: apply-balance --account {} to make the deposit spendable", treasury);
+    }
+    Ok(())
+}
+
+async fn run(manifest: &Manifest, progress: &mut Progress) -> Result<()> {
+    say!("\n━━━ create-mint ━━━\n");
+    let mint = create_mint::create(
+        manifest.authority.clone(),
+        manifest.decimals,
+        manifest.confidential_mint_burn,
+        manifest.require_approval,
+        manifest.auditor_pubkey.clone(),
+        manifest.auditor_keypair.clone(),
+        None,
+        None,
+    ).await?;
+    progress.mint = Some(mint);
+    progress.done.push("create-mint");
+
+    say!("\n━━━ create-account ━━━\n");
+    let treasury = create_account::create(mint.to_string(), manifest.treasury_owner.clone(), None, None).await?;
+    progress.treasury = Some(treasury);
+    progress.done.push("create-account");
+
+    if manifest.require_approval {
+        say!("\n━━━ approve-account ━━━\n");
+        approve_account::execute(treasury.to_string()).await?;
+        progress.done.push("approve-account");
+    }
+
+    if let Some(amount) = &manifest.mint_amount {
+        if manifest.confidential_mint_burn {
+            say!("\n━━━ confidential-mint-to ━━━\n");
+            confidential_mint_to::execute(treasury.to_string(), amount.clone()).await?;
+            progress.done.push("confidential-mint-to");
+        } else {
+            say!("\n━━━ mint-to ━━━\n");
+            mint_to::execute(treasury.to_string(), amount.clone()).await?;
+            progress.done.push("mint-to");
+        }
+    }
+
+    if let Some(amount) = &manifest.deposit_amount {
+        say!("\n━━━ deposit ━━━\n");
+        deposit::execute(treasury.to_string(), amount.clone(), manifest.treasury_owner.clone()).await?;
+        progress.done.push("deposit");
+    }
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use anyhow::Result;
+use serde_json::json;
+use std::{path::PathBuf, time::Instant};
+use crate::{lookup_table, output::{self, say}};
+
+pub async fn execute(bits: u8, table_bits: u8, out: PathBuf) -> Result<()> {
+    say!("🧮 Building Discrete-Log Lookup Table...\n");
+
+    if !(16..=48).contains(&bits) {
+        anyhow::bail!("--bits must be between 16 and 48");
+    }
+    if table_bits == 0 || table_bits > bits || table_bits > 32 {
+        anyhow::bail!("--table-bits must be between 1 and min(--bits, 32)");
+    }
+
+    say!("📋 Table Details:");
+    say!("  Output: {}", out.display());
+    say!("  Decodes values below: 2^{}", bits);
+    say!("  Entries: 2^{} ({} MiB)", table_bits, (32u64 << table_bits) >> 20);
+    say!("  Lookup cost: up to 2^{} point additions per ciphertext", bits - table_bits);
+
+    let started = Instant::now();
+    let mut last_percent = None;
+    let out_for_build = out.clone();
+    tokio::task::spawn_blocking(move || {
+        lookup_table::build(&out_for_build, bits, table_bits, |written, total| {
+            let percent = written * 100 / total;
+            if last_percent != Some(percent) {
+                last_percent = Some(percent);
+                eprint!("\r  Progress: {:>3}% ({}/{})", percent, written, total);
+            }
+        })
+    })
+    .await??;
+    eprintln!();
+
+    let hash_path = lookup_table::hash_path(&out);
+    say!("\n✅ Table built in {:.1}s", started.elapsed().as_secs_f64());
+    say!("   Integrity hash: {}", hash_path.display());
+
+    output::emit("build-table", json!({
+        "path": out.display().to_string(),
+        "hash_path": hash_path.display().to_string(),
+        "bits": bits,
+        "table_bits": table_bits,
+    }));
+
+    say!("\n💡 Use it for pending balance decryption:");
+    say!("   --decrypt-table {}  (or set CONFIDENTIAL_CLI_DECRYPT_TABLE)", out.display());
+    say!("   An interrupted build resumes from {}.partial when re-run", out.display());
+
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use anyhow::{Result, Context};
+use solana_sdk::{
+    signature::Signer,
+    transaction::Transaction,
+};
+use spl_token_2022::extension::StateWithExtensions;
+use serde_json::json;
+use crate::{config::{self, AppConfig}, cpi, intent::{self, TransferIntent}, output::{self, say}, rpc, utils};
+
+pub async fn execute(account: String, amount: String) -> Result<()> {
+    say!("🔥 Burning Tokens...\n");
+
+    let config = AppConfig::new()?;
+    let account_pubkey = utils::parse_pubkey(&account)?;
+
+    // Fetch the source and its mint
+    let account_data = config.rpc_client.get_account(&account_pubkey).await?;
+    let token_account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data.data)?;
+    let mint_pubkey = token_account.base.mint;
+
+    let mint_data = config.rpc_client.get_account(&mint_pubkey).await?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data.data)?;
+    let decimals = mint.base.decimals;
+    let amount = utils::parse_amount(&amount, decimals)?;
+
+    say!("📋 Burn Details:");
+    say!("  Account: {}", account_pubkey);
+    say!("  Mint: {}", mint_pubkey);
+    say!("  Amount: {}", utils::format_amount(amount, decimals));
+    say!("  Public Balance: {}", utils::format_amount(token_account.base.amount, decimals));
+
+    // Only the public balance can be burned; confidential tokens have to be withdrawn first
+    if amount > token_account.base.amount {
+        anyhow::bail!("Insufficient public balance! Available: {}, Requested: {} (withdraw confidential tokens first)",
+            utils::format_amount(token_account.base.amount, decimals),
+            utils::format_amount(amount, decimals));
+    }
+
+    // Program-owned accounts are authorized by their program via CPI, not by the payer
+    let authority = if cpi::is_program_owned(&token_account.base.owner) {
+        token_account.base.owner
+    } else {
+        config.owner().pubkey()
+    };
+    if authority != token_account.base.owner {
+        anyhow::bail!("The keypair {} does not own account {} (owner: {})", authority, account_pubkey, token_account.base.owner);
+    }
+
+    // The checked variant makes the program verify decimals too
+    let burn_ix = spl_token_2022::instruction::burn_checked(
+        &spl_token_2022::id(),
+        &account_pubkey,
+        &mint_pubkey,
+        &authority,
+        &[], // No multisig
+        amount,
+        decimals,
+    )?;
+
+    if authority != config.owner().pubkey() {
+        return cpi::emit_instructions("burn", &authority, &[burn_ix]);
+    }
+
+    let mut transaction = Transaction::new_with_payer(
+        &[burn_ix],
+        Some(&config.payer.pubkey()),
+    );
+
+    let recent_blockhash = config.rpc_client.get_latest_blockhash().await?;
+
+    if let Some(path) = config::global_options().export_intent {
+        transaction.message.recent_blockhash = recent_blockhash;
+        let intent = TransferIntent::new("burn", &transaction)?
+            .account("account", &account_pubkey)
+            .account("mint", &mint_pubkey)
+            .amount(amount);
+        return intent::export(&intent, &path);
+    }
+
+    config.sign(&mut transaction, recent_blockhash)?;
+
+    say!("\n📤 Sending burn transaction...");
+    let signature = rpc::send_once(&config, &transaction)
+        .await
+        .context("Failed to burn tokens")?;
+
+    say!("✅ Tokens burned!");
+    say!("   Signature: {}", signature);
+
+    output::emit("burn", json!({
+        "account": account_pubkey.to_string(),
+        "mint": mint_pubkey.to_string(),
+        "amount": amount,
+        "public_balance": token_account.base.amount - amount,
+        "signature": signature.to_string(),
+    }));
+
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use anyhow::{Result, Context};
+use solana_sdk::{
+    signature::Signer,
+    transaction::Transaction,
+};
+use spl_token_2022::extension::{
+    confidential_transfer::ConfidentialTransferAccount,
+    transfer_fee::TransferFeeAmount,
+    BaseStateWithExtensions,
+    StateWithExtensions,
+};
+use serde_json::json;
+use crate::{config::{self, AppConfig}, cpi, intent::{self, TransferIntent}, output::{self, say}, rpc, utils};
+
+pub async fn execute(account: String, destination: Option<String>, owner: Option<String>) -> Result<()> {
+    say!("🗑️  Closing Token Account...\n");
+
+    let config = AppConfig::new()?.with_owner(owner)?;
+    let account_pubkey = utils::parse_pubkey(&account)?;
+    let destination = match destination {
+        Some(destination) => utils::parse_pubkey(&destination)?,
+        None => config.payer.pubkey(),
+    };
+
+    // Fetch account data
+    let account_data = config.rpc_client.get_account(&account_pubkey).await?;
+    let token_account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data.data)?;
+    let rent = account_data.lamports;
+
+    say!("📋 Close Details:");
+    say!("  Account: {}", account_pubkey);
+    say!("  Mint: {}", token_account.base.mint);
+    say!("  Rent Destination: {}", destination);
+    say!("  Rent: {} lamports", rent);
+
+    // The program rejects the close otherwise; check here for a clearer error
+    if let Ok(ct_account) = token_account.get_extension::<ConfidentialTransferAccount>() {
+        if ct_account.closable().is_err() {
+            anyhow::bail!("Confidential balance is not empty; run 'empty-account' first");
+        }
+        say!("  ✅ Confidential balance is empty");
+    }
+    if token_account.base.amount != 0 {
+        anyhow::bail!("Account still holds a public balance of {}; transfer or burn it first", token_account.base.amount);
+    }
+    let withheld = token_account.get_extension::<TransferFeeAmount>()
+        .map(|fee_amount| u64::from(fee_amount.withheld_amount))
+        .unwrap_or_default();
+    if withheld != 0 {
+        anyhow::bail!("Account still holds {} in withheld transfer fees; harvest them to the mint first", withheld);
+    }
+
+    // Program-owned accounts are authorized by their program via CPI, not by the payer
+    let authority = if cpi::is_program_owned(&token_account.base.owner) {
+        token_account.base.owner
+    } else {
+        config.owner().pubkey()
+    };
+
+    let close_ix = spl_token_2022::instruction::close_account(
+        &spl_token_2022::id(),
+        &account_pubkey,
+        &destination,
+        &authority,
+        &[], // No multisig
+    )?;
+
+    if authority != config.owner().pubkey() {
+        return cpi::emit_instructions("close-account", &authority, &[close_ix]);
+    }
+
+    let mut transaction = Transaction::new_with_payer(
+        &[close_ix],
+        Some(&config.payer.pubkey()),
+    );
+
+    let recent_blockhash = config.rpc_client.get_latest_blockhash().await?;
+
+    if let Some(path) = config::global_options().export_intent {
+        transaction.message.recent_blockhash = recent_blockhash;
+        let intent = TransferIntent::new("close-account", &transaction)?
+            .account("account", &account_pubkey)
+            .account("mint", &token_account.base.mint)
+            .account("destination", &destination);
+        return intent::export(&intent, &path);
+    }
+
+    config.sign(&mut transaction, recent_blockhash)?;
+
+    say!("\n📤 Sending close transaction...");
+    let signature = rpc::send_once(&config, &transaction)
+        .await
+        .context("Failed to close account")?;
+
+    say!("✅ Account closed!");
+    say!("   Signature: {}", signature);
+    say!("   {} lamports returned to {}", rent, destination);
+
+    output::emit("close-account", json!({
+        "account": account_pubkey.to_string(),
+        "mint": token_account.base.mint.to_string(),
+        "destination": destination.to_string(),
+        "rent_lamports": rent,
+        "signature": signature.to_string(),
+    }));
+
+    Ok(())
+}
@@ -0,0 +1,151 @@
+use anyhow::{Result, Context};
+use solana_sdk::{
+    signature::Signer,
+    transaction::Transaction,
+};
+use spl_token_2022::extension::{
+    confidential_transfer::{instruction, ConfidentialTransferAccount},
+    BaseStateWithExtensions,
+    StateWithExtensions,
+};
+use serde_json::json;
+use crate::{config::{self, AppConfig}, cpi, intent::{self, TransferIntent}, multisig, output::{self, say}, rpc, utils};
+
+/// Which kind of incoming transfer a credits command gates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Credits {
+    /// Confidential transfers into the pending balance
+    Confidential,
+    /// Plain SPL transfers into the public balance
+    NonConfidential,
+}
+
+impl Credits {
+    fn label(self) -> &'static str {
+        match self {
+            Credits::Confidential => "confidential",
+            Credits::NonConfidential => "non-confidential",
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            Credits::Confidential => "Confidential",
+            Credits::NonConfidential => "Non-Confidential",
+        }
+    }
+
+    /// The account flag, as named in payloads
+    fn field(self) -> &'static str {
+        match self {
+            Credits::Confidential => "allow_confidential_credits",
+            Credits::NonConfidential => "allow_non_confidential_credits",
+        }
+    }
+
+    fn allowed(self, ct_account: &ConfidentialTransferAccount) -> bool {
+        match self {
+            Credits::Confidential => bool::from(ct_account.allow_confidential_credits),
+            Credits::NonConfidential => bool::from(ct_account.allow_non_confidential_credits),
+        }
+    }
+}
+
+/// Allows (`enable`) or blocks incoming transfers of the given kind to `account`.
+pub async fn execute(account: String, credits: Credits, enable: bool) -> Result<()> {
+    let command = format!("{}-{}-credits", if enable { "enable" } else { "disable" }, credits.label());
+    let command = command.as_str();
+    if enable {
+        say!("📥 Enabling {} Credits...\n", credits.title());
+    } else {
+        say!("🚫 Disabling {} Credits...\n", credits.title());
+    }
+
+    let config = AppConfig::new()?;
+    let account_pubkey = utils::parse_pubkey(&account)?;
+
+    let account_data = config.rpc_client.get_account(&account_pubkey).await?;
+    let token_account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data.data)?;
+    let ct_account = token_account.get_extension::<ConfidentialTransferAccount>()
+        .map_err(|_| anyhow::anyhow!("Account {} is not configured for confidential transfers; run 'create-account' first", account_pubkey))?;
+
+    say!("📋 Account Details:");
+    say!("  Account: {}", account_pubkey);
+    say!("  Mint: {}", token_account.base.mint);
+    say!("  {} credits: {}", credits.title(), if credits.allowed(ct_account) { "allowed" } else { "blocked" });
+
+    if credits.allowed(ct_account) == enable {
+        say!("\n💡 Nothing to change");
+        return Ok(());
+    }
+
+    // Program-owned and multisig-owned accounts are authorized by their owner, not by the payer
+    let multisig_signers = multisig::signer_pubkeys(&config, &token_account.base.owner).await?;
+    let authority = if cpi::is_program_owned(&token_account.base.owner) || !multisig_signers.is_empty() {
+        token_account.base.owner
+    } else {
+        config.owner().pubkey()
+    };
+
+    let builder = match (credits, enable) {
+        (Credits::Confidential, true) => instruction::enable_confidential_credits,
+        (Credits::Confidential, false) => instruction::disable_confidential_credits,
+        (Credits::NonConfidential, true) => instruction::enable_non_confidential_credits,
+        (Credits::NonConfidential, false) => instruction::disable_non_confidential_credits,
+    };
+    let credits_ix = builder(
+        &spl_token_2022::id(),
+        &account_pubkey,
+        &authority,
+        &multisig_signers.iter().collect::<Vec<_>>(),
+    )?;
+
+    if cpi::is_program_owned(&authority) {
+        return cpi::emit_instructions(command, &authority, &[credits_ix]);
+    }
+
+    let mut transaction = Transaction::new_with_payer(
+        &[credits_ix],
+        Some(&config.payer.pubkey()),
+    );
+
+    let recent_blockhash = config.rpc_client.get_latest_blockhash().await?;
+
+    if let Some(path) = config::global_options().export_intent {
+        transaction.message.recent_blockhash = recent_blockhash;
+        let intent = TransferIntent::new(command, &transaction)?
+            .account("account", &account_pubkey)
+            .account("mint", &token_account.base.mint);
+        return intent::export(&intent, &path);
+    }
+
+    config.sign(&mut transaction, recent_blockhash)?;
+
+    say!("\n📤 Sending transaction...");
+    let signature = rpc::send_once(&config, &transaction)
+        .await
+        .with_context(|| format!("Failed to {} {} credits", if enable { "enable" } else { "disable" }, credits.label()))?;
+
+    say!("✅ {} credits {}!", credits.title(), if enable { "enabled" } else { "disabled" });
+    say!("   Signature: {}", signature);
+
+    let mut payload = json!({
+        "account": account_pubkey.to_string(),
+        "mint": token_account.base.mint.to_string(),
+        "signature": signature.to_string(),
+    });
+    payload[credits.field()] = json!(enable);
+    output::emit(command, payload);
+
+    match (credits, enable) {
+        (Credits::Confidential, false) => {
+            say!("\n💡 Incoming confidential transfers will now fail; deposits and withdrawals still work");
+        }
+        (Credits::NonConfidential, false) => {
+            say!("\n💡 Plain SPL transfers into this account will now fail; confidential transfers still work");
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
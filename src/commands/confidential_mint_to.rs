@@ -0,0 +1,207 @@
+use anyhow::{Result, Context};
+use solana_sdk::{
+    signature::Signer,
+    transaction::Transaction,
+};
+use spl_token_2022::{
+    extension::{
+        confidential_mint_burn::{account_info::SupplyAccountInfo, instruction::confidential_mint_with_split_proofs, ConfidentialMintBurn},
+        confidential_transfer::{ConfidentialTransferAccount, ConfidentialTransferMint},
+        BaseStateWithExtensions,
+        StateWithExtensions,
+    },
+    solana_zk_sdk::{
+        encryption::{
+            elgamal::ElGamalPubkey,
+            pod::{auth_encryption::PodAeCiphertext, elgamal::PodElGamalPubkey},
+        },
+        zk_elgamal_proof_program::instruction::ProofInstruction,
+    },
+};
+use spl_token_confidential_transfer_proof_extraction::instruction::ProofLocation;
+use spl_token_confidential_transfer_proof_generation::mint::MintProofData;
+use serde_json::json;
+use crate::{config::AppConfig, crypto, output::{self, say}, proof_context, rpc, trace, utils};
+
+// Mint amounts are split into 16-bit lo and 32-bit hi parts for encryption, like transfers
+const MAX_MINT_AMOUNT: u64 = (1u64 << 48) - 1;
+
+pub async fn execute(account: String, amount: String) -> Result<()> {
+    say!("🪙 Confidential Mint...\n");
+
+    let config = AppConfig::new()?;
+    let account_pubkey = utils::parse_pubkey(&account)?;
+
+    // Fetch the destination and its mint
+    let account_data = config.rpc_client.get_account(&account_pubkey).await?;
+    let token_account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data.data)?;
+    let ct_account = token_account.get_extension::<ConfidentialTransferAccount>()?;
+    let mint_pubkey = token_account.base.mint;
+
+    let mint_data = config.rpc_client.get_account(&mint_pubkey).await?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data.data)?;
+    let decimals = mint.base.decimals;
+    let amount = utils::parse_amount(&amount, decimals)?;
+    let mint_burn = mint.get_extension::<ConfidentialMintBurn>()
+        .map_err(|_| anyhow::anyhow!("Mint {} does not have the ConfidentialMintBurn extension (create it with 'create-mint --confidential-mint-burn')", mint_pubkey))?;
+
+    say!("📋 Mint Details:");
+    say!("  Account: {}", account_pubkey);
+    say!("  Mint: {}", mint_pubkey);
+    say!("  Amount: {}", utils::format_amount(amount, decimals));
+
+    if amount > MAX_MINT_AMOUNT {
+        anyhow::bail!("Mint amount exceeds maximum (48-bit): {}", MAX_MINT_AMOUNT);
+    }
+
+    let mint_authority = Option::<solana_sdk::pubkey::Pubkey>::from(mint.base.mint_authority)
+        .ok_or_else(|| anyhow::anyhow!("Mint {} has no mint authority", mint_pubkey))?;
+    if mint_authority != config.owner().pubkey() {
+        anyhow::bail!("The keypair {} is not the mint authority ({})", config.owner().pubkey(), mint_authority);
+    }
+
+    // The supply keys are derived from the mint authority, as create-mint does
+    let supply_elgamal_keypair = crypto::derive_elgamal_keypair(config.owner(), &mint_pubkey)?;
+    let supply_aes_key = crypto::derive_aes_key(config.owner(), &mint_pubkey)?;
+    let supply_elgamal_pubkey: PodElGamalPubkey = (*supply_elgamal_keypair.pubkey()).into();
+    if supply_elgamal_pubkey != mint_burn.supply_elgamal_pubkey {
+        anyhow::bail!("Supply ElGamal key does not match the mint's; pass the --derivation-scheme the mint was created with");
+    }
+
+    let supply_info = SupplyAccountInfo::new(mint_burn);
+    let current_supply = supply_info.decrypted_current_supply(&supply_aes_key, &supply_elgamal_keypair)
+        .map_err(|e| anyhow::anyhow!("Failed to decrypt the confidential supply: {}", e))?;
+    let new_supply = current_supply.checked_add(amount)
+        .ok_or_else(|| anyhow::anyhow!("Confidential supply would overflow"))?;
+    say!("\n📊 Confidential Supply: {} → {}",
+        utils::format_amount(current_supply, decimals),
+        utils::format_amount(new_supply, decimals));
+
+    // Audited mints need the amount encrypted under the auditor key as a third handle
+    let ct_mint = mint.get_extension::<ConfidentialTransferMint>()?;
+    let auditor_elgamal_pubkey: Option<ElGamalPubkey> = Option::<PodElGamalPubkey>::from(ct_mint.auditor_elgamal_pubkey)
+        .map(|pubkey| pubkey.try_into())
+        .transpose()?;
+    let destination_elgamal_pubkey: ElGamalPubkey = ct_account.elgamal_pubkey.try_into()?;
+
+    say!("\n🔐 Generating mint proofs...");
+    let proof_span = trace::span("generate_proofs");
+    let MintProofData {
+        equality_proof_data,
+        ciphertext_validity_proof_data_with_ciphertext,
+        range_proof_data,
+    } = supply_info.generate_split_mint_proof_data(
+        amount,
+        &supply_elgamal_keypair,
+        &supply_aes_key,
+        &destination_elgamal_pubkey,
+        auditor_elgamal_pubkey.as_ref(),
+    ).map_err(|e| anyhow::anyhow!("Failed to generate mint proofs: {}", e))?;
+
+    say!("  ✅ Ciphertext-commitment equality proof generated");
+    say!("  ✅ Ciphertext validity proof generated");
+    say!("  ✅ Range proof generated");
+    drop(proof_span);
+
+    let new_decryptable_supply: PodAeCiphertext = supply_aes_key.encrypt(new_supply).into();
+    let payer = config.payer.pubkey();
+    let mint_with_proofs = |equality, validity, range| {
+        confidential_mint_with_split_proofs(
+            &spl_token_2022::id(),
+            &account_pubkey,
+            &mint_pubkey,
+            &ciphertext_validity_proof_data_with_ciphertext.ciphertext_lo,
+            &ciphertext_validity_proof_data_with_ciphertext.ciphertext_hi,
+            &payer,
+            &[], // No multisig
+            equality,
+            validity,
+            range,
+            &new_decryptable_supply,
+        )
+    };
+
+    // Proofs travel inline when the mint fits in one packet, otherwise via context accounts
+    let inline_instructions = mint_with_proofs(
+        ProofLocation::InstructionOffset(1.try_into()?, &equality_proof_data),
+        ProofLocation::InstructionOffset(2.try_into()?, &ciphertext_validity_proof_data_with_ciphertext.proof_data),
+        ProofLocation::InstructionOffset(3.try_into()?, &range_proof_data),
+    )?;
+    let (mut all_instructions, context_accounts) = if !proof_context::use_context_accounts(&inline_instructions, &payer)? {
+        (inline_instructions, vec![])
+    } else {
+        say!("\n📤 Verifying proofs on-chain...");
+        let mut contexts = proof_context::ContextAccounts::new(&config);
+        let equality_context = contexts.create_and_verify(
+            "Equality proof",
+            ProofInstruction::VerifyCiphertextCommitmentEquality,
+            &equality_proof_data,
+        ).await?;
+        let validity_context = contexts.create_and_verify(
+            "Validity proof",
+            ProofInstruction::VerifyBatchedGroupedCiphertext3HandlesValidity,
+            &ciphertext_validity_proof_data_with_ciphertext.proof_data,
+        ).await?;
+        let range_context = contexts.create_and_verify(
+            "Range proof",
+            ProofInstruction::VerifyBatchedRangeProofU128,
+            &range_proof_data,
+        ).await?;
+
+        let instructions = mint_with_proofs(
+            ProofLocation::ContextStateAccount(&equality_context),
+            ProofLocation::ContextStateAccount(&validity_context),
+            ProofLocation::ContextStateAccount(&range_context),
+        );
+        match instructions {
+            Ok(instructions) => (instructions, contexts.into_accounts()),
+            Err(e) => return Err(contexts.close_after_error(e.into()).await),
+        }
+    };
+
+    // Reclaim the context accounts' rent in the same transaction
+    all_instructions.extend(proof_context::close_instructions(&config, &context_accounts));
+
+    // Any failure from here on leaves the context accounts unused, so they are closed
+    let sent = async {
+        let mut transaction = Transaction::new_with_payer(
+            &all_instructions,
+            Some(&payer),
+        );
+
+        let recent_blockhash = config.rpc_client.get_latest_blockhash().await?;
+        config.sign(&mut transaction, recent_blockhash)?;
+
+        say!("\n📤 Sending mint transaction...");
+        rpc::send_once(&config, &transaction)
+            .await
+            .context("Failed to mint")
+    }.await;
+    let signature = match sent {
+        Ok(signature) => signature,
+        Err(e) => return Err(proof_context::close_after_error(&config, &context_accounts, e).await),
+    };
+
+    say!("✅ Mint successful!");
+    say!("   Signature: {}", signature);
+
+    output::emit("confidential-mint-to", json!({
+        "account": account_pubkey.to_string(),
+        "mint": mint_pubkey.to_string(),
+        "amount": amount,
+        "new_supply": new_supply,
+        "signature": signature.to_string(),
+    }));
+
+    say!("\n📚 What just happened:");
+    say!("   1. Encrypted {} under the receiver, supply and auditor ElGamal keys",
+        utils::format_amount(amount, decimals));
+    say!("   2. Proved the new encrypted supply matches its commitment, and that the");
+    say!("      amount ciphertexts are well-formed and in range");
+    say!("   3. Added the amount to the encrypted supply and the receiver's PENDING balance");
+
+    say!("\n⚠️  Next Steps:");
+    say!("   • The receiver must run 'apply-balance' before spending");
+
+    Ok(())
+}
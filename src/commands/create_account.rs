@@ -1,10 +1,17 @@
 use anyhow::{Result, Context};
 use solana_sdk::{
-    signature::{Keypair, Signer},
+    pubkey::Pubkey,
+    signature::Signer,
     transaction::Transaction,
 };
 use spl_token_2022::{
-    extension::ExtensionType,
+    extension::{
+        confidential_transfer::{ConfidentialTransferAccount, ConfidentialTransferMint},
+        confidential_transfer_fee::ConfidentialTransferFeeConfig,
+        BaseStateWithExtensions,
+        ExtensionType,
+        StateWithExtensions,
+    },
     solana_zk_sdk::{
         encryption::pod::auth_encryption::PodAeCiphertext,
         zk_elgamal_proof_program::{
@@ -15,46 +22,190 @@ use spl_token_2022::{
 };
 use spl_token_confidential_transfer_proof_extraction::instruction::ProofLocation;
 use std::num::NonZero;
-use crate::{config::AppConfig, crypto, utils};
+use std::path::PathBuf;
+use serde_json::json;
+use crate::{config::{self, AppConfig}, cpi, crypto::{self, DerivationScheme}, guardrails, multisig, output::{self, say}, proof_context::{self, ProofStrategy}, receipt::CreationReceipt, rpc, signer, trace, utils, vanity};
+
+/// Identifies provisioning reports written by `create-account --count --report`.
+pub const PROVISIONING_REPORT_FORMAT: &str = "confidential-provisioning-report";
+
+/// Bumped whenever a field is removed, renamed or changes meaning.
+pub const PROVISIONING_REPORT_VERSION: u32 = 1;
+
+pub async fn execute(
+    mint: String,
+    owner_path: Option<String>,
+    receipt: Option<PathBuf>,
+    count: u32,
+    report: Option<PathBuf>,
+    starts_with: Option<String>,
+) -> Result<()> {
+    if count == 1 && report.is_none() {
+        return create(mint, owner_path, receipt, starts_with).await.map(|_| ());
+    }
+    provision(mint, owner_path, count, report, starts_with).await
+}
+
+/// Creates `count` accounts one after another. Each needs its own owner signature, and a
+/// prompting signer can't be asked for several at once, so nothing runs in parallel.
+async fn provision(
+    mint: String,
+    owner_path: Option<String>,
+    count: u32,
+    report: Option<PathBuf>,
+    starts_with: Option<String>,
+) -> Result<()> {
+    if count == 0 {
+        anyhow::bail!("--count must be at least 1");
+    }
+    let config = AppConfig::new()?;
+    let scheme = config::global_options().derivation_scheme;
+    if scheme == DerivationScheme::Legacy && count > 1 {
+        say!("⚠️  Legacy keys are derived from the owner alone: all {} accounts will share one ElGamal key", count);
+        say!("   Use --derivation-scheme spl-token for per-account keys\n");
+    }
+
+    let mut accounts = Vec::new();
+    let mut failure = None;
+    for index in 0..count {
+        say!("━━━ Account {}/{} ━━━\n", index + 1, count);
+        match create(mint.clone(), owner_path.clone(), None, starts_with.clone()).await {
+            Ok(account) => accounts.push(account),
+            Err(e) => {
+                failure = Some(e);
+                break;
+            }
+        }
+        say!();
+    }
+
+    // Read back what landed, so the report reflects the chain rather than intentions
+    let mut entries = Vec::new();
+    for (index, account) in accounts.iter().enumerate() {
+        let account_data = config.rpc_client.get_account(account).await?;
+        let token_account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data.data)?;
+        let ct_account = token_account.get_extension::<ConfidentialTransferAccount>().ok();
+        entries.push(json!({
+            "index": index,
+            "account": account.to_string(),
+            "owner": token_account.base.owner.to_string(),
+            "elgamal_pubkey": ct_account.map(|ct_account| ct_account.elgamal_pubkey.to_string()),
+            "approved": ct_account.map(|ct_account| bool::from(ct_account.approved)),
+        }));
+    }
+    let owner = entries.first()
+        .and_then(|entry| entry["owner"].as_str().map(String::from))
+        .unwrap_or_else(|| config.owner().pubkey().to_string());
+
+    say!("📊 Provisioned {}/{} account(s):", accounts.len(), count);
+    for account in &accounts {
+        say!("  {}", account);
+    }
+
+    // Written even after a failure, so the accounts that did get created aren't lost track of
+    if let Some(path) = &report {
+        let contents = json!({
+            "format": PROVISIONING_REPORT_FORMAT,
+            "version": PROVISIONING_REPORT_VERSION,
+            "mint": mint,
+            "owner": owner,
+            "derivation_scheme": scheme.name(),
+            "requested": count,
+            "created_at": chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            "accounts": entries,
+            "error": failure.as_ref().map(|e| format!("{:#}", e)),
+        });
+        utils::write_atomic(path, serde_json::to_string_pretty(&contents)?)
+            .with_context(|| format!("Failed to write provisioning report {}", path.display()))?;
+        say!("\n📝 Report written to {}", path.display());
+    }
+
+    output::emit("create-accounts", json!({
+        "mint": mint,
+        "owner": owner,
+        "requested": count,
+        "created": accounts.len(),
+        "accounts": accounts.iter().map(|account| account.to_string()).collect::<Vec<_>>(),
+        "report": report.map(|path| path.display().to_string()),
+    }));
+
+    match failure {
+        Some(e) => Err(e.context(format!("Stopped after {} of {} accounts", accounts.len(), count))),
+        None => Ok(()),
+    }
+}
 
-pub async fn execute(mint: String, owner_path: Option<String>) -> Result<()> {
-    println!("👤 Creating Confidential Token Account...\n");
+/// Creates and configures the account, returning its address. With `starts_with`, the
+/// address is ground to start with that prefix.
+pub async fn create(
+    mint: String,
+    owner_path: Option<String>,
+    receipt: Option<PathBuf>,
+    starts_with: Option<String>,
+) -> Result<Pubkey> {
+    say!("👤 Creating Confidential Token Account...\n");
     
     let config = AppConfig::new()?;
     let mint_pubkey = utils::parse_pubkey(&mint)?;
     
-    let owner = if let Some(path) = owner_path {
-        AppConfig::load_keypair(&path)?
-    } else {
-        config.payer.insecure_clone()
+    // An off-curve --owner (a PDA) cannot sign; the payer's keys stand in for encryption
+    // and the owning program has to configure the account itself
+    let owner_pda = owner_path
+        .as_deref()
+        .and_then(|source| utils::parse_pubkey(source).ok())
+        .filter(cpi::is_program_owned);
+    // Neither can an SPL token multisig --owner; there the --multisig-signers sign configure
+    let owner_multisig = owner_path
+        .as_deref()
+        .and_then(|source| utils::parse_pubkey(source).ok())
+        .filter(|owner| !cpi::is_program_owned(owner) && !config.multisig_signers.is_empty());
+    let multisig_signers = match owner_multisig {
+        Some(owner) => multisig::signer_pubkeys(&config, &owner).await?,
+        None => Vec::new(),
+    };
+    let owner_signer = match owner_pda.or(owner_multisig) {
+        Some(_) => None,
+        None => owner_path
+            .map(|source| signer::resolve_signer(&source, "owner"))
+            .transpose()?,
     };
+    let owner = owner_signer.as_deref().unwrap_or(config.owner());
+    let owner_pubkey = owner_pda.or(owner_multisig).unwrap_or_else(|| owner.pubkey());
     
-    let account_keypair = Keypair::new();
+    let account_keypair = vanity::keypair(starts_with.as_deref())?;
     
-    println!("📋 Account Details:");
-    println!("  Address: {}", account_keypair.pubkey());
-    println!("  Owner: {}", owner.pubkey());
-    println!("  Mint: {}", mint_pubkey);
+    say!("📋 Account Details:");
+    say!("  Address: {}", account_keypair.pubkey());
+    say!("  Owner: {}", owner_pubkey);
+    say!("  Mint: {}", mint_pubkey);
+    guardrails::check_creation(&config, &owner_pubkey, None).await?;
     
     // Derive encryption keys - THIS IS CRITICAL
-    let elgamal_keypair = crypto::derive_elgamal_keypair(&owner);
-    let aes_key = crypto::derive_aes_key(&owner);
+    let elgamal_keypair = crypto::derive_elgamal_keypair(owner, &account_keypair.pubkey())?;
+    let aes_key = crypto::derive_aes_key(owner, &account_keypair.pubkey())?;
     
-    crypto::print_encryption_info(&owner);
+    crypto::print_encryption_info(owner, &account_keypair.pubkey())?;
     
-    // Calculate space with confidential transfer extension
-    let extensions = vec![ExtensionType::ConfidentialTransferAccount];
+    // Calculate space with confidential transfer extension, plus whatever the
+    // mint's extensions require (e.g. fee amounts for transfer-fee mints)
+    let mint_data = config.rpc_client.get_account(&mint_pubkey).await?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data.data)?;
+    let mut extensions = vec![ExtensionType::ConfidentialTransferAccount];
+    extensions.extend(ExtensionType::get_required_init_account_extensions(&mint.get_extension_types()?));
+    if mint.get_extension::<ConfidentialTransferFeeConfig>().is_ok() {
+        extensions.push(ExtensionType::ConfidentialTransferFeeAmount);
+    }
     let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Account>(&extensions)?;
     
     let rent = config.rpc_client
         .get_minimum_balance_for_rent_exemption(space as usize)
         .await?;
     
-    println!("\n💰 Rent: {} lamports", rent);
-    println!("📦 Account size: {} bytes", space);
+    say!("\n💰 Rent: {} lamports", rent);
+    say!("📦 Account size: {} bytes", space);
     
     // Step 1: Create the token account
-    let create_ix = solana_sdk::system_instruction::create_account(
+    let create_ix = solana_system_interface::instruction::create_account(
         &config.payer.pubkey(),
         &account_keypair.pubkey(),
         rent,
@@ -67,14 +218,14 @@ pub async fn execute(mint: String, owner_path: Option<String>) -> Result<()> {
         &spl_token_2022::id(),
         &account_keypair.pubkey(),
         &mint_pubkey,
-        &owner.pubkey(),
+        &owner_pubkey,
     )?;
     
     // Step 3: Configure confidential transfers
-    println!("\n🔐 Setting up confidential transfer components:");
-    println!("   1. Creating encrypted zero balance (decryptable by you)");
-    println!("   2. Generating pubkey validity proof");
-    println!("   3. Configuring ElGamal public key");
+    say!("\n🔐 Setting up confidential transfer components:");
+    say!("   1. Creating encrypted zero balance (decryptable by you)");
+    say!("   2. Generating pubkey validity proof");
+    say!("   3. Configuring ElGamal public key");
     
     // Create a decryptable zero balance using AES encryption
     let decryptable_zero_balance = aes_key.encrypt(0_u64);
@@ -82,75 +233,154 @@ pub async fn execute(mint: String, owner_path: Option<String>) -> Result<()> {
     
     // Generate pubkey validity proof
     // This proves that your ElGamal public key is well-formed
+    let proof_span = trace::span("generate_proofs");
     let pubkey_validity_proof_data = PubkeyValidityProofData::new(&elgamal_keypair)
         .map_err(|e| anyhow::anyhow!("Failed to create pubkey validity proof: {:?}", e))?;
+    drop(proof_span);
     
-    println!("   ✅ Pubkey validity proof generated");
-    println!("      This proves your ElGamal key is valid (ZK proof)");
-    
-    // Create the proof verification instruction using ProofInstruction enum
-    let proof_instruction = ProofInstruction::VerifyPubkeyValidity
-        .encode_verify_proof(None, &pubkey_validity_proof_data);
+    say!("   ✅ Pubkey validity proof generated");
+    say!("      This proves your ElGamal key is valid (ZK proof)");
     
     // Configure account with proof location
-    // Proof is in the previous instruction (offset -1 from configure_account)
+    // configure_account appends the proof instruction right after itself (offset 1)
     let proof_location = ProofLocation::InstructionOffset(
-        NonZero::new(-1i8).unwrap(),
+        NonZero::new(1i8).unwrap(),
         &pubkey_validity_proof_data,
     );
     
     // Configure the account with the ElGamal public key
     // This also initializes the confidential transfer extension
-    let configure_ixs = spl_token_2022::extension::confidential_transfer::instruction::configure_account(
+    let configure_account = |proof_location| spl_token_2022::extension::confidential_transfer::instruction::configure_account(
         &spl_token_2022::id(),
         &account_keypair.pubkey(),
         &mint_pubkey,
         &pod_decryptable_balance,
         u64::MAX,
-        &owner.pubkey(),
-        &[],
+        &owner_pubkey,
+        &multisig_signers.iter().collect::<Vec<_>>(),
         proof_location,
-    )?;
+    );
     
-    let mut all_instructions = vec![
-        create_ix,
-        init_account_ix,
-        proof_instruction,
-    ];
-    all_instructions.extend(configure_ixs);
+    let setup_instructions = vec![create_ix, init_account_ix];
+    let configure_instructions = configure_account(proof_location)?;
     
-    let mut transaction = Transaction::new_with_payer(
-        &all_instructions,
-        Some(&config.payer.pubkey()),
-    );
+    // Prefer a single transaction; otherwise split into setup and configure, and as a
+    // last resort verify the proof into a context account so configure stays small
+    let payer = config.payer.pubkey();
+    let all_instructions = [setup_instructions.clone(), configure_instructions.clone()].concat();
+    let batches = if owner_pda.is_some() {
+        vec![setup_instructions]
+    } else if !proof_context::use_context_accounts(&all_instructions, &payer)? {
+        vec![all_instructions]
+    } else if config::global_options().proof_strategy != ProofStrategy::Context
+        && proof_context::transaction_fits(&configure_instructions, &payer)?
+    {
+        say!("\n📦 Instructions exceed one transaction, sending setup and configure separately");
+        vec![setup_instructions, configure_instructions.clone()]
+    } else {
+        say!("\n📦 Verifying the proof into a context account");
+        let context_account = proof_context::create_and_verify(
+            &config,
+            "Pubkey validity proof",
+            ProofInstruction::VerifyPubkeyValidity,
+            &pubkey_validity_proof_data,
+        ).await?;
+        let mut configure_instructions = configure_account(ProofLocation::ContextStateAccount(&context_account))?;
+        configure_instructions.extend(proof_context::close_instructions(&config, &[context_account]));
+        vec![setup_instructions, configure_instructions]
+    };
+    
+    // configure_account must be signed by the owner (or its multisig signers) as well
+    let mut candidate_signers: Vec<&dyn Signer> = vec![config.payer.as_ref(), &account_keypair, owner];
+    candidate_signers.extend(config.multisig_signers.iter().map(|signer| signer.as_ref()));
+    
+    let mut signatures = Vec::new();
+    let mut transaction_fees = 0;
+    for (index, instructions) in batches.iter().enumerate() {
+        let mut transaction = Transaction::new_with_payer(instructions, Some(&payer));
+        
+        let signer_keys = transaction.message.signer_keys();
+        let mut signers: Vec<&dyn Signer> = Vec::new();
+        for signer in &candidate_signers {
+            if signer_keys.contains(&&signer.pubkey()) && !signers.iter().any(|s| s.pubkey() == signer.pubkey()) {
+                signers.push(*signer);
+            }
+        }
+        
+        let recent_blockhash = config.rpc_client.get_latest_blockhash().await?;
+        transaction.sign(&signers, recent_blockhash);
+        if receipt.is_some() {
+            transaction_fees += config.rpc_client.get_fee_for_message(&transaction.message).await?;
+        }
+        
+        say!("\n📤 Sending transaction {}/{}...", index + 1, batches.len());
+        rpc::sync_endpoints(&config).await?;
+        rpc::dump_instructions(&transaction);
+        let signature = config.send_client
+            .send_and_confirm_transaction(&transaction)
+            .await
+            .context("Failed to create confidential account")?;
+        say!("   Signature: {}", signature);
+        signatures.push(signature);
+    }
+    let signature = *signatures.last().expect("at least one batch");
+    
+    // Fees of a proof context account's own transactions are not included
+    if let Some(path) = &receipt {
+        CreationReceipt::new(
+            "account",
+            &account_keypair.pubkey(),
+            &mint_pubkey,
+            &owner_pubkey,
+            &payer,
+            &extensions,
+            space,
+            rent,
+            transaction_fees,
+            &signatures,
+        )
+        .elgamal_pubkey(elgamal_keypair.pubkey())
+        .save(path)?;
+    }
     
-    let recent_blockhash = config.rpc_client.get_latest_blockhash().await?;
-    transaction.sign(&[&config.payer, &account_keypair], recent_blockhash);
+    if let Some(owner_pda) = owner_pda {
+        say!("✅ Token account {} created", account_keypair.pubkey());
+        say!("   Encryption keys were derived from the payer; keep it to decrypt this account");
+        cpi::emit_instructions("create-account", &owner_pda, &configure_instructions)?;
+        return Ok(account_keypair.pubkey());
+    }
     
-    println!("\n📤 Sending transaction...");
-    let signature = config.rpc_client
-        .send_and_confirm_transaction(&transaction)
-        .await
-        .context("Failed to create confidential account")?;
+    say!("✅ Confidential token account created successfully!");
     
-    println!("✅ Confidential token account created successfully!");
-    println!("   Signature: {}", signature);
+    say!("\n🔑 Save this account address: {}", account_keypair.pubkey());
     
-    println!("\n🔑 Save this account address: {}", account_keypair.pubkey());
+    output::emit("create-account", json!({
+        "account": account_keypair.pubkey().to_string(),
+        "owner": owner_pubkey.to_string(),
+        "mint": mint_pubkey.to_string(),
+        "elgamal_pubkey": elgamal_keypair.pubkey().to_string(),
+        "rent_lamports": rent,
+        "space": space,
+        "signature": signature.to_string(),
+        "signatures": signatures.iter().map(|signature| signature.to_string()).collect::<Vec<_>>(),
+    }));
     
-    println!("\n📚 What just happened:");
-    println!("   1. Created a new Token-2022 account with ConfidentialTransfer extension");
-    println!("   2. Initialized the account with standard token functionality");
-    println!("   3. Generated a zero-knowledge proof that your ElGamal key is valid");
-    println!("   4. Configured the account with:");
-    println!("      • Encrypted zero balance (decryptable by you)");
-    println!("      • Your ElGamal public key for receiving transfers");
-    println!("      • Proof that your key is well-formed");
+    say!("\n📚 What just happened:");
+    say!("   1. Created a new Token-2022 account with ConfidentialTransfer extension");
+    say!("   2. Initialized the account with standard token functionality");
+    say!("   3. Generated a zero-knowledge proof that your ElGamal key is valid");
+    say!("   4. Configured the account with:");
+    say!("      • Encrypted zero balance (decryptable by you)");
+    say!("      • Your ElGamal public key for receiving transfers");
+    say!("      • Proof that your key is well-formed");
     
-    println!("\n💡 Next Steps:");
-    println!("   • Mint some tokens to this account using spl-token CLI");
-    println!("   • Deposit tokens to make them confidential");
-    println!("   • Use 'balance' command to check your encrypted balances");
+    say!("\n💡 Next Steps:");
+    if !mint.get_extension::<ConfidentialTransferMint>().is_ok_and(|ct_mint| bool::from(ct_mint.auto_approve_new_accounts)) {
+        say!("   • Have the mint authority run 'approve-account --account {}'", account_keypair.pubkey());
+    }
+    say!("   • Mint some tokens to this account with 'mint-to --account {}'", account_keypair.pubkey());
+    say!("   • Deposit tokens to make them confidential");
+    say!("   • Use 'balance' command to check your encrypted balances");
     
-    Ok(())
-}
\ No newline at end of file
+    Ok(account_keypair.pubkey())
+}
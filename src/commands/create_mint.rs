@@ -1,47 +1,108 @@
 use anyhow::{Result, Context};
 use solana_sdk::{
-    signature::{Keypair, Signer},
-    system_instruction,
+    pubkey::Pubkey,
+    signature::Signer,
     transaction::Transaction,
 };
 use spl_token_2022::{
     extension::{
+        confidential_mint_burn,
         confidential_transfer::instruction::initialize_mint,
         ExtensionType,
     },
+    solana_zk_sdk::encryption::pod::{auth_encryption::PodAeCiphertext, elgamal::PodElGamalPubkey},
     state::Mint,
 };
-use crate::config::AppConfig;
+use solana_system_interface::instruction as system_instruction;
+use std::path::PathBuf;
+use std::str::FromStr;
+use serde_json::json;
+use crate::{config::AppConfig, crypto, guardrails, output::{self, say}, receipt::CreationReceipt, rpc, signer, utils, vanity};
 
-pub async fn execute(authority_path: Option<String>, decimals: u8) -> Result<()> {
-    println!("🏭 Creating Confidential Mint...\n");
+#[allow(clippy::too_many_arguments)]
+pub async fn execute(
+    authority_path: Option<String>,
+    decimals: u8,
+    confidential_mint_burn: bool,
+    require_approval: bool,
+    auditor_pubkey: Option<String>,
+    auditor_keypair: Option<String>,
+    receipt: Option<PathBuf>,
+    starts_with: Option<String>,
+) -> Result<()> {
+    create(authority_path, decimals, confidential_mint_burn, require_approval, auditor_pubkey, auditor_keypair, receipt, starts_with)
+        .await
+        .map(|_| ())
+}
+
+/// Creates the mint and returns its address.
+#[allow(clippy::too_many_arguments)]
+pub async fn create(
+    authority_path: Option<String>,
+    decimals: u8,
+    confidential_mint_burn: bool,
+    require_approval: bool,
+    auditor_pubkey: Option<String>,
+    auditor_keypair: Option<String>,
+    receipt: Option<PathBuf>,
+    starts_with: Option<String>,
+) -> Result<Pubkey> {
+    say!("🏭 Creating Confidential Mint...\n");
     
     let config = AppConfig::new()?;
-    let mint_keypair = Keypair::new();
+    let mint_keypair = vanity::keypair(starts_with.as_deref())?;
     
-    let authority = if let Some(path) = authority_path {
-        AppConfig::load_keypair(&path)?
-    } else {
-        // Fixed: Use try_from instead of from_bytes
-        Keypair::try_from(&config.payer.to_bytes()[..])
-            .map_err(|e| anyhow::anyhow!("Failed to create keypair: {}", e))?
+    // The mint authority only needs to be known here, not to sign; a bare address (such
+    // as a multisig) leaves the payer's keys to stand in for the supply encryption
+    let authority_address = authority_path
+        .as_deref()
+        .and_then(|source| utils::parse_pubkey(source).ok());
+    let authority_signer = match authority_address {
+        Some(_) => None,
+        None => authority_path
+            .map(|source| signer::resolve_signer(&source, "authority"))
+            .transpose()?,
     };
+    let authority_signer = authority_signer.as_deref().unwrap_or(config.owner());
+    let authority = authority_address.unwrap_or_else(|| authority_signer.pubkey());
+    
+    say!("📋 Mint Details:");
+    say!("  Address: {}", mint_keypair.pubkey());
+    say!("  Authority: {}", authority);
+    say!("  Decimals: {}", decimals);
+    guardrails::check_creation(&config, &authority, Some(decimals)).await?;
     
-    println!("📋 Mint Details:");
-    println!("  Address: {}", mint_keypair.pubkey());
-    println!("  Authority: {}", authority.pubkey());
-    println!("  Decimals: {}", decimals);
+    // Transfer amounts are also encrypted under the auditor key, if any
+    let auditor_elgamal_pubkey: Option<PodElGamalPubkey> = match (auditor_pubkey, auditor_keypair) {
+        (Some(pubkey), _) => Some(PodElGamalPubkey::from_str(&pubkey)
+            .map_err(|_| anyhow::anyhow!("Invalid auditor ElGamal public key (expected base64): {}", pubkey))?),
+        (None, Some(source)) => {
+            let auditor_signer = signer::resolve_signer(&source, "auditor")?;
+            Some((*crypto::derive_elgamal_keypair(auditor_signer.as_ref(), &mint_keypair.pubkey())?.pubkey()).into())
+        }
+        (None, None) => None,
+    };
+    if let Some(auditor) = &auditor_elgamal_pubkey {
+        say!("  Auditor ElGamal Key: {}", auditor);
+    }
     
     // Calculate space needed for mint with confidential transfer extension
-    let extensions = vec![ExtensionType::ConfidentialTransferMint];
+    let mut extensions = vec![ExtensionType::ConfidentialTransferMint];
+    if confidential_mint_burn {
+        extensions.push(ExtensionType::ConfidentialMintBurn);
+    }
     let space = ExtensionType::try_calculate_account_len::<Mint>(&extensions)?;
     
     let rent = config.rpc_client
         .get_minimum_balance_for_rent_exemption(space)
         .await?;
     
-    println!("\n💰 Rent: {} lamports", rent);
-    println!("📦 Account size: {} bytes (with ConfidentialTransfer extension)", space);
+    say!("\n💰 Rent: {} lamports", rent);
+    if confidential_mint_burn {
+        say!("📦 Account size: {} bytes (with ConfidentialTransfer and ConfidentialMintBurn extensions)", space);
+    } else {
+        say!("📦 Account size: {} bytes (with ConfidentialTransfer extension)", space);
+    }
     
     // Create account
     let create_account_ix = system_instruction::create_account(
@@ -53,51 +114,115 @@ pub async fn execute(authority_path: Option<String>, decimals: u8) -> Result<()>
     );
     
     // Initialize confidential transfer on mint
-    // Fixed: Added the missing `auto_approve_new_accounts` parameter (4th argument)
+    // Accounts that need approval are approved by the mint authority with approve-account
     let init_confidential_transfer_ix = initialize_mint(
         &spl_token_2022::id(),
         &mint_keypair.pubkey(),
-        None,  // Authority (optional)
-        true,  // auto_approve_new_accounts - THIS WAS MISSING
-        None,  // No auditor ElGamal pubkey
+        require_approval.then_some(authority),  // Confidential transfer authority
+        !require_approval,  // auto_approve_new_accounts
+        auditor_elgamal_pubkey,
     )?;
     
+    let mut instructions = vec![create_account_ix, init_confidential_transfer_ix];
+    
+    // The supply is encrypted under keys derived from the mint authority, who mints with them
+    if confidential_mint_burn {
+        let supply_elgamal_keypair = crypto::derive_elgamal_keypair(authority_signer, &mint_keypair.pubkey())?;
+        let supply_aes_key = crypto::derive_aes_key(authority_signer, &mint_keypair.pubkey())?;
+        let decryptable_supply: PodAeCiphertext = supply_aes_key.encrypt(0).into();
+        say!("  Supply ElGamal Key: {}", supply_elgamal_keypair.pubkey());
+        instructions.push(confidential_mint_burn::instruction::initialize_mint(
+            &spl_token_2022::id(),
+            &mint_keypair.pubkey(),
+            &(*supply_elgamal_keypair.pubkey()).into(),
+            &decryptable_supply,
+        )?);
+    }
+    
     // Initialize mint
     let init_mint_ix = spl_token_2022::instruction::initialize_mint(
         &spl_token_2022::id(),
         &mint_keypair.pubkey(),
-        &authority.pubkey(),
-        Some(&authority.pubkey()),
+        &authority,
+        Some(&authority),
         decimals,
     )?;
     
+    instructions.push(init_mint_ix);
+    
     let mut transaction = Transaction::new_with_payer(
-        &[create_account_ix, init_confidential_transfer_ix, init_mint_ix],
+        &instructions,
         Some(&config.payer.pubkey()),
     );
     
     let recent_blockhash = config.rpc_client.get_latest_blockhash().await?;
-    transaction.sign(&[&config.payer, &mint_keypair], recent_blockhash);
+    transaction.sign(&[config.payer.as_ref(), &mint_keypair], recent_blockhash);
+    
+    // Only looked up for the receipt; the fee is what this message costs at send time
+    let transaction_fee = match &receipt {
+        Some(_) => config.rpc_client.get_fee_for_message(&transaction.message).await?,
+        None => 0,
+    };
     
-    println!("\n📤 Sending transaction...");
-    let signature = config.rpc_client
+    say!("\n📤 Sending transaction...");
+    rpc::sync_endpoints(&config).await?;
+    rpc::dump_instructions(&transaction);
+    let signature = config.send_client
         .send_and_confirm_transaction(&transaction)
         .await
         .context("Failed to create mint")?;
     
-    println!("✅ Mint created successfully!");
-    println!("   Signature: {}", signature);
-    println!("\n🔑 Save this mint address: {}", mint_keypair.pubkey());
+    say!("✅ Mint created successfully!");
+    say!("   Signature: {}", signature);
+    say!("\n🔑 Save this mint address: {}", mint_keypair.pubkey());
+    
+    if let Some(path) = &receipt {
+        CreationReceipt::new(
+            "mint",
+            &mint_keypair.pubkey(),
+            &mint_keypair.pubkey(),
+            &authority,
+            &config.payer.pubkey(),
+            &extensions,
+            space,
+            rent,
+            transaction_fee,
+            &[signature],
+        )
+        .auditor_elgamal_pubkey(auditor_elgamal_pubkey)
+        .save(path)?;
+    }
+    
+    output::emit("create-mint", json!({
+        "mint": mint_keypair.pubkey().to_string(),
+        "authority": authority.to_string(),
+        "decimals": decimals,
+        "rent_lamports": rent,
+        "space": space,
+        "auditor_elgamal_pubkey": auditor_elgamal_pubkey.map(|auditor| auditor.to_string()),
+        "signature": signature.to_string(),
+    }));
     
     // Educational output
-    println!("\n📚 What just happened:");
-    println!("   1. Created a new Token-2022 account");
-    println!("   2. Enabled ConfidentialTransferMint extension");
-    println!("   3. Initialized the mint with {} decimals", decimals);
-    println!("   4. Auto-approve enabled for new confidential accounts");
-    println!("\n💡 This mint now supports confidential transfers using:");
-    println!("   - Twisted ElGamal encryption for balance privacy");
-    println!("   - Zero-knowledge proofs for transfer validity");
-    
-    Ok(())
+    say!("\n📚 What just happened:");
+    say!("   1. Created a new Token-2022 account");
+    say!("   2. Enabled ConfidentialTransferMint extension");
+    say!("   3. Initialized the mint with {} decimals", decimals);
+    if auditor_elgamal_pubkey.is_some() {
+        say!("      Every transfer amount is also encrypted for the auditor");
+    }
+    if require_approval {
+        say!("   4. New confidential accounts must be approved with 'approve-account'");
+    } else {
+        say!("   4. Auto-approve enabled for new confidential accounts");
+    }
+    if confidential_mint_burn {
+        say!("   5. Enabled ConfidentialMintBurn: supply is minted with 'confidential-mint-to'");
+        say!("      and public mint-to is disabled");
+    }
+    say!("\n💡 This mint now supports confidential transfers using:");
+    say!("   - Twisted ElGamal encryption for balance privacy");
+    say!("   - Zero-knowledge proofs for transfer validity");
+    
+    Ok(mint_keypair.pubkey())
 }
\ No newline at end of file
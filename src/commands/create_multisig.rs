@@ -0,0 +1,98 @@
+use anyhow::{Result, Context};
+use solana_sdk::{
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+use spl_token_2022::{instruction::MAX_SIGNERS, state::Multisig};
+use solana_system_interface::instruction as system_instruction;
+use serde_json::json;
+use crate::{config::AppConfig, output::{self, say}, rpc, utils};
+
+/// Creates an M-of-N SPL token multisig, usable as a mint authority or account owner.
+pub async fn execute(signers: Vec<String>, threshold: u8) -> Result<()> {
+    say!("🔏 Creating Multisig...\n");
+
+    let config = AppConfig::new()?;
+    let signer_pubkeys = signers
+        .iter()
+        .map(|signer| utils::parse_pubkey(signer))
+        .collect::<Result<Vec<Pubkey>>>()?;
+
+    if signer_pubkeys.len() > MAX_SIGNERS {
+        anyhow::bail!("A multisig has at most {} signers, {} given", MAX_SIGNERS, signer_pubkeys.len());
+    }
+    if threshold == 0 || usize::from(threshold) > signer_pubkeys.len() {
+        anyhow::bail!("Threshold must be between 1 and the number of signers ({}), got {}", signer_pubkeys.len(), threshold);
+    }
+    if let Some(duplicate) = signer_pubkeys.iter().enumerate()
+        .find_map(|(index, pubkey)| signer_pubkeys[..index].contains(pubkey).then_some(pubkey))
+    {
+        anyhow::bail!("Signer {} is listed more than once", duplicate);
+    }
+
+    let multisig_keypair = Keypair::new();
+
+    say!("📋 Multisig Details:");
+    say!("  Address: {}", multisig_keypair.pubkey());
+    say!("  Threshold: {} of {}", threshold, signer_pubkeys.len());
+    for (index, pubkey) in signer_pubkeys.iter().enumerate() {
+        say!("  Signer {}: {}", index + 1, pubkey);
+    }
+
+    let rent = config.rpc_client
+        .get_minimum_balance_for_rent_exemption(Multisig::LEN)
+        .await?;
+
+    say!("\n💰 Rent: {} lamports", rent);
+    say!("📦 Account size: {} bytes", Multisig::LEN);
+
+    let create_account_ix = system_instruction::create_account(
+        &config.payer.pubkey(),
+        &multisig_keypair.pubkey(),
+        rent,
+        Multisig::LEN as u64,
+        &spl_token_2022::id(),
+    );
+    let init_multisig_ix = spl_token_2022::instruction::initialize_multisig(
+        &spl_token_2022::id(),
+        &multisig_keypair.pubkey(),
+        &signer_pubkeys.iter().collect::<Vec<_>>(),
+        threshold,
+    )?;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[create_account_ix, init_multisig_ix],
+        Some(&config.payer.pubkey()),
+    );
+
+    let recent_blockhash = config.rpc_client.get_latest_blockhash().await?;
+    transaction.sign(&[config.payer.as_ref(), &multisig_keypair], recent_blockhash);
+
+    say!("\n📤 Sending transaction...");
+    rpc::sync_endpoints(&config).await?;
+    rpc::dump_instructions(&transaction);
+    let signature = config.send_client
+        .send_and_confirm_transaction(&transaction)
+        .await
+        .context("Failed to create multisig")?;
+
+    say!("✅ Multisig created successfully!");
+    say!("   Signature: {}", signature);
+    say!("\n🔑 Save this multisig address: {}", multisig_keypair.pubkey());
+
+    output::emit("create-multisig", json!({
+        "multisig": multisig_keypair.pubkey().to_string(),
+        "threshold": threshold,
+        "signers": signer_pubkeys.iter().map(|pubkey| pubkey.to_string()).collect::<Vec<_>>(),
+        "rent_lamports": rent,
+        "signature": signature.to_string(),
+    }));
+
+    say!("\n💡 Use it as an authority or owner:");
+    say!("   • create-mint --authority {0} or create-account --owner {0}", multisig_keypair.pubkey());
+    say!("   • Then sign with {} of the signers via --multisig-signer", threshold);
+
+    Ok(())
+}
@@ -0,0 +1,68 @@
+use anyhow::Result;
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use serde_json::json;
+use spl_token_2022::solana_zk_sdk::encryption::{
+    auth_encryption::AeCiphertext,
+    pod::{auth_encryption::PodAeCiphertext, elgamal::{PodElGamalCiphertext, PodElGamalPubkey}},
+};
+use crate::{config::{self, AppConfig}, crypto::{self, DerivationScheme}, output::{self, say}, utils};
+
+// Told apart by length alone: both are fixed-size
+const ELGAMAL_CIPHERTEXT_LEN: usize = 64;
+const AE_CIPHERTEXT_LEN: usize = 36;
+
+/// Decrypts one base64 ElGamal or AE ciphertext, e.g. copied from `account-info` or a
+/// block explorer, with the keys the owner derives for `account`.
+pub async fn execute(ciphertext: String, account: Option<String>, owner: Option<String>) -> Result<()> {
+    say!("🔓 Decrypting Ciphertext...\n");
+
+    let config = AppConfig::new()?.with_owner(owner)?;
+    let account_pubkey = account.as_deref().map(utils::parse_pubkey).transpose()?;
+    // Legacy keys are the same for every account, so there the account can be left out
+    let derive_for = match account_pubkey {
+        Some(account_pubkey) => account_pubkey,
+        None if config::global_options().derivation_scheme == DerivationScheme::Legacy => config.owner().pubkey(),
+        None => anyhow::bail!("Each account has its own keys under this derivation scheme; pass the --account the ciphertext belongs to"),
+    };
+
+    let bytes = BASE64.decode(ciphertext.trim())
+        .map_err(|_| anyhow::anyhow!("--ciphertext is not base64"))?;
+    let (kind, amount, elgamal_pubkey) = match bytes.len() {
+        ELGAMAL_CIPHERTEXT_LEN => {
+            let elgamal_keypair = crypto::derive_elgamal_keypair(config.owner(), &derive_for)?;
+            let ciphertext: PodElGamalCiphertext = bytemuck::pod_read_unaligned(&bytes);
+            say!("📋 ElGamal ciphertext, decrypting with {}", elgamal_keypair.pubkey());
+            let amount = crypto::decrypt_amount(&elgamal_keypair, &ciphertext, "The amount")?;
+            ("elgamal", amount, Some(PodElGamalPubkey::from(*elgamal_keypair.pubkey())))
+        }
+        AE_CIPHERTEXT_LEN => {
+            let aes_key = crypto::derive_aes_key(config.owner(), &derive_for)?;
+            let ciphertext: AeCiphertext = bytemuck::pod_read_unaligned::<PodAeCiphertext>(&bytes).try_into()?;
+            say!("📋 AE ciphertext");
+            // An AE ciphertext authenticates, so the wrong key fails instead of yielding garbage
+            let amount = aes_key.decrypt(&ciphertext)
+                .ok_or_else(|| anyhow::anyhow!("Failed to decrypt; the ciphertext was not encrypted with the AE key of {}", derive_for))?;
+            ("ae", amount, None)
+        }
+        len => anyhow::bail!(
+            "--ciphertext is {} bytes; an ElGamal ciphertext is {} and an AE ciphertext {}",
+            len,
+            ELGAMAL_CIPHERTEXT_LEN,
+            AE_CIPHERTEXT_LEN
+        ),
+    };
+
+    say!("\n✅ Amount: {} base units", amount);
+    if kind == "elgamal" {
+        say!("   ElGamal can't tell a wrong key from a large amount; check the key above is the account's");
+    }
+
+    output::emit("decrypt", json!({
+        "kind": kind,
+        "amount": amount,
+        "account": account_pubkey.map(|account| account.to_string()),
+        "elgamal_pubkey": elgamal_pubkey.map(|pubkey| pubkey.to_string()),
+    }));
+
+    Ok(())
+}
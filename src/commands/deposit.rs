@@ -3,13 +3,14 @@ use solana_sdk::{
     signature::Signer,
     transaction::Transaction,
 };
-use spl_token_2022::extension::StateWithExtensions;
-use crate::{config::AppConfig, crypto, utils};
+use spl_token_2022::extension::{confidential_transfer::MAXIMUM_DEPOSIT_TRANSFER_AMOUNT, StateWithExtensions};
+use serde_json::json;
+use crate::{config::{self, AppConfig}, cpi, crypto, intent::{self, TransferIntent}, multisig, output::{self, say}, rpc, utils};
 
-pub async fn execute(account: String, amount: u64) -> Result<()> {
-    println!("💰 Depositing to Confidential Account...\n");
+pub async fn execute(account: String, amount: String, owner: Option<String>) -> Result<()> {
+    say!("💰 Depositing to Confidential Account...\n");
     
-    let config = AppConfig::new()?;
+    let config = AppConfig::new()?.with_owner(owner)?;
     let account_pubkey = utils::parse_pubkey(&account)?;
     
     // Fetch account to get mint
@@ -20,17 +21,38 @@ pub async fn execute(account: String, amount: u64) -> Result<()> {
     let mint_data = config.rpc_client.get_account(&token_account.base.mint).await?;
     let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data.data)?;
     let decimals = mint.base.decimals;
+    let amount = utils::parse_amount(&amount, decimals)?;
     
-    println!("📋 Deposit Details:");
-    println!("  Account: {}", account_pubkey);
-    println!("  Mint: {}", token_account.base.mint);
-    println!("  Amount: {}", utils::format_amount(amount, decimals));
-    
+    say!("📋 Deposit Details:");
+    say!("  Account: {}", account_pubkey);
+    say!("  Mint: {}", token_account.base.mint);
+    say!("  Amount: {}", utils::format_amount(amount, decimals));
+
+    if amount > token_account.base.amount {
+        anyhow::bail!("Insufficient public balance! Available: {}, Requested: {}",
+            utils::format_amount(token_account.base.amount, decimals),
+            utils::format_amount(amount, decimals));
+    }
+    // The pending balance is encrypted as a 16-bit and a 32-bit part, so larger deposits can't be represented
+    if amount > MAXIMUM_DEPOSIT_TRANSFER_AMOUNT {
+        anyhow::bail!("Deposit of {} exceeds the maximum of {} per deposit; split it into smaller deposits",
+            utils::format_amount(amount, decimals),
+            utils::format_amount(MAXIMUM_DEPOSIT_TRANSFER_AMOUNT, decimals));
+    }
+
     // Derive encryption keys for the owner
-    let elgamal_keypair = crypto::derive_elgamal_keypair(&config.payer);
+    let elgamal_keypair = crypto::derive_elgamal_keypair(config.owner(), &account_pubkey)?;
+    
+    say!("\n🔐 Encryption Info:");
+    say!("  ElGamal Public Key: {:?}", elgamal_keypair.pubkey());
     
-    println!("\n🔐 Encryption Info:");
-    println!("  ElGamal Public Key: {:?}", elgamal_keypair.pubkey());
+    // Program-owned and multisig-owned accounts are authorized by their owner, not by the payer
+    let multisig_signers = multisig::signer_pubkeys(&config, &token_account.base.owner).await?;
+    let authority = if cpi::is_program_owned(&token_account.base.owner) || !multisig_signers.is_empty() {
+        token_account.base.owner
+    } else {
+        config.owner().pubkey()
+    };
     
     // Create deposit instruction
     // This moves tokens from regular balance -> pending balance (encrypted)
@@ -40,36 +62,57 @@ pub async fn execute(account: String, amount: u64) -> Result<()> {
         &token_account.base.mint,
         amount,
         decimals,
-        &config.payer.pubkey(),
-        &[], // No multisig signers
+        &authority,
+        &multisig_signers.iter().collect::<Vec<_>>(),
     )?;
     
+    if cpi::is_program_owned(&authority) {
+        return cpi::emit_instructions("deposit", &authority, &[deposit_ix]);
+    }
+    
     let mut transaction = Transaction::new_with_payer(
         &[deposit_ix],
         Some(&config.payer.pubkey()),
     );
     
     let recent_blockhash = config.rpc_client.get_latest_blockhash().await?;
-    transaction.sign(&[&config.payer], recent_blockhash);
+
+    if let Some(path) = config::global_options().export_intent {
+        transaction.message.recent_blockhash = recent_blockhash;
+        let intent = TransferIntent::new("deposit", &transaction)?
+            .account("account", &account_pubkey)
+            .account("mint", &token_account.base.mint)
+            .amount(amount);
+        return intent::export(&intent, &path);
+    }
+
+    config.sign(&mut transaction, recent_blockhash)?;
     
-    println!("\n📤 Sending deposit transaction...");
-    let signature = config.rpc_client
-        .send_and_confirm_transaction(&transaction)
+    say!("\n📤 Sending deposit transaction...");
+    let signature = rpc::send_once(&config, &transaction)
         .await
         .context("Failed to deposit")?;
     
-    println!("✅ Deposit successful!");
-    println!("   Signature: {}", signature);
+    say!("✅ Deposit successful!");
+    say!("   Signature: {}", signature);
+    
+    output::emit("deposit", json!({
+        "account": account_pubkey.to_string(),
+        "mint": token_account.base.mint.to_string(),
+        "amount": amount,
+        "ui_amount": utils::format_amount(amount, decimals),
+        "signature": signature.to_string(),
+    }));
     
-    println!("\n📚 What just happened:");
-    println!("   1. {} tokens moved from regular balance", utils::format_amount(amount, decimals));
-    println!("   2. Amount encrypted and added to PENDING balance");
-    println!("   3. Pending balance uses ElGamal encryption");
+    say!("\n📚 What just happened:");
+    say!("   1. {} tokens moved from regular balance", utils::format_amount(amount, decimals));
+    say!("   2. Amount encrypted and added to PENDING balance");
+    say!("   3. Pending balance uses ElGamal encryption");
     
-    println!("\n⚠️  Next Steps:");
-    println!("   • Run 'apply-balance' to move pending -> available balance");
-    println!("   • Only available balance can be spent in transfers");
-    println!("   • Pending balance accumulates incoming transfers");
+    say!("\n⚠️  Next Steps:");
+    say!("   • Run 'apply-balance' to move pending -> available balance");
+    say!("   • Only available balance can be spent in transfers");
+    say!("   • Pending balance accumulates incoming transfers");
     
     Ok(())
 }
\ No newline at end of file
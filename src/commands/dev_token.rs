@@ -0,0 +1,168 @@
+use anyhow::{Result, Context};
+use sha2::{Digest, Sha256};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    signer::keypair::keypair_from_seed,
+    transaction::Transaction,
+};
+use spl_token_2022::{
+    extension::{
+        confidential_transfer::{instruction::initialize_mint, ConfidentialTransferAccount, ConfidentialTransferMint},
+        BaseStateWithExtensions,
+        ExtensionType,
+        StateWithExtensions,
+    },
+    state::Mint,
+};
+use solana_system_interface::instruction as system_instruction;
+use serde_json::json;
+use crate::{commands::create_account, config::AppConfig, guardrails, output::{self, say}, rpc, utils};
+
+// The test mint and its authority are derived from public seeds, so every copy of this
+// CLI finds the same mint and anyone can mint it: the token is worthless by design.
+const DEV_MINT_SEED: &str = "confidential-cli dev token mint v1";
+const DEV_AUTHORITY_SEED: &str = "confidential-cli dev token authority v1";
+const DEV_DECIMALS: u8 = 6;
+
+/// Mints test tokens of the shared dev mint into your confidential account,
+/// creating the mint and the account first if they don't exist yet.
+pub async fn execute(amount: String) -> Result<()> {
+    say!("🧪 Dev Token...\n");
+
+    let config = AppConfig::new()?;
+    if guardrails::is_mainnet(&config).await? {
+        anyhow::bail!("dev-token only runs on devnet, testnet or a local validator, not mainnet-beta");
+    }
+
+    let mint_keypair = seeded_keypair(DEV_MINT_SEED)?;
+    let authority_keypair = seeded_keypair(DEV_AUTHORITY_SEED)?;
+    let mint_pubkey = mint_keypair.pubkey();
+    let amount = utils::parse_amount(&amount, DEV_DECIMALS)?;
+
+    say!("📋 Dev Token:");
+    say!("  Mint: {}", mint_pubkey);
+    say!("  Authority: {} (public, anyone can mint)", authority_keypair.pubkey());
+    say!("  Amount: {}", utils::format_amount(amount, DEV_DECIMALS));
+
+    let created_mint = match config.rpc_client.get_account(&mint_pubkey).await {
+        Ok(mint_data) => {
+            let mint = StateWithExtensions::<Mint>::unpack(&mint_data.data)
+                .with_context(|| format!("{} exists but is not a mint", mint_pubkey))?;
+            if mint.get_extension::<ConfidentialTransferMint>().is_err()
+                || Option::<Pubkey>::from(mint.base.mint_authority) != Some(authority_keypair.pubkey())
+            {
+                anyhow::bail!("{} is not the dev token mint this CLI expects", mint_pubkey);
+            }
+            say!("\n♻️  Reusing the existing dev mint");
+            false
+        }
+        Err(_) => {
+            say!("\n🏭 Creating the dev mint on this cluster...");
+            create_mint(&config, &mint_keypair, &authority_keypair.pubkey()).await?;
+            true
+        }
+    };
+
+    // Any confidential account you already hold for the mint will do
+    let existing_account = rpc::get_token_accounts_by_owner(&config.scan_client, &config.owner().pubkey()).await?
+        .into_iter()
+        .find(|(_, account)| StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account.data)
+            .is_ok_and(|token_account| token_account.base.mint == mint_pubkey
+                && token_account.get_extension::<ConfidentialTransferAccount>().is_ok()))
+        .map(|(pubkey, _)| pubkey);
+    let (account_pubkey, created_account) = match existing_account {
+        Some(account) => {
+            say!("♻️  Reusing your confidential account {}", account);
+            (account, false)
+        }
+        None => {
+            say!("\n👤 You have no confidential account for the dev mint yet, creating one...\n");
+            (create_account::create(mint_pubkey.to_string(), None, None, None).await?, true)
+        }
+    };
+
+    let mint_ix = spl_token_2022::instruction::mint_to_checked(
+        &spl_token_2022::id(),
+        &mint_pubkey,
+        &account_pubkey,
+        &authority_keypair.pubkey(),
+        &[], // No multisig
+        amount,
+        DEV_DECIMALS,
+    )?;
+    let mut transaction = Transaction::new_with_payer(&[mint_ix], Some(&config.payer.pubkey()));
+    let recent_blockhash = config.rpc_client.get_latest_blockhash().await?;
+    transaction.sign(&[config.payer.as_ref(), &authority_keypair], recent_blockhash);
+
+    say!("\n📤 Minting dev tokens...");
+    let signature = rpc::send_once(&config, &transaction)
+        .await
+        .context("Failed to mint dev tokens")?;
+
+    say!("✅ Minted {} to {}", utils::format_amount(amount, DEV_DECIMALS), account_pubkey);
+    say!("   Signature: {}", signature);
+
+    output::emit("dev-token", json!({
+        "mint": mint_pubkey.to_string(),
+        "account": account_pubkey.to_string(),
+        "amount": amount,
+        "created_mint": created_mint,
+        "created_account": created_account,
+        "signature": signature.to_string(),
+    }));
+
+    say!("\n💡 The tokens are in the public balance; try the confidential flow with:");
+    say!("   1. deposit --account {} --amount {}", account_pubkey, utils::format_amount(amount, DEV_DECIMALS));
+    say!("   2. apply-balance --account {}", account_pubkey);
+    say!("   3. confidential-transfer --from {} --to <ACCOUNT> --amount 1", account_pubkey);
+
+    Ok(())
+}
+
+fn seeded_keypair(seed: &str) -> Result<Keypair> {
+    keypair_from_seed(&Sha256::digest(seed.as_bytes()))
+        .map_err(|e| anyhow::anyhow!("Failed to derive the dev token keypair: {}", e))
+}
+
+/// Auto-approving, unaudited, so any new account can receive the token at once.
+async fn create_mint(config: &AppConfig, mint_keypair: &Keypair, authority: &Pubkey) -> Result<()> {
+    let space = ExtensionType::try_calculate_account_len::<Mint>(&[ExtensionType::ConfidentialTransferMint])?;
+    let rent = config.rpc_client
+        .get_minimum_balance_for_rent_exemption(space)
+        .await?;
+
+    let instructions = [
+        system_instruction::create_account(
+            &config.payer.pubkey(),
+            &mint_keypair.pubkey(),
+            rent,
+            space as u64,
+            &spl_token_2022::id(),
+        ),
+        initialize_mint(
+            &spl_token_2022::id(),
+            &mint_keypair.pubkey(),
+            None,
+            true,
+            None,
+        )?,
+        spl_token_2022::instruction::initialize_mint(
+            &spl_token_2022::id(),
+            &mint_keypair.pubkey(),
+            authority,
+            None,
+            DEV_DECIMALS,
+        )?,
+    ];
+    let mut transaction = Transaction::new_with_payer(&instructions, Some(&config.payer.pubkey()));
+    let recent_blockhash = config.rpc_client.get_latest_blockhash().await?;
+    transaction.sign(&[config.payer.as_ref(), mint_keypair], recent_blockhash);
+
+    let signature = rpc::send_once(config, &transaction)
+        .await
+        .context("Failed to create the dev mint")?;
+    say!("✅ Dev mint created ({} lamports rent)", rent);
+    say!("   Signature: {}", signature);
+    Ok(())
+}
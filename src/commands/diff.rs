@@ -0,0 +1,92 @@
+use anyhow::Result;
+use spl_token_2022::extension::{
+    confidential_transfer::ConfidentialTransferAccount,
+    BaseStateWithExtensions,
+    StateWithExtensions,
+};
+use std::path::PathBuf;
+use serde_json::json;
+use crate::{config::AppConfig, crypto, output::{self, say}, rpc, snapshot::AccountSnapshot, utils};
+
+pub async fn execute(account: String, from_snapshot: PathBuf, owner: Option<String>) -> Result<()> {
+    say!("🔍 Diffing Account State...\n");
+
+    let config = AppConfig::new()?.with_owner(owner)?;
+    let account_pubkey = utils::parse_pubkey(&account)?;
+
+    let previous = AccountSnapshot::load(&from_snapshot)?;
+    if previous.account != account_pubkey.to_string() {
+        anyhow::bail!(
+            "Snapshot {} is for account {}, not {}",
+            from_snapshot.display(),
+            previous.account,
+            account_pubkey
+        );
+    }
+
+    // Fetch current on-chain state
+    let (account_data, slot) = rpc::get_account_with_slot(&config.rpc_client, &account_pubkey).await?;
+    let token_account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data.data)?;
+    let ct_account = token_account.get_extension::<ConfidentialTransferAccount>()?;
+
+    let mint_data = config.rpc_client.get_account(&token_account.base.mint).await?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data.data)?;
+    let decimals = mint.base.decimals;
+
+    let elgamal_keypair = crypto::derive_elgamal_keypair(config.owner(), &account_pubkey)?;
+    let aes_key = crypto::derive_aes_key(config.owner(), &account_pubkey)?;
+
+    let current = AccountSnapshot::capture(
+        &account_pubkey,
+        slot,
+        &token_account.base,
+        ct_account,
+        decimals,
+        &elgamal_keypair,
+        &aes_key,
+    );
+
+    say!("📋 Comparing:");
+    say!("  Account: {}", account_pubkey);
+    say!("  Snapshot: {} (slot {})", from_snapshot.display(), previous.slot);
+    say!("  Current:  slot {}", current.slot);
+
+    let changes = previous.diff(&current)?;
+    let changes: Vec<_> = changes.into_iter().filter(|(field, _, _)| field != "slot").collect();
+    
+    output::emit("diff", json!({
+        "account": account_pubkey.to_string(),
+        "snapshot_slot": previous.slot,
+        "current_slot": current.slot,
+        "changes": changes.iter().map(|(field, before, after)| json!({
+            "field": field,
+            "before": before,
+            "after": after,
+        })).collect::<Vec<_>>(),
+    }));
+
+    if changes.is_empty() {
+        say!("\n✅ No changes since the snapshot");
+        return Ok(());
+    }
+
+    say!("\n📝 {} field(s) changed:", changes.len());
+    for (field, before, after) in &changes {
+        say!("  • {}", field);
+        say!("      before: {}", before);
+        say!("      after:  {}", after);
+    }
+
+    if let (Some(before), Some(after)) = (previous.decrypted_available_balance, current.decrypted_available_balance) {
+        say!("\n💰 Available balance: {} → {}",
+            utils::format_amount(before, decimals),
+            utils::format_amount(after, decimals));
+    }
+    if let (Some(before), Some(after)) = (previous.decrypted_pending_balance, current.decrypted_pending_balance) {
+        say!("💰 Pending balance:   {} → {}",
+            utils::format_amount(before, decimals),
+            utils::format_amount(after, decimals));
+    }
+
+    Ok(())
+}
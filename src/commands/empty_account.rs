@@ -0,0 +1,142 @@
+use anyhow::{Result, Context};
+use solana_sdk::{
+    signature::Signer,
+    transaction::Transaction,
+};
+use spl_token_2022::{
+    extension::{
+        confidential_transfer::ConfidentialTransferAccount,
+        BaseStateWithExtensions,
+        StateWithExtensions,
+    },
+    solana_zk_sdk::{
+        encryption::{elgamal::ElGamalCiphertext, pod::elgamal::PodElGamalCiphertext},
+        zk_elgamal_proof_program::proof_data::ZeroCiphertextProofData,
+    },
+};
+use spl_token_confidential_transfer_proof_extraction::instruction::ProofLocation;
+use std::num::NonZero;
+use serde_json::json;
+use crate::{config::{self, AppConfig}, cpi, crypto, intent::{self, TransferIntent}, output::{self, say}, rpc, trace, utils};
+use bytemuck::Zeroable;
+
+pub async fn execute(account: String, owner: Option<String>) -> Result<()> {
+    say!("🧹 Emptying Confidential Account...\n");
+
+    let config = AppConfig::new()?.with_owner(owner)?;
+    let account_pubkey = utils::parse_pubkey(&account)?;
+
+    // Fetch account data
+    let account_data = config.rpc_client.get_account(&account_pubkey).await?;
+    let token_account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data.data)?;
+    let ct_account = token_account.get_extension::<ConfidentialTransferAccount>()?;
+
+    let mint_data = config.rpc_client.get_account(&token_account.base.mint).await?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data.data)?;
+    let decimals = mint.base.decimals;
+
+    say!("📋 Account Details:");
+    say!("  Account: {}", account_pubkey);
+    say!("  Mint: {}", token_account.base.mint);
+
+    // The program only accepts all-zero pending ciphertexts, which apply-balance leaves behind
+    if ct_account.pending_balance_lo != PodElGamalCiphertext::zeroed()
+        || ct_account.pending_balance_hi != PodElGamalCiphertext::zeroed()
+    {
+        anyhow::bail!("Pending balance is not empty; run 'apply-balance' and then 'withdraw' the available balance first");
+    }
+
+    let elgamal_keypair = crypto::derive_elgamal_keypair(config.owner(), &account_pubkey)?;
+    let aes_key = crypto::derive_aes_key(config.owner(), &account_pubkey)?;
+    let available_balance: ElGamalCiphertext = ct_account.available_balance.try_into()?;
+
+    if !crypto::ciphertext_encrypts(&elgamal_keypair, &available_balance, 0) {
+        let remaining = ct_account.decryptable_available_balance.try_into().ok()
+            .and_then(|ciphertext| aes_key.decrypt(&ciphertext));
+        match remaining {
+            Some(remaining) => anyhow::bail!(
+                "Available balance is {}, not zero; 'withdraw' it first",
+                utils::format_amount(remaining, decimals)
+            ),
+            None => anyhow::bail!("Available balance is not zero (or is not encrypted under your key)"),
+        }
+    }
+    say!("  ✅ Available and pending balances are zero");
+
+    say!("\n🔐 Generating zero-balance proof...");
+    let proof_span = trace::span("generate_proofs");
+    let proof_data = ZeroCiphertextProofData::new(&elgamal_keypair, &available_balance)
+        .map_err(|e| anyhow::anyhow!("Failed to generate zero-balance proof: {}", e))?;
+    say!("  ✅ Zero ciphertext proof generated");
+    drop(proof_span);
+
+    // Program-owned accounts are authorized by their program via CPI, not by the payer
+    let authority = if cpi::is_program_owned(&token_account.base.owner) {
+        token_account.base.owner
+    } else {
+        config.owner().pubkey()
+    };
+
+    // empty_account appends the proof right after itself, at offset 1
+    let instructions = spl_token_2022::extension::confidential_transfer::instruction::empty_account(
+        &spl_token_2022::id(),
+        &account_pubkey,
+        &authority,
+        &[], // No multisig
+        ProofLocation::InstructionOffset(NonZero::new(1i8).unwrap(), &proof_data),
+    )?;
+
+    if authority != config.owner().pubkey() {
+        return cpi::emit_instructions("empty-account", &authority, &instructions);
+    }
+
+    let mut transaction = Transaction::new_with_payer(
+        &instructions,
+        Some(&config.payer.pubkey()),
+    );
+
+    let recent_blockhash = config.rpc_client.get_latest_blockhash().await?;
+
+    if let Some(path) = config::global_options().export_intent {
+        transaction.message.recent_blockhash = recent_blockhash;
+        let intent = TransferIntent::new("empty-account", &transaction)?
+            .account("account", &account_pubkey)
+            .account("mint", &token_account.base.mint)
+            .ciphertext("available_balance", ct_account.available_balance)
+            .proof("zero_ciphertext", &proof_data);
+        return intent::export(&intent, &path);
+    }
+
+    config.sign(&mut transaction, recent_blockhash)?;
+
+    say!("\n📤 Sending empty-account transaction...");
+    let signature = rpc::send_once(&config, &transaction)
+        .await
+        .context("Failed to empty account")?;
+
+    say!("✅ Confidential balance emptied!");
+    say!("   Signature: {}", signature);
+
+    let public_balance = token_account.base.amount;
+    output::emit("empty-account", json!({
+        "account": account_pubkey.to_string(),
+        "mint": token_account.base.mint.to_string(),
+        "public_balance": public_balance,
+        "closable": public_balance == 0,
+        "signature": signature.to_string(),
+    }));
+
+    say!("\n📚 What just happened:");
+    say!("   1. Proved in zero knowledge that the available balance encrypts 0");
+    say!("   2. The program reset the confidential balances to all-zero ciphertexts");
+
+    if public_balance == 0 {
+        say!("\n✅ The account is now closable with 'close-account --account {}'", account_pubkey);
+    } else {
+        say!("\n⚠️  The account still holds a public balance of {}; transfer or burn it",
+            utils::format_amount(public_balance, decimals));
+        say!("   before closing the account");
+    }
+
+    Ok(())
+}
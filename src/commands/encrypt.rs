@@ -0,0 +1,45 @@
+use anyhow::Result;
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use serde_json::json;
+use spl_token_2022::solana_zk_sdk::encryption::{
+    elgamal::ElGamalPubkey,
+    pedersen::PedersenOpening,
+    pod::elgamal::{PodElGamalCiphertext, PodElGamalPubkey},
+};
+use std::str::FromStr;
+use crate::output::{self, say};
+
+/// Encrypts `amount` (base units) under an ElGamal public key with a fresh opening. Needs
+/// no keypair or cluster, so it works for anyone's key.
+pub async fn execute(amount: u64, elgamal_pubkey: String, show_opening: bool) -> Result<()> {
+    say!("🔐 Encrypting Amount...\n");
+
+    let pubkey: ElGamalPubkey = PodElGamalPubkey::from_str(&elgamal_pubkey)
+        .map_err(|_| anyhow::anyhow!("Invalid ElGamal public key (expected base64): {}", elgamal_pubkey))?
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("{} is not a valid ElGamal public key", elgamal_pubkey))?;
+
+    let opening = PedersenOpening::new_rand();
+    let ciphertext = pubkey.encrypt_with(amount, &opening);
+
+    say!("📋 Amount: {} base units", amount);
+    say!("  ElGamal Key: {}", elgamal_pubkey);
+    say!("\n✅ Ciphertext: {}", PodElGamalCiphertext::from(ciphertext));
+    say!("   Commitment: {}", BASE64.encode(ciphertext.commitment.to_bytes()));
+    say!("   Decrypt handle: {}", BASE64.encode(ciphertext.handle.to_bytes()));
+    if show_opening {
+        // The opening is what proofs about this ciphertext are built from
+        say!("   Pedersen opening: {}", BASE64.encode(opening.to_bytes()));
+    }
+
+    output::emit("encrypt", json!({
+        "amount": amount,
+        "elgamal_pubkey": elgamal_pubkey,
+        "ciphertext": PodElGamalCiphertext::from(ciphertext).to_string(),
+        "opening": show_opening.then(|| BASE64.encode(opening.to_bytes())),
+    }));
+
+    say!("\n💡 Anyone can encrypt under a public key; only its owner can decrypt with 'decrypt'");
+
+    Ok(())
+}
@@ -0,0 +1,298 @@
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use solana_client::rpc_request::RpcRequest;
+use solana_sdk::signature::Signature;
+use spl_token_2022::error::TokenError;
+use std::str::FromStr;
+use crate::{config::ReadOnlyConfig, output::{self, say}, rpc};
+
+/// A confidential-transfer failure this command knows how to recover from.
+struct FailureMode {
+    error: TokenError,
+    name: &'static str,
+    /// Log fragments that identify the failure when no error code is available
+    log_patterns: &'static [&'static str],
+    cause: &'static str,
+    recovery: &'static [&'static str],
+}
+
+const FAILURE_MODES: &[FailureMode] = &[
+    FailureMode {
+        error: TokenError::MaximumPendingBalanceCreditCounterExceeded,
+        name: "MaximumPendingBalanceCreditCounterExceeded",
+        log_patterns: &["maximum_pending_balance_credit_counter"],
+        cause: "The recipient took more credits than its pending balance allows without applying them",
+        recovery: &[
+            "apply-balance --account <RECIPIENT>   (run by the recipient's owner)",
+            "Then retry the transfer",
+        ],
+    },
+    FailureMode {
+        error: TokenError::ConfidentialTransferAccountNotApproved,
+        name: "ConfidentialTransferAccountNotApproved",
+        log_patterns: &["Account not approved for confidential transfers"],
+        cause: "The mint requires approval and this account has not been approved yet",
+        recovery: &[
+            "approve-account --account <ACCOUNT>   (signed by the mint's confidential transfer authority)",
+            "mint-info --mint <MINT> shows whether new accounts are auto-approved",
+        ],
+    },
+    FailureMode {
+        error: TokenError::ConfidentialTransferDepositsAndTransfersDisabled,
+        name: "ConfidentialTransferDepositsAndTransfersDisabled",
+        log_patterns: &["Account not accepting deposits or transfers"],
+        cause: "The receiving account has confidential credits disabled",
+        recovery: &[
+            "enable-confidential-credits --account <ACCOUNT>   (run by the account owner)",
+            "balance --account <ACCOUNT> shows which credits are allowed",
+        ],
+    },
+    FailureMode {
+        error: TokenError::NonConfidentialTransfersDisabled,
+        name: "NonConfidentialTransfersDisabled",
+        log_patterns: &["Non-confidential transfers disabled"],
+        cause: "The receiving account only accepts confidential credits",
+        recovery: &[
+            "enable-non-confidential-credits --account <ACCOUNT>   (run by the account owner)",
+            "Or send it with confidential-transfer instead",
+        ],
+    },
+    FailureMode {
+        error: TokenError::ConfidentialTransferBalanceMismatch,
+        name: "ConfidentialTransferBalanceMismatch",
+        log_patterns: &["Balance mismatch"],
+        cause: "The proofs were built against an available balance that no longer matches the account",
+        recovery: &[
+            "health   (reports stale decryptable balances)",
+            "apply-balance --account <ACCOUNT>, then retry so the proofs use the current balance",
+        ],
+    },
+    FailureMode {
+        error: TokenError::ConfidentialTransferElGamalPubkeyMismatch,
+        name: "ConfidentialTransferElGamalPubkeyMismatch",
+        log_patterns: &["ElGamal public key mismatch"],
+        cause: "The proofs were built with an ElGamal key other than the one configured on the account",
+        recovery: &[
+            "Pass the account's owner with --owner, or the --derivation-scheme it was configured with",
+            "balance --account <ACCOUNT> warns when the account uses the other derivation scheme",
+        ],
+    },
+    FailureMode {
+        error: TokenError::ProofGeneration,
+        name: "ProofGeneration",
+        log_patterns: &["proof verification failed", "Failed to generate proof"],
+        cause: "A zero-knowledge proof did not verify against the on-chain ciphertexts",
+        recovery: &[
+            "Retry: the balance may have changed between building and sending the proofs",
+            "If it keeps failing, check 'health' for a stale decryptable balance",
+        ],
+    },
+    FailureMode {
+        error: TokenError::InvalidProofInstructionOffset,
+        name: "InvalidProofInstructionOffset",
+        log_patterns: &["invalid proof instruction offset"],
+        cause: "The instruction points at a proof that is not where it expects it",
+        recovery: &[
+            "Retry with --proof-strategy context so each proof is verified into its own account",
+        ],
+    },
+    FailureMode {
+        error: TokenError::PendingBalanceNonZero,
+        name: "PendingBalanceNonZero",
+        log_patterns: &["Key rotation attempted while pending balance is not zero"],
+        cause: "Keys cannot be rotated while credits are waiting in the pending balance",
+        recovery: &[
+            "apply-balance --account <ACCOUNT>",
+            "Then rerun rotate-keys",
+        ],
+    },
+    FailureMode {
+        error: TokenError::ConfidentialTransferAccountHasBalance,
+        name: "ConfidentialTransferAccountHasBalance",
+        log_patterns: &["confidential balance is zero"],
+        cause: "The account still holds a confidential balance and cannot be closed",
+        recovery: &[
+            "apply-balance and withdraw the remaining balance",
+            "empty-account --account <ACCOUNT>, then close-account --account <ACCOUNT>",
+        ],
+    },
+    FailureMode {
+        error: TokenError::MaximumDepositAmountExceeded,
+        name: "MaximumDepositAmountExceeded",
+        log_patterns: &["Deposit amount exceeds maximum limit"],
+        cause: "A single deposit is limited to 2^48 base units",
+        recovery: &["Split it into several smaller deposits"],
+    },
+    FailureMode {
+        error: TokenError::InsufficientFunds,
+        name: "InsufficientFunds",
+        log_patterns: &["Insufficient funds"],
+        cause: "The public balance is too small for the amount",
+        recovery: &[
+            "balance --account <ACCOUNT>",
+            "withdraw from the confidential balance, or mint or receive more first",
+        ],
+    },
+    FailureMode {
+        error: TokenError::AccountFrozen,
+        name: "AccountFrozen",
+        log_patterns: &["Account is frozen"],
+        cause: "The mint's freeze authority froze one of the accounts",
+        recovery: &["Ask the freeze authority to thaw it; nothing can move until then"],
+    },
+    FailureMode {
+        error: TokenError::OwnerMismatch,
+        name: "OwnerMismatch",
+        log_patterns: &["Owner does not match"],
+        cause: "The transaction was signed by a key that does not own the account",
+        recovery: &[
+            "Pass the owner with --owner (or --multisig-signer for a multisig owner)",
+            "account-info --account <ACCOUNT> shows the owner",
+        ],
+    },
+    FailureMode {
+        error: TokenError::ExtensionNotFound,
+        name: "ExtensionNotFound",
+        log_patterns: &["Extension not found in account data"],
+        cause: "An account is not configured for confidential transfers",
+        recovery: &["create-account --mint <MINT> creates a configured account"],
+    },
+];
+
+/// Explains a failed transaction (by signature) or a token program error code (decimal,
+/// 0x hex or name), and prints the commands that usually get past it.
+pub async fn execute(error: String) -> Result<()> {
+    say!("🔎 Explaining Error...\n");
+
+    let (failure, details) = match Signature::from_str(&error) {
+        Ok(signature) => {
+            let config = ReadOnlyConfig::new()?;
+            let (failure, details) = explain_signature(&config, &signature).await?;
+            (failure, Some(details))
+        }
+        Err(_) => (Some(find_by_code(&error)?), None),
+    };
+
+    match failure {
+        Some(failure) => {
+            say!("\n❌ {} (token error {})", failure.name, failure.error.clone() as u32);
+            say!("   {}", failure.cause);
+            say!("\n💡 To recover:");
+            for (index, step) in failure.recovery.iter().enumerate() {
+                say!("   {}. {}", index + 1, step);
+            }
+        }
+        None => {
+            say!("\n🤷 Not a confidential-transfer failure this CLI knows about");
+            say!("   The logs above should name the program and error; 'support-bundle --signatures <SIG>'");
+            say!("   collects them for a bug report");
+        }
+    }
+
+    output::emit("explain-error", json!({
+        "input": error,
+        "error": failure.map(|failure| failure.name),
+        "code": failure.map(|failure| failure.error.clone() as u32),
+        "cause": failure.map(|failure| failure.cause),
+        "recovery": failure.map(|failure| failure.recovery).unwrap_or_default(),
+        "signature": details.as_ref().map(|details| details["signature"].clone()),
+        "transaction_error": details.as_ref()
+            .and_then(|details| (!details["error"].is_null()).then(|| details["error"].to_string())),
+        "failed_instruction": details.as_ref().and_then(|details| details["failed_instruction"].as_u64()),
+        "logs": details.as_ref().map(|details| details["logs"].clone()).unwrap_or_else(|| json!([])),
+    }));
+
+    Ok(())
+}
+
+fn find_by_code(input: &str) -> Result<&'static FailureMode> {
+    let code = match input.strip_prefix("0x").or_else(|| input.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => input.parse::<u32>().ok(),
+    };
+    FAILURE_MODES
+        .iter()
+        .find(|failure| match code {
+            Some(code) => failure.error.clone() as u32 == code,
+            None => failure.name.eq_ignore_ascii_case(input),
+        })
+        .ok_or_else(|| anyhow::anyhow!(
+            "Unknown error {}; pass a transaction signature, or one of: {}",
+            input,
+            FAILURE_MODES.iter().map(|failure| failure.name).collect::<Vec<_>>().join(", ")
+        ))
+}
+
+async fn explain_signature(config: &ReadOnlyConfig, signature: &Signature) -> Result<(Option<&'static FailureMode>, Value)> {
+    let params = json!([signature.to_string(), { "encoding": "json", "maxSupportedTransactionVersion": 0 }]);
+    let transaction: Value = rpc::with_scan_hint(
+        config.scan_client
+            .send(RpcRequest::GetTransaction, params)
+            .await
+            .with_context(|| format!("Failed to fetch transaction {}", signature)),
+        "getTransaction",
+    )?;
+    if transaction.is_null() {
+        anyhow::bail!("Transaction {} not found (it may have expired from the node's history)", signature);
+    }
+
+    let error = &transaction["meta"]["err"];
+    let logs: Vec<String> = transaction["meta"]["logMessages"]
+        .as_array()
+        .map(|logs| logs.iter().filter_map(|log| log.as_str().map(String::from)).collect())
+        .unwrap_or_default();
+
+    say!("📋 Transaction {}:", signature);
+    say!("  Slot: {}", transaction["slot"]);
+    if error.is_null() {
+        say!("  ✅ It succeeded; there is nothing to explain");
+        return Ok((None, json!({ "signature": signature.to_string(), "error": null, "logs": logs })));
+    }
+    say!("  Error: {}", error);
+
+    // {"InstructionError": [index, {"Custom": code}]}
+    let instruction_error = &error["InstructionError"];
+    let failed_instruction = instruction_error[0].as_u64();
+    let mut custom_code = None;
+    if let Some(index) = failed_instruction {
+        let message = &transaction["transaction"]["message"];
+        let account_keys = &message["accountKeys"];
+        let instruction = &message["instructions"][index as usize];
+        let program = instruction["programIdIndex"].as_u64()
+            .and_then(|program| account_keys[program as usize].as_str())
+            .unwrap_or("unknown");
+        say!("  Failed instruction: #{} (program {})", index, program);
+        // Custom codes only mean a TokenError when the token program raised them
+        if program == spl_token_2022::id().to_string() {
+            custom_code = instruction_error[1]["Custom"].as_u64();
+        }
+        if let Some(accounts) = instruction["accounts"].as_array() {
+            for (position, account) in accounts.iter().enumerate() {
+                let key = account.as_u64().and_then(|key| account_keys[key as usize].as_str());
+                say!("    {:>2}. {}", position, key.unwrap_or("unknown"));
+            }
+        }
+    }
+
+    say!("\n📜 Logs:");
+    for log in &logs {
+        say!("  {}", log);
+    }
+
+    let failure = FAILURE_MODES.iter().find(|failure| {
+        custom_code.is_some_and(|code| failure.error.clone() as u32 as u64 == code)
+    }).or_else(|| FAILURE_MODES.iter().find(|failure| {
+        failure.log_patterns.iter().any(|pattern| {
+            let pattern = pattern.to_lowercase();
+            logs.iter().any(|log| log.to_lowercase().contains(&pattern))
+        })
+    }));
+
+    Ok((failure, json!({
+        "signature": signature.to_string(),
+        "slot": transaction["slot"],
+        "error": error,
+        "failed_instruction": failed_instruction,
+        "logs": logs,
+    })))
+}
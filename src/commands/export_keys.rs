@@ -0,0 +1,98 @@
+use anyhow::Result;
+use serde_json::json;
+use solana_sdk::pubkey::Pubkey;
+use spl_token_2022::extension::{
+    confidential_transfer::ConfidentialTransferAccount,
+    BaseStateWithExtensions,
+    StateWithExtensions,
+};
+use spl_token_2022::solana_zk_sdk::encryption::pod::elgamal::PodElGamalPubkey;
+use std::path::PathBuf;
+use crate::{
+    config::{self, AppConfig},
+    crypto::{self, DerivationScheme},
+    keyset::{self, KeyEntry, KeysetFile},
+    output::{self, say},
+    secrets,
+    utils,
+};
+
+/// Derives the owner's encryption keys and writes them to a passphrase-encrypted keyset.
+pub async fn execute(accounts: Vec<String>, owner: Option<String>, out: PathBuf) -> Result<()> {
+    say!("🔑 Exporting Encryption Keys...\n");
+
+    let config = AppConfig::new()?.with_owner(owner)?;
+    let scheme = config::global_options().derivation_scheme;
+    let account_pubkeys = accounts
+        .iter()
+        .map(|account| utils::parse_pubkey(account))
+        .collect::<Result<Vec<Pubkey>>>()?;
+    if account_pubkeys.is_empty() && scheme != DerivationScheme::Legacy {
+        anyhow::bail!("{} keys differ per account; pass the accounts to export with --accounts", scheme.name());
+    }
+    if out.exists() {
+        anyhow::bail!("{} already exists; pick a new path so no backup is overwritten", out.display());
+    }
+
+    say!("⚠️  ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    say!("⚠️  These keys decrypt every balance and transfer amount of the");
+    say!("⚠️  accounts below. Anyone with the file AND the passphrase can");
+    say!("⚠️  read them. Store it offline and never reuse the passphrase.");
+    say!("⚠️  ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
+
+    say!("📋 Keyset:");
+    say!("  Owner: {}", config.owner().pubkey());
+    say!("  Derivation scheme: {}", scheme.name());
+
+    let mut entries = Vec::new();
+    if account_pubkeys.is_empty() {
+        // Legacy keys don't depend on the account, so one pair covers them all
+        say!("  Accounts: all (legacy keys are shared by every account of the owner)");
+        entries.push(entry(&config, &Pubkey::default(), None, scheme)?);
+    }
+    for account_pubkey in &account_pubkeys {
+        let account_data = config.rpc_client.get_account(account_pubkey).await?;
+        let token_account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data.data)?;
+        let ct_account = token_account.get_extension::<ConfidentialTransferAccount>()
+            .map_err(|_| anyhow::anyhow!("{} is not configured for confidential transfers", account_pubkey))?;
+
+        // A backup of keys that don't match the account would restore nothing
+        let elgamal_keypair = crypto::derive_elgamal_keypair(config.owner(), account_pubkey)?;
+        if PodElGamalPubkey::from(*elgamal_keypair.pubkey()) != ct_account.elgamal_pubkey {
+            crypto::warn_derivation_scheme(config.owner(), account_pubkey, &elgamal_keypair, &ct_account.elgamal_pubkey);
+            anyhow::bail!("Keys derived from {} do not match {}'s ElGamal key; pass its --owner", config.owner().pubkey(), account_pubkey);
+        }
+        say!("  Account: {} ✅", account_pubkey);
+        entries.push(entry(&config, account_pubkey, Some(account_pubkey), scheme)?);
+    }
+
+    let passphrase = keyset::passphrase(true)?;
+    say!("\n🔐 Encrypting (PBKDF2-HMAC-SHA256, AES-256-GCM-SIV)...");
+    KeysetFile::seal(config.owner().pubkey().to_string(), &entries, &passphrase)?.save(&out)?;
+
+    say!("✅ Keyset written to {}", out.display());
+
+    output::emit("export-keys", json!({
+        "path": out.display().to_string(),
+        "owner": config.owner().pubkey().to_string(),
+        "derivation_scheme": scheme.name(),
+        "accounts": account_pubkeys.iter().map(|pubkey| pubkey.to_string()).collect::<Vec<_>>(),
+    }));
+
+    say!("\n💡 Keep the passphrase apart from the file");
+    say!("   Losing the passphrase makes the file useless; losing the file is fine while you still hold the owner keypair");
+    say!("   Restore it with 'import-keys --file {}'", out.display());
+
+    Ok(())
+}
+
+fn entry(config: &AppConfig, derive_for: &Pubkey, account: Option<&Pubkey>, scheme: DerivationScheme) -> Result<KeyEntry> {
+    let elgamal_keypair = crypto::derive_elgamal_keypair(config.owner(), derive_for)?;
+    let aes_key = crypto::derive_aes_key(config.owner(), derive_for)?;
+    Ok(KeyEntry {
+        account: account.map(|account| account.to_string()),
+        derivation_scheme: scheme.name().to_string(),
+        elgamal_keypair: secrets::encode_elgamal_keypair(&elgamal_keypair).to_string(),
+        aes_key: secrets::encode_aes_key(&aes_key).to_string(),
+    })
+}
@@ -0,0 +1,150 @@
+use anyhow::Result;
+use serde_json::json;
+use solana_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
+use solana_sdk::pubkey::Pubkey;
+use spl_token_2022::{
+    extension::{
+        confidential_transfer::ConfidentialTransferAccount,
+        BaseStateWithExtensions,
+        StateWithExtensions,
+    },
+    solana_zk_sdk::encryption::pod::elgamal::PodElGamalCiphertext,
+};
+use bytemuck::Zeroable;
+use crate::{commands::apply_balance, config::AppConfig, crypto, output::{self, say}, rpc};
+
+pub async fn execute(pending_age_hours: u64, counter_threshold_percent: u64, fix: bool, owner_path: Option<String>) -> Result<()> {
+    say!("🩺 Checking Confidential Account Health...\n");
+
+    let config = AppConfig::new()?.with_owner(owner_path.clone())?;
+    let owner = config.owner().pubkey();
+
+    let accounts = rpc::get_token_accounts_by_owner(&config.scan_client, &owner).await?;
+    say!("📋 Owner: {}", owner);
+    say!("  Token-2022 accounts: {}", accounts.len());
+
+    let now = chrono::Utc::now().timestamp();
+    let mut reports = Vec::new();
+    let mut to_apply = Vec::new();
+
+    for (address, account) in &accounts {
+        let token_account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account.data)?;
+        let Ok(ct_account) = token_account.get_extension::<ConfidentialTransferAccount>() else {
+            continue;
+        };
+
+        let elgamal_keypair = crypto::derive_elgamal_keypair(config.owner(), address)?;
+        let aes_key = crypto::derive_aes_key(config.owner(), address)?;
+        let mut issues = Vec::new();
+
+        // Keys derived under the other scheme decrypt nothing below
+        if let Some(scheme) = crypto::mismatched_derivation_scheme(config.owner(), address, &elgamal_keypair, &ct_account.elgamal_pubkey) {
+            issues.push(format!("configured with {} keys; pass --derivation-scheme {}", scheme.name(), scheme.name()));
+        }
+
+        // Pending credits waiting to be applied
+        let pending_credits = u64::from(ct_account.pending_balance_credit_counter);
+        if pending_credits > 0 {
+            match oldest_recent_activity(&config, address, pending_credits).await? {
+                Some(block_time) if now - block_time >= (pending_age_hours * 3600) as i64 => {
+                    issues.push(format!(
+                        "{} pending credit(s) unapplied for at least {}h",
+                        pending_credits,
+                        (now - block_time) / 3600
+                    ));
+                    to_apply.push(*address);
+                }
+                Some(_) => {}
+                None => {
+                    issues.push(format!("{} pending credit(s) of unknown age", pending_credits));
+                    to_apply.push(*address);
+                }
+            }
+        }
+
+        // Counter close to the point where new credits are rejected
+        let maximum = u64::from(ct_account.maximum_pending_balance_credit_counter);
+        if maximum > 0 && pending_credits * 100 >= maximum * counter_threshold_percent {
+            issues.push(format!("pending credit counter at {}/{}", pending_credits, maximum));
+            if !to_apply.contains(address) {
+                to_apply.push(*address);
+            }
+        }
+
+        // Decryptable balance that no longer matches the ElGamal available balance
+        if crypto::decryptable_balance_missing(&ct_account.available_balance, &ct_account.decryptable_available_balance) {
+            issues.push("decryptable balance is missing (zeroed); 'apply-balance' rewrites it".to_string());
+            if !to_apply.contains(address) {
+                to_apply.push(*address);
+            }
+        } else if ct_account.available_balance != PodElGamalCiphertext::zeroed() {
+            let decryptable = ct_account.decryptable_available_balance.try_into().ok()
+                .and_then(|ciphertext| aes_key.decrypt(&ciphertext));
+            let available = ct_account.available_balance.try_into().ok();
+            match (decryptable, available) {
+                (Some(amount), Some(available)) if crypto::ciphertext_encrypts(&elgamal_keypair, &available, amount) => {}
+                (Some(_), _) => issues.push("decryptable balance is stale (does not match available balance)".to_string()),
+                (None, _) => issues.push("decryptable balance cannot be decrypted with your key".to_string()),
+            }
+        }
+
+        if issues.is_empty() {
+            say!("  ✅ {}", address);
+        } else {
+            say!("  ⚠️  {}", address);
+            for issue in &issues {
+                say!("      • {}", issue);
+            }
+        }
+
+        reports.push(json!({
+            "account": address.to_string(),
+            "mint": token_account.base.mint.to_string(),
+            "issues": issues,
+        }));
+    }
+
+    let unhealthy = reports.iter().filter(|report| report["issues"].as_array().is_some_and(|issues| !issues.is_empty())).count();
+    say!("\n📊 Summary: {} confidential account(s), {} need attention", reports.len(), unhealthy);
+
+    let mut fixed = Vec::new();
+    if fix && !to_apply.is_empty() {
+        say!("\n🔧 Applying pending balances...");
+        for address in &to_apply {
+            say!("\n━━━ {} ━━━", address);
+            match apply_balance::execute(address.to_string(), owner_path.clone()).await {
+                Ok(()) => fixed.push(address.to_string()),
+                Err(e) => say!("❌ Failed to apply pending balance for {}: {:#}", address, e),
+            }
+        }
+    } else if !to_apply.is_empty() {
+        say!("\n💡 Re-run with --fix to apply pending balances on {} account(s)", to_apply.len());
+    }
+
+    if unhealthy > 0 {
+        say!("\n💡 Stale or undecryptable decryptable balances usually mean another tool or key");
+        say!("   updated the account; confirm you are using the owner key that configured it");
+    }
+
+    output::emit("health", json!({
+        "owner": owner.to_string(),
+        "accounts": reports,
+        "fixed": fixed,
+    }));
+
+    Ok(())
+}
+
+// Block time of the oldest of the `count` most recent transactions touching `address`.
+// Since not every transaction is a credit, this is a lower bound on the pending age.
+async fn oldest_recent_activity(config: &AppConfig, address: &Pubkey, count: u64) -> Result<Option<i64>> {
+    let signatures = config.scan_client
+        .get_signatures_for_address_with_config(address, GetConfirmedSignaturesForAddress2Config {
+            limit: Some(count.min(1000) as usize),
+            ..GetConfirmedSignaturesForAddress2Config::default()
+        })
+        .await
+        .map_err(anyhow::Error::from);
+    let signatures = rpc::with_scan_hint(signatures, "getSignaturesForAddress")?;
+    Ok(signatures.last().and_then(|signature| signature.block_time))
+}
@@ -0,0 +1,85 @@
+use anyhow::Result;
+use serde_json::json;
+use spl_token_2022::extension::{
+    confidential_transfer::ConfidentialTransferAccount,
+    BaseStateWithExtensions,
+    StateWithExtensions,
+};
+use spl_token_2022::solana_zk_sdk::encryption::pod::elgamal::PodElGamalPubkey;
+use std::path::PathBuf;
+use crate::{
+    config::ReadOnlyConfig,
+    key_store::{self, KeyStore},
+    keyset::{self, KeysetFile},
+    output::{self, say},
+    utils,
+};
+
+/// Loads a keyset written by `export-keys` into the key store, where later commands
+/// find the keys before deriving any from the owner's signature.
+pub async fn execute(file: PathBuf) -> Result<()> {
+    say!("🔑 Importing Encryption Keys...\n");
+
+    let keyset = KeysetFile::load(&file)?;
+    say!("📋 Keyset:");
+    say!("  File: {}", file.display());
+    say!("  Owner: {}", keyset.owner);
+    say!("  Exported: {}", keyset.created_at);
+
+    let passphrase = keyset::passphrase(false)?;
+    say!("\n🔐 Decrypting...");
+    let entries = keyset.open(&passphrase)?;
+
+    // Keys that don't match the account would make every later decryption fail
+    let config = ReadOnlyConfig::new()?;
+    for entry in &entries {
+        let elgamal_keypair = entry.elgamal_keypair()?;
+        entry.aes_key()?;
+        let Some(account) = &entry.account else {
+            say!("  All accounts ({} keys) ✅", entry.derivation_scheme);
+            continue;
+        };
+        let account_pubkey = utils::parse_pubkey(account)?;
+        match config.rpc_client.get_account(&account_pubkey).await {
+            Ok(account_data) => {
+                let token_account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data.data)?;
+                let ct_account = token_account.get_extension::<ConfidentialTransferAccount>()
+                    .map_err(|_| anyhow::anyhow!("{} is not configured for confidential transfers", account))?;
+                if PodElGamalPubkey::from(*elgamal_keypair.pubkey()) != ct_account.elgamal_pubkey {
+                    anyhow::bail!("The keyset's keys for {} do not match its ElGamal key (were they rotated since the export?)", account);
+                }
+                say!("  Account: {} ✅", account);
+            }
+            Err(e) => say!("  Account: {} (not checked: {})", account, e),
+        }
+    }
+
+    let path = key_store::path();
+    let mut store = KeyStore::load_or_default(&path)?;
+    let mut replaced = 0;
+    for entry in &entries {
+        if store.insert(&keyset.owner, entry.clone()) {
+            replaced += 1;
+        }
+    }
+    store.save(&path)?;
+
+    say!("\n✅ Imported {} key set(s) into {}", entries.len(), path.display());
+    if replaced > 0 {
+        say!("   {} earlier import(s) of the same accounts were replaced", replaced);
+    }
+
+    output::emit("import-keys", json!({
+        "path": file.display().to_string(),
+        "key_store": path.display().to_string(),
+        "owner": keyset.owner,
+        "accounts": entries.iter().filter_map(|entry| entry.account.clone()).collect::<Vec<_>>(),
+        "owner_wide": entries.iter().any(|entry| entry.account.is_none()),
+        "replaced": replaced,
+    }));
+
+    say!("\n💡 balance, apply-balance, withdraw and confidential-transfer now use these keys for");
+    say!("   {}'s accounts; the key store is unencrypted, so keep the keyset file as the backup", keyset.owner);
+
+    Ok(())
+}
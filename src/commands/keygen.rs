@@ -0,0 +1,50 @@
+use anyhow::Result;
+use serde_json::json;
+use solana_sdk::signature::{Keypair, Signer, read_keypair_file};
+use std::path::PathBuf;
+use crate::{
+    keypair_file::EncryptedKeypair,
+    keyset,
+    output::{self, say},
+};
+
+/// Writes a passphrase-encrypted keypair file: a new keypair, or `from`'s plaintext one.
+pub async fn execute(out: PathBuf, from: Option<PathBuf>) -> Result<()> {
+    say!("🔑 Generating Encrypted Keypair...\n");
+
+    if out.exists() {
+        anyhow::bail!("{} already exists; pick a new path so no keypair is overwritten", out.display());
+    }
+    let keypair = match &from {
+        Some(path) => read_keypair_file(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read keypair {}: {}", path.display(), e))?,
+        None => Keypair::new(),
+    };
+
+    say!("📋 Keypair:");
+    say!("  Public key: {}", keypair.pubkey());
+    match &from {
+        Some(path) => say!("  Source: {}", path.display()),
+        None => say!("  Source: newly generated"),
+    }
+
+    let passphrase = keyset::read_passphrase("CONFIDENTIAL_CLI_KEYPAIR_PASSPHRASE", "New keypair passphrase: ", true)?;
+    say!("\n🔐 Encrypting (PBKDF2-HMAC-SHA256, AES-256-GCM-SIV)...");
+    EncryptedKeypair::seal(&keypair, &passphrase)?.save(&out)?;
+
+    say!("✅ Keypair written to {}", out.display());
+
+    output::emit("keygen", json!({
+        "path": out.display().to_string(),
+        "pubkey": keypair.pubkey().to_string(),
+        "source": from.as_ref().map(|path| path.display().to_string()),
+    }));
+
+    say!("\n💡 Use it anywhere a keypair file goes, e.g. --keypair {}", out.display());
+    say!("   The passphrase is asked for each run, or read from CONFIDENTIAL_CLI_KEYPAIR_PASSPHRASE");
+    if from.is_some() {
+        say!("   Delete the plaintext keypair once you've checked the encrypted one works");
+    }
+
+    Ok(())
+}
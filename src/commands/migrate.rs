@@ -0,0 +1,257 @@
+use anyhow::{Context, Result};
+use serde_json::{json, Map, Value};
+use std::path::{Path, PathBuf};
+use crate::{
+    balance_proof::{self, BalanceProof},
+    commands::{acknowledge_transfer, audit_session, bootstrap, create_account, support_bundle},
+    intent::{self, TransferIntent},
+    key_store::{self, KeyStore},
+    keypair_file::{self, EncryptedKeypair},
+    keyset::{self, KeysetFile},
+    lookup_table,
+    output::{self, say},
+    proof_data::{self, ProofDataFile},
+    receipt::{self, CreationReceipt},
+    schedule::{self, TransferSchedule},
+    snapshot::{self, AccountSnapshot},
+    utils,
+};
+
+/// The JSON files this CLI writes, each versioned by its own `format`/`version` fields.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Artifact {
+    Intent,
+    Snapshot,
+    Receipt,
+    AuditSession,
+    SupportBundle,
+    Schedule,
+    ProvisioningReport,
+    BootstrapReport,
+    Acknowledgment,
+    Keyset,
+    KeyStore,
+    EncryptedKeypair,
+    BalanceProof,
+    ProofData,
+}
+
+impl Artifact {
+    const ALL: [Artifact; 14] = [
+        Artifact::Intent,
+        Artifact::Snapshot,
+        Artifact::Receipt,
+        Artifact::AuditSession,
+        Artifact::SupportBundle,
+        Artifact::Schedule,
+        Artifact::ProvisioningReport,
+        Artifact::BootstrapReport,
+        Artifact::Acknowledgment,
+        Artifact::Keyset,
+        Artifact::KeyStore,
+        Artifact::EncryptedKeypair,
+        Artifact::BalanceProof,
+        Artifact::ProofData,
+    ];
+
+    fn format(self) -> &'static str {
+        match self {
+            Artifact::Intent => intent::INTENT_FORMAT,
+            Artifact::Snapshot => snapshot::SNAPSHOT_FORMAT,
+            Artifact::Receipt => receipt::RECEIPT_FORMAT,
+            Artifact::AuditSession => audit_session::AUDIT_SESSION_FORMAT,
+            Artifact::SupportBundle => support_bundle::SUPPORT_BUNDLE_FORMAT,
+            Artifact::Schedule => schedule::SCHEDULE_FORMAT,
+            Artifact::ProvisioningReport => create_account::PROVISIONING_REPORT_FORMAT,
+            Artifact::BootstrapReport => bootstrap::BOOTSTRAP_REPORT_FORMAT,
+            Artifact::Acknowledgment => acknowledge_transfer::ACKNOWLEDGMENT_FORMAT,
+            Artifact::Keyset => keyset::KEYSET_FORMAT,
+            Artifact::KeyStore => key_store::KEY_STORE_FORMAT,
+            Artifact::EncryptedKeypair => keypair_file::ENCRYPTED_KEYPAIR_FORMAT,
+            Artifact::BalanceProof => balance_proof::BALANCE_PROOF_FORMAT,
+            Artifact::ProofData => proof_data::PROOF_DATA_FORMAT,
+        }
+    }
+
+    fn current_version(self) -> u32 {
+        match self {
+            Artifact::Intent => intent::INTENT_VERSION,
+            Artifact::Snapshot => snapshot::SNAPSHOT_VERSION,
+            Artifact::Receipt => receipt::RECEIPT_VERSION,
+            Artifact::AuditSession => audit_session::AUDIT_SESSION_VERSION,
+            Artifact::SupportBundle => support_bundle::SUPPORT_BUNDLE_VERSION,
+            Artifact::Schedule => schedule::SCHEDULE_VERSION,
+            Artifact::ProvisioningReport => create_account::PROVISIONING_REPORT_VERSION,
+            Artifact::BootstrapReport => bootstrap::BOOTSTRAP_REPORT_VERSION,
+            Artifact::Acknowledgment => acknowledge_transfer::ACKNOWLEDGMENT_VERSION,
+            Artifact::Keyset => keyset::KEYSET_VERSION,
+            Artifact::KeyStore => key_store::KEY_STORE_VERSION,
+            Artifact::EncryptedKeypair => keypair_file::ENCRYPTED_KEYPAIR_VERSION,
+            Artifact::BalanceProof => balance_proof::BALANCE_PROOF_VERSION,
+            Artifact::ProofData => proof_data::PROOF_DATA_VERSION,
+        }
+    }
+
+    /// Files holding keys, which are written readable by their owner only.
+    fn is_secret(self) -> bool {
+        matches!(self, Artifact::Keyset | Artifact::KeyStore | Artifact::EncryptedKeypair)
+    }
+
+    /// Files from before versioning carry no `format`, so they are recognized by the
+    /// fields only that artifact has.
+    fn detect(value: &Value) -> Option<Self> {
+        if let Some(format) = value["format"].as_str() {
+            return Self::ALL.into_iter().find(|artifact| artifact.format() == format);
+        }
+        let has = |field: &str| value.get(field).is_some();
+        if has("pending_balance_lo") && has("decryptable_available_balance") {
+            Some(Artifact::Snapshot)
+        } else if has("funding") && has("kind") {
+            Some(Artifact::Receipt)
+        } else if has("body") && has("signer") {
+            Some(Artifact::AuditSession)
+        } else if has("environment") && has("cluster") {
+            Some(Artifact::SupportBundle)
+        } else {
+            None
+        }
+    }
+
+    /// Rewrites `path` in the current version. Snapshots and receipts go through their own
+    /// loaders, which upgrade in memory; the rest only gain their `format` and `version`.
+    fn upgrade(self, path: &Path, value: Value) -> Result<String> {
+        let upgraded = match self {
+            Artifact::Snapshot => serde_json::to_value(AccountSnapshot::load(path)?)?,
+            Artifact::Receipt => serde_json::to_value(CreationReceipt::load(path)?)?,
+            Artifact::Intent => serde_json::to_value(TransferIntent::load(path)?)?,
+            Artifact::Schedule => serde_json::to_value(TransferSchedule::load(path)?)?,
+            Artifact::Keyset => serde_json::to_value(KeysetFile::load(path)?)?,
+            Artifact::KeyStore => serde_json::to_value(KeyStore::load(path)?)?,
+            Artifact::EncryptedKeypair => serde_json::to_value(EncryptedKeypair::load(path)?)?,
+            Artifact::BalanceProof => serde_json::to_value(BalanceProof::load(path)?)?,
+            Artifact::ProofData => serde_json::to_value(ProofDataFile::load(path)?)?,
+            Artifact::AuditSession | Artifact::SupportBundle | Artifact::ProvisioningReport | Artifact::BootstrapReport | Artifact::Acknowledgment => {
+                let Value::Object(fields) = value else {
+                    anyhow::bail!("{} is not a JSON object", path.display());
+                };
+                let mut upgraded = Map::new();
+                upgraded.insert("format".to_string(), json!(self.format()));
+                upgraded.insert("version".to_string(), json!(self.current_version()));
+                upgraded.extend(fields.into_iter().filter(|(field, _)| field != "format" && field != "version"));
+                Value::Object(upgraded)
+            }
+        };
+        Ok(serde_json::to_string_pretty(&upgraded)?)
+    }
+}
+
+/// Brings files written by older versions of this CLI up to the current formats, keeping
+/// each original next to it as `<file>.v<N>.bak`.
+pub async fn execute(files: Vec<PathBuf>, dry_run: bool) -> Result<()> {
+    say!("🗂️  Migrating Local Files...\n");
+
+    let mut reports = Vec::new();
+    for path in &files {
+        let report = migrate_file(path, dry_run)
+            .unwrap_or_else(|e| json!({
+                "path": path.display().to_string(),
+                "format": null,
+                "from_version": null,
+                "to_version": null,
+                "status": "error",
+                "error": format!("{:#}", e),
+            }));
+        let status = report["status"].as_str().unwrap_or_default();
+        let icon = match status {
+            "current" => "✅",
+            "migrated" | "would-migrate" => "🔼",
+            _ => "⚠️ ",
+        };
+        match report["error"].as_str() {
+            Some(error) => say!("  {} {}  {}", icon, path.display(), error),
+            None => say!("  {} {}  {} v{} → v{} ({})", icon, path.display(),
+                report["format"].as_str().unwrap_or("?"),
+                report["from_version"], report["to_version"], status),
+        }
+        reports.push(report);
+    }
+
+    let migrated = reports.iter().filter(|report| report["status"] == "migrated").count();
+    let errors = reports.iter().filter(|report| report["status"] == "error").count();
+    say!("\n📊 Summary: {} file(s), {} migrated, {} error(s)", files.len(), migrated, errors);
+
+    output::emit("migrate", json!({
+        "files": reports,
+        "migrated": migrated,
+        "errors": errors,
+    }));
+
+    if dry_run && reports.iter().any(|report| report["status"] == "would-migrate") {
+        say!("\n💡 Re-run without --dry-run to write the upgraded files");
+    }
+    if errors > 0 {
+        anyhow::bail!("{} file(s) could not be migrated", errors);
+    }
+
+    Ok(())
+}
+
+fn migrate_file(path: &Path, dry_run: bool) -> Result<Value> {
+    let contents = std::fs::read(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let report = |format: &str, from: u64, to: u64, status: &str, error: Option<String>| json!({
+        "path": path.display().to_string(),
+        "format": format,
+        "from_version": from,
+        "to_version": to,
+        "status": status,
+        "error": error,
+    });
+
+    // Lookup tables are derived from nothing but their parameters, so they are rebuilt
+    if let Some(version) = lookup_table::header_version(&contents) {
+        let current = lookup_table::FORMAT_VERSION;
+        return Ok(if version == current {
+            report("lookup-table", version.into(), current.into(), "current", None)
+        } else {
+            report("lookup-table", version.into(), current.into(), "error",
+                Some(format!("lookup table format {} cannot be migrated; rebuild it with build-table", version)))
+        });
+    }
+
+    let value: Value = serde_json::from_slice(&contents)
+        .with_context(|| format!("{} is neither JSON nor a lookup table", path.display()))?;
+    let artifact = Artifact::detect(&value)
+        .ok_or_else(|| anyhow::anyhow!("not a file written by this CLI"))?;
+    let from = value["version"].as_u64().unwrap_or(0);
+    let to = u64::from(artifact.current_version());
+
+    if from > to {
+        return Ok(report(artifact.format(), from, to, "error",
+            Some(format!("version {} is newer than this CLI understands ({}); upgrade the CLI", from, to))));
+    }
+    if from == to {
+        return Ok(report(artifact.format(), from, to, "current", None));
+    }
+    if dry_run {
+        return Ok(report(artifact.format(), from, to, "would-migrate", None));
+    }
+
+    let upgraded = artifact.upgrade(path, value)?;
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(format!(".v{}.bak", from));
+    let backup = PathBuf::from(backup);
+    let write = |target: &Path, contents: &[u8]| if artifact.is_secret() {
+        utils::write_private(target, contents, true)
+    } else {
+        utils::write_atomic(target, contents)
+    };
+    if !backup.exists() {
+        write(&backup, &contents)
+            .with_context(|| format!("Failed to back up {} to {}", path.display(), backup.display()))?;
+    }
+    write(path, upgraded.as_bytes())
+        .with_context(|| format!("Failed to write migrated {}", path.display()))?;
+
+    Ok(report(artifact.format(), from, to, "migrated", None))
+}
@@ -0,0 +1,80 @@
+use anyhow::Result;
+use solana_sdk::pubkey::Pubkey;
+use spl_token_2022::{
+    extension::{
+        confidential_transfer::ConfidentialTransferMint,
+        BaseStateWithExtensions,
+        StateWithExtensions,
+    },
+    solana_zk_sdk::encryption::pod::elgamal::PodElGamalPubkey,
+};
+use serde_json::json;
+use crate::{config::ReadOnlyConfig, output::{self, say}, utils};
+
+/// Prints a mint's base state and confidential transfer configuration; needs no keypair.
+pub async fn execute(mint: String) -> Result<()> {
+    say!("🔎 Mint Info...\n");
+
+    let config = ReadOnlyConfig::new()?;
+    let mint_pubkey = utils::parse_pubkey(&mint)?;
+
+    let mint_data = config.rpc_client.get_account(&mint_pubkey).await?;
+    if mint_data.owner != spl_token_2022::id() {
+        anyhow::bail!("{} is not a Token-2022 mint (owner: {})", mint_pubkey, mint_data.owner);
+    }
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data.data)?;
+    let decimals = mint.base.decimals;
+
+    let mint_authority = Option::<Pubkey>::from(mint.base.mint_authority);
+    let freeze_authority = Option::<Pubkey>::from(mint.base.freeze_authority);
+    let extensions = mint.get_extension_types()?
+        .iter()
+        .map(|extension| format!("{:?}", extension))
+        .collect::<Vec<_>>();
+
+    let describe = |authority: &Option<Pubkey>| match authority {
+        Some(authority) => authority.to_string(),
+        None => "none".to_string(),
+    };
+    say!("📋 Mint: {}", mint_pubkey);
+    say!("  Decimals: {}", decimals);
+    say!("  Supply: {}", utils::format_amount(mint.base.supply, decimals));
+    say!("  Mint authority: {}", describe(&mint_authority));
+    say!("  Freeze authority: {}", describe(&freeze_authority));
+    say!("  Extensions: {}", if extensions.is_empty() { "none".to_string() } else { extensions.join(", ") });
+
+    // A mint without the extension can't hold confidential balances at all
+    let ct_mint = mint.get_extension::<ConfidentialTransferMint>().ok();
+    let ct_authority = ct_mint.and_then(|ct_mint| Option::<Pubkey>::from(ct_mint.authority));
+    let auto_approve = ct_mint.map(|ct_mint| bool::from(ct_mint.auto_approve_new_accounts));
+    let auditor = ct_mint.and_then(|ct_mint| Option::<PodElGamalPubkey>::from(ct_mint.auditor_elgamal_pubkey));
+
+    match ct_mint {
+        Some(_) => {
+            say!("\n🔐 Confidential Transfers:");
+            say!("  Authority: {}", describe(&ct_authority));
+            say!("  Auto-approve: {}", auto_approve.unwrap_or_default());
+            say!("  Auditor: {}", auditor.map(|auditor| auditor.to_string()).unwrap_or_else(|| "none".to_string()));
+        }
+        None => say!("\n⚠️  Confidential transfers are not enabled on this mint"),
+    }
+
+    output::emit("mint-info", json!({
+        "mint": mint_pubkey.to_string(),
+        "decimals": decimals,
+        "supply": mint.base.supply,
+        "mint_authority": mint_authority.map(|authority| authority.to_string()),
+        "freeze_authority": freeze_authority.map(|authority| authority.to_string()),
+        "extensions": extensions,
+        "confidential_transfers": ct_mint.is_some(),
+        "confidential_transfer_authority": ct_authority.map(|authority| authority.to_string()),
+        "auto_approve_new_accounts": auto_approve,
+        "auditor_elgamal_pubkey": auditor.map(|auditor| auditor.to_string()),
+    }));
+
+    if auto_approve == Some(false) {
+        say!("\n💡 New accounts need 'approve-account' from the confidential transfer authority");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use anyhow::{Result, Context};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::Signer,
+    transaction::Transaction,
+};
+use spl_token_2022::extension::{
+    confidential_mint_burn::ConfidentialMintBurn,
+    BaseStateWithExtensions,
+    StateWithExtensions,
+};
+use serde_json::json;
+use crate::{config::{self, AppConfig}, intent::{self, TransferIntent}, output::{self, say}, rpc, utils};
+
+pub async fn execute(account: String, amount: String) -> Result<()> {
+    say!("🪙 Minting Tokens...\n");
+
+    let config = AppConfig::new()?;
+    let account_pubkey = utils::parse_pubkey(&account)?;
+
+    // Fetch the destination and its mint
+    let account_data = config.rpc_client.get_account(&account_pubkey).await?;
+    let token_account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data.data)?;
+    let mint_pubkey = token_account.base.mint;
+
+    let mint_data = config.rpc_client.get_account(&mint_pubkey).await?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data.data)?;
+    let decimals = mint.base.decimals;
+    let amount = utils::parse_amount(&amount, decimals)?;
+
+    say!("📋 Mint Details:");
+    say!("  Destination: {}", account_pubkey);
+    say!("  Mint: {}", mint_pubkey);
+    say!("  Amount: {}", utils::format_amount(amount, decimals));
+
+    // The program rejects public mints once the supply is confidential
+    if mint.get_extension::<ConfidentialMintBurn>().is_ok() {
+        anyhow::bail!("Mint {} has a confidential supply; use 'confidential-mint-to' instead", mint_pubkey);
+    }
+
+    let mint_authority = Option::<Pubkey>::from(mint.base.mint_authority)
+        .ok_or_else(|| anyhow::anyhow!("Mint {} has no mint authority, so its supply is fixed", mint_pubkey))?;
+    if mint_authority != config.owner().pubkey() {
+        anyhow::bail!("The keypair {} is not the mint authority ({})", config.owner().pubkey(), mint_authority);
+    }
+
+    // The checked variant makes the program verify decimals too
+    let mint_ix = spl_token_2022::instruction::mint_to_checked(
+        &spl_token_2022::id(),
+        &mint_pubkey,
+        &account_pubkey,
+        &mint_authority,
+        &[], // No multisig
+        amount,
+        decimals,
+    )?;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[mint_ix],
+        Some(&config.payer.pubkey()),
+    );
+
+    let recent_blockhash = config.rpc_client.get_latest_blockhash().await?;
+
+    if let Some(path) = config::global_options().export_intent {
+        transaction.message.recent_blockhash = recent_blockhash;
+        let intent = TransferIntent::new("mint-to", &transaction)?
+            .account("account", &account_pubkey)
+            .account("mint", &mint_pubkey)
+            .amount(amount);
+        return intent::export(&intent, &path);
+    }
+
+    config.sign(&mut transaction, recent_blockhash)?;
+
+    say!("\n📤 Sending mint transaction...");
+    let signature = rpc::send_once(&config, &transaction)
+        .await
+        .context("Failed to mint tokens")?;
+
+    say!("✅ Tokens minted!");
+    say!("   Signature: {}", signature);
+
+    output::emit("mint-to", json!({
+        "account": account_pubkey.to_string(),
+        "mint": mint_pubkey.to_string(),
+        "amount": amount,
+        "public_balance": token_account.base.amount + amount,
+        "signature": signature.to_string(),
+    }));
+
+    say!("\n💡 The tokens are in the public balance; run 'deposit' to make them confidential");
+
+    Ok(())
+}
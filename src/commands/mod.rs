@@ -1,13 +1,53 @@
 pub mod create_mint;
+pub mod create_multisig;
+pub mod dev_token;
 pub mod create_account;
 pub mod deposit;
 pub mod apply_balance;
 pub mod transfer;
+pub mod public_transfer;
 pub mod withdraw;
 pub mod balance;
+pub mod prove_balance;
+pub mod verify_proof;
+pub mod bootstrap;
+pub mod account_info;
+pub mod acknowledge_transfer;
+pub mod decrypt;
+pub mod diff;
+pub mod encrypt;
+pub mod top_up;
+pub mod schema;
+pub mod health;
+pub mod submit_intent;
+pub mod build_table;
+pub mod confidential_mint_to;
+pub mod mint_to;
+pub mod burn;
+pub mod empty_account;
+pub mod close_account;
+pub mod approve_account;
+pub mod update_mint;
+pub mod mint_info;
+pub mod reconcile;
+pub mod support_bundle;
+pub mod explain_error;
+pub mod migrate;
+pub mod run_due;
+pub mod audit_session;
+pub mod rotate_keys;
+pub mod export_keys;
+pub mod import_keys;
+pub mod keygen;
+pub mod register_elgamal_key;
+pub mod confidential_credits;
 
 use anyhow::Result;
 use clap::Subcommand;
+use std::path::PathBuf;
+use crate::utils::{stdin_list_or, stdin_or};
+use self::confidential_credits::Credits;
+use crate::schedule::TransferKind;
 
 #[derive(Subcommand, Debug)]
 pub enum Commands {
@@ -17,6 +57,87 @@ pub enum Commands {
 
     #[arg(short, long, default_value = "9")]
     decimals: u8,
+
+    /// Also enable ConfidentialMintBurn, so supply is minted into pending balances with
+    /// confidential-mint-to (public minting is disabled)
+    #[arg(long)]
+    confidential_mint_burn: bool,
+
+    /// Don't auto-approve new confidential accounts; the mint authority approves each
+    /// one with approve-account
+    #[arg(long)]
+    require_approval: bool,
+
+    /// Auditor ElGamal public key (base64) that every transfer amount is also encrypted for
+    #[arg(long, conflicts_with = "auditor_keypair")]
+    auditor_pubkey: Option<String>,
+
+    /// Derive the auditor key from this signer (keypair path, usb://, prompt://, ...) instead
+    #[arg(long)]
+    auditor_keypair: Option<String>,
+
+    /// Write a creation receipt (addresses, rent and fees paid, extensions, signatures) to this file
+    #[arg(long)]
+    receipt: Option<PathBuf>,
+
+    /// Grind the mint address until it starts with this base58 prefix; each extra
+    /// character takes about 58 times longer
+    #[arg(long, value_name = "PREFIX")]
+    starts_with: Option<String>,
+  },
+
+  CreateMultisig {
+    /// Comma-separated signer addresses (at most 11)
+    #[arg(short, long, value_delimiter = ',', required = true)]
+    signers: Vec<String>,
+
+    /// How many of the signers must sign (M of N)
+    #[arg(short = 'm', long)]
+    threshold: u8,
+  },
+
+  DevToken {
+    /// How many test tokens to mint into your confidential account
+    #[arg(short, long, default_value = "100")]
+    amount: String,
+  },
+
+  Bootstrap {
+    /// JSON manifest: decimals, and optionally authority, confidential_mint_burn,
+    /// require_approval, auditor_pubkey or auditor_keypair, treasury_owner, mint_amount
+    /// and deposit_amount
+    #[arg(short, long)]
+    manifest: PathBuf,
+
+    /// Write the created addresses and completed steps to this file, even if a step fails
+    #[arg(long)]
+    report: Option<PathBuf>,
+
+    /// Only print the steps the manifest would run
+    #[arg(long)]
+    dry_run: bool,
+  },
+
+  UpdateMint {
+    #[arg(short, long)]
+    mint: String,
+
+    /// Whether new confidential accounts are approved automatically
+    #[arg(long)]
+    auto_approve: Option<bool>,
+
+    /// New auditor ElGamal public key (base64)
+    #[arg(long, conflicts_with = "remove_auditor")]
+    auditor: Option<String>,
+
+    /// Stop encrypting transfer amounts for an auditor
+    #[arg(long)]
+    remove_auditor: bool,
+  },
+
+  MintInfo {
+    #[arg(short, long)]
+    mint: String,
   },
 
   CreateAccount {
@@ -25,69 +146,661 @@ pub enum Commands {
 
     #[arg(short, long)]
     owner: Option<String>,
+
+    /// Write a creation receipt (addresses, rent and fees paid, extensions, ElGamal key,
+    /// signatures) to this file
+    #[arg(long, conflicts_with = "count")]
+    receipt: Option<PathBuf>,
+
+    /// Create this many accounts for the owner, e.g. to shard incoming payments; each
+    /// gets its own keys under the spl-token derivation scheme
+    #[arg(long, default_value_t = 1)]
+    count: u32,
+
+    /// Write every created address with its owner, ElGamal key and approval to this file
+    #[arg(long)]
+    report: Option<PathBuf>,
+
+    /// Grind each account address until it starts with this base58 prefix; each extra
+    /// character takes about 58 times longer
+    #[arg(long, value_name = "PREFIX")]
+    starts_with: Option<String>,
+  },
+
+  ApproveAccount {
+    #[arg(short, long)]
+    account: String,
+  },
+
+  EnableConfidentialCredits {
+    #[arg(short, long)]
+    account: String,
+  },
+
+  DisableConfidentialCredits {
+    #[arg(short, long)]
+    account: String,
+  },
+
+  EnableNonConfidentialCredits {
+    #[arg(short, long)]
+    account: String,
+  },
+
+  DisableNonConfidentialCredits {
+    #[arg(short, long)]
+    account: String,
   },
 
   Deposit {
     #[arg(short, long)]
     account: String,
 
-    #[arg(short = 'a', long)]
-    amount: u64,
+    #[arg(long)]
+    amount: String,
+
+    /// Keypair that owns the account, when it isn't the payer; its keys decrypt and its
+    /// signature authorizes (given like --keypair)
+    #[arg(short, long)]
+    owner: Option<String>,
   },
 
-  ApplyBalance {
+  MintTo {
+    #[arg(short, long)]
+    account: String,
+
+    #[arg(long)]
+    amount: String,
+  },
+
+  Burn {
+    #[arg(short, long)]
+    account: String,
+
+    #[arg(long)]
+    amount: String,
+  },
+
+  ConfidentialMintTo {
     #[arg(short, long)]
     account: String,
+
+    #[arg(long)]
+    amount: String,
+  },
+
+  ApplyBalance {
+    #[arg(short, long, required_unless_present_any = ["accounts", "all_mine"])]
+    account: Option<String>,
+
+    /// Comma-separated accounts, a file with one per line, or - for stdin
+    #[arg(long, value_delimiter = ',', conflicts_with_all = ["account", "all_mine"])]
+    accounts: Vec<String>,
+
+    /// Every confidential account owned by the keypair (or --owner)
+    #[arg(long, conflicts_with = "account")]
+    all_mine: bool,
+
+    /// Keypair that owns the account, when it isn't the payer; its keys decrypt and its
+    /// signature authorizes (given like --keypair)
+    #[arg(short, long)]
+    owner: Option<String>,
+  },
+
+  Transfer {
+    #[arg(short, long)]
+    from: String,
+
+    #[arg(short, long)]
+    to: String,
+
+    #[arg(short, long)]
+    amount: String,
+
+    /// Don't send now; queue the transfer for this time (RFC3339) and let 'run-due' send it
+    #[arg(long, value_name = "TIME")]
+    execute_at: Option<String>,
+
+    /// Where scheduled transfers are kept
+    #[arg(long, env = "CONFIDENTIAL_CLI_SCHEDULE", default_value = "scheduled-transfers.json")]
+    schedule: PathBuf,
+
+    /// Keypair that owns the account, when it isn't the payer; its keys decrypt and its
+    /// signature authorizes (given like --keypair)
+    #[arg(short, long)]
+    owner: Option<String>,
   },
 
   ConfidentialTransfer {
     #[arg(short, long)]
     from: String,
 
+    /// Recipient token account, or a wallet with a confidential account for the mint
     #[arg(short, long)]
     to: String,
 
     #[arg(short, long)]
-    amount: u64,
+    amount: String,
+
+    /// Send amounts above the 48-bit transfer limit as several consecutive transfers
+    #[arg(long)]
+    auto_split: bool,
+
+    /// Compare the cost of inline proofs and context accounts without sending anything
+    #[arg(long, conflicts_with_all = ["auto_split", "execute_at"])]
+    plan: bool,
+
+    /// Don't send now; queue the transfer for this time (RFC3339) and let 'run-due' send it
+    #[arg(long, value_name = "TIME")]
+    execute_at: Option<String>,
+
+    /// Where scheduled transfers are kept
+    #[arg(long, env = "CONFIDENTIAL_CLI_SCHEDULE", default_value = "scheduled-transfers.json")]
+    schedule: PathBuf,
+
+    /// Keypair that owns the account, when it isn't the payer; its keys decrypt and its
+    /// signature authorizes (given like --keypair)
+    #[arg(short, long)]
+    owner: Option<String>,
   },
 
   Withdraw {
     #[arg(short, long)]
     account: String,
 
-    #[arg(short = 'a', long)]
-    amount: u64
+    #[arg(long)]
+    amount: String,
+
+    /// Keypair that owns the account, when it isn't the payer; its keys decrypt and its
+    /// signature authorizes (given like --keypair)
+    #[arg(short, long)]
+    owner: Option<String>,
+  },
+
+  EmptyAccount {
+    #[arg(short, long)]
+    account: String,
+
+    /// Keypair that owns the account, when it isn't the payer; its keys decrypt and its
+    /// signature authorizes (given like --keypair)
+    #[arg(short, long)]
+    owner: Option<String>,
+  },
+
+  CloseAccount {
+    #[arg(short, long)]
+    account: String,
+
+    /// Where the account's rent goes (defaults to the payer)
+    #[arg(short, long)]
+    destination: Option<String>,
+
+    /// Keypair that owns the account, when it isn't the payer; its signature authorizes
+    /// (given like --keypair)
+    #[arg(short, long)]
+    owner: Option<String>,
+  },
+
+  RotateKeys {
+    #[arg(short, long)]
+    account: String,
+
+    /// Keypair that owns the account, when it isn't the payer; its keys decrypt and its
+    /// signature authorizes (given like --keypair)
+    #[arg(short, long)]
+    owner: Option<String>,
+  },
+
+  ExportKeys {
+    /// Accounts whose keys to export; may be left out under --derivation-scheme legacy,
+    /// whose one key pair covers every account
+    #[arg(short, long, value_delimiter = ',')]
+    accounts: Vec<String>,
+
+    /// Keypair that owns the accounts, when it isn't the payer (given like --keypair)
+    #[arg(short, long)]
+    owner: Option<String>,
+
+    /// Where to write the encrypted keyset; the passphrase is prompted for, or read
+    /// from CONFIDENTIAL_CLI_KEYSET_PASSPHRASE
+    #[arg(long)]
+    out: PathBuf,
+  },
+
+  ImportKeys {
+    /// Keyset written by export-keys; the passphrase is prompted for, or read from
+    /// CONFIDENTIAL_CLI_KEYSET_PASSPHRASE
+    #[arg(short, long)]
+    file: PathBuf,
+  },
+
+  Keygen {
+    /// Where to write the encrypted keypair; the passphrase is prompted for, or read
+    /// from CONFIDENTIAL_CLI_KEYPAIR_PASSPHRASE
+    #[arg(long)]
+    out: PathBuf,
+
+    /// Encrypt this plaintext keypair file instead of generating a new keypair
+    #[arg(long)]
+    from: Option<PathBuf>,
+  },
+
+  RegisterElgamalKey {
+    /// Account whose ElGamal key to publish; may be left out under --derivation-scheme
+    /// legacy, whose one key covers every account
+    #[arg(short, long)]
+    account: Option<String>,
+
+    /// Wallet publishing the key, when it isn't the payer (given like --keypair)
+    #[arg(short, long)]
+    owner: Option<String>,
   },
 
   Balance {
     #[arg(short, long)]
     account: String,
+
+    #[arg(long)]
+    snapshot: Option<PathBuf>,
+
+    /// Keypair that owns the account, when it isn't the payer; its keys decrypt and its
+    /// signature authorizes (given like --keypair)
+    #[arg(short, long)]
+    owner: Option<String>,
+  },
+
+  ProveBalance {
+    #[arg(short, long)]
+    account: String,
+
+    /// Keypair that owns the account, when it isn't the payer (given like --keypair)
+    #[arg(short, long)]
+    owner: Option<String>,
+
+    /// Where to write the balance proof for the verifier
+    #[arg(long)]
+    out: PathBuf,
+  },
+
+  VerifyProof {
+    /// A balance proof from prove-balance or an exported proof data file
+    #[arg(long, conflicts_with_all = ["kind", "data"])]
+    file: Option<PathBuf>,
+
+    /// Proof type of --data
+    #[arg(long, value_enum, requires = "data")]
+    kind: Option<crate::proof_data::ProofKind>,
+
+    /// Base64 proof data, as the ZK ElGamal proof program takes it after the instruction byte
+    #[arg(long, requires = "kind")]
+    data: Option<String>,
+  },
+
+  AccountInfo {
+    #[arg(short, long)]
+    account: String,
+  },
+
+  Decrypt {
+    /// Base64 ElGamal or AE ciphertext, e.g. an account's pending_balance_lo or
+    /// decryptable_available_balance
+    #[arg(short, long)]
+    ciphertext: String,
+
+    /// Account whose keys decrypt it; may be left out under --derivation-scheme legacy
+    #[arg(short, long)]
+    account: Option<String>,
+
+    /// Keypair that owns the account, when it isn't the payer (given like --keypair)
+    #[arg(short, long)]
+    owner: Option<String>,
+  },
+
+  Encrypt {
+    /// Amount in base units
+    #[arg(short, long)]
+    amount: u64,
+
+    /// Base64 ElGamal public key to encrypt under, e.g. from account-info
+    #[arg(short, long)]
+    elgamal_pubkey: String,
+
+    /// Also print the Pedersen opening, which proofs about the ciphertext are built from
+    #[arg(long)]
+    show_opening: bool,
+  },
+
+  Diff {
+    #[arg(short, long)]
+    account: String,
+
+    #[arg(long)]
+    from_snapshot: PathBuf,
+
+    /// Keypair that owns the account, when it isn't the payer (given like --keypair)
+    #[arg(short, long)]
+    owner: Option<String>,
+  },
+
+  Reconcile {
+    /// JSON object of account → expected confidential balance (available + pending)
+    #[arg(short, long)]
+    expected: PathBuf,
+
+    /// Largest difference, in token units, still reported as a match
+    #[arg(long, default_value = "0")]
+    tolerance: String,
+  },
+
+  AuditSession {
+    #[arg(short, long, value_delimiter = ',', required = true)]
+    accounts: Vec<String>,
+
+    /// Start of the period (RFC3339)
+    #[arg(long)]
+    from: String,
+
+    /// End of the period (RFC3339)
+    #[arg(long)]
+    to: String,
+
+    #[arg(short, long)]
+    out: PathBuf,
+  },
+
+  VerifyAuditSession {
+    #[arg(short, long)]
+    file: PathBuf,
+
+    /// Also compare every entry with its transaction on chain
+    #[arg(long)]
+    check_chain: bool,
+  },
+
+  AcknowledgeTransfer {
+    /// Your account that received the confidential transfer
+    #[arg(short, long)]
+    account: String,
+
+    /// Signature of the transaction that sent it
+    #[arg(short, long)]
+    signature: String,
+
+    /// Keypair that owns the account, when it isn't the payer (given like --keypair)
+    #[arg(short, long)]
+    owner: Option<String>,
+
+    /// Also include the decrypted amount, with a zero-knowledge proof that it is what
+    /// the transfer encrypted
+    #[arg(long)]
+    prove_amount: bool,
+
+    /// Where to write the signed acknowledgment
+    #[arg(long)]
+    out: PathBuf,
+  },
+
+  VerifyReceipt {
+    /// Acknowledgment written by acknowledge-transfer
+    #[arg(short, long)]
+    file: PathBuf,
+
+    /// The amount you sent, checked against the recipient's proven amount
+    #[arg(long)]
+    amount: Option<String>,
+  },
+
+  TopUp {
+    #[arg(short, long, value_delimiter = ',', required = true)]
+    accounts: Vec<String>,
+
+    #[arg(long)]
+    dry_run: bool,
+  },
+
+  Health {
+    #[arg(long, default_value = "24")]
+    pending_age_hours: u64,
+
+    #[arg(long, default_value = "90")]
+    counter_threshold_percent: u64,
+
+    #[arg(long)]
+    fix: bool,
+
+    /// Keypair whose accounts to check, when it isn't the payer; with --fix its
+    /// signature authorizes the applies (given like --keypair)
+    #[arg(short, long)]
+    owner: Option<String>,
+  },
+
+  ExplainError {
+    /// A failed transaction signature, or a token error code (decimal, 0x hex or name)
+    #[arg(value_name = "CODE|SIGNATURE")]
+    error: String,
+  },
+
+  SubmitIntent {
+    #[arg(short, long)]
+    file: PathBuf,
+
+    #[arg(long)]
+    out: Option<PathBuf>,
+  },
+
+  BuildTable {
+    #[arg(long, default_value = "32")]
+    bits: u8,
+
+    #[arg(long, default_value = "20")]
+    table_bits: u8,
+
+    #[arg(short, long)]
+    out: PathBuf,
+  },
+
+  Schema {
+    #[arg(short, long)]
+    command: Option<String>,
+  },
+
+  SupportBundle {
+    #[arg(short, long)]
+    out: PathBuf,
+
+    /// Token accounts to snapshot (public state only)
+    #[arg(short, long, value_delimiter = ',')]
+    accounts: Vec<String>,
+
+    /// Transactions whose error and program logs to include
+    #[arg(short, long, value_delimiter = ',')]
+    signatures: Vec<String>,
+  },
+
+  Migrate {
+    /// Snapshots, intents, receipts, schedules, audit sessions, support bundles or lookup tables
+    #[arg(short, long, value_delimiter = ',', required = true)]
+    files: Vec<PathBuf>,
+
+    /// Only report what would change
+    #[arg(long)]
+    dry_run: bool,
+  },
+
+  RunDue {
+    /// Where scheduled transfers are kept
+    #[arg(long, env = "CONFIDENTIAL_CLI_SCHEDULE", default_value = "scheduled-transfers.json")]
+    schedule: PathBuf,
+
+    /// Keep running and send transfers as they come due
+    #[arg(long)]
+    watch: bool,
+
+    /// Seconds between checks with --watch
+    #[arg(long, default_value = "30", requires = "watch")]
+    interval: u64,
   },
 
 }
 
 pub async fn handle_command(command: Commands) -> Result<()> {
     match command {
-        Commands::CreateMint { authority, decimals } => {
-            create_mint::execute(authority, decimals).await
+        Commands::CreateMint { authority, decimals, confidential_mint_burn, require_approval, auditor_pubkey, auditor_keypair, receipt, starts_with } => {
+            create_mint::execute(authority, decimals, confidential_mint_burn, require_approval, auditor_pubkey, auditor_keypair, receipt, starts_with).await
+        }
+        Commands::CreateMultisig { signers, threshold } => {
+            create_multisig::execute(signers, threshold).await
+        }
+        Commands::DevToken { amount } => {
+            dev_token::execute(amount).await
+        }
+        Commands::Bootstrap { manifest, report, dry_run } => {
+            bootstrap::execute(manifest, report, dry_run).await
+        }
+        Commands::UpdateMint { mint, auto_approve, auditor, remove_auditor } => {
+            update_mint::execute(stdin_or(mint, "--mint")?, auto_approve, auditor, remove_auditor).await
+        }
+        Commands::MintInfo { mint } => {
+            mint_info::execute(stdin_or(mint, "--mint")?).await
+        }
+        Commands::CreateAccount { mint, owner, receipt, count, report, starts_with } => {
+            create_account::execute(stdin_or(mint, "--mint")?, owner, receipt, count, report, starts_with).await
+        }
+        Commands::ApproveAccount { account } => {
+            approve_account::execute(stdin_or(account, "--account")?).await
+        }
+        Commands::EnableConfidentialCredits { account } => {
+            confidential_credits::execute(stdin_or(account, "--account")?, Credits::Confidential, true).await
+        }
+        Commands::DisableConfidentialCredits { account } => {
+            confidential_credits::execute(stdin_or(account, "--account")?, Credits::Confidential, false).await
+        }
+        Commands::EnableNonConfidentialCredits { account } => {
+            confidential_credits::execute(stdin_or(account, "--account")?, Credits::NonConfidential, true).await
+        }
+        Commands::DisableNonConfidentialCredits { account } => {
+            confidential_credits::execute(stdin_or(account, "--account")?, Credits::NonConfidential, false).await
+        }
+        Commands::Deposit { account, amount, owner } => {
+            deposit::execute(stdin_or(account, "--account")?, stdin_or(amount, "--amount")?, owner).await
+        }
+        Commands::MintTo { account, amount } => {
+            mint_to::execute(stdin_or(account, "--account")?, stdin_or(amount, "--amount")?).await
+        }
+        Commands::Burn { account, amount } => {
+            burn::execute(stdin_or(account, "--account")?, stdin_or(amount, "--amount")?).await
+        }
+        Commands::ConfidentialMintTo { account, amount } => {
+            confidential_mint_to::execute(stdin_or(account, "--account")?, stdin_or(amount, "--amount")?).await
+        }
+        Commands::ApplyBalance { account, accounts, all_mine, owner } => match account {
+            Some(account) => apply_balance::execute(stdin_or(account, "--account")?, owner).await,
+            None => apply_balance::execute_many(stdin_list_or(accounts, "--accounts")?, all_mine, owner).await,
+        },
+        Commands::Transfer { from, to, amount, execute_at, schedule, owner } => {
+            let (from, to, amount) = (stdin_or(from, "--from")?, stdin_or(to, "--to")?, stdin_or(amount, "--amount")?);
+            match execute_at {
+                Some(execute_at) => {
+                    run_due::schedule(TransferKind::Transfer, from, to, amount, false, owner, execute_at, schedule).await
+                }
+                None => public_transfer::execute(from, to, amount, owner).await,
+            }
+        }
+        Commands::ConfidentialTransfer { from, to, amount, auto_split, plan, execute_at, schedule, owner } => {
+            let (from, to, amount) = (stdin_or(from, "--from")?, stdin_or(to, "--to")?, stdin_or(amount, "--amount")?);
+            match execute_at {
+                Some(execute_at) => {
+                    run_due::schedule(TransferKind::ConfidentialTransfer, from, to, amount, auto_split, owner, execute_at, schedule).await
+                }
+                None => transfer::execute(from, to, amount, auto_split, plan, owner).await,
+            }
+        }
+        Commands::Withdraw { account, amount, owner } => {
+            withdraw::execute(stdin_or(account, "--account")?, stdin_or(amount, "--amount")?, owner).await
+        }
+        Commands::EmptyAccount { account, owner } => {
+            empty_account::execute(stdin_or(account, "--account")?, owner).await
+        }
+        Commands::CloseAccount { account, destination, owner } => {
+            close_account::execute(stdin_or(account, "--account")?, destination, owner).await
+        }
+        Commands::RotateKeys { account, owner } => {
+            rotate_keys::execute(stdin_or(account, "--account")?, owner).await
+        }
+        Commands::ExportKeys { accounts, owner, out } => {
+            export_keys::execute(accounts, owner, out).await
+        }
+        Commands::ImportKeys { file } => {
+            import_keys::execute(file).await
+        }
+        Commands::Keygen { out, from } => {
+            keygen::execute(out, from).await
+        }
+        Commands::RegisterElgamalKey { account, owner } => {
+            register_elgamal_key::execute(account, owner).await
+        }
+        Commands::Balance { account, snapshot, owner } => {
+            balance::execute(stdin_or(account, "--account")?, snapshot, owner).await
+        }
+        Commands::ProveBalance { account, owner, out } => {
+            prove_balance::execute(stdin_or(account, "--account")?, owner, out).await
+        }
+        Commands::VerifyProof { file, kind, data } => {
+            verify_proof::execute(file, kind, data).await
+        }
+        Commands::AccountInfo { account } => {
+            account_info::execute(stdin_or(account, "--account")?).await
+        }
+        Commands::Decrypt { ciphertext, account, owner } => {
+            decrypt::execute(stdin_or(ciphertext, "--ciphertext")?, account, owner).await
+        }
+        Commands::Encrypt { amount, elgamal_pubkey, show_opening } => {
+            encrypt::execute(amount, elgamal_pubkey, show_opening).await
+        }
+        Commands::Diff { account, from_snapshot, owner } => {
+            diff::execute(stdin_or(account, "--account")?, from_snapshot, owner).await
+        }
+        Commands::Reconcile { expected, tolerance } => {
+            reconcile::execute(expected, tolerance).await
+        }
+        Commands::AuditSession { accounts, from, to, out } => {
+            audit_session::execute(stdin_list_or(accounts, "--accounts")?, from, to, out).await
+        }
+        Commands::VerifyAuditSession { file, check_chain } => {
+            audit_session::verify(file, check_chain).await
+        }
+        Commands::AcknowledgeTransfer { account, signature, owner, prove_amount, out } => {
+            acknowledge_transfer::execute(stdin_or(account, "--account")?, signature, owner, prove_amount, out).await
+        }
+        Commands::VerifyReceipt { file, amount } => {
+            acknowledge_transfer::verify(file, amount).await
+        }
+        Commands::TopUp { accounts, dry_run } => {
+            top_up::execute(stdin_list_or(accounts, "--accounts")?, dry_run).await
+        }
+        Commands::Health { pending_age_hours, counter_threshold_percent, fix, owner } => {
+            health::execute(pending_age_hours, counter_threshold_percent, fix, owner).await
+        }
+        Commands::ExplainError { error } => {
+            explain_error::execute(error).await
         }
-        Commands::CreateAccount { mint, owner } => {
-            create_account::execute(mint, owner).await
+        Commands::SubmitIntent { file, out } => {
+            submit_intent::execute(file, out).await
         }
-        Commands::Deposit { account, amount } => {
-            deposit::execute(account, amount).await
+        Commands::BuildTable { bits, table_bits, out } => {
+            build_table::execute(bits, table_bits, out).await
         }
-        Commands::ApplyBalance { account } => {
-            apply_balance::execute(account).await
+        Commands::Schema { command } => {
+            schema::execute(command).await
         }
-        Commands::ConfidentialTransfer { from, to, amount } => {
-            transfer::execute(from, to, amount).await
+        Commands::SupportBundle { out, accounts, signatures } => {
+            support_bundle::execute(out, accounts, signatures).await
         }
-        Commands::Withdraw { account, amount } => {
-            withdraw::execute(account, amount).await
+        Commands::Migrate { files, dry_run } => {
+            migrate::execute(files, dry_run).await
         }
-        Commands::Balance { account } => {
-            balance::execute(account).await
+        Commands::RunDue { schedule, watch, interval } => {
+            run_due::execute(schedule, watch, interval).await
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,92 @@
+use anyhow::Result;
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use serde_json::json;
+use spl_token_2022::{
+    extension::{confidential_transfer::ConfidentialTransferAccount, BaseStateWithExtensions, StateWithExtensions},
+    solana_zk_sdk::encryption::pod::elgamal::PodElGamalPubkey,
+};
+use std::path::PathBuf;
+use crate::{
+    balance_proof::{self, BalanceProof},
+    config::AppConfig,
+    crypto,
+    output::{self, say},
+    rpc,
+    trace,
+    utils,
+};
+
+/// Writes a proof that `account`'s available balance encrypts the amount it reveals, for a
+/// third party such as an exchange or auditor to check without the owner's keys.
+pub async fn execute(account: String, owner: Option<String>, out: PathBuf) -> Result<()> {
+    say!("🧾 Proving Confidential Balance...\n");
+
+    let config = AppConfig::new()?.with_owner(owner)?;
+    let account_pubkey = utils::parse_pubkey(&account)?;
+
+    let (account_data, slot) = rpc::get_account_with_slot(&config.rpc_client, &account_pubkey).await?;
+    let token_account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data.data)?;
+    let ct_account = token_account.get_extension::<ConfidentialTransferAccount>()
+        .map_err(|_| anyhow::anyhow!("Account {} is not configured for confidential transfers", account_pubkey))?;
+    let mint_data = config.rpc_client.get_account(&token_account.base.mint).await?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data.data)?;
+
+    let elgamal_keypair = crypto::derive_elgamal_keypair(config.owner(), &account_pubkey)?;
+    let aes_key = crypto::derive_aes_key(config.owner(), &account_pubkey)?;
+    if PodElGamalPubkey::from(*elgamal_keypair.pubkey()) != ct_account.elgamal_pubkey {
+        crypto::warn_derivation_scheme(config.owner(), &account_pubkey, &elgamal_keypair, &ct_account.elgamal_pubkey);
+        anyhow::bail!("The derived ElGamal key is not account {}'s, so its balance cannot be proven", account_pubkey);
+    }
+    let amount = crypto::decrypt_available_balance(
+        &elgamal_keypair,
+        &aes_key,
+        &ct_account.available_balance,
+        &ct_account.decryptable_available_balance,
+    )?;
+
+    say!("📋 Disclosure:");
+    say!("  Account: {}", account_pubkey);
+    say!("  Mint: {}", token_account.base.mint);
+    say!("  Slot: {}", slot);
+    say!("  Available balance: {}", utils::format_amount(amount, mint.base.decimals));
+
+    let proof_span = trace::span("generate_proofs");
+    let (commitment, opening, proof) = BalanceProof::prove(&elgamal_keypair, &ct_account.available_balance, amount)?;
+    drop(proof_span);
+
+    let balance_proof = BalanceProof {
+        format: balance_proof::BALANCE_PROOF_FORMAT.to_string(),
+        version: balance_proof::BALANCE_PROOF_VERSION,
+        created_at: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        account: account_pubkey.to_string(),
+        mint: token_account.base.mint.to_string(),
+        owner: token_account.base.owner.to_string(),
+        slot,
+        amount,
+        elgamal_pubkey: ct_account.elgamal_pubkey.to_string(),
+        available_balance: ct_account.available_balance.to_string(),
+        commitment: commitment.to_string(),
+        opening: BASE64.encode(opening.to_bytes()),
+        proof: BASE64.encode(bytemuck::bytes_of(&proof)),
+    };
+    // A file that doesn't verify would only embarrass the owner in front of the verifier
+    balance_proof.verify()?;
+    balance_proof.save(&out)?;
+
+    say!("\n✅ Balance proof written to {}", out.display());
+    say!("   Only this one balance is revealed; the keys and other amounts stay private");
+
+    output::emit("prove-balance", json!({
+        "path": out.display().to_string(),
+        "account": account_pubkey.to_string(),
+        "mint": token_account.base.mint.to_string(),
+        "slot": slot,
+        "amount": amount,
+    }));
+
+    say!("\n💡 The verifier checks it offline with 'verify-proof --file {}', and can compare", out.display());
+    say!("   available_balance with the account's at slot {} to tie the proof to the chain", slot);
+    say!("   The balance changes with the next transfer or apply");
+
+    Ok(())
+}
@@ -0,0 +1,150 @@
+use anyhow::{Result, Context};
+use solana_sdk::{
+    signature::{Signature, Signer},
+    transaction::Transaction,
+};
+use spl_token_2022::extension::{
+    confidential_transfer::ConfidentialTransferAccount,
+    transfer_fee::{instruction::transfer_checked_with_fee, TransferFeeConfig},
+    BaseStateWithExtensions,
+    StateWithExtensions,
+};
+use serde_json::json;
+use crate::{config::{self, AppConfig}, cpi, intent::{self, TransferIntent}, output::{self, say}, rpc, utils};
+
+/// A plain `transfer_checked` between public balances, for comparison with `confidential-transfer`.
+pub async fn execute(from: String, to: String, amount: String, owner: Option<String>) -> Result<()> {
+    run(from, to, amount, owner).await.map(|_| ())
+}
+
+/// Sends the transfer, returning its signature; none when nothing was sent.
+pub async fn run(from: String, to: String, amount: String, owner: Option<String>) -> Result<Option<Signature>> {
+    say!("➡️  Public Transfer...\n");
+
+    let config = AppConfig::new()?.with_owner(owner)?;
+    let from_pubkey = utils::parse_pubkey(&from)?;
+    let to_pubkey = utils::parse_pubkey(&to)?;
+
+    // Fetch both accounts and the mint
+    let from_data = config.rpc_client.get_account(&from_pubkey).await?;
+    let from_account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&from_data.data)?;
+    let to_data = config.rpc_client.get_account(&to_pubkey).await?;
+    let to_account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&to_data.data)?;
+    let mint_pubkey = from_account.base.mint;
+
+    if to_account.base.mint != mint_pubkey {
+        anyhow::bail!("Accounts belong to different mints ({} and {})", mint_pubkey, to_account.base.mint);
+    }
+
+    let mint_data = config.rpc_client.get_account(&mint_pubkey).await?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data.data)?;
+    let decimals = mint.base.decimals;
+    let amount = utils::parse_amount(&amount, decimals)?;
+
+    say!("📋 Transfer Details:");
+    say!("  From: {}", from_pubkey);
+    say!("  To: {}", to_pubkey);
+    say!("  Mint: {}", mint_pubkey);
+    say!("  Amount: {}", utils::format_amount(amount, decimals));
+
+    if amount > from_account.base.amount {
+        anyhow::bail!("Insufficient public balance! Available: {}, Requested: {}",
+            utils::format_amount(from_account.base.amount, decimals),
+            utils::format_amount(amount, decimals));
+    }
+    if to_account.get_extension::<ConfidentialTransferAccount>()
+        .is_ok_and(|ct_account| !bool::from(ct_account.allow_non_confidential_credits))
+    {
+        anyhow::bail!("{} blocks non-confidential credits; its owner can run 'enable-non-confidential-credits'", to_pubkey);
+    }
+
+    // Fee mints get the fee stated explicitly, so a fee change can't take more than shown
+    let fee = match mint.get_extension::<TransferFeeConfig>() {
+        Ok(transfer_fee_config) => {
+            let epoch = config.rpc_client.get_epoch_info().await?.epoch;
+            let fee = transfer_fee_config.calculate_epoch_fee(epoch, amount)
+                .ok_or_else(|| anyhow::anyhow!("Failed to calculate transfer fee"))?;
+            say!("  Fee: {}", utils::format_amount(fee, decimals));
+            Some(fee)
+        }
+        Err(_) => None,
+    };
+
+    // Program-owned accounts are authorized by their program via CPI, not by the payer
+    let authority = if cpi::is_program_owned(&from_account.base.owner) {
+        from_account.base.owner
+    } else {
+        config.owner().pubkey()
+    };
+
+    let transfer_ix = match fee {
+        Some(fee) => transfer_checked_with_fee(
+            &spl_token_2022::id(),
+            &from_pubkey,
+            &mint_pubkey,
+            &to_pubkey,
+            &authority,
+            &[], // No multisig
+            amount,
+            decimals,
+            fee,
+        )?,
+        None => spl_token_2022::instruction::transfer_checked(
+            &spl_token_2022::id(),
+            &from_pubkey,
+            &mint_pubkey,
+            &to_pubkey,
+            &authority,
+            &[], // No multisig
+            amount,
+            decimals,
+        )?,
+    };
+
+    if cpi::is_program_owned(&authority) {
+        cpi::emit_instructions("transfer", &authority, &[transfer_ix])?;
+        return Ok(None);
+    }
+
+    let mut transaction = Transaction::new_with_payer(
+        &[transfer_ix],
+        Some(&config.payer.pubkey()),
+    );
+
+    let recent_blockhash = config.rpc_client.get_latest_blockhash().await?;
+
+    if let Some(path) = config::global_options().export_intent {
+        transaction.message.recent_blockhash = recent_blockhash;
+        let intent = TransferIntent::new("transfer", &transaction)?
+            .account("from", &from_pubkey)
+            .account("to", &to_pubkey)
+            .account("mint", &mint_pubkey)
+            .amount(amount);
+        intent::export(&intent, &path)?;
+        return Ok(None);
+    }
+
+    config.sign(&mut transaction, recent_blockhash)?;
+
+    say!("\n📤 Sending transfer transaction...");
+    let signature = rpc::send_once(&config, &transaction)
+        .await
+        .context("Failed to transfer tokens")?;
+
+    say!("✅ Transfer complete!");
+    say!("   Signature: {}", signature);
+
+    output::emit("transfer", json!({
+        "from": from_pubkey.to_string(),
+        "to": to_pubkey.to_string(),
+        "mint": mint_pubkey.to_string(),
+        "amount": amount,
+        "fee": fee,
+        "signature": signature.to_string(),
+    }));
+
+    say!("\n💡 The amount and both balances are visible on chain; 'confidential-transfer'");
+    say!("   moves the same tokens with the amount encrypted");
+
+    Ok(Some(signature))
+}
@@ -0,0 +1,155 @@
+use anyhow::{Context, Result};
+use spl_token_2022::{
+    extension::{
+        confidential_transfer::ConfidentialTransferAccount,
+        BaseStateWithExtensions,
+        StateWithExtensions,
+    },
+};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use serde_json::json;
+use crate::{config::AppConfig, crypto, output::{self, say}, rpc, utils};
+
+struct Reconciled {
+    mint: Pubkey,
+    decimals: u8,
+    expected: u64,
+    actual: u64,
+    tolerance: u64,
+}
+
+/// `expected` is a JSON object mapping account addresses to expected confidential
+/// balances (available + pending) in token units, e.g. `{ "<account>": "12.5" }`.
+pub async fn execute(expected: PathBuf, tolerance: String) -> Result<()> {
+    say!("🧾 Reconciling Confidential Balances...\n");
+
+    let contents = std::fs::read_to_string(&expected)
+        .with_context(|| format!("Failed to read expected balances {}", expected.display()))?;
+    let ledger: BTreeMap<String, String> = serde_json::from_str(&contents)
+        .with_context(|| format!("{} must be a JSON object of account → amount", expected.display()))?;
+
+    let config = AppConfig::new()?;
+    say!("📋 Ledger: {} ({} accounts, tolerance {})", expected.display(), ledger.len(), tolerance);
+
+    let mut decimals_by_mint = HashMap::new();
+    let mut reports = Vec::new();
+    let mut discrepancies = 0;
+    let mut errors = 0;
+
+    for (account, expected_amount) in &ledger {
+        match reconcile_account(&config, &mut decimals_by_mint, account, expected_amount, &tolerance).await {
+            Ok(reconciled) => {
+                let difference = i128::from(reconciled.actual) - i128::from(reconciled.expected);
+                // Reported as an i64; a gap beyond that is rare enough to just flag
+                let Ok(reported_difference) = i64::try_from(difference) else {
+                    errors += 1;
+                    say!("  ⚠️  {}  expected {} and actual {} differ by more than the i64 range", account, reconciled.expected, reconciled.actual);
+                    reports.push(json!({
+                        "account": account,
+                        "status": "error",
+                        "error": format!("difference {} exceeds the i64 range", difference),
+                    }));
+                    continue;
+                };
+                let matches = difference.unsigned_abs() <= u128::from(reconciled.tolerance);
+                let decimals = reconciled.decimals;
+                if matches {
+                    say!("  ✅ {}  {}", account, utils::format_amount(reconciled.actual, decimals));
+                } else {
+                    discrepancies += 1;
+                    say!("  ❌ {}  expected {}, actual {} ({}{})",
+                        account,
+                        utils::format_amount(reconciled.expected, decimals),
+                        utils::format_amount(reconciled.actual, decimals),
+                        if difference > 0 { "+" } else { "-" },
+                        utils::format_amount(difference.unsigned_abs(), decimals));
+                }
+                reports.push(json!({
+                    "account": account,
+                    "mint": reconciled.mint.to_string(),
+                    "expected": reconciled.expected,
+                    "actual": reconciled.actual,
+                    "difference": reported_difference,
+                    "tolerance": reconciled.tolerance,
+                    "status": if matches { "match" } else { "mismatch" },
+                }));
+            }
+            Err(e) => {
+                errors += 1;
+                say!("  ⚠️  {}  {:#}", account, e);
+                reports.push(json!({
+                    "account": account,
+                    "status": "error",
+                    "error": format!("{:#}", e),
+                }));
+            }
+        }
+    }
+
+    say!("\n📊 Summary: {} account(s), {} mismatch(es), {} error(s)", ledger.len(), discrepancies, errors);
+
+    output::emit("reconcile", json!({
+        "ledger": expected.display().to_string(),
+        "accounts": reports,
+        "mismatches": discrepancies,
+        "errors": errors,
+    }));
+
+    // Non-zero exit so scheduled jobs notice
+    if discrepancies > 0 || errors > 0 {
+        anyhow::bail!("Reconciliation failed: {} mismatch(es), {} error(s)", discrepancies, errors);
+    }
+
+    Ok(())
+}
+
+async fn reconcile_account(
+    config: &AppConfig,
+    decimals_by_mint: &mut HashMap<Pubkey, u8>,
+    account: &str,
+    expected_amount: &str,
+    tolerance: &str,
+) -> Result<Reconciled> {
+    let account_pubkey = utils::parse_pubkey(account)?;
+    let (account_data, _) = rpc::get_account_with_slot(&config.rpc_client, &account_pubkey).await?;
+    let token_account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data.data)?;
+    let ct_account = token_account.get_extension::<ConfidentialTransferAccount>()?;
+    let mint = token_account.base.mint;
+
+    let decimals = match decimals_by_mint.get(&mint) {
+        Some(decimals) => *decimals,
+        None => {
+            let (mint_data, _) = rpc::get_account_with_slot(&config.rpc_client, &mint).await?;
+            let decimals = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data.data)?.base.decimals;
+            decimals_by_mint.insert(mint, decimals);
+            decimals
+        }
+    };
+    let expected = utils::parse_amount(expected_amount, decimals)?;
+    let tolerance = utils::parse_amount(tolerance, decimals)?;
+
+    let elgamal_keypair = crypto::derive_elgamal_keypair(config.owner(), &account_pubkey)?;
+    let aes_key = crypto::derive_aes_key(config.owner(), &account_pubkey)?;
+
+    let available_balance = crypto::decrypt_available_balance(
+        &elgamal_keypair,
+        &aes_key,
+        &ct_account.available_balance,
+        &ct_account.decryptable_available_balance,
+    )?;
+    let pending_balance = crypto::decrypt_pending_balance(
+        &elgamal_keypair,
+        &ct_account.pending_balance_lo,
+        &ct_account.pending_balance_hi,
+    )?;
+
+    Ok(Reconciled {
+        mint,
+        decimals,
+        expected,
+        actual: available_balance + pending_balance,
+        tolerance,
+    })
+}
@@ -0,0 +1,118 @@
+use anyhow::{Result, Context};
+use solana_sdk::{signature::Signer, transaction::Transaction};
+use spl_elgamal_registry::state::ELGAMAL_REGISTRY_ACCOUNT_LEN;
+use spl_token_2022::{
+    extension::{
+        confidential_transfer::ConfidentialTransferAccount,
+        BaseStateWithExtensions,
+        StateWithExtensions,
+    },
+    solana_zk_sdk::{
+        encryption::pod::elgamal::PodElGamalPubkey,
+        zk_elgamal_proof_program::proof_data::PubkeyValidityProofData,
+    },
+};
+use spl_token_confidential_transfer_proof_extraction::instruction::ProofLocation;
+use std::num::NonZero;
+use serde_json::json;
+use crate::{config::{self, AppConfig}, crypto::{self, DerivationScheme}, elgamal_registry, output::{self, say}, rpc, trace, utils};
+
+/// Publishes the owner's ElGamal public key in the registry, so senders can give
+/// `confidential-transfer --to` the wallet address instead of a token account.
+pub async fn execute(account: Option<String>, owner: Option<String>) -> Result<()> {
+    say!("📇 Registering ElGamal Key...\n");
+
+    let config = AppConfig::new()?.with_owner(owner)?;
+    let wallet = config.owner().pubkey();
+    let registry_address = elgamal_registry::address(&wallet);
+
+    // The key of an existing account, so what senders find is an account that can receive.
+    // Legacy keys are the same for every account, so there any account will do.
+    let account_pubkey = account.as_deref().map(utils::parse_pubkey).transpose()?;
+    if account_pubkey.is_none() && config::global_options().derivation_scheme != DerivationScheme::Legacy {
+        anyhow::bail!("Each account has its own key under this derivation scheme; pass --account to register one");
+    }
+    let derive_for = account_pubkey.unwrap_or(wallet);
+    let elgamal_keypair = crypto::derive_elgamal_keypair(config.owner(), &derive_for)?;
+    let elgamal_pubkey = PodElGamalPubkey::from(*elgamal_keypair.pubkey());
+    if let Some(account_pubkey) = &account_pubkey {
+        let account_data = config.rpc_client.get_account(account_pubkey).await?;
+        let token_account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data.data)?;
+        let ct_account = token_account.get_extension::<ConfidentialTransferAccount>()
+            .map_err(|_| anyhow::anyhow!("Account {} is not configured for confidential transfers; run 'create-account' first", account_pubkey))?;
+        if token_account.base.owner != wallet {
+            anyhow::bail!("Account {} belongs to {}, not {}", account_pubkey, token_account.base.owner, wallet);
+        }
+        if ct_account.elgamal_pubkey != elgamal_pubkey {
+            crypto::warn_derivation_scheme(config.owner(), account_pubkey, &elgamal_keypair, &ct_account.elgamal_pubkey);
+            anyhow::bail!("The derived key does not match account {}'s ElGamal key, so it cannot be registered for it", account_pubkey);
+        }
+    }
+
+    let existing = config.rpc_client.get_multiple_accounts(&[registry_address]).await?
+        .remove(0)
+        .and_then(|registry| elgamal_registry::from_account(&registry));
+
+    say!("📋 Registration Details:");
+    say!("  Wallet: {}", wallet);
+    say!("  Registry: {}", registry_address);
+    say!("  ElGamal Key: {}", elgamal_pubkey);
+    if let Some(account_pubkey) = &account_pubkey {
+        say!("  Account: {}", account_pubkey);
+    }
+    if let Some(existing) = &existing {
+        say!("  Currently registered: {}", existing.elgamal_pubkey);
+        if existing.elgamal_pubkey == elgamal_pubkey {
+            say!("\n💡 This key is already registered, nothing to do");
+            return Ok(());
+        }
+    }
+
+    let proof_span = trace::span("generate_proofs");
+    let proof_data = PubkeyValidityProofData::new(&elgamal_keypair)
+        .map_err(|e| anyhow::anyhow!("Failed to create pubkey validity proof: {:?}", e))?;
+    drop(proof_span);
+
+    // Both instructions put the proof right after themselves (offset 1)
+    let proof_location = ProofLocation::InstructionOffset(NonZero::new(1i8).unwrap(), &proof_data);
+    let instructions = match &existing {
+        Some(_) => spl_elgamal_registry::instruction::update_registry(&wallet, proof_location)?,
+        None => {
+            // The program only allocates the registry, so it has to be funded beforehand
+            let rent = config.rpc_client
+                .get_minimum_balance_for_rent_exemption(ELGAMAL_REGISTRY_ACCOUNT_LEN)
+                .await?;
+            say!("\n💰 Rent: {} lamports", rent);
+            let fund_ix = solana_system_interface::instruction::transfer(&config.payer.pubkey(), &registry_address, rent);
+            [vec![fund_ix], spl_elgamal_registry::instruction::create_registry(&wallet, proof_location)?].concat()
+        }
+    };
+
+    let mut transaction = Transaction::new_with_payer(&instructions, Some(&config.payer.pubkey()));
+    let recent_blockhash = config.rpc_client.get_latest_blockhash().await?;
+    config.sign(&mut transaction, recent_blockhash)?;
+
+    say!("\n📤 Sending registration transaction...");
+    rpc::sync_endpoints(&config).await?;
+    rpc::dump_instructions(&transaction);
+    let signature = rpc::send_once(&config, &transaction)
+        .await
+        .context("Failed to register ElGamal key")?;
+
+    say!("✅ ElGamal key {}!", if existing.is_some() { "updated" } else { "registered" });
+    say!("   Signature: {}", signature);
+
+    output::emit("register-elgamal-key", json!({
+        "wallet": wallet.to_string(),
+        "registry": registry_address.to_string(),
+        "elgamal_pubkey": elgamal_pubkey.to_string(),
+        "account": account_pubkey.map(|account| account.to_string()),
+        "updated": existing.is_some(),
+        "signature": signature.to_string(),
+    }));
+
+    say!("\n💡 Senders can now pass --to {} to confidential-transfer", wallet);
+    say!("   It reaches your account for their mint that uses this key");
+
+    Ok(())
+}
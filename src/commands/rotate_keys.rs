@@ -0,0 +1,185 @@
+use anyhow::{Result, Context};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::Signer,
+    transaction::Transaction,
+};
+use spl_token_2022::{
+    extension::{
+        confidential_transfer::{ConfidentialTransferAccount, ConfidentialTransferMint},
+        transfer_fee::TransferFeeConfig,
+        BaseStateWithExtensions,
+        StateWithExtensions,
+    },
+};
+use serde_json::json;
+use crate::{
+    commands::{apply_balance, approve_account, close_account, create_account, deposit, empty_account, withdraw},
+    config::{self, AppConfig},
+    cpi,
+    crypto,
+    output::{self, say},
+    rpc,
+    utils,
+};
+
+/// Moves the funds of `account` to a freshly configured account and closes the old one.
+///
+/// Token-2022 cannot reconfigure an account's ElGamal key in place, and keys here are
+/// derived from the account address, so a new address is what gives the new keys.
+pub async fn execute(account: String, owner_path: Option<String>) -> Result<()> {
+    say!("🔄 Rotating Confidential Account Keys...\n");
+
+    if config::global_options().export_intent.is_some() {
+        anyhow::bail!("rotate-keys sends several dependent transactions and cannot be exported as one intent");
+    }
+
+    let config = AppConfig::new()?.with_owner(owner_path.clone())?;
+    let account_pubkey = utils::parse_pubkey(&account)?;
+
+    let account_data = config.rpc_client.get_account(&account_pubkey).await?;
+    let token_account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data.data)?;
+    let ct_account = token_account.get_extension::<ConfidentialTransferAccount>()
+        .map_err(|_| anyhow::anyhow!("Account {} is not configured for confidential transfers", account_pubkey))?;
+    let owner = token_account.base.owner;
+    let mint_pubkey = token_account.base.mint;
+
+    if cpi::is_program_owned(&owner) {
+        anyhow::bail!("Account {} is owned by a program; its keys have to be rotated through that program", account_pubkey);
+    }
+    if owner != config.owner().pubkey() {
+        anyhow::bail!("The keypair {} does not own account {} (owner: {})", config.owner().pubkey(), account_pubkey, owner);
+    }
+
+    let mint_data = config.rpc_client.get_account(&mint_pubkey).await?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data.data)?;
+    let ct_mint = mint.get_extension::<ConfidentialTransferMint>()?;
+    let decimals = mint.base.decimals;
+
+    // A new account on a mint without auto-approve can only receive the deposit once approved
+    let needs_approval = !bool::from(ct_mint.auto_approve_new_accounts);
+    // approve-account signs as the payer, not --owner
+    if needs_approval && Option::<Pubkey>::from(ct_mint.authority) != Some(config.payer.pubkey()) {
+        anyhow::bail!("Mint {} requires approval of new accounts by its authority; rotate with that authority or ask them to approve the new account", mint_pubkey);
+    }
+
+    let elgamal_keypair = crypto::derive_elgamal_keypair(config.owner(), &account_pubkey)?;
+    let aes_key = crypto::derive_aes_key(config.owner(), &account_pubkey)?;
+    let available_balance = crypto::decrypt_available_balance(
+        &elgamal_keypair,
+        &aes_key,
+        &ct_account.available_balance,
+        &ct_account.decryptable_available_balance,
+    )?;
+    let pending_balance = crypto::decrypt_pending_balance(
+        &elgamal_keypair,
+        &ct_account.pending_balance_lo,
+        &ct_account.pending_balance_hi,
+    )?;
+    let confidential_balance = available_balance + pending_balance;
+    let public_balance = token_account.base.amount;
+    let moved = public_balance + confidential_balance;
+
+    // Moving the tokens is an ordinary transfer, so fee mints withhold part of it
+    let fee = match mint.get_extension::<TransferFeeConfig>() {
+        Ok(transfer_fee_config) => {
+            let epoch = config.rpc_client.get_epoch_info().await?.epoch;
+            transfer_fee_config.calculate_epoch_fee(epoch, moved)
+                .ok_or_else(|| anyhow::anyhow!("Failed to calculate transfer fee"))?
+        }
+        Err(_) => 0,
+    };
+    let redeposit = confidential_balance.min(moved - fee);
+
+    say!("📋 Rotation Plan:");
+    say!("  Account: {}", account_pubkey);
+    say!("  Mint: {}", mint_pubkey);
+    say!("  Confidential Balance: {}", utils::format_amount(confidential_balance, decimals));
+    say!("  Public Balance: {}", utils::format_amount(public_balance, decimals));
+    if fee > 0 {
+        say!("  Transfer Fee: {}", utils::format_amount(fee, decimals));
+    }
+    say!("\n  1. Apply the pending balance and withdraw everything");
+    say!("  2. Prove the old account empty");
+    say!("  3. Create a new account with new keys");
+    say!("  4. Move the tokens over and deposit the confidential part again");
+    say!("  5. Close the old account");
+
+    // Step 1: bring the whole confidential balance into the public balance
+    if pending_balance > 0 {
+        say!("\n━━━ Applying pending balance ━━━");
+        apply_balance::execute(account_pubkey.to_string(), owner_path.clone()).await?;
+    }
+    if confidential_balance > 0 {
+        say!("\n━━━ Withdrawing confidential balance ━━━");
+        withdraw::execute(account_pubkey.to_string(), utils::format_amount(confidential_balance, decimals), owner_path.clone()).await?;
+    }
+
+    // Step 2
+    say!("\n━━━ Emptying old account ━━━");
+    empty_account::execute(account_pubkey.to_string(), owner_path.clone()).await?;
+
+    // Step 3
+    say!("\n━━━ Creating new account ━━━");
+    let new_account = create_account::create(mint_pubkey.to_string(), owner_path.clone(), None, None).await?;
+    if needs_approval {
+        say!("\n━━━ Approving new account ━━━");
+        approve_account::execute(new_account.to_string()).await?;
+    }
+
+    // Step 4
+    let mut transfer_signature = None;
+    if moved > 0 {
+        say!("\n━━━ Moving {} to the new account ━━━", utils::format_amount(moved, decimals));
+        let transfer_ix = spl_token_2022::instruction::transfer_checked(
+            &spl_token_2022::id(),
+            &account_pubkey,
+            &mint_pubkey,
+            &new_account,
+            &owner,
+            &[], // No multisig
+            moved,
+            decimals,
+        )?;
+        let mut transaction = Transaction::new_with_payer(&[transfer_ix], Some(&config.payer.pubkey()));
+        let recent_blockhash = config.rpc_client.get_latest_blockhash().await?;
+        config.sign(&mut transaction, recent_blockhash)?;
+        let signature = rpc::send_once(&config, &transaction)
+            .await
+            .with_context(|| format!("Failed to move tokens to {}; the old account is empty and still open", new_account))?;
+        say!("   Signature: {}", signature);
+        transfer_signature = Some(signature);
+    }
+    if redeposit > 0 {
+        say!("\n━━━ Depositing into new account ━━━");
+        deposit::execute(new_account.to_string(), utils::format_amount(redeposit, decimals), owner_path.clone()).await?;
+        apply_balance::execute(new_account.to_string(), owner_path.clone()).await?;
+    }
+
+    // Step 5
+    say!("\n━━━ Closing old account ━━━");
+    close_account::execute(account_pubkey.to_string(), None, owner_path).await?;
+
+    say!("\n✅ Keys rotated!");
+    say!("   Old account: {} (closed)", account_pubkey);
+    say!("   New account: {}", new_account);
+
+    let new_elgamal_keypair = crypto::derive_elgamal_keypair(config.owner(), &new_account)?;
+    output::emit("rotate-keys", json!({
+        "old_account": account_pubkey.to_string(),
+        "new_account": new_account.to_string(),
+        "mint": mint_pubkey.to_string(),
+        "elgamal_pubkey": new_elgamal_keypair.pubkey().to_string(),
+        "confidential_amount": redeposit,
+        "public_amount": moved - fee - redeposit,
+        "fee": fee,
+        "transfer_signature": transfer_signature.map(|signature| signature.to_string()),
+    }));
+
+    say!("\n📚 What just happened:");
+    say!("   Funds now sit under ElGamal and AES keys derived for the new address;");
+    say!("   the old keys can no longer decrypt anything sent from now on");
+    say!("\n💡 Give senders the new account address; transfers to the old one will fail");
+
+    Ok(())
+}
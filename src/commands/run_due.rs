@@ -0,0 +1,321 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use spl_token_2022::{
+    extension::{
+        confidential_transfer::ConfidentialTransferAccount,
+        BaseStateWithExtensions,
+        StateWithExtensions,
+    },
+};
+use std::{path::{Path, PathBuf}, time::Duration};
+use crate::{
+    commands::{public_transfer, transfer},
+    config::{self, AppConfig},
+    crypto,
+    output::{self, say},
+    schedule::{ScheduledTransfer, Status, TransferKind, TransferSchedule},
+    utils,
+};
+
+/// Validates a transfer as if it were sent now and queues it in `path` for `execute_at`.
+#[allow(clippy::too_many_arguments)]
+pub async fn schedule(
+    kind: TransferKind,
+    from: String,
+    to: String,
+    amount: String,
+    auto_split: bool,
+    owner: Option<String>,
+    execute_at: String,
+    path: PathBuf,
+) -> Result<()> {
+    say!("⏰ Scheduling {}...\n", kind.command());
+
+    if config::global_options().export_intent.is_some() {
+        anyhow::bail!("--execute-at cannot be combined with --export-intent");
+    }
+    let execute_at = DateTime::parse_from_rfc3339(&execute_at)
+        .with_context(|| format!("Invalid RFC3339 time: {}", execute_at))?
+        .with_timezone(&Utc);
+    if execute_at <= Utc::now() {
+        anyhow::bail!("{} has already passed; drop --execute-at to send it now", execute_at.to_rfc3339());
+    }
+
+    let config = AppConfig::new()?.with_owner(owner.clone())?;
+    let from_pubkey = utils::parse_pubkey(&from)?;
+    let to_pubkey = utils::parse_pubkey(&to)?;
+    let from_data = config.rpc_client.get_account(&from_pubkey).await?;
+    let from_account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&from_data.data)?;
+    let mint_data = config.rpc_client.get_account(&from_account.base.mint).await?;
+    let decimals = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data.data)?.base.decimals;
+
+    let mut entry = ScheduledTransfer {
+        id: 0,
+        kind,
+        from: from_pubkey.to_string(),
+        to: to_pubkey.to_string(),
+        mint: from_account.base.mint.to_string(),
+        amount: utils::parse_amount(&amount, decimals)?,
+        decimals,
+        auto_split,
+        owner,
+        execute_at: execute_at.to_rfc3339(),
+        created_at: Utc::now().to_rfc3339(),
+        status: Status::Scheduled,
+        executed_at: None,
+        signatures: Vec::new(),
+        error: None,
+    };
+
+    say!("📋 Transfer Details:");
+    say!("  From: {}", entry.from);
+    say!("  To: {}", entry.to);
+    say!("  Mint: {}", entry.mint);
+    say!("  Amount: {}", entry.ui_amount());
+    say!("  Execute at: {}", entry.execute_at);
+
+    // The same checks run again just before sending
+    check_ready(&config, &entry).await?;
+    say!("\n✅ Sender and recipient are ready");
+
+    let _lock = utils::lock_exclusive(&path)?;
+    let mut schedule = TransferSchedule::load(&path)?;
+    entry.id = schedule.next_id();
+    schedule.transfers.push(entry.clone());
+    schedule.save(&path)?;
+
+    say!("🗓️  Scheduled as #{} in {}", entry.id, path.display());
+
+    output::emit("schedule-transfer", json!({
+        "id": entry.id,
+        "command": kind.command(),
+        "from": entry.from,
+        "to": entry.to,
+        "mint": entry.mint,
+        "amount": entry.amount,
+        "execute_at": entry.execute_at,
+        "schedule": path.display().to_string(),
+    }));
+
+    say!("\n💡 Nothing was sent; run 'run-due' at or after {}", entry.execute_at);
+    say!("   (or keep 'run-due --watch' running) to send it");
+
+    Ok(())
+}
+
+/// Sends every scheduled transfer whose time has come; with `watch`, keeps doing so
+/// every `interval` seconds.
+pub async fn execute(path: PathBuf, watch: bool, interval: u64) -> Result<()> {
+    say!("⏰ Running Due Transfers...\n");
+
+    if config::global_options().export_intent.is_some() {
+        anyhow::bail!("run-due sends transfers and cannot be combined with --export-intent");
+    }
+    // Fails on a missing keypair or config up front rather than on every due transfer
+    AppConfig::new()?;
+
+    #[cfg(not(feature = "watch"))]
+    if watch {
+        anyhow::bail!("--watch needs a build with the `watch` feature");
+    }
+    if !watch {
+        let failed = run_pass(&path).await?;
+        if failed > 0 {
+            anyhow::bail!("{} scheduled transfer(s) failed", failed);
+        }
+        return Ok(());
+    }
+
+    say!("👀 Watching {} every {}s (Ctrl+C to stop)", path.display(), interval);
+    loop {
+        run_pass(&path).await?;
+        tokio::time::sleep(Duration::from_secs(interval.max(1))).await;
+    }
+}
+
+/// Sends the due transfers once, returning how many failed. The schedule is locked for
+/// the whole pass and saved after every transfer.
+async fn run_pass(path: &Path) -> Result<usize> {
+    let _lock = utils::lock_exclusive(path)?;
+    let mut schedule = TransferSchedule::load(path)?;
+    let now = Utc::now();
+
+    for transfer in schedule.transfers.iter().filter(|transfer| transfer.status == Status::Executing) {
+        say!("⚠️  #{} was interrupted while sending; check {}'s history before rescheduling it",
+            transfer.id, transfer.from);
+    }
+
+    let mut due = Vec::new();
+    for (index, transfer) in schedule.transfers.iter().enumerate() {
+        if transfer.status == Status::Scheduled && transfer.execute_at()? <= now {
+            due.push(index);
+        }
+    }
+    if due.is_empty() {
+        let next = schedule.transfers.iter()
+            .filter(|transfer| transfer.status == Status::Scheduled)
+            .filter_map(|transfer| transfer.execute_at().ok())
+            .min();
+        match next {
+            Some(next) => say!("💤 Nothing due; next at {}", next.to_rfc3339()),
+            None => say!("💤 Nothing scheduled"),
+        }
+        return Ok(0);
+    }
+
+    say!("📋 Due: {}", due.len());
+    let mut reports = Vec::new();
+    for index in due {
+        schedule.transfers[index].status = Status::Executing;
+        schedule.save(path)?;
+
+        let transfer = schedule.transfers[index].clone();
+        say!("\n━━━ #{}: {} {} → {} ━━━", transfer.id, transfer.ui_amount(), transfer.from, transfer.to);
+        let result = match AppConfig::new()?.with_owner(transfer.owner.clone()) {
+            Ok(config) => match check_ready(&config, &transfer).await {
+                Ok(()) => send(&transfer).await,
+                Err(e) => Err(e),
+            },
+            Err(e) => Err(e),
+        };
+
+        let entry = &mut schedule.transfers[index];
+        entry.executed_at = Some(Utc::now().to_rfc3339());
+        match result {
+            Ok(signatures) => {
+                entry.status = Status::Executed;
+                entry.signatures = signatures;
+            }
+            Err(e) => {
+                say!("❌ #{} failed: {:#}", entry.id, e);
+                entry.status = Status::Failed;
+                entry.error = Some(format!("{:#}", e));
+            }
+        }
+        reports.push(json!({
+            "id": entry.id,
+            "command": entry.kind.command(),
+            "from": entry.from,
+            "to": entry.to,
+            "amount": entry.amount,
+            "status": entry.status,
+            "signatures": entry.signatures,
+            "error": entry.error,
+        }));
+        schedule.save(path)?;
+    }
+
+    let executed = reports.iter().filter(|report| report["status"] == "executed").count();
+    let failed = reports.len() - executed;
+    say!("\n📊 Summary: {} due, {} executed, {} failed", reports.len(), executed, failed);
+
+    output::emit("run-due", json!({
+        "schedule": path.display().to_string(),
+        "transfers": reports,
+        "executed": executed,
+        "failed": failed,
+    }));
+
+    Ok(failed)
+}
+
+async fn send(transfer: &ScheduledTransfer) -> Result<Vec<String>> {
+    let signatures = match transfer.kind {
+        TransferKind::Transfer => {
+            public_transfer::run(transfer.from.clone(), transfer.to.clone(), transfer.ui_amount(), transfer.owner.clone())
+                .await?
+                .into_iter()
+                .collect()
+        }
+        TransferKind::ConfidentialTransfer => transfer::run(
+            transfer.from.clone(),
+            transfer.to.clone(),
+            transfer.ui_amount(),
+            transfer.auto_split,
+            false,
+            transfer.owner.clone(),
+        ).await?,
+    };
+    if signatures.is_empty() {
+        anyhow::bail!("Nothing was sent; program-owned senders can't be scheduled");
+    }
+    Ok(signatures.iter().map(|signature| signature.to_string()).collect())
+}
+
+/// Checks the transfer could be sent right now: both accounts usable, the sender funded
+/// and the recipient able to receive it.
+async fn check_ready(config: &AppConfig, transfer: &ScheduledTransfer) -> Result<()> {
+    let from_pubkey = utils::parse_pubkey(&transfer.from)?;
+    let to_pubkey = utils::parse_pubkey(&transfer.to)?;
+    let from_data = config.rpc_client.get_account(&from_pubkey).await
+        .with_context(|| format!("Sender {} not found", from_pubkey))?;
+    let from_account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&from_data.data)?;
+    let to_data = config.rpc_client.get_account(&to_pubkey).await
+        .with_context(|| format!("Recipient {} not found", to_pubkey))?;
+    let to_account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&to_data.data)?;
+
+    if from_account.base.mint != to_account.base.mint {
+        anyhow::bail!("Accounts belong to different mints ({} and {})", from_account.base.mint, to_account.base.mint);
+    }
+    for (role, pubkey, account) in [("Sender", from_pubkey, &from_account.base), ("Recipient", to_pubkey, &to_account.base)] {
+        if account.is_frozen() {
+            anyhow::bail!("{} {} is frozen", role, pubkey);
+        }
+    }
+
+    match transfer.kind {
+        TransferKind::Transfer => {
+            if transfer.amount > from_account.base.amount {
+                anyhow::bail!("Insufficient public balance! Available: {}, Requested: {}",
+                    utils::format_amount(from_account.base.amount, transfer.decimals),
+                    transfer.ui_amount());
+            }
+            if to_account.get_extension::<ConfidentialTransferAccount>()
+                .is_ok_and(|ct_account| !bool::from(ct_account.allow_non_confidential_credits))
+            {
+                anyhow::bail!("{} blocks non-confidential credits; its owner can run 'enable-non-confidential-credits'", to_pubkey);
+            }
+        }
+        TransferKind::ConfidentialTransfer => {
+            if transfer.amount > transfer::MAX_TRANSFER_AMOUNT && !transfer.auto_split {
+                anyhow::bail!(
+                    "Transfer amount exceeds maximum (48-bit): {}; pass --auto-split to send it as several transfers",
+                    transfer::MAX_TRANSFER_AMOUNT
+                );
+            }
+            let from_ct_account = from_account.get_extension::<ConfidentialTransferAccount>()
+                .map_err(|_| anyhow::anyhow!("Sender {} is not configured for confidential transfers", from_pubkey))?;
+            let to_ct_account = to_account.get_extension::<ConfidentialTransferAccount>()
+                .map_err(|_| anyhow::anyhow!("Recipient {} is not configured for confidential transfers", to_pubkey))?;
+
+            let elgamal_keypair = crypto::derive_elgamal_keypair(config.owner(), &from_pubkey)?;
+            let aes_key = crypto::derive_aes_key(config.owner(), &from_pubkey)?;
+            let available_balance = crypto::decrypt_available_balance(
+                &elgamal_keypair,
+                &aes_key,
+                &from_ct_account.available_balance,
+                &from_ct_account.decryptable_available_balance,
+            )?;
+            if transfer.amount > available_balance {
+                anyhow::bail!("Insufficient balance! Available: {}, Requested: {}",
+                    utils::format_amount(available_balance, transfer.decimals),
+                    transfer.ui_amount());
+            }
+
+            if !bool::from(to_ct_account.approved) {
+                anyhow::bail!("Recipient {} is not approved for confidential transfers yet", to_pubkey);
+            }
+            if !bool::from(to_ct_account.allow_confidential_credits) {
+                anyhow::bail!("{} blocks confidential credits; its owner can run 'enable-confidential-credits'", to_pubkey);
+            }
+            if u64::from(to_ct_account.pending_balance_credit_counter)
+                >= u64::from(to_ct_account.maximum_pending_balance_credit_counter)
+            {
+                anyhow::bail!("Recipient {} has no pending credits left; its owner must run 'apply-balance'", to_pubkey);
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,28 @@
+use anyhow::Result;
+use serde_json::Value;
+use crate::output;
+
+pub async fn execute(command: Option<String>) -> Result<()> {
+    let schemas: Vec<Value> = match command {
+        Some(command) => {
+            let schema = output::schema(&command).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "No schema for '{}'. Known: {}",
+                    command,
+                    output::schema_names().collect::<Vec<_>>().join(", ")
+                )
+            })?;
+            vec![schema]
+        }
+        None => output::schema_names().filter_map(output::schema).collect(),
+    };
+
+    let rendered = if schemas.len() == 1 {
+        serde_json::to_string_pretty(&schemas[0])?
+    } else {
+        serde_json::to_string_pretty(&schemas)?
+    };
+    println!("{}", rendered);
+
+    Ok(())
+}
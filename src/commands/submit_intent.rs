@@ -0,0 +1,90 @@
+use anyhow::{Context, Result};
+use serde_json::json;
+use solana_sdk::signature::Signature;
+use std::path::PathBuf;
+use crate::{config::AppConfig, intent::TransferIntent, output::{self, say}, rpc};
+
+pub async fn execute(file: PathBuf, out: Option<PathBuf>) -> Result<()> {
+    say!("📥 Submitting Confidential Transfer Intent...\n");
+
+    let config = AppConfig::new()?;
+    let mut intent = TransferIntent::load(&file)?;
+    let mut transaction = intent.decode_transaction()?;
+
+    say!("📋 Intent Details:");
+    say!("  File: {}", file.display());
+    say!("  Operation: {}", intent.command);
+    for (name, pubkey) in &intent.accounts {
+        say!("  {}: {}", name, pubkey);
+    }
+    if let Some(amount) = intent.amount {
+        say!("  Amount (base units): {}", amount);
+    }
+
+    // Sign with the configured keypair (and --fee-payer) if the intent asks for it;
+    // signatures already collected by another wallet are kept.
+    let num_signers = transaction.message.header.num_required_signatures as usize;
+    for signer in [config.payer.as_ref(), config.owner()] {
+        let pubkey = signer.pubkey();
+        let index = transaction.message.account_keys[..num_signers].iter().position(|key| *key == pubkey);
+        if let Some(index) = index
+            && transaction.signatures[index] == Signature::default()
+        {
+            let recent_blockhash = transaction.message.recent_blockhash;
+            transaction
+                .try_partial_sign(&[signer], recent_blockhash)
+                .context("Failed to sign intent")?;
+            say!("\n✍️  Signed as {}", pubkey);
+        }
+    }
+
+    let missing_signers: Vec<String> = transaction.message.account_keys[..num_signers]
+        .iter()
+        .zip(&transaction.signatures)
+        .filter(|(_, signature)| **signature == Signature::default())
+        .map(|(key, _)| key.to_string())
+        .collect();
+
+    if !missing_signers.is_empty() {
+        let Some(out) = out else {
+            anyhow::bail!(
+                "Intent still needs signatures from {}; pass --out to hand it on",
+                missing_signers.join(", ")
+            );
+        };
+        intent.set_transaction(&transaction)?;
+        intent.save(&out)?;
+
+        say!("\n📦 Partially signed intent written to {}", out.display());
+        say!("   Still waiting on:");
+        for signer in &missing_signers {
+            say!("   • {}", signer);
+        }
+
+        output::emit("submit-intent", json!({
+            "command": intent.command,
+            "signature": null,
+            "missing_signers": missing_signers,
+        }));
+        return Ok(());
+    }
+
+    say!("\n📤 Sending transaction...");
+    let signature = rpc::send_once(&config, &transaction)
+        .await
+        .context("Failed to submit intent")?;
+
+    say!("✅ Intent submitted!");
+    say!("   Signature: {}", signature);
+
+    output::emit("submit-intent", json!({
+        "command": intent.command,
+        "signature": signature.to_string(),
+        "missing_signers": missing_signers,
+    }));
+
+    say!("\n💡 Intents carry a recent blockhash and expire after ~150 slots;");
+    say!("   rebuild with --export-intent if submission reports an expired blockhash");
+
+    Ok(())
+}
@@ -0,0 +1,176 @@
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use solana_client::rpc_request::RpcRequest;
+use spl_token_2022::extension::{
+    confidential_transfer::ConfidentialTransferAccount,
+    BaseStateWithExtensions,
+    StateWithExtensions,
+};
+use std::path::PathBuf;
+use crate::{config::{self, ReadOnlyConfig}, output::{self, say}, rpc, utils};
+
+/// Writes everything needed to triage a bug report into one JSON file. Nothing secret
+/// goes in: keypair paths and RPC credentials are redacted and account snapshots keep
+/// only public state, never ciphertexts or encryption keys.
+/// Identifies support bundles; the first bundles had no `format` or `version` and
+/// count as version 0.
+pub const SUPPORT_BUNDLE_FORMAT: &str = "confidential-support-bundle";
+
+/// Bumped whenever a field is removed, renamed or changes meaning.
+pub const SUPPORT_BUNDLE_VERSION: u32 = 1;
+
+pub async fn execute(out: PathBuf, accounts: Vec<String>, signatures: Vec<String>) -> Result<()> {
+    say!("🧰 Building Support Bundle...\n");
+
+    let config = ReadOnlyConfig::new()?;
+    let cli_config = config::load_cli_config().ok();
+
+    say!("📋 Collecting:");
+    say!("  CLI and environment");
+    let environment = json!({
+        "cli_version": env!("CARGO_PKG_VERSION"),
+        "os": std::env::consts::OS,
+        "arch": std::env::consts::ARCH,
+        "generated_at": chrono::Utc::now().to_rfc3339(),
+    });
+
+    say!("  Configuration (redacted)");
+    let options = config::global_options();
+    let configuration = json!({
+        "config_file": cli_config.is_some(),
+        "json_rpc_url": cli_config.as_ref().map(|cli_config| redact_url(&cli_config.json_rpc_url)),
+        "scan_url": options.scan_url.as_deref().map(redact_url),
+        "commitment": cli_config.as_ref().map(|cli_config| cli_config.commitment.clone()),
+        "keypair": options.keypair.as_deref().map(signer_kind),
+        "derivation_scheme": format!("{:?}", options.derivation_scheme),
+        "decrypt_timeout": options.decrypt_timeout,
+        "decrypt_threads": options.decrypt_threads,
+        "decrypt_table": options.decrypt_table.is_some(),
+        "proof_strategy": format!("{:?}", options.proof_strategy),
+    });
+
+    say!("  Cluster");
+    let cluster = json!({
+        "version": or_error(config.rpc_client.get_version().await.map(|version| version.solana_core)),
+        "health": or_error(config.rpc_client.get_health().await.map(|_| "ok")),
+        "slot": or_error(config.rpc_client.get_slot().await),
+    });
+
+    say!("  {} account snapshot(s)", accounts.len());
+    let mut account_snapshots = Vec::new();
+    for account in &accounts {
+        let snapshot = snapshot_account(&config, account).await
+            .unwrap_or_else(|e| json!({ "account": account, "error": format!("{:#}", e) }));
+        account_snapshots.push(snapshot);
+    }
+
+    say!("  {} transaction log(s)", signatures.len());
+    let mut transactions = Vec::new();
+    for signature in &signatures {
+        let transaction = transaction_logs(&config, signature).await
+            .unwrap_or_else(|e| json!({ "signature": signature, "error": format!("{:#}", e) }));
+        transactions.push(transaction);
+    }
+
+    let bundle = json!({
+        "format": SUPPORT_BUNDLE_FORMAT,
+        "version": SUPPORT_BUNDLE_VERSION,
+        "environment": environment,
+        "configuration": configuration,
+        "cluster": cluster,
+        "accounts": account_snapshots,
+        "transactions": transactions,
+    });
+    utils::write_atomic(&out, serde_json::to_string_pretty(&bundle)?)
+        .with_context(|| format!("Failed to write support bundle {}", out.display()))?;
+
+    say!("\n✅ Support bundle written to {}", out.display());
+
+    output::emit("support-bundle", json!({
+        "path": out.display().to_string(),
+        "accounts": account_snapshots.len(),
+        "transactions": transactions.len(),
+    }));
+
+    say!("\n💡 Review the file before attaching it to a bug report; it contains account");
+    say!("   addresses and transaction signatures but no keys or balances");
+
+    Ok(())
+}
+
+/// Public account state only: no ElGamal pubkey, ciphertexts or decryptable balances.
+async fn snapshot_account(config: &ReadOnlyConfig, account: &str) -> Result<Value> {
+    let account_pubkey = utils::parse_pubkey(account)?;
+    let (account_data, slot) = rpc::get_account_with_slot(&config.rpc_client, &account_pubkey).await?;
+    let token_account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data.data)?;
+
+    let confidential = token_account.get_extension::<ConfidentialTransferAccount>().ok().map(|ct_account| json!({
+        "approved": bool::from(ct_account.approved),
+        "allow_confidential_credits": bool::from(ct_account.allow_confidential_credits),
+        "allow_non_confidential_credits": bool::from(ct_account.allow_non_confidential_credits),
+        "pending_balance_credit_counter": u64::from(ct_account.pending_balance_credit_counter),
+        "maximum_pending_balance_credit_counter": u64::from(ct_account.maximum_pending_balance_credit_counter),
+        "expected_pending_balance_credit_counter": u64::from(ct_account.expected_pending_balance_credit_counter),
+        "actual_pending_balance_credit_counter": u64::from(ct_account.actual_pending_balance_credit_counter),
+    }));
+
+    Ok(json!({
+        "account": account_pubkey.to_string(),
+        "slot": slot,
+        "mint": token_account.base.mint.to_string(),
+        "owner": token_account.base.owner.to_string(),
+        "state": format!("{:?}", token_account.base.state),
+        "extensions": token_account.get_extension_types()?
+            .iter()
+            .map(|extension| format!("{:?}", extension))
+            .collect::<Vec<_>>(),
+        "confidential_transfer": confidential,
+    }))
+}
+
+async fn transaction_logs(config: &ReadOnlyConfig, signature: &str) -> Result<Value> {
+    let params = json!([signature, { "encoding": "json", "maxSupportedTransactionVersion": 0 }]);
+    let transaction: Value = config.scan_client
+        .send(RpcRequest::GetTransaction, params)
+        .await
+        .with_context(|| format!("Failed to fetch transaction {}", signature))?;
+    if transaction.is_null() {
+        anyhow::bail!("Transaction {} not found (it may have expired from the node's history)", signature);
+    }
+
+    Ok(json!({
+        "signature": signature,
+        "slot": transaction["slot"],
+        "error": transaction["meta"]["err"],
+        "compute_units_consumed": transaction["meta"]["computeUnitsConsumed"],
+        "logs": transaction["meta"]["logMessages"],
+    }))
+}
+
+fn or_error<T: serde::Serialize, E: std::fmt::Display>(result: std::result::Result<T, E>) -> Value {
+    match result {
+        Ok(value) => json!(value),
+        Err(e) => json!({ "error": e.to_string() }),
+    }
+}
+
+/// Keeps the scheme and host; paths and query strings often carry RPC API keys.
+fn redact_url(url: &str) -> String {
+    let (scheme, rest) = url.split_once("://").unwrap_or(("", url));
+    let host = rest.split(['/', '?']).next().unwrap_or_default();
+    let host = host.rsplit('@').next().unwrap_or_default();
+    let redacted = if rest.len() > host.len() && rest != format!("{}/", host) { "/<redacted>" } else { "" };
+    if scheme.is_empty() {
+        format!("{}{}", host, redacted)
+    } else {
+        format!("{}://{}{}", scheme, host, redacted)
+    }
+}
+
+/// The kind of signer given by `--keypair`, never its path or contents.
+fn signer_kind(source: &str) -> String {
+    match source.split_once("://") {
+        Some((scheme, _)) => format!("{}://", scheme),
+        None => "file".to_string(),
+    }
+}
@@ -0,0 +1,144 @@
+use anyhow::{Result, Context};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::Signer,
+    transaction::Transaction,
+};
+use solana_system_interface::instruction as system_instruction;
+use serde_json::json;
+use crate::{config::{AppConfig, ReadOnlyConfig}, output::{self, say}, rpc, utils};
+
+// Keeps each top-up transaction comfortably below the packet size limit
+const TRANSFERS_PER_TRANSACTION: usize = 20;
+
+// getMultipleAccounts accepts at most 100 keys per request
+const ACCOUNTS_PER_REQUEST: usize = 100;
+
+pub async fn execute(accounts: Vec<String>, dry_run: bool) -> Result<()> {
+    say!("⛽ Checking Rent Exemption...\n");
+
+    // Scanning never signs, so a dry run works without a keypair
+    let read_only = ReadOnlyConfig::new()?;
+    let pubkeys = accounts
+        .iter()
+        .map(|account| utils::parse_pubkey(account))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut shortfalls = Vec::new();
+
+    for chunk in pubkeys.chunks(ACCOUNTS_PER_REQUEST) {
+        let fetched = read_only.rpc_client.get_multiple_accounts(chunk).await?;
+
+        for (pubkey, account) in chunk.iter().zip(fetched) {
+            let Some(account) = account else {
+                say!("  ❓ {} does not exist, skipping", pubkey);
+                continue;
+            };
+
+            let minimum = read_only.rpc_client
+                .get_minimum_balance_for_rent_exemption(account.data.len())
+                .await?;
+
+            if account.lamports >= minimum {
+                say!("  ✅ {} is rent exempt ({} lamports)", pubkey, account.lamports);
+            } else {
+                let shortfall = minimum - account.lamports;
+                say!("  ⚠️  {} is short by {} lamports ({} bytes, has {}, needs {})",
+                    pubkey, shortfall, account.data.len(), account.lamports, minimum);
+                shortfalls.push((*pubkey, shortfall));
+            }
+        }
+    }
+
+    if shortfalls.is_empty() {
+        say!("\n✅ All accounts are rent exempt, nothing to do");
+        emit_summary(pubkeys.len(), &shortfalls, 0, &[]);
+        return Ok(());
+    }
+
+    let total = total_lamports(&shortfalls)?;
+    say!("\n📋 Top-Up Summary:");
+    say!("  Accounts: {}", shortfalls.len());
+    say!("  Total: {} lamports", total);
+
+    if dry_run {
+        say!("\n🔎 Dry run, no transactions sent");
+        emit_summary(pubkeys.len(), &shortfalls, total, &[]);
+        return Ok(());
+    }
+
+    let config = AppConfig::new()?;
+    say!("  Funded by: {}", config.payer.pubkey());
+
+    let mut signatures = Vec::new();
+    for (batch_index, batch) in shortfalls.chunks(TRANSFERS_PER_TRANSACTION).enumerate() {
+        let instructions: Vec<_> = batch
+            .iter()
+            .map(|(pubkey, lamports)| system_instruction::transfer(&config.payer.pubkey(), pubkey, *lamports))
+            .collect();
+
+        let mut transaction = Transaction::new_with_payer(
+            &instructions,
+            Some(&config.payer.pubkey()),
+        );
+
+        let recent_blockhash = config.rpc_client.get_latest_blockhash().await?;
+        config.sign(&mut transaction, recent_blockhash)?;
+
+        say!("\n📤 Sending top-up batch {} ({} accounts)...", batch_index + 1, batch.len());
+        let signature = rpc::send_once(&config, &transaction)
+            .await
+            .context("Failed to top up accounts")?;
+
+        say!("✅ Batch confirmed");
+        say!("   Signature: {}", signature);
+        signatures.push(signature.to_string());
+    }
+
+    emit_summary(pubkeys.len(), &shortfalls, total, &signatures);
+
+    say!("\n📚 What just happened:");
+    say!("   1. Compared each account's lamports with the rent-exempt minimum for its size");
+    say!("   2. Transferred exactly the missing lamports from the payer");
+    say!("   3. Accounts that grew (e.g. after reallocate) are rent exempt again");
+
+    Ok(())
+}
+
+/// Summed in u128 so many large shortfalls fail loudly instead of overflowing.
+fn total_lamports(shortfalls: &[(Pubkey, u64)]) -> Result<u64> {
+    let total = shortfalls.iter().map(|(_, lamports)| u128::from(*lamports)).sum();
+    utils::checked_u64(total, "Total top-up")
+}
+
+fn emit_summary(accounts_checked: usize, shortfalls: &[(Pubkey, u64)], total_lamports: u64, signatures: &[String]) {
+    output::emit("top-up", json!({
+        "accounts_checked": accounts_checked,
+        "total_lamports": total_lamports,
+        "shortfalls": shortfalls.iter().map(|(pubkey, lamports)| json!({
+            "account": pubkey.to_string(),
+            "lamports": lamports,
+        })).collect::<Vec<_>>(),
+        "signatures": signatures,
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn total_lamports_up_to_u64_max() {
+        let shortfalls = [(Pubkey::new_unique(), u64::MAX - 1), (Pubkey::new_unique(), 1)];
+        assert_eq!(total_lamports(&shortfalls).unwrap(), u64::MAX);
+        assert_eq!(total_lamports(&[]).unwrap(), 0);
+    }
+
+    #[test]
+    fn total_lamports_fails_past_u64_max() {
+        let shortfalls = [(Pubkey::new_unique(), u64::MAX), (Pubkey::new_unique(), u64::MAX)];
+        assert!(total_lamports(&shortfalls).is_err());
+        let shortfalls = [(Pubkey::new_unique(), u64::MAX), (Pubkey::new_unique(), 1)];
+        assert!(total_lamports(&shortfalls).is_err());
+    }
+}
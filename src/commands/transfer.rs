@@ -1,26 +1,226 @@
 use anyhow::{Result, Context};
 use solana_sdk::{
-    signature::Signer,
+    pubkey::Pubkey,
+    signature::{Signature, Signer},
     transaction::Transaction,
 };
 use spl_token_2022::{
     extension::{
-        confidential_transfer::ConfidentialTransferAccount,
+        confidential_transfer::{ConfidentialTransferAccount, ConfidentialTransferMint},
+        confidential_transfer_fee::ConfidentialTransferFeeConfig,
+        transfer_fee::TransferFeeConfig,
         StateWithExtensions,
     },
-    solana_zk_sdk::encryption::pod::elgamal::PodElGamalCiphertext,
+    solana_zk_sdk::{
+        encryption::{
+            elgamal::ElGamalPubkey,
+            pod::{
+                auth_encryption::PodAeCiphertext,
+                elgamal::PodElGamalPubkey,
+            },
+        },
+        zk_elgamal_proof_program::instruction::ProofInstruction,
+    },
+};
+use spl_token_confidential_transfer_proof_extraction::instruction::ProofLocation;
+use spl_token_confidential_transfer_proof_generation::{
+    transfer::{transfer_split_proof_data, TransferProofData},
+    transfer_with_fee::{transfer_with_fee_split_proof_data, TransferWithFeeProofData},
 };
-use crate::{config::AppConfig, crypto, utils};
+use serde_json::json;
+use crate::{config::{self, AppConfig}, cpi, crypto, multisig, output::{self, say}, proof_context, recipient, rpc, trace, utils};
 use spl_token_2022::extension::BaseStateWithExtensions;
-use bytemuck::Zeroable;
 
 
-pub async fn execute(from: String, to: String, amount: u64) -> Result<()> {
-    println!("🔒 Confidential Transfer (Simplified - Missing ZK Proofs)...\n");
+// Transfer amounts are split into 16-bit lo and 32-bit hi parts for encryption
+pub const MAX_TRANSFER_AMOUNT: u64 = (1u64 << 48) - 1; // 2^48 - 1
+
+struct TransferReceipt {
+    mint: Pubkey,
+    decimals: u8,
+    fee: u64,
+    new_balance: u64,
+    audited: bool,
+    inline_proofs: bool,
+    signature: Signature,
+}
+
+pub async fn execute(from: String, to: String, amount: String, auto_split: bool, plan: bool, owner: Option<String>) -> Result<()> {
+    run(from, to, amount, auto_split, plan, owner).await.map(|_| ())
+}
+
+/// Sends the transfer, returning its signatures; none when nothing was sent.
+pub async fn run(
+    from: String,
+    to: String,
+    amount: String,
+    auto_split: bool,
+    plan: bool,
+    owner: Option<String>,
+) -> Result<Vec<Signature>> {
+    say!("🔒 Confidential Transfer...\n");
     
-    let config = AppConfig::new()?;
+    let config = AppConfig::new()?.with_owner(owner)?;
     let from_pubkey = utils::parse_pubkey(&from)?;
-    let to_pubkey = utils::parse_pubkey(&to)?;
+    
+    // Fetch the sender and its mint to parse the amount
+    let from_account_data = config.rpc_client.get_account(&from_pubkey).await?;
+    let from_token_account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&from_account_data.data)?;
+    let to_pubkey = recipient::resolve(&config, &utils::parse_pubkey(&to)?, &from_token_account.base.mint).await?;
+    let mint_data = config.rpc_client.get_account(&from_token_account.base.mint).await?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data.data)?;
+    let decimals = mint.base.decimals;
+    let amount = utils::parse_amount(&amount, decimals)?;
+    
+    if amount > MAX_TRANSFER_AMOUNT {
+        if !auto_split {
+            anyhow::bail!(
+                "Transfer amount exceeds maximum (48-bit): {}; pass --auto-split to send it as several transfers",
+                MAX_TRANSFER_AMOUNT
+            );
+        }
+        return split_transfer(&config, &from_pubkey, &to_pubkey, amount, decimals).await;
+    }
+    
+    let Some(receipt) = rpc::rebuild_on_stale_balance(|| send_transfer(&config, &from_pubkey, &to_pubkey, amount, plan)).await? else {
+        return Ok(Vec::new());
+    };
+    let decimals = receipt.decimals;
+    
+    output::emit("confidential-transfer", json!({
+        "from": from_pubkey.to_string(),
+        "to": to_pubkey.to_string(),
+        "mint": receipt.mint.to_string(),
+        "amount": amount,
+        "fee": receipt.fee,
+        "signature": receipt.signature.to_string(),
+        "signatures": [receipt.signature.to_string()],
+    }));
+    
+    say!("\n📚 What just happened:");
+    say!("   1. Split {} into lo (16-bit) and hi (32-bit) parts", 
+        utils::format_amount(amount, decimals));
+    if receipt.audited {
+        say!("   2. Encrypted both parts under the sender, receiver and auditor ElGamal keys");
+    } else {
+        say!("   2. Encrypted both parts under the sender and receiver ElGamal keys");
+    }
+    if receipt.inline_proofs {
+        say!("   3. Generated the ZK proofs and verified them inline with the transfer:");
+    } else {
+        say!("   3. Generated the ZK proofs and verified each into a context account:");
+    }
+    say!("      • Equality proof: new sender balance matches its commitment");
+    say!("      • Validity proof: amount ciphertexts are well-formed");
+    say!("      • Range proof: amount and remaining balance are non-negative");
+    if receipt.fee > 0 {
+        say!("      • Fee proofs: {} fee withheld, encrypted for the withdraw-withheld authority",
+            utils::format_amount(receipt.fee, decimals));
+    }
+    say!("   4. Moved the encrypted amount to the receiver's PENDING balance");
+    if !receipt.inline_proofs {
+        say!("   5. Closed the context accounts and reclaimed their rent");
+    }
+    
+    say!("\n⚠️  Next Steps:");
+    say!("   • The receiver must run 'apply-balance' before spending");
+    say!("   • Your new available balance: {}", utils::format_amount(receipt.new_balance, decimals));
+    
+    Ok(vec![receipt.signature])
+}
+
+// Sends `amount` as consecutive transfers of at most `MAX_TRANSFER_AMOUNT`. Each part
+// re-reads the sender, so it proves against the balance the previous part left behind.
+async fn split_transfer(
+    config: &AppConfig,
+    from_pubkey: &Pubkey,
+    to_pubkey: &Pubkey,
+    amount: u64,
+    decimals: u8,
+) -> Result<Vec<Signature>> {
+    let from_account_data = config.rpc_client.get_account(from_pubkey).await?;
+    let from_token_account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&from_account_data.data)?;
+    if cpi::is_program_owned(&from_token_account.base.owner) {
+        anyhow::bail!("--auto-split is not supported for program-owned accounts (nothing is sent between parts)");
+    }
+    
+    // Check the whole amount up front so a short balance doesn't leave a partial transfer
+    let from_ct_account = from_token_account.get_extension::<ConfidentialTransferAccount>()?;
+    let elgamal_keypair = crypto::derive_elgamal_keypair(config.owner(), from_pubkey)?;
+    let aes_key = crypto::derive_aes_key(config.owner(), from_pubkey)?;
+    let available_balance = crypto::decrypt_available_balance(
+        &elgamal_keypair,
+        &aes_key,
+        &from_ct_account.available_balance,
+        &from_ct_account.decryptable_available_balance,
+    )?;
+    if amount > available_balance {
+        anyhow::bail!("Insufficient balance!");
+    }
+    
+    let parts: Vec<u64> = (0..amount.div_ceil(MAX_TRANSFER_AMOUNT))
+        .map(|index| (amount - index * MAX_TRANSFER_AMOUNT).min(MAX_TRANSFER_AMOUNT))
+        .collect();
+    say!("✂️  Splitting {} into {} transfers of at most {}",
+        utils::format_amount(amount, decimals),
+        parts.len(),
+        utils::format_amount(MAX_TRANSFER_AMOUNT, decimals));
+    
+    let mut signatures = Vec::new();
+    let mut fee = 0;
+    let mut receipt = None;
+    for (index, part) in parts.iter().enumerate() {
+        say!("\n━━━ Part {}/{}: {} ━━━", index + 1, parts.len(), utils::format_amount(*part, decimals));
+        let part_receipt = rpc::rebuild_on_stale_balance(|| send_transfer(config, from_pubkey, to_pubkey, *part, false))
+            .await
+            .with_context(|| format!(
+                "Part {}/{} failed after {} of {} was sent",
+                index + 1,
+                parts.len(),
+                utils::format_amount(parts[..index].iter().sum::<u64>(), decimals),
+                utils::format_amount(amount, decimals),
+            ))?
+            .ok_or_else(|| anyhow::anyhow!("Part {} was not sent", index + 1))?;
+        fee += part_receipt.fee;
+        signatures.push(part_receipt.signature);
+        receipt = Some(part_receipt);
+    }
+    let receipt = receipt.ok_or_else(|| anyhow::anyhow!("Nothing to transfer"))?;
+    
+    say!("\n✅ Sent {} in {} transfers:", utils::format_amount(amount, decimals), signatures.len());
+    for (index, signature) in signatures.iter().enumerate() {
+        say!("   {}. {}", index + 1, signature);
+    }
+    
+    output::emit("confidential-transfer", json!({
+        "from": from_pubkey.to_string(),
+        "to": to_pubkey.to_string(),
+        "mint": receipt.mint.to_string(),
+        "amount": amount,
+        "fee": fee,
+        "signature": receipt.signature.to_string(),
+        "signatures": signatures.iter().map(|signature| signature.to_string()).collect::<Vec<_>>(),
+    }));
+    
+    say!("\n⚠️  Next Steps:");
+    say!("   • The receiver must run 'apply-balance' before spending (each part is its own pending credit)");
+    say!("   • Your new available balance: {}", utils::format_amount(receipt.new_balance, decimals));
+    
+    Ok(signatures)
+}
+
+// Builds, proves and sends one transfer of at most `MAX_TRANSFER_AMOUNT`. Returns `None`
+// when nothing was sent: the proof strategies were only planned, or the sender is
+// program-owned and the instructions were emitted instead.
+async fn send_transfer(
+    config: &AppConfig,
+    from_pubkey: &Pubkey,
+    to_pubkey: &Pubkey,
+    amount: u64,
+    plan: bool,
+) -> Result<Option<TransferReceipt>> {
+    let from_pubkey = *from_pubkey;
+    let to_pubkey = *to_pubkey;
     
     // Fetch both accounts
     let from_account_data = config.rpc_client.get_account(&from_pubkey).await?;
@@ -42,104 +242,350 @@ pub async fn execute(from: String, to: String, amount: u64) -> Result<()> {
     let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data.data)?;
     let decimals = mint.base.decimals;
     
-    println!("📋 Transfer Details:");
-    println!("  From: {}", from_pubkey);
-    println!("  To: {}", to_pubkey);
-    println!("  Amount: {}", utils::format_amount(amount, decimals));
+    say!("📋 Transfer Details:");
+    say!("  From: {}", from_pubkey);
+    say!("  To: {}", to_pubkey);
+    say!("  Amount: {}", utils::format_amount(amount, decimals));
     
     // Derive encryption keys
-    let elgamal_keypair = crypto::derive_elgamal_keypair(&config.payer);
-    let aes_key = crypto::derive_aes_key(&config.payer);
+    let elgamal_keypair = crypto::derive_elgamal_keypair(config.owner(), &from_pubkey)?;
+    let aes_key = crypto::derive_aes_key(config.owner(), &from_pubkey)?;
+    crypto::warn_derivation_scheme(config.owner(), &from_pubkey, &elgamal_keypair, &from_ct_account.elgamal_pubkey);
     
     // Decrypt available balance
-    let available_balance = if from_ct_account.available_balance == PodElGamalCiphertext::zeroed() {
-        0u64
-    } else {
-        aes_key.decrypt(&from_ct_account.decryptable_available_balance.try_into()?)
-            .ok_or_else(|| anyhow::anyhow!("Failed to decrypt available balance"))?
-    };
+    let available_balance = crypto::decrypt_available_balance(
+        &elgamal_keypair,
+        &aes_key,
+        &from_ct_account.available_balance,
+        &from_ct_account.decryptable_available_balance,
+    )?;
     
-    println!("\n💰 Sender Available Balance: {}", 
+    say!("\n💰 Sender Available Balance: {}", 
         utils::format_amount(available_balance, decimals));
     
     if amount > available_balance {
         anyhow::bail!("Insufficient balance!");
     }
     
-    // Validate amount is within 48-bit range
-    const MAX_TRANSFER_AMOUNT: u64 = (1u64 << 48) - 1; // 2^48 - 1
-    if amount > MAX_TRANSFER_AMOUNT {
-        anyhow::bail!("Transfer amount exceeds maximum (48-bit): {}", MAX_TRANSFER_AMOUNT);
+    // Audited mints need the amount encrypted under the auditor key as a third handle
+    let ct_mint = mint.get_extension::<ConfidentialTransferMint>()?;
+    let auditor_elgamal_pubkey: Option<ElGamalPubkey> = Option::<PodElGamalPubkey>::from(ct_mint.auditor_elgamal_pubkey)
+        .map(|pubkey| pubkey.try_into())
+        .transpose()?;
+    if let Some(auditor) = &auditor_elgamal_pubkey {
+        say!("  Auditor ElGamal Key: {}", auditor);
+    }
+    if config::global_options().export_intent.is_some() {
+        anyhow::bail!("--export-intent is not supported for confidential-transfer (it spans several transactions)");
     }
     
-    println!("\n⚠️  IMPORTANT LIMITATION:");
-    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-    println!("This is a SIMPLIFIED implementation for demonstration.");
-    println!("A complete confidential transfer requires:");
-    println!();
-    println!("1. Transfer Amount Encryption (lo/hi split):");
-    println!("   • amount_lo: Low 16 bits encrypted");
-    println!("   • amount_hi: High 32 bits encrypted");
-    println!("   • Encrypted under sender, receiver, and auditor keys");
-    println!();
-    println!("2. Zero-Knowledge Proofs Required:");
-    println!("   • Validity Proof: Ciphertexts are well-formed");
-    println!("   • Range Proof: Amount is positive 48-bit number");
-    println!("   • Equality Proof: New source balance is correct");
-    println!("   • Fee Sigma Proof: If mint has transfer fees");
-    println!();
-    println!("3. Proof Generation Complexity:");
-    println!("   • BatchedGroupedCiphertext2HandlesValidityProofData");
-    println!("   • BatchedRangeProofU128Data (Bulletproofs)");
-    println!("   • CiphertextCommitmentEqualityProofData");
-    println!();
-    println!("4. Fee Handling (if enabled):");
-    println!("   • Calculate fee based on mint parameters");
-    println!("   • Encrypt fee amount");
-    println!("   • Generate fee validity and sigma proofs");
-    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-    
-    println!("\n📚 Transfer Flow (Conceptual):");
-    println!("  1. Decrypt sender's available balance: {}", 
-        utils::format_amount(available_balance, decimals));
-    println!("  2. Verify sufficient funds (amount <= available)");
-    println!("  3. Split amount into lo (16-bit) and hi (32-bit):");
+    // Mints with transfer fees need the fee encrypted under the withdraw-withheld authority key
+    let fee_parameters = match mint.get_extension::<TransferFeeConfig>() {
+        Ok(transfer_fee_config) => {
+            let ct_fee_config = mint.get_extension::<ConfidentialTransferFeeConfig>()
+                .map_err(|_| anyhow::anyhow!("Mint has transfer fees but no confidential transfer fee config"))?;
+            let epoch = config.rpc_client.get_epoch_info().await?.epoch;
+            let withdraw_withheld_authority_elgamal_pubkey: ElGamalPubkey =
+                ct_fee_config.withdraw_withheld_authority_elgamal_pubkey.try_into()?;
+            Some((*transfer_fee_config.get_epoch_fee(epoch), withdraw_withheld_authority_elgamal_pubkey))
+        }
+        Err(_) => None,
+    };
     
-    let amount_lo = amount & 0xFFFF; // Low 16 bits
-    let amount_hi = amount >> 16;     // High 32 bits
+    let fee = match &fee_parameters {
+        Some((transfer_fee, _)) => transfer_fee.calculate_fee(amount)
+            .ok_or_else(|| anyhow::anyhow!("Failed to calculate transfer fee"))?,
+        None => 0,
+    };
+    if let Some((transfer_fee, _)) = &fee_parameters {
+        say!("  Fee: {} ({} bps, max {})",
+            utils::format_amount(fee, decimals),
+            u16::from(transfer_fee.transfer_fee_basis_points),
+            utils::format_amount(u64::from(transfer_fee.maximum_fee), decimals));
+        say!("  Recipient Receives: {}", utils::format_amount(amount - fee, decimals));
+    }
     
-    println!("     amount_lo: {} (16-bit)", amount_lo);
-    println!("     amount_hi: {} (32-bit)", amount_hi);
+    let new_balance = available_balance - amount;
+    say!("  After Transfer: {}", utils::format_amount(new_balance, decimals));
     
-    println!("  4. Encrypt under 3 keys:");
-    println!("     • Sender ElGamal key");
-    println!("     • Receiver ElGamal key: {:?}", to_ct_account.elgamal_pubkey);
-    println!("     • Auditor key (if mint has auditor)");
+    say!("\n🔐 Generating transfer proofs...");
+    let proof_span = trace::span("generate_proofs");
     
-    println!("  5. Generate ZK proofs (NOT IMPLEMENTED):");
-    println!("     • Validity proof for ciphertexts");
-    println!("     • Range proof for positive amounts");
-    println!("     • Equality proof for new balance");
+    let destination_elgamal_pubkey: ElGamalPubkey = to_ct_account.elgamal_pubkey.try_into()?;
+    let current_available_balance = from_ct_account.available_balance.try_into()?;
+    // The proofs read the balance from its AES copy, so stand in a fresh one if it's missing
+    let current_decryptable_available_balance = if crypto::decryptable_balance_missing(
+        &from_ct_account.available_balance,
+        &from_ct_account.decryptable_available_balance,
+    ) {
+        aes_key.encrypt(available_balance)
+    } else {
+        from_ct_account.decryptable_available_balance.try_into()?
+    };
     
-    let new_balance = available_balance - amount;
-    println!("  6. Calculate new sender balance: {}", 
-        utils::format_amount(new_balance, decimals));
-    
-    println!("  7. Create transfer instruction with:");
-    println!("     • Encrypted amounts (lo/hi)");
-    println!("     • All ZK proofs");
-    println!("     • New decryptable balance");
-    
-    println!("\n💡 For Production Implementation:");
-    println!("  Refer to official examples:");
-    println!("  https://github.com/solana-labs/solana-program-library");
-    println!("  /tree/master/token/program-2022-test/tests");
-    println!();
-    println!("  Or use the spl-token-2022 CLI:");
-    println!("  $ spl-token transfer --confidential <MINT> <AMOUNT> <RECIPIENT>");
-    
-    println!("\n⚠️  This command is intentionally incomplete to show");
-    println!("   the complexity of confidential transfers.");
-    
-    Ok(())
-}
\ No newline at end of file
+    // Program-owned and multisig-owned accounts are authorized by their owner, not by the payer
+    let multisig_signers = multisig::signer_pubkeys(config, &from_token_account.base.owner).await?;
+    let authority = if cpi::is_program_owned(&from_token_account.base.owner) || !multisig_signers.is_empty() {
+        from_token_account.base.owner
+    } else {
+        config.owner().pubkey()
+    };
+    
+    // Create new decryptable balance
+    let new_decryptable_balance: PodAeCiphertext = aes_key.encrypt(new_balance).into();
+    
+    // Proofs travel inline when the transfer fits in one packet; otherwise each is
+    // verified into its own context state account and referenced by the transfer
+    let payer = config.payer.pubkey();
+    let (mut all_instructions, context_accounts) = match fee_parameters {
+        None => {
+            let TransferProofData {
+                equality_proof_data,
+                ciphertext_validity_proof_data_with_ciphertext,
+                range_proof_data,
+            } = transfer_split_proof_data(
+                &current_available_balance,
+                &current_decryptable_available_balance,
+                amount,
+                &elgamal_keypair,
+                &aes_key,
+                &destination_elgamal_pubkey,
+                auditor_elgamal_pubkey.as_ref(),
+            ).map_err(|e| anyhow::anyhow!("Failed to generate transfer proofs: {}", e))?;
+            
+            say!("  ✅ Ciphertext-commitment equality proof generated");
+            say!("  ✅ Ciphertext validity proof generated");
+            say!("  ✅ Range proof generated");
+            drop(proof_span);
+            
+            let transfer_with_proofs = |equality, validity, range| {
+                spl_token_2022::extension::confidential_transfer::instruction::transfer(
+                    &spl_token_2022::id(),
+                    &from_pubkey,
+                    &from_token_account.base.mint,
+                    &to_pubkey,
+                    &new_decryptable_balance,
+                    &ciphertext_validity_proof_data_with_ciphertext.ciphertext_lo,
+                    &ciphertext_validity_proof_data_with_ciphertext.ciphertext_hi,
+                    &authority,
+                    &multisig_signers.iter().collect::<Vec<_>>(),
+                    equality,
+                    validity,
+                    range,
+                )
+            };
+            let inline_instructions = transfer_with_proofs(
+                ProofLocation::InstructionOffset(1.try_into()?, &equality_proof_data),
+                ProofLocation::InstructionOffset(2.try_into()?, &ciphertext_validity_proof_data_with_ciphertext.proof_data),
+                ProofLocation::InstructionOffset(3.try_into()?, &range_proof_data),
+            )?;
+            if plan {
+                let proofs = [
+                    proof_context::plan_context_account(&payer, "Equality proof", ProofInstruction::VerifyCiphertextCommitmentEquality, &equality_proof_data)?,
+                    proof_context::plan_context_account(&payer, "Validity proof", ProofInstruction::VerifyBatchedGroupedCiphertext3HandlesValidity, &ciphertext_validity_proof_data_with_ciphertext.proof_data)?,
+                    proof_context::plan_context_account(&payer, "Range proof", ProofInstruction::VerifyBatchedRangeProofU128, &range_proof_data)?,
+                ];
+                let placeholders = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+                let mut context_instructions = transfer_with_proofs(
+                    ProofLocation::ContextStateAccount(&placeholders[0]),
+                    ProofLocation::ContextStateAccount(&placeholders[1]),
+                    ProofLocation::ContextStateAccount(&placeholders[2]),
+                )?;
+                context_instructions.extend(proof_context::close_instructions(config, &placeholders));
+                proof_context::plan(config, "confidential-transfer", &inline_instructions, &proofs, &context_instructions).await?;
+                return Ok(None);
+            }
+            if !proof_context::use_context_accounts(&inline_instructions, &payer)? {
+                (inline_instructions, vec![])
+            } else {
+                say!("\n📤 Verifying proofs on-chain...");
+                let mut contexts = proof_context::ContextAccounts::new(config);
+                let equality_context = contexts.create_and_verify(
+                    "Equality proof",
+                    ProofInstruction::VerifyCiphertextCommitmentEquality,
+                    &equality_proof_data,
+                ).await?;
+                let validity_context = contexts.create_and_verify(
+                    "Validity proof",
+                    ProofInstruction::VerifyBatchedGroupedCiphertext3HandlesValidity,
+                    &ciphertext_validity_proof_data_with_ciphertext.proof_data,
+                ).await?;
+                let range_context = contexts.create_and_verify(
+                    "Range proof",
+                    ProofInstruction::VerifyBatchedRangeProofU128,
+                    &range_proof_data,
+                ).await?;
+            
+                let instructions = transfer_with_proofs(
+                    ProofLocation::ContextStateAccount(&equality_context),
+                    ProofLocation::ContextStateAccount(&validity_context),
+                    ProofLocation::ContextStateAccount(&range_context),
+                );
+                match instructions {
+                    Ok(instructions) => (instructions, contexts.into_accounts()),
+                    Err(e) => return Err(contexts.close_after_error(e.into()).await),
+                }
+            }
+        }
+        Some((transfer_fee, withdraw_withheld_authority_elgamal_pubkey)) => {
+            let TransferWithFeeProofData {
+                equality_proof_data,
+                transfer_amount_ciphertext_validity_proof_data_with_ciphertext,
+                percentage_with_cap_proof_data,
+                fee_ciphertext_validity_proof_data,
+                range_proof_data,
+            } = transfer_with_fee_split_proof_data(
+                &current_available_balance,
+                &current_decryptable_available_balance,
+                amount,
+                &elgamal_keypair,
+                &aes_key,
+                &destination_elgamal_pubkey,
+                auditor_elgamal_pubkey.as_ref(),
+                &withdraw_withheld_authority_elgamal_pubkey,
+                transfer_fee.transfer_fee_basis_points.into(),
+                transfer_fee.maximum_fee.into(),
+            ).map_err(|e| anyhow::anyhow!("Failed to generate transfer-with-fee proofs: {}", e))?;
+            
+            say!("  ✅ Ciphertext-commitment equality proof generated");
+            say!("  ✅ Transfer amount validity proof generated");
+            say!("  ✅ Fee sigma (percentage-with-cap) proof generated");
+            say!("  ✅ Fee ciphertext validity proof generated");
+            say!("  ✅ Range proof generated");
+            drop(proof_span);
+            
+            let transfer_with_proofs = |equality, transfer_amount_validity, fee_sigma, fee_validity, range| {
+                spl_token_2022::extension::confidential_transfer::instruction::transfer_with_fee(
+                    &spl_token_2022::id(),
+                    &from_pubkey,
+                    &from_token_account.base.mint,
+                    &to_pubkey,
+                    &new_decryptable_balance,
+                    &transfer_amount_ciphertext_validity_proof_data_with_ciphertext.ciphertext_lo,
+                    &transfer_amount_ciphertext_validity_proof_data_with_ciphertext.ciphertext_hi,
+                    &authority,
+                    &multisig_signers.iter().collect::<Vec<_>>(),
+                    equality,
+                    transfer_amount_validity,
+                    fee_sigma,
+                    fee_validity,
+                    range,
+                )
+            };
+            let inline_instructions = transfer_with_proofs(
+                ProofLocation::InstructionOffset(1.try_into()?, &equality_proof_data),
+                ProofLocation::InstructionOffset(2.try_into()?, &transfer_amount_ciphertext_validity_proof_data_with_ciphertext.proof_data),
+                ProofLocation::InstructionOffset(3.try_into()?, &percentage_with_cap_proof_data),
+                ProofLocation::InstructionOffset(4.try_into()?, &fee_ciphertext_validity_proof_data),
+                ProofLocation::InstructionOffset(5.try_into()?, &range_proof_data),
+            )?;
+            if plan {
+                let proofs = [
+                    proof_context::plan_context_account(&payer, "Equality proof", ProofInstruction::VerifyCiphertextCommitmentEquality, &equality_proof_data)?,
+                    proof_context::plan_context_account(&payer, "Transfer amount validity proof", ProofInstruction::VerifyBatchedGroupedCiphertext3HandlesValidity, &transfer_amount_ciphertext_validity_proof_data_with_ciphertext.proof_data)?,
+                    proof_context::plan_context_account(&payer, "Fee sigma proof", ProofInstruction::VerifyPercentageWithCap, &percentage_with_cap_proof_data)?,
+                    proof_context::plan_context_account(&payer, "Fee validity proof", ProofInstruction::VerifyBatchedGroupedCiphertext2HandlesValidity, &fee_ciphertext_validity_proof_data)?,
+                    proof_context::plan_context_account(&payer, "Range proof", ProofInstruction::VerifyBatchedRangeProofU256, &range_proof_data)?,
+                ];
+                let placeholders = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+                let mut context_instructions = transfer_with_proofs(
+                    ProofLocation::ContextStateAccount(&placeholders[0]),
+                    ProofLocation::ContextStateAccount(&placeholders[1]),
+                    ProofLocation::ContextStateAccount(&placeholders[2]),
+                    ProofLocation::ContextStateAccount(&placeholders[3]),
+                    ProofLocation::ContextStateAccount(&placeholders[4]),
+                )?;
+                context_instructions.extend(proof_context::close_instructions(config, &placeholders));
+                proof_context::plan(config, "confidential-transfer", &inline_instructions, &proofs, &context_instructions).await?;
+                return Ok(None);
+            }
+            if !proof_context::use_context_accounts(&inline_instructions, &payer)? {
+                (inline_instructions, vec![])
+            } else {
+                say!("\n📤 Verifying proofs on-chain...");
+                let mut contexts = proof_context::ContextAccounts::new(config);
+                let equality_context = contexts.create_and_verify(
+                    "Equality proof",
+                    ProofInstruction::VerifyCiphertextCommitmentEquality,
+                    &equality_proof_data,
+                ).await?;
+                let transfer_amount_validity_context = contexts.create_and_verify(
+                    "Transfer amount validity proof",
+                    ProofInstruction::VerifyBatchedGroupedCiphertext3HandlesValidity,
+                    &transfer_amount_ciphertext_validity_proof_data_with_ciphertext.proof_data,
+                ).await?;
+                let fee_sigma_context = contexts.create_and_verify(
+                    "Fee sigma proof",
+                    ProofInstruction::VerifyPercentageWithCap,
+                    &percentage_with_cap_proof_data,
+                ).await?;
+                let fee_validity_context = contexts.create_and_verify(
+                    "Fee validity proof",
+                    ProofInstruction::VerifyBatchedGroupedCiphertext2HandlesValidity,
+                    &fee_ciphertext_validity_proof_data,
+                ).await?;
+                let range_context = contexts.create_and_verify(
+                    "Range proof",
+                    ProofInstruction::VerifyBatchedRangeProofU256,
+                    &range_proof_data,
+                ).await?;
+            
+                let instructions = transfer_with_proofs(
+                    ProofLocation::ContextStateAccount(&equality_context),
+                    ProofLocation::ContextStateAccount(&transfer_amount_validity_context),
+                    ProofLocation::ContextStateAccount(&fee_sigma_context),
+                    ProofLocation::ContextStateAccount(&fee_validity_context),
+                    ProofLocation::ContextStateAccount(&range_context),
+                );
+                match instructions {
+                    Ok(instructions) => (instructions, contexts.into_accounts()),
+                    Err(e) => return Err(contexts.close_after_error(e.into()).await),
+                }
+            }
+        }
+    };
+    
+    // Reclaim the context accounts' rent in the same transaction
+    all_instructions.extend(proof_context::close_instructions(config, &context_accounts));
+    
+    if cpi::is_program_owned(&authority) {
+        cpi::emit_instructions("confidential-transfer", &authority, &all_instructions)?;
+        return Ok(None);
+    }
+    
+    // Any failure from here on leaves the context accounts unused, so they are closed
+    let sent = async {
+        // Proving takes a while; catch a balance that moved meanwhile before it fails on chain
+        rpc::ensure_balance_unchanged(config, &from_pubkey, &from_ct_account.available_balance).await?;
+        
+        let mut transaction = Transaction::new_with_payer(
+            &all_instructions,
+            Some(&payer),
+        );
+        
+        let recent_blockhash = config.rpc_client.get_latest_blockhash().await?;
+        config.sign(&mut transaction, recent_blockhash)?;
+        
+        say!("\n📤 Sending transfer transaction...");
+        rpc::send_once(config, &transaction)
+            .await
+            .context("Failed to transfer")
+    }.await;
+    let signature = match sent {
+        Ok(signature) => signature,
+        Err(e) => return Err(proof_context::close_after_error(config, &context_accounts, e).await),
+    };
+    
+    say!("✅ Transfer successful!");
+    say!("   Signature: {}", signature);
+    
+    Ok(Some(TransferReceipt {
+        mint: from_token_account.base.mint,
+        decimals,
+        fee,
+        new_balance,
+        audited: auditor_elgamal_pubkey.is_some(),
+        inline_proofs: context_accounts.is_empty(),
+        signature,
+    }))
+}
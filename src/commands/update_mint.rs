@@ -0,0 +1,110 @@
+use anyhow::{Result, Context};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::Signer,
+    transaction::Transaction,
+};
+use spl_token_2022::{
+    extension::{
+        confidential_transfer::ConfidentialTransferMint,
+        BaseStateWithExtensions,
+        StateWithExtensions,
+    },
+    solana_zk_sdk::encryption::pod::elgamal::PodElGamalPubkey,
+};
+use std::str::FromStr;
+use serde_json::json;
+use crate::{config::{self, AppConfig}, intent::{self, TransferIntent}, output::{self, say}, rpc, utils};
+
+pub async fn execute(
+    mint: String,
+    auto_approve: Option<bool>,
+    auditor: Option<String>,
+    remove_auditor: bool,
+) -> Result<()> {
+    say!("🛠️  Updating Confidential Mint...\n");
+
+    let config = AppConfig::new()?;
+    let mint_pubkey = utils::parse_pubkey(&mint)?;
+
+    let mint_data = config.rpc_client.get_account(&mint_pubkey).await?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data.data)?;
+    let ct_mint = mint.get_extension::<ConfidentialTransferMint>()?;
+
+    let authority = Option::<Pubkey>::from(ct_mint.authority)
+        .ok_or_else(|| anyhow::anyhow!("Mint {} has no confidential transfer authority, so its config cannot change", mint_pubkey))?;
+    if authority != config.owner().pubkey() {
+        anyhow::bail!("The keypair {} is not the mint's confidential transfer authority ({})", config.owner().pubkey(), authority);
+    }
+
+    // update_mint sets both fields, so anything not given keeps its current value
+    let current_auto_approve = bool::from(ct_mint.auto_approve_new_accounts);
+    let current_auditor = Option::<PodElGamalPubkey>::from(ct_mint.auditor_elgamal_pubkey);
+    let new_auto_approve = auto_approve.unwrap_or(current_auto_approve);
+    let new_auditor = match (auditor, remove_auditor) {
+        (Some(auditor), _) => Some(PodElGamalPubkey::from_str(&auditor)
+            .map_err(|_| anyhow::anyhow!("Invalid auditor ElGamal public key (expected base64): {}", auditor))?),
+        (None, true) => None,
+        (None, false) => current_auditor,
+    };
+
+    let describe = |auditor: &Option<PodElGamalPubkey>| match auditor {
+        Some(auditor) => auditor.to_string(),
+        None => "none".to_string(),
+    };
+    say!("📋 Mint: {}", mint_pubkey);
+    say!("  Auto-approve: {} → {}", current_auto_approve, new_auto_approve);
+    say!("  Auditor: {} → {}", describe(&current_auditor), describe(&new_auditor));
+
+    if new_auto_approve == current_auto_approve && new_auditor == current_auditor {
+        say!("\n💡 Nothing to change");
+        return Ok(());
+    }
+
+    let update_ix = spl_token_2022::extension::confidential_transfer::instruction::update_mint(
+        &spl_token_2022::id(),
+        &mint_pubkey,
+        &authority,
+        &[], // No multisig
+        new_auto_approve,
+        new_auditor,
+    )?;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[update_ix],
+        Some(&config.payer.pubkey()),
+    );
+
+    let recent_blockhash = config.rpc_client.get_latest_blockhash().await?;
+
+    if let Some(path) = config::global_options().export_intent {
+        transaction.message.recent_blockhash = recent_blockhash;
+        let intent = TransferIntent::new("update-mint", &transaction)?
+            .account("mint", &mint_pubkey);
+        return intent::export(&intent, &path);
+    }
+
+    config.sign(&mut transaction, recent_blockhash)?;
+
+    say!("\n📤 Sending update transaction...");
+    let signature = rpc::send_once(&config, &transaction)
+        .await
+        .context("Failed to update mint")?;
+
+    say!("✅ Mint updated!");
+    say!("   Signature: {}", signature);
+
+    output::emit("update-mint", json!({
+        "mint": mint_pubkey.to_string(),
+        "auto_approve_new_accounts": new_auto_approve,
+        "auditor_elgamal_pubkey": new_auditor.map(|auditor| auditor.to_string()),
+        "signature": signature.to_string(),
+    }));
+
+    if new_auditor != current_auditor {
+        say!("\n⚠️  Only transfers sent from now on are encrypted under the new auditor key;");
+        say!("   earlier transfers stay readable only by the previous auditor");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,92 @@
+use anyhow::{Context, Result};
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use crate::{
+    balance_proof::{self, BalanceProof},
+    output::{self, say},
+    proof_data::{self, ProofDataFile, ProofKind},
+};
+
+/// Checks a proof offline with the ZK SDK, so a counterparty can trust a disclosure
+/// without the chain: a `prove-balance` file, an exported proof data file, or base64
+/// proof data given with its `--kind`.
+pub async fn execute(file: Option<PathBuf>, kind: Option<ProofKind>, data: Option<String>) -> Result<()> {
+    say!("🔍 Verifying Proof...\n");
+
+    let mut checks: Vec<(&str, Result<()>)> = Vec::new();
+    let mut balance = None;
+    let kind = match (&file, kind, data) {
+        (Some(path), None, None) => {
+            let contents = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            let value: Value = serde_json::from_str(&contents)
+                .with_context(|| format!("{} is not JSON", path.display()))?;
+            match value["format"].as_str() {
+                Some(balance_proof::BALANCE_PROOF_FORMAT) => {
+                    let proof = BalanceProof::load(path)?;
+                    say!("📋 Balance proof for {}", proof.account);
+                    say!("  Mint: {}", proof.mint);
+                    say!("  Slot: {}", proof.slot);
+                    say!("  Amount: {} base units\n", proof.amount);
+                    checks.push(("balance disclosure", proof.verify()));
+                    balance = Some(proof);
+                    ProofKind::CiphertextCommitmentEquality
+                }
+                Some(proof_data::PROOF_DATA_FORMAT) => {
+                    let proof = ProofDataFile::load(path)?;
+                    say!("📋 {} proof data\n", proof.kind.name());
+                    checks.push(("proof", proof.verify()));
+                    proof.kind
+                }
+                _ => anyhow::bail!("{} is neither a balance proof nor exported proof data", path.display()),
+            }
+        }
+        (None, Some(kind), Some(data)) => {
+            say!("📋 {} proof data\n", kind.name());
+            let result = BASE64.decode(data.trim())
+                .map_err(|_| anyhow::anyhow!("--data is not base64"))
+                .and_then(|bytes| kind.verify(&bytes));
+            checks.push(("proof", result));
+            kind
+        }
+        (None, _, _) => anyhow::bail!("Pass --file, or --kind with --data"),
+        (Some(_), _, _) => anyhow::bail!("--file carries its own proof; don't combine it with --kind or --data"),
+    };
+
+    let mut failures = Vec::new();
+    for (check, result) in &checks {
+        match result {
+            Ok(()) => say!("  ✅ {}", check),
+            Err(e) => {
+                say!("  ❌ {}  {:#}", check, e);
+                failures.push(json!({ "check": check, "error": format!("{:#}", e) }));
+            }
+        }
+    }
+
+    output::emit("verify-proof", json!({
+        "kind": kind.name(),
+        "file": file.as_ref().map(|path| path.display().to_string()),
+        "account": balance.as_ref().map(|proof| proof.account.clone()),
+        "slot": balance.as_ref().map(|proof| proof.slot),
+        "amount": balance.as_ref().map(|proof| proof.amount),
+        "failures": failures,
+    }));
+
+    if !failures.is_empty() {
+        anyhow::bail!("Proof failed verification");
+    }
+    match &balance {
+        Some(proof) => {
+            say!("\n✅ Account {} held {} base units at slot {}", proof.account, proof.amount, proof.slot);
+            say!("   The proof stands on its own; compare available_balance with the account at that slot to tie it to the chain");
+        }
+        None => {
+            say!("\n✅ The {} proof is valid", kind.name());
+            say!("   It shows the statement holds for the keys and ciphertexts in its context, not whose they are");
+        }
+    }
+
+    Ok(())
+}
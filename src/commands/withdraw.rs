@@ -1,5 +1,6 @@
 use anyhow::{Result, Context};
 use solana_sdk::{
+    pubkey::Pubkey,
     signature::Signer,
     transaction::Transaction,
 };
@@ -10,29 +11,30 @@ use spl_token_2022::{
     },
     solana_zk_sdk::{
         encryption::{
+            elgamal::ElGamalCiphertext,
             pod::elgamal::PodElGamalCiphertext,
-            pedersen::PedersenOpening,
-        },
-        zk_elgamal_proof_program::{
-            proof_data::{
-                CiphertextCommitmentEqualityProofData,
-                BatchedRangeProofU64Data,
-            },
-            instruction::ProofInstruction,
         },
+        zk_elgamal_proof_program::instruction::ProofInstruction,
     },
 };
 use spl_token_confidential_transfer_proof_extraction::instruction::ProofLocation;
+use spl_token_confidential_transfer_proof_generation::withdraw::{withdraw_proof_data, WithdrawProofData};
 use std::num::NonZero;
-use crate::{config::AppConfig, crypto, utils};
+use serde_json::json;
+use crate::{config::{self, AppConfig}, cpi, crypto, intent::{self, TransferIntent}, multisig, output::{self, say}, proof_context, rpc, trace, utils};
 use spl_token_2022::extension::BaseStateWithExtensions;
-use bytemuck::Zeroable;
 
-pub async fn execute(account: String, amount: u64) -> Result<()> {
-    println!("💸 Withdrawing from Confidential Account...\n");
+pub async fn execute(account: String, amount: String, owner: Option<String>) -> Result<()> {
+    say!("💸 Withdrawing from Confidential Account...\n");
     
-    let config = AppConfig::new()?;
+    let config = AppConfig::new()?.with_owner(owner)?;
     let account_pubkey = utils::parse_pubkey(&account)?;
+    rpc::rebuild_on_stale_balance(|| withdraw_once(&config, &account_pubkey, &amount)).await
+}
+
+/// Reads the account, proves the withdrawal against its current balance and sends it.
+async fn withdraw_once(config: &AppConfig, account_pubkey: &Pubkey, amount: &str) -> Result<()> {
+    let account_pubkey = *account_pubkey;
     
     // Fetch account data
     let account_data = config.rpc_client.get_account(&account_pubkey).await?;
@@ -43,24 +45,26 @@ pub async fn execute(account: String, amount: u64) -> Result<()> {
     let mint_data = config.rpc_client.get_account(&token_account.base.mint).await?;
     let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data.data)?;
     let decimals = mint.base.decimals;
+    let amount = utils::parse_amount(amount, decimals)?;
     
-    println!("📋 Withdrawal Details:");
-    println!("  Account: {}", account_pubkey);
-    println!("  Amount: {}", utils::format_amount(amount, decimals));
+    say!("📋 Withdrawal Details:");
+    say!("  Account: {}", account_pubkey);
+    say!("  Amount: {}", utils::format_amount(amount, decimals));
     
     // Derive encryption keys
-    let elgamal_keypair = crypto::derive_elgamal_keypair(&config.payer);
-    let aes_key = crypto::derive_aes_key(&config.payer);
+    let elgamal_keypair = crypto::derive_elgamal_keypair(config.owner(), &account_pubkey)?;
+    let aes_key = crypto::derive_aes_key(config.owner(), &account_pubkey)?;
+    crypto::warn_derivation_scheme(config.owner(), &account_pubkey, &elgamal_keypair, &ct_account.elgamal_pubkey);
     
     // Decrypt current available balance
-    let current_available_balance = if ct_account.available_balance == PodElGamalCiphertext::zeroed() {
-        0u64
-    } else {
-        aes_key.decrypt(&ct_account.decryptable_available_balance.try_into()?)
-            .ok_or_else(|| anyhow::anyhow!("Failed to decrypt available balance"))?
-    };
+    let current_available_balance = crypto::decrypt_available_balance(
+        &elgamal_keypair,
+        &aes_key,
+        &ct_account.available_balance,
+        &ct_account.decryptable_available_balance,
+    )?;
     
-    println!("\n💰 Current Available Balance: {}", 
+    say!("\n💰 Current Available Balance: {}", 
         utils::format_amount(current_available_balance, decimals));
     
     if amount > current_available_balance {
@@ -72,121 +76,162 @@ pub async fn execute(account: String, amount: u64) -> Result<()> {
     // Calculate new balance after withdrawal
     let new_available_balance = current_available_balance - amount;
     
-    println!("  After Withdrawal: {}", utils::format_amount(new_available_balance, decimals));
-    
-    println!("\n🔐 Generating withdrawal proofs...");
+    say!("  After Withdrawal: {}", utils::format_amount(new_available_balance, decimals));
     
-    // 1. Generate CiphertextCommitmentEquality proof
-    // This proves the withdrawal amount ciphertext matches the commitment
-    let destination_pubkey = elgamal_keypair.pubkey();
-    let opening = PedersenOpening::new_rand();
-    let withdrawal_ct = destination_pubkey.encrypt_with(amount, &opening);
+    say!("\n🔐 Generating withdrawal proofs...");
+    let proof_span = trace::span("generate_proofs");
     
-    // Create commitment to the withdrawal amount
-    let commitment = spl_token_2022::solana_zk_sdk::encryption::pedersen::Pedersen::with(amount, &opening);
-    
-    let equality_proof_data = CiphertextCommitmentEqualityProofData::new(
-        &elgamal_keypair,
-        &withdrawal_ct,
-        &commitment,
-        &opening,
+    // Both proofs are over the remaining balance ciphertext the program will compute,
+    // available_balance - encode(amount): the equality proof ties it to a fresh commitment
+    // whose opening we know, and the range proof shows that commitment is a valid u64
+    let available_balance: ElGamalCiphertext = ct_account.available_balance.try_into()?;
+    if !crypto::ciphertext_encrypts(&elgamal_keypair, &available_balance, current_available_balance) {
+        anyhow::bail!("The decryptable balance does not match the available balance ciphertext, so the proofs would not verify");
+    }
+    let remaining_balance_ciphertext = available_balance.subtract_amount(amount);
+    let WithdrawProofData { equality_proof_data, range_proof_data } = withdraw_proof_data(
+        &available_balance,
+        current_available_balance,
         amount,
-    ).map_err(|e| anyhow::anyhow!("Failed to create equality proof: {:?}", e))?;
-    
-    println!("  ✅ Ciphertext-commitment equality proof generated");
-    
-    // 2. Generate Range proof
-    // This proves the new balance after withdrawal is a valid u64
-    let new_balance_opening = PedersenOpening::new_rand();
-    let (new_balance_commitment, _) = spl_token_2022::solana_zk_sdk::encryption::pedersen::Pedersen::new(new_available_balance);
-    
-    let range_proof_data = BatchedRangeProofU64Data::new(
-        vec![&new_balance_commitment],
-        vec![new_available_balance],
-        vec![64], // bit length
-        vec![&new_balance_opening],
-    ).map_err(|e| anyhow::anyhow!("Failed to create range proof: {:?}", e))?;
-    
-    println!("  ✅ Range proof generated");
-    
-    // Create proof instructions
-    let equality_proof_instruction = ProofInstruction::VerifyCiphertextCommitmentEquality
-        .encode_verify_proof(None, &equality_proof_data);
+        &elgamal_keypair,
+    ).map_err(|e| anyhow::anyhow!("Failed to create withdrawal proofs: {}", e))?;
     
-    let range_proof_instruction = ProofInstruction::VerifyBatchedRangeProofU64
-        .encode_verify_proof(None, &range_proof_data);
+    say!("  ✅ Ciphertext-commitment equality proof generated");
+    say!("  ✅ Range proof generated");
+    drop(proof_span);
     
     // Create new decryptable balance
     let new_decryptable_balance = aes_key.encrypt(new_available_balance);
     let pod_decryptable_balance: spl_token_2022::solana_zk_sdk::encryption::pod::auth_encryption::PodAeCiphertext 
         = new_decryptable_balance.into();
     
-    // Proof locations: 
-    // equality proof is at -2 (two instructions back)
-    // range proof is at -1 (one instruction back)
-    let equality_proof_location = ProofLocation::InstructionOffset(
-        NonZero::new(-2i8).unwrap(),
-        &equality_proof_data,
-    );
-    
-    let range_proof_location = ProofLocation::InstructionOffset(
-        NonZero::new(-1i8).unwrap(),
-        &range_proof_data,
-    );
-    
-    // Create withdraw instruction
-    let withdraw_ixs = spl_token_2022::extension::confidential_transfer::instruction::withdraw(
-        &spl_token_2022::id(),
-        &account_pubkey,
-        &token_account.base.mint,
-        amount,
-        decimals,
-        &pod_decryptable_balance,
-        &config.payer.pubkey(),
-        &[], // No multisig
-        equality_proof_location,
-        range_proof_location,
+    // Program-owned and multisig-owned accounts are authorized by their owner, not by the payer
+    let multisig_signers = multisig::signer_pubkeys(config, &token_account.base.owner).await?;
+    let authority = if cpi::is_program_owned(&token_account.base.owner) || !multisig_signers.is_empty() {
+        token_account.base.owner
+    } else {
+        config.owner().pubkey()
+    };
+    
+    let withdraw_with_proofs = |equality_proof_location, range_proof_location| {
+        spl_token_2022::extension::confidential_transfer::instruction::withdraw(
+            &spl_token_2022::id(),
+            &account_pubkey,
+            &token_account.base.mint,
+            amount,
+            decimals,
+            &pod_decryptable_balance,
+            &authority,
+            &multisig_signers.iter().collect::<Vec<_>>(),
+            equality_proof_location,
+            range_proof_location,
+        )
+    };
+    
+    // Inline proofs: withdraw appends them right after itself
+    // (equality proof at offset 1, range proof at offset 2)
+    let mut all_instructions = withdraw_with_proofs(
+        ProofLocation::InstructionOffset(NonZero::new(1i8).unwrap(), &equality_proof_data),
+        ProofLocation::InstructionOffset(NonZero::new(2i8).unwrap(), &range_proof_data),
     )?;
     
-    // Combine instructions: proof1, proof2, withdraw
-    let mut all_instructions = vec![
-        equality_proof_instruction,
-        range_proof_instruction,
-    ];
-    all_instructions.extend(withdraw_ixs);
+    if cpi::is_program_owned(&authority) {
+        return cpi::emit_instructions("withdraw", &authority, &all_instructions);
+    }
     
-    let mut transaction = Transaction::new_with_payer(
-        &all_instructions,
-        Some(&config.payer.pubkey()),
-    );
+    let mut context_accounts = Vec::new();
+    if proof_context::use_context_accounts(&all_instructions, &config.payer.pubkey())? {
+        say!("\n📤 Verifying proofs into context accounts...");
+        let mut contexts = proof_context::ContextAccounts::new(config);
+        let equality_context = contexts.create_and_verify(
+            "Equality proof",
+            ProofInstruction::VerifyCiphertextCommitmentEquality,
+            &equality_proof_data,
+        ).await?;
+        let range_context = contexts.create_and_verify(
+            "Range proof",
+            ProofInstruction::VerifyBatchedRangeProofU64,
+            &range_proof_data,
+        ).await?;
+        
+        let instructions = withdraw_with_proofs(
+            ProofLocation::ContextStateAccount(&equality_context),
+            ProofLocation::ContextStateAccount(&range_context),
+        );
+        all_instructions = match instructions {
+            Ok(instructions) => instructions,
+            Err(e) => return Err(contexts.close_after_error(e.into()).await),
+        };
+        context_accounts = contexts.into_accounts();
+        all_instructions.extend(proof_context::close_instructions(config, &context_accounts));
+    }
     
-    let recent_blockhash = config.rpc_client.get_latest_blockhash().await?;
-    transaction.sign(&[&config.payer], recent_blockhash);
+    // Any failure from here on leaves the context accounts unused, so they are closed
+    let sent = async {
+        let mut transaction = Transaction::new_with_payer(
+            &all_instructions,
+            Some(&config.payer.pubkey()),
+        );
+        
+        let recent_blockhash = config.rpc_client.get_latest_blockhash().await?;
+
+        if let Some(path) = config::global_options().export_intent {
+            transaction.message.recent_blockhash = recent_blockhash;
+            let intent = TransferIntent::new("withdraw", &transaction)?
+                .account("account", &account_pubkey)
+                .account("mint", &token_account.base.mint)
+                .amount(amount)
+                .ciphertext("remaining_available_balance", PodElGamalCiphertext::from(remaining_balance_ciphertext))
+                .ciphertext("new_decryptable_available_balance", pod_decryptable_balance)
+                .proof("ciphertext_commitment_equality", &equality_proof_data)
+                .proof("batched_range_proof_u64", &range_proof_data);
+            return intent::export(&intent, &path).map(|()| None);
+        }
+
+        // Proving takes a while; catch a balance that moved meanwhile before it fails on chain
+        rpc::ensure_balance_unchanged(config, &account_pubkey, &ct_account.available_balance).await?;
+        
+        config.sign(&mut transaction, recent_blockhash)?;
+        
+        say!("\n📤 Sending withdrawal transaction...");
+        rpc::send_once(config, &transaction)
+            .await
+            .context("Failed to withdraw")
+            .map(Some)
+    }.await;
+    let signature = match sent {
+        Ok(Some(signature)) => signature,
+        // The exported intent consumes and closes the context accounts when it is submitted
+        Ok(None) => return Ok(()),
+        Err(e) => return Err(proof_context::close_after_error(config, &context_accounts, e).await),
+    };
     
-    println!("\n📤 Sending withdrawal transaction...");
-    let signature = config.rpc_client
-        .send_and_confirm_transaction(&transaction)
-        .await
-        .context("Failed to withdraw")?;
+    say!("✅ Withdrawal successful!");
+    say!("   Signature: {}", signature);
     
-    println!("✅ Withdrawal successful!");
-    println!("   Signature: {}", signature);
+    output::emit("withdraw", json!({
+        "account": account_pubkey.to_string(),
+        "mint": token_account.base.mint.to_string(),
+        "amount": amount,
+        "new_available_balance": new_available_balance,
+        "signature": signature.to_string(),
+    }));
     
-    println!("\n📚 What just happened:");
-    println!("   1. Withdrew {} tokens from encrypted balance", 
+    say!("\n📚 What just happened:");
+    say!("   1. Withdrew {} tokens from encrypted balance", 
         utils::format_amount(amount, decimals));
-    println!("   2. Tokens moved from confidential -> regular balance");
-    println!("   3. Generated two ZK proofs:");
-    println!("      • Equality proof: withdrawal amount is correct");
-    println!("      • Range proof: new balance is valid u64");
-    println!("   4. Updated available balance: {}", 
+    say!("   2. Tokens moved from confidential -> regular balance");
+    say!("   3. Generated two ZK proofs:");
+    say!("      • Equality proof: the remaining balance ciphertext matches a fresh commitment");
+    say!("      • Range proof: new balance is valid u64");
+    say!("   4. Updated available balance: {}", 
         utils::format_amount(new_available_balance, decimals));
     
-    println!("\n💡 Key Concepts:");
-    println!("   • Withdrawal converts confidential -> non-confidential tokens");
-    println!("   • Requires two ZK proofs (equality + range)");
-    println!("   • Deducted from available balance only");
-    println!("   • Regular balance is now visible on-chain");
+    say!("\n💡 Key Concepts:");
+    say!("   • Withdrawal converts confidential -> non-confidential tokens");
+    say!("   • Requires two ZK proofs (equality + range)");
+    say!("   • Deducted from available balance only");
+    say!("   • Regular balance is now visible on-chain");
     
     Ok(())
 }
\ No newline at end of file
@@ -1,34 +1,147 @@
 use anyhow::{Context, Result};
 use solana_cli_config::Config;
 use solana_client::nonblocking::rpc_client::RpcClient;
-use solana_sdk::signature::{Keypair, read_keypair_file};
+use solana_sdk::{hash::Hash, signature::Signer, transaction::Transaction};
 use std::path::PathBuf;
+use std::sync::OnceLock;
+use crate::{crypto::{self, DerivationScheme}, proof_context::ProofStrategy, signer, trace};
+
+/// Options given on the command line before or after the subcommand.
+#[derive(Debug, Default, Clone)]
+pub struct GlobalOptions {
+    pub keypair: Option<String>,
+    pub derivation_path: Option<String>,
+    pub fee_payer: Option<String>,
+    pub export_intent: Option<PathBuf>,
+    pub derivation_scheme: DerivationScheme,
+    pub decrypt_timeout: u64,
+    pub decrypt_threads: usize,
+    pub decrypt_table: Option<PathBuf>,
+    pub proof_strategy: ProofStrategy,
+    pub dump_instructions: bool,
+    pub scan_url: Option<String>,
+    pub send_url: Option<String>,
+    pub price_url: Option<String>,
+    pub price_currency: String,
+    pub key_store: Option<PathBuf>,
+    pub keyring: bool,
+    pub i_know_what_im_doing: bool,
+    pub allow_insecure_signer: bool,
+    pub multisig_signers: Vec<String>,
+    pub elgamal_key: Option<String>,
+    pub aes_key: Option<String>,
+}
+
+static GLOBAL_OPTIONS: OnceLock<GlobalOptions> = OnceLock::new();
+
+pub fn set_global_options(options: GlobalOptions) {
+    let _ = GLOBAL_OPTIONS.set(options);
+}
+
+pub fn global_options() -> GlobalOptions {
+    GLOBAL_OPTIONS.get().cloned().unwrap_or_default()
+}
+
+pub fn load_cli_config() -> Result<Config> {
+    let config_file = solana_cli_config::CONFIG_FILE
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("Unable to get config file path"))?;
+
+    Config::load(config_file)
+        .context("Failed to load Solana CLI config")
+}
+
+/// Client for account scans and history lookups: `--scan-url` when given, since some
+/// providers disable those methods on their transaction endpoint, else the primary.
+fn scan_client(json_rpc_url: &str) -> RpcClient {
+    RpcClient::new(global_options().scan_url.unwrap_or_else(|| json_rpc_url.to_string()))
+}
+
+/// RPC access only, for commands that never sign. No keypair is loaded or required,
+/// and a missing Solana CLI config falls back to its defaults.
+pub struct ReadOnlyConfig {
+    pub rpc_client: RpcClient,
+    pub scan_client: RpcClient,
+}
+
+impl ReadOnlyConfig {
+    pub fn new() -> Result<Self> {
+        let config = load_cli_config().unwrap_or_default();
+        Ok(Self {
+            scan_client: scan_client(&config.json_rpc_url),
+            rpc_client: RpcClient::new(config.json_rpc_url),
+        })
+    }
+}
 
 pub struct AppConfig {
     pub rpc_client: RpcClient,
-    pub payer: Keypair,
+    pub scan_client: RpcClient,
+    /// Where transactions are sent: `--send-url` when given, else the primary.
+    /// `rpc::sync_endpoints` keeps it consistent with the reads on `rpc_client`.
+    pub send_client: RpcClient,
+    /// Pays fees and rent: `--fee-payer` when given, else `--keypair`
+    pub payer: Box<dyn Signer>,
+    /// `--owner`, or `--keypair` under `--fee-payer`, when the accounts belong to a keypair other than the payer
+    pub owner: Option<Box<dyn Signer>>,
+    /// `--multisig-signer`s, for accounts owned by an SPL token multisig (see `multisig`)
+    pub multisig_signers: Vec<Box<dyn Signer>>,
 }
 
 impl AppConfig {
+    /// Loads the signers and any injected encryption keys. Read-only commands use
+    /// `ReadOnlyConfig` instead, so they start without touching either.
     pub fn new() -> Result<Self> {
-
-        let config_file = solana_cli_config::CONFIG_FILE
-            .as_ref()
-            .ok_or_else(|| anyhow::anyhow!("Unable to get config file path"))?;
-        
-        let config = Config::load(config_file)
-            .context("Failed to load Solana CLI config")?;
+        let _span = trace::span("load_config");
+        crypto::load_key_overrides()?;
+        let config = load_cli_config()?;
 
         let rpc_client = RpcClient::new(config.json_rpc_url.clone());
+        let scan_client = scan_client(&config.json_rpc_url);
+        let send_client = RpcClient::new(global_options().send_url.unwrap_or_else(|| config.json_rpc_url.clone()));
+
+        let keypair_source = global_options().keypair.unwrap_or(config.keypair_path);
+        let keypair = signer::resolve_signer(&keypair_source, "keypair")?;
+        let (payer, owner) = match global_options().fee_payer {
+            Some(source) => (signer::resolve_signer(&source, "fee-payer")?, Some(keypair)),
+            None => (keypair, None),
+        };
+        let multisig_signers = global_options().multisig_signers
+            .iter()
+            .map(|source| signer::resolve_signer(source, "multisig-signer"))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self { rpc_client, scan_client, send_client, payer, owner, multisig_signers })
+    }
 
-        let payer = read_keypair_file(&config.keypair_path)
-            .map_err(|e| anyhow::anyhow!("Failed to read keypair file: {}", e))?;
+    /// Uses `--owner` for encryption keys and authority signatures instead of the payer.
+    pub fn with_owner(mut self, owner: Option<String>) -> Result<Self> {
+        if let Some(source) = owner {
+            self.owner = Some(signer::resolve_signer(&source, "owner")?);
+        }
+        Ok(self)
+    }
 
-        Ok(Self { rpc_client, payer })
+    /// The keypair the accounts belong to: `--owner` when given, else `--keypair`.
+    pub fn owner(&self) -> &dyn Signer {
+        self.owner.as_deref().unwrap_or(self.payer.as_ref())
     }
 
-    pub fn load_keypair(path: &str) -> Result<Keypair> {
-        read_keypair_file(path)
-            .map_err(|e| anyhow::anyhow!("Failed to read keypair from {}: {}", path, e))
+    /// Signs with whichever of the payer, `--owner` and `--multisig-signer`s the
+    /// transaction needs.
+    pub fn sign(&self, transaction: &mut Transaction, recent_blockhash: Hash) -> Result<()> {
+        let signer_keys = transaction.message.signer_keys();
+        let mut signers: Vec<&dyn Signer> = Vec::new();
+        let candidates = [self.payer.as_ref(), self.owner()]
+            .into_iter()
+            .chain(self.multisig_signers.iter().map(|signer| signer.as_ref()));
+        for signer in candidates {
+            let pubkey = signer.pubkey();
+            if signer_keys.contains(&&pubkey) && !signers.iter().any(|s| s.pubkey() == pubkey) {
+                signers.push(signer);
+            }
+        }
+        transaction.try_sign(&signers, recent_blockhash)
+            .context("Missing a signer; pass the account owner with --owner or its --multisig-signer")
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,63 @@
+use anyhow::Result;
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use serde_json::json;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+use crate::output::{self, say};
+
+/// Off-curve owners (PDAs such as program-owned treasuries) have no private key,
+/// so only their program can authorize instructions for them.
+pub fn is_program_owned(owner: &Pubkey) -> bool {
+    !owner.is_on_curve()
+}
+
+/// Prints the instructions the program controlling `owner` has to issue, instead of
+/// signing them here.
+///
+/// Any instruction that lists `owner` as a signer must be invoked by that program with
+/// `invoke_signed`; the rest (proof verification, rent reclaim) are plain top-level
+/// instructions that have to stay in the given order around it.
+pub fn emit_instructions(command: &str, owner: &Pubkey, instructions: &[Instruction]) -> Result<()> {
+    say!("\n🏛️  Owner {} is off-curve (program-owned), nothing was signed or sent", owner);
+    say!("\n📋 Instructions for the owning program:");
+
+    for (index, instruction) in instructions.iter().enumerate() {
+        let needs_owner = instruction
+            .accounts
+            .iter()
+            .any(|account| account.is_signer && account.pubkey == *owner);
+        say!("  {}. {} ({} accounts, {} bytes){}",
+            index + 1,
+            instruction.program_id,
+            instruction.accounts.len(),
+            instruction.data.len(),
+            if needs_owner { "  ← invoke_signed by the owner's program" } else { "" });
+    }
+
+    say!("\n💡 What the controlling program must do:");
+    say!("   1. Keep every instruction in this order in one transaction");
+    say!("   2. Replace each marked instruction with its own instruction that CPIs it");
+    say!("      via invoke_signed with the owner's PDA seeds");
+    say!("   3. Pass all listed accounts through to the CPI");
+    say!("   Use --output json for program ids, account metas and instruction data");
+
+    let instructions: Vec<_> = instructions
+        .iter()
+        .map(|instruction| json!({
+            "program_id": instruction.program_id.to_string(),
+            "accounts": instruction.accounts.iter().map(|account| json!({
+                "pubkey": account.pubkey.to_string(),
+                "is_signer": account.is_signer,
+                "is_writable": account.is_writable,
+            })).collect::<Vec<_>>(),
+            "data": BASE64.encode(&instruction.data),
+        }))
+        .collect();
+
+    output::emit("cpi-instructions", json!({
+        "command": command,
+        "owner": owner.to_string(),
+        "instructions": instructions,
+    }));
+
+    Ok(())
+}
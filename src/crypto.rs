@@ -1,28 +1,614 @@
-use solana_sdk::signature::Keypair;
+use anyhow::Result;
+use bytemuck::Zeroable;
+use clap::ValueEnum;
+use solana_offchain_message::OffchainMessage;
+use solana_sdk::{pubkey::Pubkey, signature::{Signature, Signer}};
+use crate::{config, key_store::{self, KeyStore}, keyring, keyset::KeyEntry, lookup_table::LookupTable, output::say, secrets::{self, AesKey, SecretBytes}, signer, trace};
 use spl_token_2022::solana_zk_sdk::encryption::{
-  auth_encryption::AeKey,
-  elgamal::{ElGamalKeypair, ElGamalSecretKey},
+  auth_encryption::{AeCiphertext, AeKey},
+  discrete_log::DiscreteLog,
+  elgamal::{ElGamalCiphertext, ElGamalKeypair, ElGamalSecretKey},
+  pod::{auth_encryption::PodAeCiphertext, elgamal::{PodElGamalCiphertext, PodElGamalPubkey}},
 };
+use std::{collections::HashMap, fmt, num::NonZeroUsize, sync::{atomic::{AtomicUsize, Ordering}, mpsc, Mutex, OnceLock}, time::{Duration, Instant}};
 
-pub fn derive_elgamal_keypair(signer: &Keypair) -> ElGamalKeypair {
-  ElGamalKeypair::new_from_signer(
-    signer, 
-    b""
-  ).unwrap()
+/// How encryption keys are derived from the owner's signature.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DerivationScheme {
+  /// Sign an empty seed; one key pair per owner (this CLI's original scheme)
+  Legacy,
+  /// Sign the token account address, as `spl-token configure-confidential-transfer-account` does
+  #[default]
+  SplToken,
+  /// Sign an off-chain message naming the token account; the scheme to use with a
+  /// Ledger, whose Solana app refuses to sign raw messages that aren't transactions
+  Offchain,
+}
+
+/// Which of the two keys a derivation signature seeds.
+#[derive(Clone, Copy)]
+enum KeyKind {
+  ElGamal,
+  Ae,
+}
+
+impl DerivationScheme {
+  /// The message whose signature seeds the key. Legacy and spl-token sign what
+  /// `ElGamalKeypair::new_from_signer` and `AeKey::new_from_signer` would.
+  fn message(self, key: KeyKind, account: &Pubkey) -> Result<Vec<u8>> {
+    let prefix: &[u8] = match key {
+      KeyKind::ElGamal => b"ElGamalSecretKey",
+      KeyKind::Ae => b"AeKey",
+    };
+    match self {
+      DerivationScheme::Legacy => Ok(prefix.to_vec()),
+      DerivationScheme::SplToken => Ok([prefix, account.as_ref()].concat()),
+      DerivationScheme::Offchain => {
+        // Printable ASCII, so the device shows it in full before signing
+        let name = match key {
+          KeyKind::ElGamal => "ElGamal",
+          KeyKind::Ae => "AE",
+        };
+        let text = format!("confidential-cli: derive the {} key of token account {}", name, account);
+        OffchainMessage::new(0, text.as_bytes())
+          .and_then(|message| message.serialize())
+          .map_err(|e| anyhow::anyhow!("Failed to build the key derivation message: {}", e))
+      }
+    }
+  }
+
+  pub fn name(self) -> &'static str {
+    match self {
+      DerivationScheme::Legacy => "legacy",
+      DerivationScheme::SplToken => "spl-token",
+      DerivationScheme::Offchain => "offchain",
+    }
+  }
+}
 
+/// Keys given with `--elgamal-key`/`--aes-key`, used for every account instead of deriving them.
+#[derive(Default)]
+struct KeyOverrides {
+  elgamal: Option<SecretBytes>,
+  aes: Option<SecretBytes>,
 }
 
-pub fn derive_aes_key(signer: &Keypair) -> AeKey {
-  AeKey::new_from_signer(signer, b"").unwrap()
+static KEY_OVERRIDES: OnceLock<KeyOverrides> = OnceLock::new();
+
+/// Loads and checks the injected keys when a command first needs keys (`AppConfig::new`),
+/// so a bad key still fails before any work but read-only commands never read them.
+pub fn load_key_overrides() -> Result<()> {
+  if KEY_OVERRIDES.get().is_some() {
+    return Ok(());
+  }
+  let options = config::global_options();
+  let elgamal = options.elgamal_key.as_deref()
+    .map(|source| -> Result<SecretBytes> {
+      let bytes = signer::read_key_bytes(source, "elgamal-key")?;
+      let keypair = match bytes.len() {
+        // A bare secret key; the public half follows from it
+        32 => ElGamalSecretKey::try_from(bytes.as_slice()).map(ElGamalKeypair::new),
+        _ => ElGamalKeypair::try_from(bytes.as_slice()),
+      }.map_err(|e| anyhow::anyhow!("Invalid --elgamal-key (expected a 64-byte keypair or 32-byte secret key): {}", e))?;
+      Ok(SecretBytes::new(<[u8; 64]>::from(&keypair).to_vec()))
+    })
+    .transpose()?;
+  let aes = options.aes_key.as_deref()
+    .map(|source| -> Result<SecretBytes> {
+      let bytes = signer::read_key_bytes(source, "aes-key")?;
+      AeKey::try_from(bytes.as_slice())
+        .map_err(|e| anyhow::anyhow!("Invalid --aes-key (expected 16 bytes): {}", e))?;
+      Ok(bytes)
+    })
+    .transpose()?;
+  let _ = KEY_OVERRIDES.set(KeyOverrides { elgamal, aes });
+  Ok(())
+}
+
+fn key_overrides() -> &'static KeyOverrides {
+  KEY_OVERRIDES.get_or_init(KeyOverrides::default)
+}
+
+static KEY_STORE: OnceLock<KeyStore> = OnceLock::new();
+
+/// Keys `import-keys` stored for this owner and account, which win over derived ones.
+fn imported_keys(signer: &dyn Signer, account: &Pubkey) -> Option<&'static KeyEntry> {
+  let store = KEY_STORE.get_or_init(|| {
+    let path = key_store::path();
+    KeyStore::load_or_default(&path).unwrap_or_else(|e| {
+      say!("⚠️  Ignoring the key store: {:#}", e);
+      KeyStore::default()
+    })
+  });
+  if store.keys.is_empty() {
+    return None;
+  }
+  store.find(&signer.pubkey(), account, config::global_options().derivation_scheme)
 }
 
-pub fn print_encryption_info(keypair: &Keypair) {
-  let elgamal_keypair = derive_elgamal_keypair(keypair);
+/// Derivation signatures already made this run, by signer and message.
+type SignatureCache = Mutex<HashMap<(Pubkey, Vec<u8>), Signature>>;
 
-  println!("\n🔐 Encryption Keys Derived:");
-  println!("  ElGamal Public Key: {:?}", elgamal_keypair.pubkey());
-  println!("  AES-GCM-SIV Key: Derived (32 bytes)");
-  println!("\n💡 These keys are deterministically derived from your Solana keypair");
-  println!("   - ElGamal: Used for homomorphic encryption (Twisted ElGamal)");
-  println!("   - AES: Used for authenticated encryption of opening values");
-}
\ No newline at end of file
+static DERIVATION_SIGNATURES: OnceLock<SignatureCache> = OnceLock::new();
+
+/// Signs a derivation message once per run, since a hardware wallet asks for every signature.
+fn derivation_signature(signer: &dyn Signer, message: &[u8]) -> Result<Signature> {
+  let cache = DERIVATION_SIGNATURES.get_or_init(Default::default);
+  let key = (signer.pubkey(), message.to_vec());
+  if let Some(signature) = cache.lock().unwrap_or_else(|e| e.into_inner()).get(&key) {
+    return Ok(*signature);
+  }
+
+  if signer.is_interactive() {
+    say!("\n✍️  Approve the key derivation message on your hardware wallet...");
+  }
+  let signature = signer.try_sign_message(message)
+    .map_err(|e| anyhow::anyhow!("Failed to derive encryption keys with {}: {}", signer.pubkey(), e))?;
+  // Some signers return the default signature, which is no key material at all
+  if signature == Signature::default() {
+    anyhow::bail!("{} returned an empty signature, which cannot seed encryption keys", signer.pubkey());
+  }
+  cache.lock().unwrap_or_else(|e| e.into_inner()).insert(key, signature);
+  Ok(signature)
+}
+
+fn elgamal_keypair_for(signer: &dyn Signer, account: &Pubkey, scheme: DerivationScheme) -> Result<ElGamalKeypair> {
+  let signature = derivation_signature(signer, &scheme.message(KeyKind::ElGamal, account)?)?;
+  ElGamalKeypair::new_from_signature(&signature)
+    .map_err(|e| anyhow::anyhow!("Failed to derive the ElGamal keypair: {}", e))
+}
+
+fn aes_key_for(signer: &dyn Signer, account: &Pubkey, scheme: DerivationScheme) -> Result<AesKey> {
+  let signature = derivation_signature(signer, &scheme.message(KeyKind::Ae, account)?)?;
+  AeKey::new_from_signature(&signature)
+    .map(AesKey::new)
+    .map_err(|e| anyhow::anyhow!("Failed to derive the AE key: {}", e))
+}
+
+/// Keys already read from or written to the OS keyring this run, by item name.
+static KEYRING_KEYS: OnceLock<Mutex<HashMap<String, (ElGamalKeypair, AesKey)>>> = OnceLock::new();
+
+/// With `--keyring`, the keys cached in the OS keyring, deriving and caching both on a
+/// miss so later runs skip the signer. Keyring trouble is only warned about: the keys
+/// can always be derived again.
+fn keyring_keys(signer: &dyn Signer, account: &Pubkey) -> Result<Option<(ElGamalKeypair, AesKey)>> {
+  let options = config::global_options();
+  if !options.keyring {
+    return Ok(None);
+  }
+  let scheme = options.derivation_scheme;
+  // Legacy keys are the same for every account of the owner
+  let name = match scheme {
+    DerivationScheme::Legacy => format!("{}:{}", scheme.name(), signer.pubkey()),
+    _ => format!("{}:{}:{}", scheme.name(), signer.pubkey(), account),
+  };
+  let cache = KEYRING_KEYS.get_or_init(Default::default);
+  if let Some(keys) = cache.lock().unwrap_or_else(|e| e.into_inner()).get(&name) {
+    return Ok(Some(keys.clone()));
+  }
+
+  let cached = keyring::get(&name).and_then(|secret| secret.map(|secret| parse_keyring_keys(&secret)).transpose());
+  let keys = match cached {
+    Ok(Some(keys)) => keys,
+    Ok(None) | Err(_) => {
+      if let Err(e) = &cached {
+        say!("⚠️  Ignoring the keyring item {}: {:#}", name, e);
+      }
+      let keys = (elgamal_keypair_for(signer, account, scheme)?, aes_key_for(signer, account, scheme)?);
+      let secret = secrets::SecretString::new(format!(
+        "{}.{}",
+        secrets::encode_elgamal_keypair(&keys.0).as_str(),
+        secrets::encode_aes_key(&keys.1).as_str(),
+      ));
+      if let Err(e) = keyring::set(&name, &secret) {
+        say!("⚠️  Could not cache the keys in the keyring: {:#}", e);
+      }
+      keys
+    }
+  };
+  cache.lock().unwrap_or_else(|e| e.into_inner()).insert(name, keys.clone());
+  Ok(Some(keys))
+}
+
+fn parse_keyring_keys(secret: &str) -> Result<(ElGamalKeypair, AesKey)> {
+  let (elgamal, aes) = secret.split_once('.')
+    .ok_or_else(|| anyhow::anyhow!("expected '<ElGamal keypair>.<AE key>' in base64"))?;
+  let elgamal = ElGamalKeypair::try_from(secrets::decode_base64(elgamal)?.as_slice())
+    .map_err(|e| anyhow::anyhow!("Invalid ElGamal keypair: {}", e))?;
+  let aes = AeKey::try_from(secrets::decode_base64(aes)?.as_slice())
+    .map_err(|e| anyhow::anyhow!("Invalid AE key: {}", e))?;
+  Ok((elgamal, AesKey::new(aes)))
+}
+
+pub fn derive_elgamal_keypair(signer: &dyn Signer, account: &Pubkey) -> Result<ElGamalKeypair> {
+  if let Some(bytes) = &key_overrides().elgamal {
+    return Ok(ElGamalKeypair::try_from(bytes.as_slice()).expect("checked in load_key_overrides"));
+  }
+  if let Some(entry) = imported_keys(signer, account) {
+    return entry.elgamal_keypair();
+  }
+  if let Some((elgamal_keypair, _)) = keyring_keys(signer, account)? {
+    return Ok(elgamal_keypair);
+  }
+  elgamal_keypair_for(signer, account, config::global_options().derivation_scheme)
+}
+
+pub fn derive_aes_key(signer: &dyn Signer, account: &Pubkey) -> Result<AesKey> {
+  if let Some(bytes) = &key_overrides().aes {
+    return Ok(AesKey::new(AeKey::try_from(bytes.as_slice()).expect("checked in load_key_overrides")));
+  }
+  if let Some(entry) = imported_keys(signer, account) {
+    return entry.aes_key();
+  }
+  if let Some((_, aes_key)) = keyring_keys(signer, account)? {
+    return Ok(aes_key);
+  }
+  aes_key_for(signer, account, config::global_options().derivation_scheme)
+}
+
+/// Another scheme, when it (and not the configured one) derives the account's
+/// on-chain ElGamal key; `None` when the keys match or can't be told apart.
+pub fn mismatched_derivation_scheme(
+  signer: &dyn Signer,
+  account: &Pubkey,
+  elgamal_keypair: &ElGamalKeypair,
+  elgamal_pubkey: &PodElGamalPubkey,
+) -> Option<DerivationScheme> {
+  // Probing costs a device confirmation per scheme, so hardware wallets are spared it
+  if key_overrides().elgamal.is_some()
+    || imported_keys(signer, account).is_some()
+    || signer.is_interactive()
+    || PodElGamalPubkey::from(*elgamal_keypair.pubkey()) == *elgamal_pubkey
+  {
+    return None;
+  }
+  let current = config::global_options().derivation_scheme;
+  DerivationScheme::value_variants()
+    .iter()
+    .copied()
+    .filter(|scheme| *scheme != current)
+    .find(|scheme| elgamal_keypair_for(signer, account, *scheme)
+      .is_ok_and(|keypair| PodElGamalPubkey::from(*keypair.pubkey()) == *elgamal_pubkey))
+}
+
+/// Tells the user to switch schemes when the account was configured under the other one.
+pub fn warn_derivation_scheme(
+  signer: &dyn Signer,
+  account: &Pubkey,
+  elgamal_keypair: &ElGamalKeypair,
+  elgamal_pubkey: &PodElGamalPubkey,
+) {
+  if let Some(scheme) = mismatched_derivation_scheme(signer, account, elgamal_keypair, elgamal_pubkey) {
+    say!("\n⚠️  {} was configured with {} keys, but --derivation-scheme is {}", account, scheme.name(), config::global_options().derivation_scheme.name());
+    say!("   Pass --derivation-scheme {} (or set CONFIDENTIAL_CLI_DERIVATION_SCHEME) to use it", scheme.name());
+  }
+}
+
+pub fn print_encryption_info(keypair: &dyn Signer, account: &Pubkey) -> Result<()> {
+  let elgamal_keypair = derive_elgamal_keypair(keypair, account)?;
+
+  say!("\n🔐 Encryption Keys Derived:");
+  say!("  ElGamal Public Key: {:?}", elgamal_keypair.pubkey());
+  say!("  AES-GCM-SIV Key: Derived (32 bytes)");
+  if key_overrides().elgamal.is_some() || key_overrides().aes.is_some() {
+    say!("\n💡 Keys given with --elgamal-key/--aes-key are used as-is; any other is derived");
+  } else if imported_keys(keypair, account).is_some() {
+    say!("\n💡 These keys were imported with 'import-keys' ({})", key_store::path().display());
+  } else {
+    say!("\n💡 These keys are deterministically derived from your Solana keypair");
+    say!("   - Scheme: {:?} (pass the same --derivation-scheme to every command)", config::global_options().derivation_scheme);
+    if config::global_options().keyring {
+      say!("   - Cached in the OS keyring (--keyring), so later runs don't ask the signer");
+    }
+  }
+  say!("   - ElGamal: Used for homomorphic encryption (Twisted ElGamal)");
+  say!("   - AES: Used for authenticated encryption of opening values");
+  Ok(())
+}
+
+/// Checks, without a discrete-log search, whether `ciphertext` encrypts exactly `amount`.
+pub fn ciphertext_encrypts(elgamal_keypair: &ElGamalKeypair, ciphertext: &ElGamalCiphertext, amount: u64) -> bool {
+  // Decrypting `ciphertext - amount` leaves the identity point iff the amounts match
+  ciphertext.subtract_amount(amount).decrypt(elgamal_keypair.secret()).target == Default::default()
+}
+
+/// True when the available balance holds funds but its AES copy was never written
+/// (zeroed), so it can only be read from the ElGamal ciphertext.
+pub fn decryptable_balance_missing(
+  available_balance: &PodElGamalCiphertext,
+  decryptable_available_balance: &PodAeCiphertext,
+) -> bool {
+  *available_balance != PodElGamalCiphertext::zeroed() && *decryptable_available_balance == PodAeCiphertext::zeroed()
+}
+
+/// Decrypts the available balance from its AES copy, falling back to the ElGamal
+/// ciphertext (through `--decrypt-table` when given) if that copy is missing.
+pub fn decrypt_available_balance(
+  elgamal_keypair: &ElGamalKeypair,
+  aes_key: &AeKey,
+  available_balance: &PodElGamalCiphertext,
+  decryptable_available_balance: &PodAeCiphertext,
+) -> Result<u64> {
+  if *available_balance == PodElGamalCiphertext::zeroed() {
+    return Ok(0);
+  }
+  if !decryptable_balance_missing(available_balance, decryptable_available_balance) {
+    let decryptable: AeCiphertext = (*decryptable_available_balance).try_into()?;
+    return aes_key.decrypt(&decryptable)
+      .ok_or_else(|| anyhow::anyhow!("Failed to decrypt available balance"));
+  }
+
+  say!("\n⚠️  The decryptable available balance is zeroed but the encrypted balance is not");
+  say!("  Recovering it from the ElGamal ciphertext (can be slow without --decrypt-table)...");
+  let mut span = trace::span("decrypt_available_balance");
+  let result = search_amount(elgamal_keypair, available_balance, "Available balance");
+  let balance = trace::record(&mut span, result)?;
+  say!("  Recovered {} base units; run 'apply-balance' on the account to rewrite its decryptable balance", balance);
+  Ok(balance)
+}
+
+/// Decrypts a single ElGamal ciphertext by discrete-log search, through `--decrypt-table`
+/// when given. `what` names the amount in errors.
+pub fn decrypt_amount(elgamal_keypair: &ElGamalKeypair, ciphertext: &PodElGamalCiphertext, what: &str) -> Result<u64> {
+  let mut span = trace::span("decrypt_amount");
+  let result = search_amount(elgamal_keypair, ciphertext, what);
+  trace::record(&mut span, result)
+}
+
+fn search_amount(elgamal_keypair: &ElGamalKeypair, ciphertext: &PodElGamalCiphertext, what: &str) -> Result<u64> {
+  let options = config::global_options();
+  let ciphertext: ElGamalCiphertext = (*ciphertext).try_into()?;
+  if let Some(path) = &options.decrypt_table {
+    let table = lookup_table(path)?;
+    return table
+      .solve(ciphertext.decrypt(elgamal_keypair.secret()).target)
+      .ok_or_else(|| anyhow::anyhow!("{} is outside the {}-bit range of {}", what, table.bits, path.display()));
+  }
+
+  let budget = (options.decrypt_timeout > 0).then(|| Duration::from_secs(options.decrypt_timeout));
+  let threads = NonZeroUsize::new(options.decrypt_threads)
+    .ok_or_else(|| anyhow::anyhow!("--decrypt-threads must be at least 1"))?;
+  decode_within(elgamal_keypair, &ciphertext, threads, budget)?
+    .ok_or_else(|| anyhow::anyhow!(
+      "{} decryption stopped after {}s; pass --decrypt-table <FILE> or a longer --decrypt-timeout",
+      what,
+      options.decrypt_timeout
+    ))?
+    .ok_or_else(|| anyhow::anyhow!("{} is 2^32 or more; pass a --decrypt-table built with 'build-table --bits 48'", what))
+}
+
+/// Returned when pending balance decryption runs past `--decrypt-timeout`.
+#[derive(Debug)]
+pub struct DecryptionTimeout {
+  pub budget: Duration,
+  pub elapsed: Duration,
+  pub lo: Option<u64>,
+  pub estimated_remaining: Option<Duration>,
+}
+
+impl fmt::Display for DecryptionTimeout {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    writeln!(f, "Pending balance decryption stopped after {:.1}s (budget {}s)", self.elapsed.as_secs_f64(), self.budget.as_secs())?;
+    match self.lo {
+      Some(lo) => writeln!(f, "  lo (low 16 bits): {}", lo)?,
+      None => writeln!(f, "  lo (low 16 bits): not recovered")?,
+    }
+    writeln!(f, "  hi (high 32 bits): not recovered")?;
+    match self.estimated_remaining {
+      Some(remaining) => writeln!(f, "  Estimated time remaining: ~{}s", remaining.as_secs().max(1))?,
+      None => writeln!(f, "  Estimated time remaining: more than {}s", self.budget.as_secs())?,
+    }
+    writeln!(f, "  Build a lookup table once with 'build-table --out <FILE>' and pass --decrypt-table <FILE>,")?;
+    write!(f, "  or retry with --decrypt-threads 8 to search in parallel (--decrypt-timeout 0 waits without a limit)")
+  }
+}
+
+impl std::error::Error for DecryptionTimeout {}
+
+/// Decrypts a pending balance from its lo (16-bit) and hi (32-bit) halves, giving up once
+/// `--decrypt-timeout` has passed and using `--decrypt-threads` for each discrete-log search.
+pub fn decrypt_pending_balance(
+  elgamal_keypair: &ElGamalKeypair,
+  pending_balance_lo: &PodElGamalCiphertext,
+  pending_balance_hi: &PodElGamalCiphertext,
+) -> Result<u64> {
+  let mut span = trace::span("decrypt_pending_balance");
+  let result = search_pending_balance(elgamal_keypair, pending_balance_lo, pending_balance_hi);
+  trace::record(&mut span, result)
+}
+
+/// Decrypts many lo/hi amount pairs like `decrypt_pending_balance`, several at a time, and
+/// returns the results in input order.
+///
+/// As many searches run at once as fit the machine next to `--decrypt-threads`; each worker
+/// takes the next pair only once it is free, so a long input never queues more searches
+/// than that. Every search shares the one `--decrypt-table` load.
+pub fn decrypt_pending_balances(
+  elgamal_keypair: &ElGamalKeypair,
+  ciphertexts: &[(PodElGamalCiphertext, PodElGamalCiphertext)],
+) -> Vec<Result<u64>> {
+  let threads_per_search = config::global_options().decrypt_threads.max(1);
+  let jobs = std::thread::available_parallelism()
+    .map_or(1, |cores| cores.get() / threads_per_search)
+    .clamp(1, ciphertexts.len().max(1));
+
+  let next = AtomicUsize::new(0);
+  let results = Mutex::new((0..ciphertexts.len()).map(|_| None).collect::<Vec<_>>());
+  std::thread::scope(|scope| {
+    for _ in 0..jobs {
+      scope.spawn(|| loop {
+        let index = next.fetch_add(1, Ordering::Relaxed);
+        let Some((lo, hi)) = ciphertexts.get(index) else {
+          break;
+        };
+        let result = decrypt_pending_balance(elgamal_keypair, lo, hi);
+        results.lock().unwrap_or_else(|e| e.into_inner())[index] = Some(result);
+      });
+    }
+  });
+
+  results.into_inner()
+    .unwrap_or_else(|e| e.into_inner())
+    .into_iter()
+    .map(|result| result.unwrap_or_else(|| Err(anyhow::anyhow!("Decryption worker stopped early"))))
+    .collect()
+}
+
+fn search_pending_balance(
+  elgamal_keypair: &ElGamalKeypair,
+  pending_balance_lo: &PodElGamalCiphertext,
+  pending_balance_hi: &PodElGamalCiphertext,
+) -> Result<u64> {
+  let options = config::global_options();
+  if let Some(path) = &options.decrypt_table {
+    return decrypt_pending_balance_with_table(elgamal_keypair, pending_balance_lo, pending_balance_hi, path);
+  }
+
+  let budget = (options.decrypt_timeout > 0).then(|| Duration::from_secs(options.decrypt_timeout));
+  let threads = NonZeroUsize::new(options.decrypt_threads)
+    .ok_or_else(|| anyhow::anyhow!("--decrypt-threads must be at least 1"))?;
+  let started = Instant::now();
+
+  let timed_out = |lo, estimated_remaining| DecryptionTimeout {
+    budget: budget.unwrap_or_default(),
+    elapsed: started.elapsed(),
+    lo,
+    estimated_remaining,
+  };
+
+  let lo_ciphertext: ElGamalCiphertext = (*pending_balance_lo).try_into()?;
+  let lo = decode_within(elgamal_keypair, &lo_ciphertext, threads, budget)?
+    .ok_or_else(|| timed_out(None, None))?
+    .ok_or_else(|| anyhow::anyhow!("Failed to decrypt pending balance lo"))?;
+
+  // Both halves scan the same 2^16 giant steps regardless of the value, so the
+  // lo search time is a good estimate for the hi search.
+  let lo_elapsed = started.elapsed();
+  let remaining_budget = budget.map(|budget| budget.saturating_sub(lo_elapsed));
+
+  let hi_ciphertext: ElGamalCiphertext = (*pending_balance_hi).try_into()?;
+  let hi = decode_within(elgamal_keypair, &hi_ciphertext, threads, remaining_budget)?
+    .ok_or_else(|| timed_out(Some(lo), Some(lo_elapsed.saturating_sub(remaining_budget.unwrap_or_default()))))?
+    .ok_or_else(|| anyhow::anyhow!("Failed to decrypt pending balance hi"))?;
+
+  Ok(lo + (hi << 16))
+}
+
+// Runs the discrete-log search on a worker thread; `None` means the budget ran out.
+// The abandoned search keeps running until the process exits.
+fn decode_within(
+  elgamal_keypair: &ElGamalKeypair,
+  ciphertext: &ElGamalCiphertext,
+  threads: NonZeroUsize,
+  budget: Option<Duration>,
+) -> Result<Option<Option<u64>>> {
+  let mut discrete_log: DiscreteLog = ciphertext.decrypt(elgamal_keypair.secret());
+  if threads.get() > 1 {
+    discrete_log
+      .num_threads(threads)
+      .map_err(|_| anyhow::anyhow!("--decrypt-threads must be a power of two"))?;
+  }
+
+  let (sender, receiver) = mpsc::channel();
+  std::thread::spawn(move || {
+    let _ = sender.send(discrete_log.decode_u32());
+  });
+
+  Ok(match budget {
+    Some(budget) => receiver.recv_timeout(budget).ok(),
+    None => receiver.recv().ok(),
+  })
+}
+
+fn decrypt_pending_balance_with_table(
+  elgamal_keypair: &ElGamalKeypair,
+  pending_balance_lo: &PodElGamalCiphertext,
+  pending_balance_hi: &PodElGamalCiphertext,
+  path: &std::path::Path,
+) -> Result<u64> {
+  let table = lookup_table(path)?;
+
+  let solve = |ciphertext: &PodElGamalCiphertext, half: &str| -> Result<u64> {
+    let ciphertext: ElGamalCiphertext = (*ciphertext).try_into()?;
+    table
+      .solve(ciphertext.decrypt(elgamal_keypair.secret()).target)
+      .ok_or_else(|| anyhow::anyhow!("Pending balance {} is outside the {}-bit range of {}", half, table.bits, path.display()))
+  };
+
+  Ok(solve(pending_balance_lo, "lo")? + (solve(pending_balance_hi, "hi")? << 16))
+}
+
+// Each table is loaded on first use and kept for the rest of the run, keyed by path so
+// a run that reads several never solves with the wrong one
+fn lookup_table(path: &std::path::Path) -> Result<&'static LookupTable> {
+  static TABLES: OnceLock<Mutex<HashMap<std::path::PathBuf, &'static LookupTable>>> = OnceLock::new();
+  let mut tables = TABLES.get_or_init(Default::default).lock().unwrap_or_else(|e| e.into_inner());
+  if let Some(table) = tables.get(path) {
+    return Ok(table);
+  }
+  let table: &'static LookupTable = Box::leak(Box::new(LookupTable::load(path)?));
+  tables.insert(path.to_path_buf(), table);
+  Ok(table)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use solana_sdk::signature::Keypair;
+
+  fn signer() -> Keypair {
+    Keypair::new_from_array([7; 32])
+  }
+
+  fn account() -> Pubkey {
+    Pubkey::new_from_array([9; 32])
+  }
+
+  fn elgamal_bytes(keypair: &ElGamalKeypair) -> [u8; 64] {
+    keypair.into()
+  }
+
+  fn aes_bytes(key: &AeKey) -> [u8; 16] {
+    key.clone().into()
+  }
+
+  #[test]
+  fn spl_token_scheme_matches_spl_token() {
+    // The defaults select spl-token; the key store is pointed somewhere empty so
+    // nothing imported by hand on this machine takes precedence
+    config::set_global_options(config::GlobalOptions {
+      key_store: Some(std::env::temp_dir().join("confidential-cli-test-no-key-store.json")),
+      ..Default::default()
+    });
+    assert_eq!(config::global_options().derivation_scheme, DerivationScheme::SplToken);
+
+    let (signer, account) = (signer(), account());
+    let expected_elgamal = ElGamalKeypair::new_from_signer(&signer, account.as_ref()).unwrap();
+    let expected_aes = AeKey::new_from_signer(&signer, account.as_ref()).unwrap();
+
+    assert_eq!(elgamal_bytes(&derive_elgamal_keypair(&signer, &account).unwrap()), elgamal_bytes(&expected_elgamal));
+    assert_eq!(aes_bytes(&derive_aes_key(&signer, &account).unwrap()), aes_bytes(&expected_aes));
+  }
+
+  #[test]
+  fn legacy_scheme_ignores_the_account() {
+    let signer = signer();
+    let expected_elgamal = ElGamalKeypair::new_from_signer(&signer, &[]).unwrap();
+    let expected_aes = AeKey::new_from_signer(&signer, &[]).unwrap();
+
+    let elgamal = elgamal_keypair_for(&signer, &account(), DerivationScheme::Legacy).unwrap();
+    let aes = aes_key_for(&signer, &account(), DerivationScheme::Legacy).unwrap();
+    assert_eq!(elgamal_bytes(&elgamal), elgamal_bytes(&expected_elgamal));
+    assert_eq!(aes_bytes(&aes), aes_bytes(&expected_aes));
+  }
+
+  #[test]
+  fn schemes_derive_different_keys() {
+    let (signer, account) = (signer(), account());
+    let spl_token = elgamal_keypair_for(&signer, &account, DerivationScheme::SplToken).unwrap();
+    let legacy = elgamal_keypair_for(&signer, &account, DerivationScheme::Legacy).unwrap();
+    let offchain = elgamal_keypair_for(&signer, &account, DerivationScheme::Offchain).unwrap();
+    assert_ne!(spl_token.pubkey(), legacy.pubkey());
+    assert_ne!(spl_token.pubkey(), offchain.pubkey());
+  }
+}
@@ -0,0 +1,15 @@
+use solana_sdk::{account::Account, pubkey::Pubkey};
+use spl_elgamal_registry::state::ElGamalRegistry;
+
+/// The registry account `wallet` publishes its ElGamal key in.
+pub fn address(wallet: &Pubkey) -> Pubkey {
+    spl_elgamal_registry::get_elgamal_registry_address(wallet, &spl_elgamal_registry::id())
+}
+
+/// The registry `account` holds; `None` when it isn't a registry account.
+pub fn from_account(account: &Account) -> Option<ElGamalRegistry> {
+    if account.owner != spl_elgamal_registry::id() {
+        return None;
+    }
+    bytemuck::try_from_bytes::<ElGamalRegistry>(&account.data).ok().copied()
+}
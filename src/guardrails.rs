@@ -0,0 +1,57 @@
+use anyhow::{Context, Result};
+use solana_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+use crate::{config::{self, AppConfig}, output::say};
+
+const MAINNET_GENESIS_HASH: &str = "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d";
+
+/// Decimals real tokens use; anything else is more likely a typo than a design.
+const CONVENTIONAL_DECIMALS: [u8; 5] = [0, 2, 6, 8, 9];
+
+pub async fn is_mainnet(config: &AppConfig) -> Result<bool> {
+    let genesis_hash = config.rpc_client.get_genesis_hash().await
+        .context("Failed to identify the cluster")?;
+    Ok(genesis_hash.to_string() == MAINNET_GENESIS_HASH)
+}
+
+/// Refuses, on mainnet-beta only, a mint or account creation that looks like a mistake:
+/// a payer with no history (a freshly generated keypair), unusual `decimals`, or an
+/// `authority` that is a separate `--fee-payer`. `--i-know-what-im-doing` skips this.
+pub async fn check_creation(config: &AppConfig, authority: &Pubkey, decimals: Option<u8>) -> Result<()> {
+    if !is_mainnet(config).await? {
+        return Ok(());
+    }
+    if config::global_options().i_know_what_im_doing {
+        say!("\n⚠️  mainnet-beta: creation checks skipped (--i-know-what-im-doing)");
+        return Ok(());
+    }
+
+    let payer = config.payer.pubkey();
+    let mut problems = Vec::new();
+    let history = config.rpc_client
+        .get_signatures_for_address_with_config(&payer, GetConfirmedSignaturesForAddress2Config {
+            limit: Some(1),
+            ..GetConfirmedSignaturesForAddress2Config::default()
+        })
+        .await
+        .context("Failed to look up the payer's history")?;
+    if history.is_empty() {
+        problems.push(format!("the payer {} has never transacted on mainnet-beta; is it a keypair generated for testing?", payer));
+    }
+    if let Some(decimals) = decimals.filter(|decimals| !CONVENTIONAL_DECIMALS.contains(decimals)) {
+        problems.push(format!("{} decimals is unusual (most tokens use 6 or 9) and can never be changed", decimals));
+    }
+    // A separate fee payer is usually a hot wallet, not something to hand authority to
+    if config.owner.is_some() && *authority == payer {
+        problems.push(format!("the authority {} is the --fee-payer rather than the --keypair", authority));
+    }
+
+    if problems.is_empty() {
+        return Ok(());
+    }
+    say!("\n🛑 This would create on mainnet-beta, where mistakes cost real SOL:");
+    for problem in &problems {
+        say!("   - {}", problem);
+    }
+    anyhow::bail!("Refusing to create on mainnet-beta; pass --i-know-what-im-doing if this is intended")
+}
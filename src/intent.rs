@@ -0,0 +1,144 @@
+use anyhow::{Context, Result};
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use solana_sdk::{pubkey::Pubkey, transaction::Transaction};
+use std::collections::BTreeMap;
+use std::path::Path;
+use crate::{output::{self, say}, utils};
+
+/// Identifies intent files; checked on import so unrelated JSON is rejected early.
+pub const INTENT_FORMAT: &str = "confidential-transfer-intent";
+
+/// Bumped whenever a field is removed, renamed or changes meaning.
+pub const INTENT_VERSION: u32 = 1;
+
+/// A confidential operation that has been built but not (fully) signed or submitted.
+///
+/// Everything except `transaction` is informational, so a wallet can show the user what
+/// it is signing without re-deriving it. Keys and ciphertexts use their usual base58 and
+/// base64 encodings; `transaction` is the base64 wire encoding, with zeroed signatures
+/// for signers that have not signed yet.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TransferIntent {
+    pub format: String,
+    pub version: u32,
+    pub command: String,
+    pub accounts: BTreeMap<String, String>,
+    pub amount: Option<u64>,
+    pub ciphertexts: BTreeMap<String, String>,
+    pub proofs: Vec<IntentProof>,
+    pub required_signers: Vec<String>,
+    pub transaction: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct IntentProof {
+    pub kind: String,
+    pub data: String,
+}
+
+impl TransferIntent {
+    pub fn new(command: &str, transaction: &Transaction) -> Result<Self> {
+        let num_signers = transaction.message.header.num_required_signatures as usize;
+        let required_signers = transaction.message.account_keys[..num_signers]
+            .iter()
+            .map(|key| key.to_string())
+            .collect();
+
+        Ok(Self {
+            format: INTENT_FORMAT.to_string(),
+            version: INTENT_VERSION,
+            command: command.to_string(),
+            accounts: BTreeMap::new(),
+            amount: None,
+            ciphertexts: BTreeMap::new(),
+            proofs: Vec::new(),
+            required_signers,
+            transaction: BASE64.encode(bincode::serialize(transaction)?),
+        })
+    }
+
+    pub fn account(mut self, name: &str, pubkey: &Pubkey) -> Self {
+        self.accounts.insert(name.to_string(), pubkey.to_string());
+        self
+    }
+
+    pub fn amount(mut self, amount: u64) -> Self {
+        self.amount = Some(amount);
+        self
+    }
+
+    pub fn ciphertext(mut self, name: &str, ciphertext: impl std::fmt::Display) -> Self {
+        self.ciphertexts.insert(name.to_string(), ciphertext.to_string());
+        self
+    }
+
+    pub fn proof<T: bytemuck::Pod>(mut self, kind: &str, proof_data: &T) -> Self {
+        self.proofs.push(IntentProof {
+            kind: kind.to_string(),
+            data: BASE64.encode(bytemuck::bytes_of(proof_data)),
+        });
+        self
+    }
+
+    pub fn decode_transaction(&self) -> Result<Transaction> {
+        let bytes = BASE64
+            .decode(&self.transaction)
+            .context("Intent transaction is not valid base64")?;
+        bincode::deserialize(&bytes).context("Intent transaction is not a valid wire transaction")
+    }
+
+    pub fn set_transaction(&mut self, transaction: &Transaction) -> Result<()> {
+        self.transaction = BASE64.encode(bincode::serialize(transaction)?);
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read intent {}", path.display()))?;
+        let intent: Self = serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse intent {}", path.display()))?;
+
+        if intent.format != INTENT_FORMAT {
+            anyhow::bail!("{} is not a confidential transfer intent", path.display());
+        }
+        if intent.version > INTENT_VERSION {
+            anyhow::bail!(
+                "Intent {} uses format version {}, this CLI understands up to {}",
+                path.display(),
+                intent.version,
+                INTENT_VERSION
+            );
+        }
+
+        Ok(intent)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let contents = serde_json::to_string_pretty(self)?;
+        utils::write_atomic(path, contents)
+            .with_context(|| format!("Failed to write intent {}", path.display()))
+    }
+}
+
+/// Writes `intent` for another wallet to pick up instead of signing and sending it here.
+pub fn export(intent: &TransferIntent, path: &Path) -> Result<()> {
+    intent.save(path)?;
+
+    say!("\n📦 Intent exported to {}", path.display());
+    say!("   Required signers:");
+    for signer in &intent.required_signers {
+        say!("   • {}", signer);
+    }
+    say!("\n💡 Sign and send it with 'submit-intent --file {}' or any wallet that", path.display());
+    say!("   reads the {} v{} format", INTENT_FORMAT, INTENT_VERSION);
+
+    output::emit("export-intent", json!({
+        "path": path.display().to_string(),
+        "command": intent.command,
+        "required_signers": intent.required_signers,
+    }));
+
+    Ok(())
+}
@@ -0,0 +1,119 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use std::path::{Path, PathBuf};
+use crate::{config, crypto::DerivationScheme, keyset::KeyEntry, utils};
+
+/// Identifies the key store `import-keys` writes.
+pub const KEY_STORE_FORMAT: &str = "confidential-key-store";
+
+/// Bumped whenever a field is removed, renamed or changes meaning.
+pub const KEY_STORE_VERSION: u32 = 1;
+
+/// Keys imported with `import-keys`, used instead of deriving them from the owner's
+/// signature. Unencrypted like a Solana keypair file, and readable by its owner only.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct KeyStore {
+    pub format: String,
+    pub version: u32,
+    pub keys: Vec<StoredKey>,
+}
+
+/// A keyset entry together with the owner it was exported for.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StoredKey {
+    pub owner: String,
+    #[serde(flatten)]
+    pub entry: KeyEntry,
+    pub imported_at: String,
+}
+
+impl Default for KeyStore {
+    fn default() -> Self {
+        Self {
+            format: KEY_STORE_FORMAT.to_string(),
+            version: KEY_STORE_VERSION,
+            keys: Vec::new(),
+        }
+    }
+}
+
+/// `--key-store`, else `~/.config/confidential-cli/keys.json`.
+pub fn path() -> PathBuf {
+    config::global_options().key_store.unwrap_or_else(|| {
+        std::env::var_os("HOME")
+            .map(PathBuf::from)
+            .unwrap_or_default()
+            .join(".config/confidential-cli/keys.json")
+    })
+}
+
+impl KeyStore {
+    /// Reads the store, checking every key decodes so using one later can't fail.
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read key store {}", path.display()))?;
+        let store: Self = serde_json::from_str(&contents)
+            .with_context(|| format!("Invalid key store {}", path.display()))?;
+
+        if store.format != KEY_STORE_FORMAT {
+            anyhow::bail!("{} is not a key store written by import-keys", path.display());
+        }
+        if store.version > KEY_STORE_VERSION {
+            anyhow::bail!(
+                "Key store {} uses format version {}, this CLI understands up to {}",
+                path.display(),
+                store.version,
+                KEY_STORE_VERSION
+            );
+        }
+        for key in &store.keys {
+            key.entry.elgamal_keypair()
+                .and(key.entry.aes_key().map(|_| ()))
+                .with_context(|| format!("Key store {} has a bad key for {}", path.display(), key.owner))?;
+        }
+        Ok(store)
+    }
+
+    /// Like `load`, but a store that was never written is just empty.
+    pub fn load_or_default(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Self::load(path)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let contents = serde_json::to_string_pretty(self)?;
+        utils::write_private(path, contents, true)
+            .with_context(|| format!("Failed to write key store {}", path.display()))
+    }
+
+    /// Adds `entry`, replacing any earlier import for the same owner and account.
+    /// Returns whether one was replaced.
+    pub fn insert(&mut self, owner: &str, entry: KeyEntry) -> bool {
+        let before = self.keys.len();
+        self.keys.retain(|key| key.owner != owner || key.entry.account != entry.account);
+        let replaced = self.keys.len() != before;
+        self.keys.push(StoredKey {
+            owner: owner.to_string(),
+            entry,
+            imported_at: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        });
+        replaced
+    }
+
+    /// The keys imported for `account`, else the owner-wide keys imported under `scheme`.
+    pub fn find(&self, owner: &Pubkey, account: &Pubkey, scheme: DerivationScheme) -> Option<&KeyEntry> {
+        let owner = owner.to_string();
+        let account = account.to_string();
+        let owned = || self.keys.iter().filter(|key| key.owner == owner).map(|key| &key.entry);
+        owned()
+            .find(|entry| entry.account.as_deref() == Some(account.as_str()))
+            .or_else(|| owned().find(|entry| entry.account.is_none() && entry.derivation_scheme == scheme.name()))
+    }
+}
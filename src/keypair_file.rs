@@ -0,0 +1,98 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use solana_sdk::signature::{Keypair, Signer};
+use std::path::Path;
+use zeroize::Zeroizing;
+use crate::{keyset::{self, Sealed}, utils};
+
+/// Identifies keypair files written by `keygen`.
+pub const ENCRYPTED_KEYPAIR_FORMAT: &str = "confidential-encrypted-keypair";
+
+/// Bumped whenever a field is removed, renamed or changes meaning.
+pub const ENCRYPTED_KEYPAIR_VERSION: u32 = 1;
+
+/// A Solana keypair sealed under a passphrase. The public key stays readable so the
+/// file can be told apart from others without the passphrase.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EncryptedKeypair {
+    pub format: String,
+    pub version: u32,
+    pub pubkey: String,
+    pub created_at: String,
+    #[serde(flatten)]
+    pub sealed: Sealed,
+}
+
+/// True when `contents` look like an encrypted keypair rather than the JSON byte array
+/// `solana-keygen` writes.
+pub fn is_encrypted(contents: &str) -> bool {
+    contents.trim_start().starts_with('{')
+}
+
+impl EncryptedKeypair {
+    pub fn seal(keypair: &Keypair, passphrase: &str) -> Result<Self> {
+        Ok(Self {
+            format: ENCRYPTED_KEYPAIR_FORMAT.to_string(),
+            version: ENCRYPTED_KEYPAIR_VERSION,
+            pubkey: keypair.pubkey().to_string(),
+            created_at: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            sealed: Sealed::seal(Zeroizing::new(keypair.to_bytes()).as_slice(), passphrase)?,
+        })
+    }
+
+    /// Decrypts the keypair, checking it is the one the file says it holds.
+    pub fn open(&self, passphrase: &str) -> Result<Keypair> {
+        let bytes = self.sealed.open(passphrase).context("Failed to decrypt keypair")?;
+        let keypair = Keypair::try_from(bytes.as_slice())
+            .map_err(|e| anyhow::anyhow!("Decrypted keypair is malformed: {}", e))?;
+        if keypair.pubkey().to_string() != self.pubkey {
+            anyhow::bail!("Decrypted keypair is {}, but the file says {}", keypair.pubkey(), self.pubkey);
+        }
+        Ok(keypair)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read keypair {}", path.display()))?;
+        Self::parse(&contents, path)
+    }
+
+    pub fn parse(contents: &str, path: &Path) -> Result<Self> {
+        let keypair: Self = serde_json::from_str(contents)
+            .with_context(|| format!("Invalid encrypted keypair file {}", path.display()))?;
+
+        if keypair.format != ENCRYPTED_KEYPAIR_FORMAT {
+            anyhow::bail!("{} is not a keypair written by keygen", path.display());
+        }
+        if keypair.version > ENCRYPTED_KEYPAIR_VERSION {
+            anyhow::bail!(
+                "Keypair {} uses format version {}, this CLI understands up to {}",
+                path.display(),
+                keypair.version,
+                ENCRYPTED_KEYPAIR_VERSION
+            );
+        }
+        Ok(keypair)
+    }
+
+    /// Refuses to overwrite, since the file may be a keypair's only copy.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let contents = serde_json::to_string_pretty(self)?;
+        utils::write_private(path, contents, false).map_err(|e| match e.kind() {
+            std::io::ErrorKind::AlreadyExists => anyhow::anyhow!(
+                "{} already exists; pick a new path so no keypair is overwritten", path.display()),
+            _ => anyhow::Error::new(e).context(format!("Failed to write keypair {}", path.display())),
+        })
+    }
+}
+
+/// Reads `--<role>` from an encrypted keypair file, asking for its passphrase.
+pub fn read(path: &Path, contents: &str, role: &str) -> Result<Keypair> {
+    let keypair = EncryptedKeypair::parse(contents, path)?;
+    let passphrase = keyset::read_passphrase(
+        "CONFIDENTIAL_CLI_KEYPAIR_PASSPHRASE",
+        &format!("Passphrase for --{} ({}): ", role, keypair.pubkey),
+        false,
+    )?;
+    keypair.open(&passphrase)
+}
@@ -0,0 +1,104 @@
+use anyhow::{Context, Result};
+use std::io::Write;
+use std::process::{Command, Stdio};
+use zeroize::Zeroizing;
+use crate::secrets::SecretString;
+
+/// The service every secret is filed under in the OS keyring.
+const SERVICE: &str = "confidential-cli";
+
+/// Reads the secret stored as `name`; `None` when there is none.
+///
+/// Goes through the OS's own tool (`security` on macOS, PowerShell's PasswordVault on
+/// Windows, `secret-tool` for the Secret Service elsewhere) so no native library is
+/// linked. Secrets only ever travel over stdin and stdout, never the command line.
+pub fn get(name: &str) -> Result<Option<SecretString>> {
+    let output = lookup(name)?;
+    if !output.status.success() {
+        // Every backend exits non-zero for a missing item, which is the common case
+        return Ok(None);
+    }
+    let secret = Zeroizing::new(String::from_utf8(output.stdout).context("Keyring item is not UTF-8")?);
+    let secret = secret.trim_end_matches(['\r', '\n']);
+    Ok((!secret.is_empty()).then(|| Zeroizing::new(secret.to_string())))
+}
+
+/// Stores `secret` as `name`, replacing what was there.
+pub fn set(name: &str, secret: &str) -> Result<()> {
+    let (mut command, input) = store(name, secret);
+    let input = Zeroizing::new(input);
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to run {:?}; is the OS keyring available?", command.get_program()))?;
+    child.stdin.take().expect("stdin is piped").write_all(input.as_bytes())?;
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        anyhow::bail!("The keyring refused to store {}: {}", name, String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn lookup(name: &str) -> Result<std::process::Output> {
+    Command::new("security")
+        .args(["find-generic-password", "-s", SERVICE, "-a", name, "-w"])
+        .stderr(Stdio::null())
+        .output()
+        .context("Failed to run security; is the Keychain available?")
+}
+
+#[cfg(target_os = "macos")]
+fn store(name: &str, secret: &str) -> (Command, String) {
+    // `security -i` reads the command from stdin, keeping the secret out of `ps`
+    let mut command = Command::new("security");
+    command.arg("-i");
+    (command, format!("add-generic-password -U -s {} -a {} -w {}\n", SERVICE, name, secret))
+}
+
+#[cfg(windows)]
+const VAULT: &str = "[void][Windows.Security.Credentials.PasswordVault,Windows.Security.Credentials,ContentType=WindowsRuntime]; \
+    $vault = New-Object Windows.Security.Credentials.PasswordVault";
+
+#[cfg(windows)]
+fn lookup(name: &str) -> Result<std::process::Output> {
+    let script = format!(
+        "{}; $credential = $vault.Retrieve('{}', '{}'); $credential.RetrievePassword(); $credential.Password",
+        VAULT, SERVICE, name
+    );
+    Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+        .stderr(Stdio::null())
+        .output()
+        .context("Failed to run PowerShell; is the Credential Manager available?")
+}
+
+#[cfg(windows)]
+fn store(name: &str, secret: &str) -> (Command, String) {
+    let script = format!(
+        "{}; $secret = [Console]::In.ReadLine(); \
+        $vault.Add((New-Object Windows.Security.Credentials.PasswordCredential('{}', '{}', $secret)))",
+        VAULT, SERVICE, name
+    );
+    let mut command = Command::new("powershell");
+    command.args(["-NoProfile", "-NonInteractive", "-Command", &script]);
+    (command, format!("{}\n", secret))
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn lookup(name: &str) -> Result<std::process::Output> {
+    Command::new("secret-tool")
+        .args(["lookup", "service", SERVICE, "account", name])
+        .stderr(Stdio::null())
+        .output()
+        .context("Failed to run secret-tool; install libsecret-tools for a Secret Service keyring")
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn store(name: &str, secret: &str) -> (Command, String) {
+    let mut command = Command::new("secret-tool");
+    command.args(["store", &format!("--label={} {}", SERVICE, name), "service", SERVICE, "account", name]);
+    (command, secret.to_string())
+}
@@ -0,0 +1,229 @@
+use aes_gcm_siv::{aead::Aead, Aes256GcmSiv, KeyInit, Nonce};
+use anyhow::{Context, Result};
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use hmac::Hmac;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use spl_token_2022::solana_zk_sdk::encryption::{auth_encryption::AeKey, elgamal::ElGamalKeypair};
+use std::path::Path;
+use zeroize::{Zeroize, Zeroizing};
+use crate::{secrets::{self, AesKey, SecretBytes, SecretString}, utils};
+
+/// Identifies keyset files written by `export-keys`.
+pub const KEYSET_FORMAT: &str = "confidential-keyset";
+
+/// Bumped whenever a field is removed, renamed or changes meaning.
+pub const KEYSET_VERSION: u32 = 1;
+
+const KDF: &str = "pbkdf2-hmac-sha256";
+const CIPHER: &str = "aes-256-gcm-siv";
+
+/// OWASP's 2023 recommendation for PBKDF2-HMAC-SHA256.
+const KDF_ROUNDS: u32 = 600_000;
+
+/// Bounds on the rounds a file may ask for: fewer would make the passphrase cheap to
+/// brute-force, more would stall the CLI on a crafted file.
+const MIN_KDF_ROUNDS: u32 = 100_000;
+const MAX_KDF_ROUNDS: u32 = 10_000_000;
+
+/// An exported keyset as written to disk: everything secret is sealed.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct KeysetFile {
+    pub format: String,
+    pub version: u32,
+    pub owner: String,
+    pub created_at: String,
+    #[serde(flatten)]
+    pub sealed: Sealed,
+}
+
+/// Bytes encrypted under a key stretched from a passphrase, as stored in keysets and
+/// encrypted keypair files.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Sealed {
+    pub kdf: String,
+    pub kdf_rounds: u32,
+    pub salt: String,
+    pub cipher: String,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+impl Sealed {
+    pub fn seal(plaintext: &[u8], passphrase: &str) -> Result<Self> {
+        let salt: [u8; 16] = rand::random();
+        let nonce: [u8; 12] = rand::random();
+        let ciphertext = cipher(passphrase, &salt, KDF_ROUNDS)
+            .encrypt(&Nonce::from(nonce), plaintext)
+            .map_err(|_| anyhow::anyhow!("Failed to encrypt"))?;
+
+        Ok(Self {
+            kdf: KDF.to_string(),
+            kdf_rounds: KDF_ROUNDS,
+            salt: BASE64.encode(salt),
+            cipher: CIPHER.to_string(),
+            nonce: BASE64.encode(nonce),
+            ciphertext: BASE64.encode(ciphertext),
+        })
+    }
+
+    /// A wrong passphrase and a tampered file look the same.
+    pub fn open(&self, passphrase: &str) -> Result<SecretBytes> {
+        if self.kdf != KDF || self.cipher != CIPHER {
+            anyhow::bail!("Encrypted with {} and {}, this CLI understands {} with {}", self.kdf, self.cipher, KDF, CIPHER);
+        }
+        if !(MIN_KDF_ROUNDS..=MAX_KDF_ROUNDS).contains(&self.kdf_rounds) {
+            anyhow::bail!(
+                "The file asks for {} {} rounds; only {} to {} are accepted",
+                self.kdf_rounds, KDF, MIN_KDF_ROUNDS, MAX_KDF_ROUNDS
+            );
+        }
+        let salt = BASE64.decode(&self.salt).context("Salt is not base64")?;
+        let nonce: [u8; 12] = BASE64.decode(&self.nonce).context("Nonce is not base64")?
+            .try_into()
+            .map_err(|nonce: Vec<u8>| anyhow::anyhow!("Nonce must be 12 bytes, got {}", nonce.len()))?;
+        let ciphertext = BASE64.decode(&self.ciphertext).context("Ciphertext is not base64")?;
+
+        cipher(passphrase, &salt, self.kdf_rounds)
+            .decrypt(&Nonce::from(nonce), ciphertext.as_slice())
+            .map(Zeroizing::new)
+            .map_err(|_| anyhow::anyhow!("Wrong passphrase, or the file was modified"))
+    }
+}
+
+/// The keys for one account, or for every account of the owner when `account` is
+/// `None` (the legacy scheme derives a single pair per owner).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct KeyEntry {
+    pub account: Option<String>,
+    pub derivation_scheme: String,
+    /// The 64-byte ElGamal keypair, base64
+    pub elgamal_keypair: String,
+    /// The 16-byte AE key, base64
+    pub aes_key: String,
+}
+
+impl KeysetFile {
+    /// Encrypts `entries` under a key stretched from `passphrase`.
+    pub fn seal(owner: String, entries: &[KeyEntry], passphrase: &str) -> Result<Self> {
+        Ok(Self {
+            format: KEYSET_FORMAT.to_string(),
+            version: KEYSET_VERSION,
+            owner,
+            created_at: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            sealed: Sealed::seal(&Zeroizing::new(serde_json::to_vec(entries)?), passphrase)?,
+        })
+    }
+
+    pub fn open(&self, passphrase: &str) -> Result<Vec<KeyEntry>> {
+        let plaintext = self.sealed.open(passphrase).context("Failed to decrypt keyset")?;
+        serde_json::from_slice(&plaintext).context("Decrypted keyset is malformed")
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read keyset {}", path.display()))?;
+        let keyset: Self = serde_json::from_str(&contents)
+            .with_context(|| format!("Invalid keyset file {}", path.display()))?;
+
+        if keyset.format != KEYSET_FORMAT {
+            anyhow::bail!("{} is not a keyset exported by export-keys", path.display());
+        }
+        if keyset.version > KEYSET_VERSION {
+            anyhow::bail!(
+                "Keyset {} uses format version {}, this CLI understands up to {}",
+                path.display(),
+                keyset.version,
+                KEYSET_VERSION
+            );
+        }
+        Ok(keyset)
+    }
+
+    /// Refuses to overwrite, since the file may be the keys' only backup.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let contents = serde_json::to_string_pretty(self)?;
+        utils::write_private(path, contents, false).map_err(|e| match e.kind() {
+            std::io::ErrorKind::AlreadyExists => anyhow::anyhow!(
+                "{} already exists; pick a new path so no backup is overwritten", path.display()),
+            _ => anyhow::Error::new(e).context(format!("Failed to write keyset {}", path.display())),
+        })
+    }
+}
+
+impl KeyEntry {
+    pub fn elgamal_keypair(&self) -> Result<ElGamalKeypair> {
+        let bytes = secrets::decode_base64(&self.elgamal_keypair).context("ElGamal keypair is not base64")?;
+        ElGamalKeypair::try_from(bytes.as_slice())
+            .map_err(|e| anyhow::anyhow!("Invalid ElGamal keypair: {}", e))
+    }
+
+    pub fn aes_key(&self) -> Result<AesKey> {
+        let bytes = secrets::decode_base64(&self.aes_key).context("AE key is not base64")?;
+        AeKey::try_from(bytes.as_slice())
+            .map(Zeroizing::new)
+            .map_err(|e| anyhow::anyhow!("Invalid AE key: {}", e))
+    }
+}
+
+// Entries are the decrypted contents of a keyset, so they are wiped like the keys they hold
+impl Drop for KeyEntry {
+    fn drop(&mut self) {
+        self.elgamal_keypair.zeroize();
+        self.aes_key.zeroize();
+    }
+}
+
+fn cipher(passphrase: &str, salt: &[u8], rounds: u32) -> Aes256GcmSiv {
+    let mut key = [0u8; 32];
+    pbkdf2::pbkdf2::<Hmac<Sha256>>(passphrase.as_bytes(), salt, rounds, &mut key);
+    let cipher = Aes256GcmSiv::new(&key.into());
+    key.zeroize();
+    cipher
+}
+
+/// Reads the keyset passphrase from `CONFIDENTIAL_CLI_KEYSET_PASSPHRASE`, or prompts for
+/// it (twice when `confirm`, so a typo can't lock the backup away).
+pub fn passphrase(confirm: bool) -> Result<SecretString> {
+    read_passphrase("CONFIDENTIAL_CLI_KEYSET_PASSPHRASE", "Keyset passphrase: ", confirm)
+}
+
+/// Reads a passphrase from `env_var`, or prompts for it with `prompt` (twice when `confirm`).
+pub fn read_passphrase(env_var: &str, prompt: &str, confirm: bool) -> Result<SecretString> {
+    let from_env = std::env::var(env_var).ok().map(Zeroizing::new);
+    let passphrase = match &from_env {
+        Some(passphrase) => passphrase.clone(),
+        None => Zeroizing::new(rpassword::prompt_password(prompt).context("Failed to read passphrase")?),
+    };
+    if passphrase.is_empty() {
+        anyhow::bail!("The passphrase must not be empty");
+    }
+    if confirm
+        && from_env.is_none()
+        && *Zeroizing::new(rpassword::prompt_password("Same passphrase again: ").context("Failed to read passphrase")?) != *passphrase
+    {
+        anyhow::bail!("Passphrases did not match");
+    }
+    Ok(passphrase)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn open_rejects_kdf_rounds_out_of_bounds() {
+        // Rejected before any key is stretched, so the rest of the file needn't be valid
+        for rounds in [0, 1, MIN_KDF_ROUNDS - 1, MAX_KDF_ROUNDS + 1, u32::MAX] {
+            let sealed = Sealed {
+                kdf: KDF.to_string(),
+                kdf_rounds: rounds,
+                salt: String::new(),
+                cipher: CIPHER.to_string(),
+                nonce: String::new(),
+                ciphertext: String::new(),
+            };
+            assert!(sealed.open("passphrase").unwrap_err().to_string().contains("rounds"));
+        }
+    }
+}
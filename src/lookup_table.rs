@@ -0,0 +1,176 @@
+use anyhow::{Context, Result};
+use curve25519_dalek::{
+    constants::RISTRETTO_BASEPOINT_POINT as G,
+    ristretto::RistrettoPoint,
+    scalar::Scalar,
+    traits::Identity,
+};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+const MAGIC: &[u8; 8] = b"CCLIDLOG";
+pub const FORMAT_VERSION: u8 = 1;
+const HEADER_LEN: usize = MAGIC.len() + 3;
+const ENTRY_LEN: usize = 32;
+
+// Entries are written and flushed in chunks so an interrupted build can resume
+const CHUNK_ENTRIES: u64 = 1 << 16;
+
+/// Baby-step giant-step table for solving `x * G = target` with `x < 2^bits`.
+///
+/// Entry `j` is `j * 2^(bits - table_bits) * G`, so solving takes at most
+/// `2^(bits - table_bits)` point additions against a `2^table_bits` entry table.
+pub struct LookupTable {
+    pub bits: u8,
+    pub table_bits: u8,
+    giant_steps: HashMap<[u8; ENTRY_LEN], u32>,
+}
+
+impl LookupTable {
+    /// Loads a table written by `build`, checking it against its `.sha256` sidecar.
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read(path)
+            .with_context(|| format!("Failed to read lookup table {}", path.display()))?;
+
+        let expected = std::fs::read_to_string(hash_path(path))
+            .with_context(|| format!("Missing integrity hash for lookup table {}", path.display()))?;
+        let expected = expected.split_whitespace().next().unwrap_or_default();
+        if hex_digest(&contents) != expected {
+            anyhow::bail!("Lookup table {} does not match its integrity hash; rebuild it with build-table", path.display());
+        }
+
+        let (bits, table_bits) = parse_header(&contents).ok_or_else(|| match header_version(&contents) {
+            Some(version) => anyhow::anyhow!(
+                "Lookup table {} uses format version {}, this CLI reads version {}; rebuild it with build-table",
+                path.display(), version, FORMAT_VERSION),
+            None => anyhow::anyhow!("{} is not a lookup table built by this CLI", path.display()),
+        })?;
+        let entries = &contents[HEADER_LEN..];
+        if entries.len() != ENTRY_LEN << table_bits {
+            anyhow::bail!("Lookup table {} is incomplete", path.display());
+        }
+
+        let giant_steps = entries
+            .chunks_exact(ENTRY_LEN)
+            .enumerate()
+            .map(|(index, entry)| (entry.try_into().unwrap(), index as u32))
+            .collect();
+
+        Ok(Self { bits, table_bits, giant_steps })
+    }
+
+    /// Solves `x * G = target`, returning `None` if `x` is not below `2^bits`.
+    pub fn solve(&self, target: RistrettoPoint) -> Option<u64> {
+        let baby_steps = 1u64 << (self.bits - self.table_bits);
+        let mut point = target;
+        for baby_step in 0..baby_steps {
+            if let Some(giant_step) = self.giant_steps.get(&point.compress().to_bytes()) {
+                return Some(u64::from(*giant_step) * baby_steps + baby_step);
+            }
+            point -= G;
+        }
+        None
+    }
+}
+
+/// Builds the table at `out`, resuming from `<out>.partial` if a previous build was interrupted.
+///
+/// `progress` is called with the number of entries written so far and the total.
+pub fn build(out: &Path, bits: u8, table_bits: u8, mut progress: impl FnMut(u64, u64)) -> Result<()> {
+    // Two builds appending to the same partial file would interleave entries
+    let _lock = crate::utils::lock_exclusive(out)?;
+    let partial = partial_path(out);
+    let total = 1u64 << table_bits;
+
+    let mut written = match std::fs::read(&partial) {
+        Ok(contents) if parse_header(&contents) == Some((bits, table_bits)) => {
+            ((contents.len() - HEADER_LEN) / ENTRY_LEN) as u64
+        }
+        _ => {
+            let mut header = MAGIC.to_vec();
+            header.extend([FORMAT_VERSION, bits, table_bits]);
+            std::fs::write(&partial, header)
+                .with_context(|| format!("Failed to create {}", partial.display()))?;
+            0
+        }
+    };
+
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .open(&partial)
+        .with_context(|| format!("Failed to open {}", partial.display()))?;
+    // Drop any half-written entry from an interrupted chunk
+    file.set_len((HEADER_LEN as u64) + written * ENTRY_LEN as u64)?;
+    file.seek(SeekFrom::End(0))?;
+    let mut file = BufWriter::new(file);
+
+    let giant_step = Scalar::from(1u64 << (bits - table_bits)) * G;
+    let mut point = if written == 0 {
+        RistrettoPoint::identity()
+    } else {
+        Scalar::from(written) * giant_step
+    };
+
+    progress(written, total);
+    while written < total {
+        let chunk_end = (written + CHUNK_ENTRIES).min(total);
+        for _ in written..chunk_end {
+            file.write_all(&point.compress().to_bytes())?;
+            point += giant_step;
+        }
+        file.flush()?;
+        written = chunk_end;
+        progress(written, total);
+    }
+    drop(file);
+
+    let digest = hex_digest(&std::fs::read(&partial)?);
+
+    std::fs::rename(&partial, out)
+        .with_context(|| format!("Failed to move {} to {}", partial.display(), out.display()))?;
+    let file_name = out.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    crate::utils::write_atomic(&hash_path(out), format!("{}  {}\n", digest, file_name))
+        .with_context(|| format!("Failed to write integrity hash for {}", out.display()))?;
+
+    Ok(())
+}
+
+/// The `sha256sum`-compatible integrity file written next to a table.
+pub fn hash_path(path: &Path) -> PathBuf {
+    append_extension(path, "sha256")
+}
+
+fn partial_path(path: &Path) -> PathBuf {
+    append_extension(path, "partial")
+}
+
+fn append_extension(path: &Path, extension: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(".");
+    path.push(extension);
+    PathBuf::from(path)
+}
+
+/// The format version in a table's header, or `None` if `contents` is not a table at all.
+///
+/// Tables are derived data: one of another version is rebuilt rather than migrated.
+pub fn header_version(contents: &[u8]) -> Option<u8> {
+    match contents.get(..MAGIC.len() + 1) {
+        Some(header) if &header[..MAGIC.len()] == MAGIC => Some(header[MAGIC.len()]),
+        _ => None,
+    }
+}
+
+fn parse_header(contents: &[u8]) -> Option<(u8, u8)> {
+    let header = contents.get(..HEADER_LEN)?;
+    if &header[..MAGIC.len()] != MAGIC || header[MAGIC.len()] != FORMAT_VERSION {
+        return None;
+    }
+    Some((header[MAGIC.len() + 1], header[MAGIC.len() + 2]))
+}
+
+fn hex_digest(contents: &[u8]) -> String {
+    Sha256::digest(contents).iter().map(|byte| format!("{:02x}", byte)).collect()
+}
@@ -1,10 +1,38 @@
+mod balance_proof;
 mod commands;
 mod config;
+mod cpi;
 mod crypto;
+mod elgamal_registry;
+mod guardrails;
+mod intent;
+mod key_store;
+mod keyring;
+mod keypair_file;
+mod keyset;
+mod lookup_table;
+mod multisig;
+mod output;
+mod price;
+mod proof_context;
+mod proof_data;
+mod receipt;
+mod recipient;
+#[cfg(feature = "kms")]
+mod remote_signer;
+mod rpc;
+mod schedule;
+mod secrets;
+mod signer;
+mod snapshot;
+mod telemetry;
+mod trace;
 mod utils;
+mod vanity;
 
 use anyhow::Result;
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches, Parser};
+use std::time::Instant;
 
 #[derive(Parser, Debug)]
 #[command(name = "confidential-cli")]
@@ -12,10 +40,184 @@ use clap::Parser;
 struct Cli {
     #[command(subcommand)]
     command: commands::Commands,
+
+    /// Payer/default signer: a keypair path, usb://ledger, prompt:// (seed phrase), ASK,
+    /// stdin:// or -, env://VAR, https:// (a remote signing service), or the JSON keypair
+    /// bytes themselves (defaults to the Solana CLI config keypair)
+    #[arg(short, long, global = true, env = "CONFIDENTIAL_CLI_KEYPAIR", hide_env_values = true)]
+    keypair: Option<String>,
+
+    /// BIP44 path for prompt:// and usb:// signers without a ?key= or ?full-path= query,
+    /// as `m/44'/501'/<ACCOUNT>'/<CHANGE>'` or `<ACCOUNT>/<CHANGE>`
+    #[arg(long, global = true, value_name = "PATH")]
+    derivation_path: Option<String>,
+
+    /// Pays transaction fees and rent instead of --keypair, which then only signs as the
+    /// account owner or authority (given like --keypair)
+    #[arg(long, global = true, env = "CONFIDENTIAL_CLI_FEE_PAYER", value_name = "KEYPAIR", hide_env_values = true)]
+    fee_payer: Option<String>,
+
+    /// Signer of the SPL token multisig that owns the account, given like --keypair;
+    /// repeat it until the multisig's threshold is met
+    #[arg(long = "multisig-signer", global = true, value_name = "KEYPAIR")]
+    multisig_signers: Vec<String>,
+
+    /// ElGamal key to use for every account instead of deriving one: env://VAR or a file with
+    /// a JSON byte array or base64 (keypair or secret key), so CI never writes keys to disk;
+    /// keypair files from solana-zk-keygen or another wallet load as-is
+    #[arg(long, visible_alias = "elgamal-keypair", global = true, value_name = "SOURCE")]
+    elgamal_key: Option<String>,
+
+    /// AES key to use for every account instead of deriving one, given like --elgamal-key
+    #[arg(long, global = true, value_name = "SOURCE")]
+    aes_key: Option<String>,
+
+    /// Build the transaction but write it as an unsigned intent file instead of sending it
+    #[arg(long, global = true, value_name = "PATH")]
+    export_intent: Option<std::path::PathBuf>,
+
+    /// How ElGamal/AES keys are derived; `spl-token` matches the official spl-token CLI,
+    /// `legacy` is this CLI's original scheme for accounts configured before it, and
+    /// `offchain` is the one a usb://ledger signer can derive keys with
+    #[arg(long, visible_alias = "key-derivation", global = true, value_enum, env = "CONFIDENTIAL_CLI_DERIVATION_SCHEME", default_value_t = crypto::DerivationScheme::SplToken)]
+    derivation_scheme: crypto::DerivationScheme,
+
+    /// Seconds to spend decrypting a pending balance before giving up (0 = no limit)
+    #[arg(long, global = true, default_value = "30")]
+    decrypt_timeout: u64,
+
+    /// Where 'import-keys' keeps imported encryption keys (default ~/.config/confidential-cli/keys.json)
+    #[arg(long, global = true, env = "CONFIDENTIAL_CLI_KEY_STORE", value_name = "PATH")]
+    key_store: Option<std::path::PathBuf>,
+
+    /// Cache derived encryption keys in the OS keyring (Keychain, Credential Manager or
+    /// Secret Service), so later runs don't need the signer to derive them again
+    #[arg(long, global = true, env = "CONFIDENTIAL_CLI_KEYRING")]
+    keyring: bool,
+
+    /// Discrete-log table from `build-table`; makes pending balance decryption near-instant
+    #[arg(long, global = true, env = "CONFIDENTIAL_CLI_DECRYPT_TABLE", value_name = "PATH")]
+    decrypt_table: Option<std::path::PathBuf>,
+
+    /// Threads per pending balance discrete-log search (power of two)
+    #[arg(long, global = true, default_value = "1")]
+    decrypt_threads: usize,
+
+    /// Where ZK proofs go: inline, in context state accounts, or auto (inline when it fits)
+    #[arg(long, global = true, value_enum, default_value_t = proof_context::ProofStrategy::Auto)]
+    proof_strategy: proof_context::ProofStrategy,
+
+    /// Secondary RPC endpoint for account scans and history lookups, for primaries that
+    /// disable them; transactions always go to the Solana CLI config endpoint
+    #[arg(long, global = true, env = "CONFIDENTIAL_CLI_SCAN_URL", value_name = "URL")]
+    scan_url: Option<String>,
+
+    /// Separate endpoint for sending transactions (e.g. a low-latency sender); reads,
+    /// including those proofs are built from, stay on the Solana CLI config endpoint
+    #[arg(long, global = true, env = "CONFIDENTIAL_CLI_SEND_URL", value_name = "URL")]
+    send_url: Option<String>,
+
+    /// Price feed for fiat estimates next to balances (off unless set); `{mint}` in the URL
+    /// is replaced with the mint. Plain `{"price": ..}`, Jupiter and Pyth Hermes responses work
+    #[arg(long, global = true, env = "CONFIDENTIAL_CLI_PRICE_URL", value_name = "URL")]
+    price_url: Option<String>,
+
+    /// Currency label the price feed quotes in
+    #[arg(long, global = true, default_value = "USD", value_name = "CODE")]
+    price_currency: String,
+
+    /// Print each instruction's program id, account metas and data as hex before sending
+    #[arg(long, global = true)]
+    dump_instructions: bool,
+
+    /// Skip the mainnet-beta checks before create-mint and create-account (payer with
+    /// no history, unusual decimals, authority handed to the fee payer)
+    #[arg(long, global = true)]
+    i_know_what_im_doing: bool,
+
+    /// Accept an http:// remote signer on a loopback address, for testing a signing
+    /// service locally; anywhere else the token and messages would cross the network in
+    /// cleartext, so only https:// is accepted
+    #[arg(long, global = true)]
+    allow_insecure_signer: bool,
+
+    /// Output format; `json` prints a versioned payload on stdout (see `schema`)
+    #[arg(long, global = true, value_enum, default_value_t = output::OutputFormat::Human)]
+    output: output::OutputFormat,
+
+    /// Print the result through a template instead, e.g. '{{available_balance}} {{mint}}';
+    /// fields are those of the JSON payload (see `schema`)
+    #[arg(long, global = true, value_name = "TEMPLATE", conflicts_with = "output")]
+    format: Option<String>,
+
+    /// Opt in to anonymous usage metrics (command name, duration, error class)
+    #[arg(long, global = true, env = "CONFIDENTIAL_CLI_TELEMETRY")]
+    enable_telemetry: bool,
+
+    /// Where opted-in usage metrics are sent
+    #[arg(long, global = true, env = "CONFIDENTIAL_CLI_TELEMETRY_ENDPOINT")]
+    telemetry_endpoint: Option<String>,
+
+    /// Export spans for RPC calls, proof generation and confirmations to this OTLP/HTTP
+    /// collector (e.g. http://localhost:4318)
+    #[arg(long, global = true, env = "OTEL_EXPORTER_OTLP_ENDPOINT")]
+    otlp_endpoint: Option<String>,
+
+    /// W3C trace context to attach exported spans to
+    #[arg(long, global = true, env = "TRACEPARENT")]
+    traceparent: Option<String>,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let cli = Cli::parse();
-    commands::handle_command(cli.command).await
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches)?;
+    let command_name = matches.subcommand_name().unwrap_or_default().to_string();
+    output::set_format(cli.output);
+    if let Some(template) = cli.format {
+        output::set_template(template);
+    }
+    config::set_global_options(config::GlobalOptions {
+        keypair: cli.keypair,
+        derivation_path: cli.derivation_path,
+        fee_payer: cli.fee_payer,
+        export_intent: cli.export_intent,
+        derivation_scheme: cli.derivation_scheme,
+        decrypt_timeout: cli.decrypt_timeout,
+        decrypt_threads: cli.decrypt_threads,
+        decrypt_table: cli.decrypt_table,
+        proof_strategy: cli.proof_strategy,
+        dump_instructions: cli.dump_instructions,
+        scan_url: cli.scan_url,
+        send_url: cli.send_url,
+        price_url: cli.price_url,
+        price_currency: cli.price_currency,
+        key_store: cli.key_store,
+        keyring: cli.keyring,
+        i_know_what_im_doing: cli.i_know_what_im_doing,
+        allow_insecure_signer: cli.allow_insecure_signer,
+        multisig_signers: cli.multisig_signers,
+        elgamal_key: cli.elgamal_key,
+        aes_key: cli.aes_key,
+    });
+
+    trace::init(cli.otlp_endpoint, cli.traceparent.as_deref());
+
+    let started = Instant::now();
+    let mut span = trace::span(&command_name);
+    let result = trace::record(&mut span, commands::handle_command(cli.command).await);
+    drop(span);
+    trace::export().await;
+
+    if cli.enable_telemetry {
+        match cli.telemetry_endpoint.as_deref() {
+            Some(endpoint) => {
+                let event = telemetry::CommandEvent::new(command_name, started.elapsed(), &result);
+                telemetry::report(endpoint, &event).await;
+            }
+            None => eprintln!("⚠️  Telemetry enabled but no --telemetry-endpoint configured, nothing sent"),
+        }
+    }
+
+    result
 }
@@ -0,0 +1,51 @@
+use anyhow::{Context, Result};
+use solana_sdk::{
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::Signer,
+};
+use spl_token_2022::state::Multisig;
+use crate::config::AppConfig;
+
+/// Checks that `owner` is an SPL token multisig the `--multisig-signer`s can act for, and
+/// returns their pubkeys for the instruction's signer list.
+///
+/// Without `--multisig-signer` this returns nothing and the owner is left to the caller;
+/// with it, the account must be owned by a multisig that lists every given signer and
+/// whose threshold they meet.
+pub async fn signer_pubkeys(config: &AppConfig, owner: &Pubkey) -> Result<Vec<Pubkey>> {
+    if config.multisig_signers.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let owner_data = config.rpc_client.get_account(owner).await
+        .with_context(|| format!("Failed to fetch owner {} to check its multisig", owner))?;
+    if owner_data.owner != spl_token_2022::id() || owner_data.data.len() != Multisig::LEN {
+        anyhow::bail!("--multisig-signer was given, but owner {} is not an SPL token multisig", owner);
+    }
+    let multisig = Multisig::unpack(&owner_data.data)
+        .map_err(|e| anyhow::anyhow!("Owner {} is not an initialized multisig: {}", owner, e))?;
+    let members = &multisig.signers[..usize::from(multisig.n)];
+
+    let mut pubkeys: Vec<Pubkey> = Vec::new();
+    for signer in &config.multisig_signers {
+        let pubkey = signer.pubkey();
+        if !members.contains(&pubkey) {
+            anyhow::bail!("{} is not a signer of multisig {}", pubkey, owner);
+        }
+        if !pubkeys.contains(&pubkey) {
+            pubkeys.push(pubkey);
+        }
+    }
+    if pubkeys.len() < usize::from(multisig.m) {
+        anyhow::bail!(
+            "Multisig {} needs {} of its {} signers, but only {} --multisig-signer given",
+            owner,
+            multisig.m,
+            multisig.n,
+            pubkeys.len()
+        );
+    }
+
+    Ok(pubkeys)
+}
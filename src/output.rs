@@ -0,0 +1,544 @@
+use serde_json::{json, Value};
+use std::sync::OnceLock;
+
+/// Bumped whenever a field is removed, renamed or changes type in any payload.
+/// Adding optional fields does not bump it.
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    #[default]
+    Human,
+    Json,
+}
+
+static FORMAT: OnceLock<OutputFormat> = OnceLock::new();
+static TEMPLATE: OnceLock<String> = OnceLock::new();
+
+pub fn set_format(format: OutputFormat) {
+    let _ = FORMAT.set(format);
+}
+
+/// Renders payloads through `template` (see `render`) instead of the selected format.
+pub fn set_template(template: String) {
+    let _ = TEMPLATE.set(template);
+}
+
+pub fn is_json() -> bool {
+    FORMAT.get().copied().unwrap_or_default() == OutputFormat::Json
+}
+
+/// Whether stdout is kept for the payload, i.e. JSON mode or a `--format` template.
+pub fn stdout_reserved() -> bool {
+    is_json() || TEMPLATE.get().is_some()
+}
+
+/// Human-readable narration. In JSON and template mode it moves to stderr so stdout stays parseable.
+macro_rules! say {
+    () => {
+        if $crate::output::stdout_reserved() { eprintln!() } else { println!() }
+    };
+    ($($arg:tt)*) => {
+        if $crate::output::stdout_reserved() { eprintln!($($arg)*) } else { println!($($arg)*) }
+    };
+}
+pub(crate) use say;
+
+/// Prints a command's machine-readable result. No-op in human mode.
+pub fn emit(command: &str, data: Value) {
+    if let Some(template) = TEMPLATE.get() {
+        println!("{}", render(command, template, &data));
+    } else if is_json() {
+        let envelope = json!({
+            "schema": command,
+            "schema_version": SCHEMA_VERSION,
+            "data": data,
+        });
+        println!("{}", serde_json::to_string_pretty(&envelope).unwrap_or_default());
+    }
+}
+
+/// Fills `{{field}}` placeholders from the payload; `{{a.b}}` and `{{list.0}}` reach into
+/// nested values. Strings print bare, null prints nothing and anything else as compact JSON.
+/// `\n` and `\t` are expanded so single-quoted shell templates can span lines.
+fn render(command: &str, template: &str, data: &Value) -> String {
+    let template = template.replace("\\n", "\n").replace("\\t", "\t");
+    let mut rendered = String::new();
+    let mut rest = template.as_str();
+
+    while let Some(start) = rest.find("{{") {
+        rendered.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find("}}") else {
+            rest = &rest[start..];
+            break;
+        };
+        let path = rest[start + 2..start + end].trim();
+        let value = path.split('.').try_fold(data, |value, key| match value {
+            Value::Array(items) => key.parse::<usize>().ok().and_then(|index| items.get(index)),
+            value => value.get(key),
+        });
+        match value {
+            Some(Value::String(value)) => rendered.push_str(value),
+            Some(Value::Null) => {}
+            Some(value) => rendered.push_str(&value.to_string()),
+            None => {
+                let fields = data.as_object()
+                    .map(|fields| fields.keys().cloned().collect::<Vec<_>>().join(", "))
+                    .unwrap_or_default();
+                eprintln!("⚠️  --format: '{}' is not in the {} payload (fields: {})", path, command, fields);
+            }
+        }
+        rest = &rest[start + end + 2..];
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+const STRING: &str = "string";
+const INTEGER: &str = "integer";
+const BOOLEAN: &str = "boolean";
+const OPTIONAL_STRING: &str = "string|null";
+const OPTIONAL_INTEGER: &str = "integer|null";
+const OPTIONAL_BOOLEAN: &str = "boolean|null";
+
+// (command, [(field, type)]) for every payload passed to `emit`
+const PAYLOADS: &[(&str, &[(&str, &str)])] = &[
+    ("create-mint", &[
+        ("mint", STRING),
+        ("authority", STRING),
+        ("decimals", INTEGER),
+        ("rent_lamports", INTEGER),
+        ("space", INTEGER),
+        ("auditor_elgamal_pubkey", OPTIONAL_STRING),
+        ("signature", STRING),
+    ]),
+    ("create-multisig", &[
+        ("multisig", STRING),
+        ("threshold", INTEGER),
+        ("signers", "array"),
+        ("rent_lamports", INTEGER),
+        ("signature", STRING),
+    ]),
+    ("dev-token", &[
+        ("mint", STRING),
+        ("account", STRING),
+        ("amount", INTEGER),
+        ("created_mint", BOOLEAN),
+        ("created_account", BOOLEAN),
+        ("signature", STRING),
+    ]),
+    ("bootstrap", &[
+        ("mint", OPTIONAL_STRING),
+        ("treasury", OPTIONAL_STRING),
+        ("planned", "array"),
+        ("completed", "array"),
+        ("report", OPTIONAL_STRING),
+    ]),
+    ("update-mint", &[
+        ("mint", STRING),
+        ("auto_approve_new_accounts", BOOLEAN),
+        ("auditor_elgamal_pubkey", OPTIONAL_STRING),
+        ("signature", STRING),
+    ]),
+    ("mint-info", &[
+        ("mint", STRING),
+        ("decimals", INTEGER),
+        ("supply", INTEGER),
+        ("mint_authority", OPTIONAL_STRING),
+        ("freeze_authority", OPTIONAL_STRING),
+        ("extensions", "array"),
+        ("confidential_transfers", BOOLEAN),
+        ("confidential_transfer_authority", OPTIONAL_STRING),
+        ("auto_approve_new_accounts", OPTIONAL_BOOLEAN),
+        ("auditor_elgamal_pubkey", OPTIONAL_STRING),
+    ]),
+    ("create-account", &[
+        ("account", STRING),
+        ("owner", STRING),
+        ("mint", STRING),
+        ("elgamal_pubkey", STRING),
+        ("rent_lamports", INTEGER),
+        ("space", INTEGER),
+        ("signature", STRING),
+        ("signatures", "array"),
+    ]),
+    ("create-accounts", &[
+        ("mint", STRING),
+        ("owner", STRING),
+        ("requested", INTEGER),
+        ("created", INTEGER),
+        ("accounts", "array"),
+        ("report", OPTIONAL_STRING),
+    ]),
+    ("approve-account", &[
+        ("account", STRING),
+        ("owner", STRING),
+        ("mint", STRING),
+        ("signature", STRING),
+    ]),
+    ("enable-confidential-credits", &[
+        ("account", STRING),
+        ("mint", STRING),
+        ("signature", STRING),
+        ("allow_confidential_credits", BOOLEAN),
+    ]),
+    ("disable-confidential-credits", &[
+        ("account", STRING),
+        ("mint", STRING),
+        ("signature", STRING),
+        ("allow_confidential_credits", BOOLEAN),
+    ]),
+    ("enable-non-confidential-credits", &[
+        ("account", STRING),
+        ("mint", STRING),
+        ("signature", STRING),
+        ("allow_non_confidential_credits", BOOLEAN),
+    ]),
+    ("disable-non-confidential-credits", &[
+        ("account", STRING),
+        ("mint", STRING),
+        ("signature", STRING),
+        ("allow_non_confidential_credits", BOOLEAN),
+    ]),
+    ("deposit", &[
+        ("account", STRING),
+        ("mint", STRING),
+        ("amount", INTEGER),
+        ("ui_amount", STRING),
+        ("signature", STRING),
+    ]),
+    ("mint-to", &[
+        ("account", STRING),
+        ("mint", STRING),
+        ("amount", INTEGER),
+        ("public_balance", INTEGER),
+        ("signature", STRING),
+    ]),
+    ("burn", &[
+        ("account", STRING),
+        ("mint", STRING),
+        ("amount", INTEGER),
+        ("public_balance", INTEGER),
+        ("signature", STRING),
+    ]),
+    ("confidential-mint-to", &[
+        ("account", STRING),
+        ("mint", STRING),
+        ("amount", INTEGER),
+        ("new_supply", INTEGER),
+        ("signature", STRING),
+    ]),
+    ("apply-balance", &[
+        ("account", STRING),
+        ("mint", STRING),
+        ("previous_available_balance", INTEGER),
+        ("pending_balance", INTEGER),
+        ("new_available_balance", INTEGER),
+        ("signature", OPTIONAL_STRING),
+        ("stale_credits", INTEGER),
+    ]),
+    ("apply-balance-batch", &[
+        ("accounts", "array"),
+        ("applied", INTEGER),
+        ("errors", INTEGER),
+        ("signatures", "array"),
+    ]),
+    ("transfer", &[
+        ("from", STRING),
+        ("to", STRING),
+        ("mint", STRING),
+        ("amount", INTEGER),
+        ("fee", OPTIONAL_INTEGER),
+        ("signature", STRING),
+    ]),
+    ("confidential-transfer", &[
+        ("from", STRING),
+        ("to", STRING),
+        ("mint", STRING),
+        ("amount", INTEGER),
+        ("fee", INTEGER),
+        ("signature", OPTIONAL_STRING),
+        ("signatures", "array"),
+    ]),
+    ("schedule-transfer", &[
+        ("id", INTEGER),
+        ("command", STRING),
+        ("from", STRING),
+        ("to", STRING),
+        ("mint", STRING),
+        ("amount", INTEGER),
+        ("execute_at", STRING),
+        ("schedule", STRING),
+    ]),
+    ("run-due", &[
+        ("schedule", STRING),
+        ("transfers", "array"),
+        ("executed", INTEGER),
+        ("failed", INTEGER),
+    ]),
+    ("proof-plan", &[
+        ("command", STRING),
+        ("selected", STRING),
+        ("inline_fits", BOOLEAN),
+        ("inline_transactions", INTEGER),
+        ("inline_fee_lamports", OPTIONAL_INTEGER),
+        ("context_transactions", INTEGER),
+        ("context_fee_lamports", INTEGER),
+        ("context_rent_lamports", INTEGER),
+    ]),
+    ("withdraw", &[
+        ("account", STRING),
+        ("mint", STRING),
+        ("amount", INTEGER),
+        ("new_available_balance", INTEGER),
+        ("signature", STRING),
+    ]),
+    ("empty-account", &[
+        ("account", STRING),
+        ("mint", STRING),
+        ("public_balance", INTEGER),
+        ("closable", BOOLEAN),
+        ("signature", STRING),
+    ]),
+    ("close-account", &[
+        ("account", STRING),
+        ("mint", STRING),
+        ("destination", STRING),
+        ("rent_lamports", INTEGER),
+        ("signature", STRING),
+    ]),
+    ("export-keys", &[
+        ("path", STRING),
+        ("owner", STRING),
+        ("derivation_scheme", STRING),
+        ("accounts", "array"),
+    ]),
+    ("import-keys", &[
+        ("path", STRING),
+        ("key_store", STRING),
+        ("owner", STRING),
+        ("accounts", "array"),
+        ("owner_wide", BOOLEAN),
+        ("replaced", INTEGER),
+    ]),
+    ("keygen", &[
+        ("path", STRING),
+        ("pubkey", STRING),
+        ("source", OPTIONAL_STRING),
+    ]),
+    ("register-elgamal-key", &[
+        ("wallet", STRING),
+        ("registry", STRING),
+        ("elgamal_pubkey", STRING),
+        ("account", OPTIONAL_STRING),
+        ("updated", BOOLEAN),
+        ("signature", STRING),
+    ]),
+    ("decrypt", &[
+        ("kind", STRING),
+        ("amount", INTEGER),
+        ("account", OPTIONAL_STRING),
+        ("elgamal_pubkey", OPTIONAL_STRING),
+    ]),
+    ("encrypt", &[
+        ("amount", INTEGER),
+        ("elgamal_pubkey", STRING),
+        ("ciphertext", STRING),
+        ("opening", OPTIONAL_STRING),
+    ]),
+    ("prove-balance", &[
+        ("path", STRING),
+        ("account", STRING),
+        ("mint", STRING),
+        ("slot", INTEGER),
+        ("amount", INTEGER),
+    ]),
+    ("verify-proof", &[
+        ("kind", STRING),
+        ("file", OPTIONAL_STRING),
+        ("account", OPTIONAL_STRING),
+        ("slot", OPTIONAL_INTEGER),
+        ("amount", OPTIONAL_INTEGER),
+        ("failures", "array"),
+    ]),
+    ("balance", &[
+        ("account", STRING),
+        ("mint", STRING),
+        ("owner", STRING),
+        ("decimals", INTEGER),
+        ("slot", INTEGER),
+        ("elgamal_pubkey", STRING),
+        ("approved", BOOLEAN),
+        ("allow_confidential_credits", BOOLEAN),
+        ("allow_non_confidential_credits", BOOLEAN),
+        ("public_balance", INTEGER),
+        ("available_balance", INTEGER),
+        ("pending_balance", INTEGER),
+        ("pending_balance_credit_counter", INTEGER),
+        ("maximum_pending_balance_credit_counter", INTEGER),
+        ("fiat_estimate", OPTIONAL_STRING),
+        ("fiat_currency", OPTIONAL_STRING),
+    ]),
+    ("account-info", &[
+        ("account", STRING),
+        ("mint", STRING),
+        ("owner", STRING),
+        ("approved", BOOLEAN),
+        ("elgamal_pubkey", STRING),
+        ("pending_balance_lo", STRING),
+        ("pending_balance_hi", STRING),
+        ("available_balance", STRING),
+        ("decryptable_available_balance", STRING),
+        ("allow_confidential_credits", BOOLEAN),
+        ("allow_non_confidential_credits", BOOLEAN),
+        ("pending_balance_credit_counter", INTEGER),
+        ("maximum_pending_balance_credit_counter", INTEGER),
+        ("expected_pending_balance_credit_counter", INTEGER),
+        ("actual_pending_balance_credit_counter", INTEGER),
+        ("raw", STRING),
+    ]),
+    ("diff", &[
+        ("account", STRING),
+        ("snapshot_slot", INTEGER),
+        ("current_slot", INTEGER),
+        ("changes", "array"),
+    ]),
+    ("reconcile", &[
+        ("ledger", STRING),
+        ("accounts", "array"),
+        ("mismatches", INTEGER),
+        ("errors", INTEGER),
+    ]),
+    ("audit-session", &[
+        ("path", STRING),
+        ("signer", STRING),
+        ("entries", INTEGER),
+        ("unresolved", INTEGER),
+    ]),
+    ("verify-audit-session", &[
+        ("signer", STRING),
+        ("signature_valid", BOOLEAN),
+        ("entries", INTEGER),
+        ("checked_chain", BOOLEAN),
+        ("failures", "array"),
+    ]),
+    ("acknowledge-transfer", &[
+        ("path", STRING),
+        ("account", STRING),
+        ("transfer_signature", STRING),
+        ("applied_signature", STRING),
+        ("signer", STRING),
+        ("amount_proven", BOOLEAN),
+    ]),
+    ("verify-receipt", &[
+        ("account", STRING),
+        ("transfer_signature", STRING),
+        ("applied_signature", STRING),
+        ("signer", STRING),
+        ("amount", OPTIONAL_INTEGER),
+        ("failures", "array"),
+    ]),
+    ("top-up", &[
+        ("accounts_checked", INTEGER),
+        ("total_lamports", INTEGER),
+        ("shortfalls", "array"),
+        ("signatures", "array"),
+    ]),
+    ("health", &[
+        ("owner", STRING),
+        ("accounts", "array"),
+        ("fixed", "array"),
+    ]),
+    ("export-intent", &[
+        ("path", STRING),
+        ("command", STRING),
+        ("required_signers", "array"),
+    ]),
+    ("rotate-keys", &[
+        ("old_account", STRING),
+        ("new_account", STRING),
+        ("mint", STRING),
+        ("elgamal_pubkey", STRING),
+        ("confidential_amount", INTEGER),
+        ("public_amount", INTEGER),
+        ("fee", INTEGER),
+        ("transfer_signature", OPTIONAL_STRING),
+    ]),
+    ("explain-error", &[
+        ("input", STRING),
+        ("error", OPTIONAL_STRING),
+        ("code", OPTIONAL_INTEGER),
+        ("cause", OPTIONAL_STRING),
+        ("recovery", "array"),
+        ("signature", OPTIONAL_STRING),
+        ("transaction_error", OPTIONAL_STRING),
+        ("failed_instruction", OPTIONAL_INTEGER),
+        ("logs", "array"),
+    ]),
+    ("support-bundle", &[
+        ("path", STRING),
+        ("accounts", INTEGER),
+        ("transactions", INTEGER),
+    ]),
+    ("migrate", &[
+        ("files", "array"),
+        ("migrated", INTEGER),
+        ("errors", INTEGER),
+    ]),
+    ("submit-intent", &[
+        ("command", STRING),
+        ("signature", OPTIONAL_STRING),
+        ("missing_signers", "array"),
+    ]),
+    ("cpi-instructions", &[
+        ("command", STRING),
+        ("owner", STRING),
+        ("instructions", "array"),
+    ]),
+    ("build-table", &[
+        ("path", STRING),
+        ("hash_path", STRING),
+        ("bits", INTEGER),
+        ("table_bits", INTEGER),
+    ]),
+];
+
+pub fn schema_names() -> impl Iterator<Item = &'static str> {
+    PAYLOADS.iter().map(|(command, _)| *command)
+}
+
+/// JSON Schema (draft 2020-12) for the envelope `emit` prints for `command`.
+pub fn schema(command: &str) -> Option<Value> {
+    let (_, fields) = PAYLOADS.iter().find(|(name, _)| *name == command)?;
+
+    let properties: serde_json::Map<String, Value> = fields
+        .iter()
+        .map(|(field, kind)| {
+            let kind = match *kind {
+                OPTIONAL_STRING => json!([STRING, "null"]),
+                OPTIONAL_INTEGER => json!([INTEGER, "null"]),
+                OPTIONAL_BOOLEAN => json!([BOOLEAN, "null"]),
+                kind => json!(kind),
+            };
+            (field.to_string(), json!({ "type": kind }))
+        })
+        .collect();
+    let required: Vec<&str> = fields.iter().map(|(field, _)| *field).collect();
+
+    Some(json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "$id": format!("confidential-cli/{}/v{}", command, SCHEMA_VERSION),
+        "title": command,
+        "type": "object",
+        "required": ["schema", "schema_version", "data"],
+        "properties": {
+            "schema": { "const": command },
+            "schema_version": { "const": SCHEMA_VERSION },
+            "data": {
+                "type": "object",
+                "required": required,
+                "properties": properties,
+            },
+        },
+    }))
+}
@@ -0,0 +1,66 @@
+use anyhow::{Context, Result};
+use serde_json::Value;
+use solana_sdk::pubkey::Pubkey;
+use std::time::Duration;
+use crate::{config, output::say};
+
+/// A token's price in `--price-currency`, as reported by `--price-url`.
+pub struct Price {
+    pub per_token: f64,
+    pub currency: String,
+}
+
+impl Price {
+    /// The fiat value of `amount` base units, rounded to cents. An estimate only: it goes
+    /// through f64 and a price feed nobody has vouched for.
+    pub fn estimate(&self, amount: u128, decimals: u8) -> String {
+        let tokens = amount as f64 / 10f64.powi(i32::from(decimals));
+        format!("{:.2}", tokens * self.per_token)
+    }
+}
+
+/// Looks up `mint`'s price when `--price-url` is set; `None` when it isn't or the
+/// lookup fails, which is only ever warned about since prices are cosmetic.
+pub async fn fetch(mint: &Pubkey) -> Option<Price> {
+    let options = config::global_options();
+    let template = options.price_url?;
+    let url = template.replace("{mint}", &mint.to_string());
+
+    match query(&url, mint).await {
+        Ok(per_token) => Some(Price { per_token, currency: options.price_currency }),
+        Err(e) => {
+            say!("\n⚠️  No fiat estimate: {:#}", e);
+            None
+        }
+    }
+}
+
+async fn query(url: &str, mint: &Pubkey) -> Result<f64> {
+    let client = reqwest::Client::builder().timeout(Duration::from_secs(3)).build()?;
+    let body: Value = client.get(url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .context("Price feed request failed")?
+        .json()
+        .await
+        .context("Price feed did not return JSON")?;
+    parse_price(&body, mint).ok_or_else(|| anyhow::anyhow!("Price feed response has no price for {}", mint))
+}
+
+// Accepts `{"price": 1.23}`, Jupiter-style `{"data": {"<mint>": {"price": "1.23"}}}` and
+// Pyth Hermes `{"parsed": [{"price": {"price": "123", "expo": -2}}]}`
+fn parse_price(body: &Value, mint: &Pubkey) -> Option<f64> {
+    let number = |value: &Value| value.as_f64().or_else(|| value.as_str()?.parse().ok());
+
+    if let Some(price) = number(&body["price"]) {
+        return Some(price);
+    }
+    if let Some(price) = number(&body["data"][mint.to_string()]["price"]) {
+        return Some(price);
+    }
+    let pyth = &body["parsed"][0]["price"];
+    let mantissa = number(&pyth["price"])?;
+    let exponent = pyth["expo"].as_i64()?;
+    Some(mantissa * 10f64.powi(exponent as i32))
+}
@@ -0,0 +1,390 @@
+use anyhow::{Context, Result};
+use bytemuck::Pod;
+use serde_json::json;
+use solana_sdk::{
+    instruction::Instruction,
+    message::Message,
+    packet::PACKET_DATA_SIZE,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+use spl_token_2022::solana_zk_sdk::zk_elgamal_proof_program::{
+    self,
+    instruction::{close_context_state, ContextStateInfo, ProofInstruction},
+    proof_data::ZkProofData,
+    state::{ProofContextState, ProofContextStateMeta},
+};
+use crate::{config::{self, AppConfig}, output::{self, say}, rpc, trace};
+
+/// Where ZK proofs travel: inline in the consuming transaction or in context state accounts.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ProofStrategy {
+    /// Inline when the transaction fits in one packet, context accounts otherwise
+    #[default]
+    Auto,
+    /// Always inline; fails if the transaction is too large
+    Inline,
+    /// Always verify into context state accounts first
+    Context,
+}
+
+pub fn transaction_fits(instructions: &[Instruction], payer: &Pubkey) -> Result<bool> {
+    let transaction = Transaction::new_with_payer(instructions, Some(payer));
+    Ok(bincode::serialized_size(&transaction)? as usize <= PACKET_DATA_SIZE)
+}
+
+/// Decides, per `--proof-strategy`, whether `inline_instructions` should be replaced by
+/// a context-account flow.
+pub fn use_context_accounts(inline_instructions: &[Instruction], payer: &Pubkey) -> Result<bool> {
+    Ok(match config::global_options().proof_strategy {
+        ProofStrategy::Inline => false,
+        ProofStrategy::Context => true,
+        ProofStrategy::Auto => !transaction_fits(inline_instructions, payer)?,
+    })
+}
+
+/// Verifies `proof_data` into a new context state account whose authority is the payer,
+/// for instructions whose proofs are too large to travel inline.
+///
+/// Account creation and verification share a transaction when they fit in one packet,
+/// otherwise they are sent one after the other. Retries reuse the same account and skip
+/// whatever step already landed, so a failed attempt never strands a second account. An
+/// account created but never verified has no authority and can't be closed; it is named
+/// in the error so its rent isn't lost silently.
+pub async fn create_and_verify<T, U>(
+    config: &AppConfig,
+    label: &str,
+    proof_instruction: ProofInstruction,
+    proof_data: &T,
+) -> Result<Pubkey>
+where
+    T: Pod + ZkProofData<U>,
+    U: Pod,
+{
+    let mut span = trace::span("verify_proof_context");
+    trace::attr(&mut span, "proof", label);
+    let result = verify_into_new_account(config, label, proof_instruction, proof_data).await;
+    trace::record(&mut span, result)
+}
+
+async fn verify_into_new_account<T, U>(
+    config: &AppConfig,
+    label: &str,
+    proof_instruction: ProofInstruction,
+    proof_data: &T,
+) -> Result<Pubkey>
+where
+    T: Pod + ZkProofData<U>,
+    U: Pod,
+{
+    let context_keypair = Keypair::new();
+    let context_pubkey = context_keypair.pubkey();
+    let payer = config.payer.pubkey();
+
+    let space = std::mem::size_of::<ProofContextState<U>>();
+    let rent = config.rpc_client
+        .get_minimum_balance_for_rent_exemption(space)
+        .await?;
+
+    let (create_ix, verify_ix) = context_instructions(&payer, &context_pubkey, rent, proof_instruction, proof_data);
+    let (create_ix, verify_ix, context_keypair) = (&create_ix, &verify_ix, &context_keypair);
+
+    if transaction_fits(&[create_ix.clone(), verify_ix.clone()], &payer)? {
+        // Both land or neither does, so an initialized account means an earlier attempt landed
+        rpc::retry_idempotent(label, || async move {
+            if context_state(config, &context_pubkey).await? == ContextState::Verified {
+                return Ok(());
+            }
+            send(config, &[create_ix.clone(), verify_ix.clone()], context_keypair).await
+                .with_context(|| format!("Failed to verify {} into a context account", label))
+        }).await?;
+    } else {
+        rpc::retry_idempotent(&format!("{} context account creation", label), || async move {
+            if context_state(config, &context_pubkey).await? != ContextState::Missing {
+                return Ok(());
+            }
+            send(config, std::slice::from_ref(create_ix), context_keypair).await
+                .with_context(|| format!("Failed to create context account for {}", label))
+        }).await?;
+
+        let verified = rpc::retry_idempotent(label, || async move {
+            if context_state(config, &context_pubkey).await? == ContextState::Verified {
+                return Ok(());
+            }
+            send(config, std::slice::from_ref(verify_ix), context_keypair).await
+                .with_context(|| format!("Failed to verify {} into a context account", label))
+        }).await;
+        if let Err(e) = verified {
+            say!("  ⚠️  Context account {} was created but never verified; its {} lamports of rent can't be reclaimed", context_pubkey, rent);
+            return Err(e.context(format!(
+                "Context account {} for {} holds {} lamports but, never verified, has no authority to close it",
+                context_pubkey,
+                label,
+                rent
+            )));
+        }
+    }
+
+    say!("  ✅ {} verified into {}", label, context_pubkey);
+    Ok(context_pubkey)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ContextState {
+    Missing,
+    Created,
+    Verified,
+}
+
+async fn context_state(config: &AppConfig, context_pubkey: &Pubkey) -> Result<ContextState> {
+    let account = config.rpc_client
+        .get_account_with_commitment(context_pubkey, config.rpc_client.commitment())
+        .await
+        .with_context(|| format!("Failed to fetch context account {}", context_pubkey))?
+        .value;
+    Ok(match account {
+        None => ContextState::Missing,
+        // Verification records the authority; until then the data is all zeroes
+        Some(account) if ProofContextStateMeta::try_from_bytes(&account.data)
+            .is_ok_and(|meta| meta.context_state_authority != Pubkey::default()) => ContextState::Verified,
+        Some(_) => ContextState::Created,
+    })
+}
+
+/// Sends `instructions`, signed by the payer and, when creating it, the context account.
+async fn send(config: &AppConfig, instructions: &[Instruction], context_keypair: &Keypair) -> Result<()> {
+    let payer = config.payer.pubkey();
+    let recent_blockhash = config.rpc_client.get_latest_blockhash().await?;
+    let mut transaction = Transaction::new_with_payer(instructions, Some(&payer));
+    let signer_keys = transaction.message.signer_keys();
+    let mut signers: Vec<&dyn Signer> = vec![config.payer.as_ref()];
+    if signer_keys.contains(&&context_keypair.pubkey()) {
+        signers.push(context_keypair);
+    }
+    transaction.try_sign(&signers, recent_blockhash)?;
+    rpc::sync_endpoints(config).await?;
+    rpc::dump_instructions(&transaction);
+    config.send_client.send_and_confirm_transaction(&transaction).await?;
+    Ok(())
+}
+
+/// The context accounts verified so far for one consuming transaction. When a later step
+/// fails they are closed again, so their rent goes back to the payer.
+pub struct ContextAccounts<'a> {
+    config: &'a AppConfig,
+    accounts: Vec<Pubkey>,
+}
+
+impl<'a> ContextAccounts<'a> {
+    pub fn new(config: &'a AppConfig) -> Self {
+        Self { config, accounts: Vec::new() }
+    }
+
+    /// Like `create_and_verify`, closing the accounts verified before this one if it fails.
+    pub async fn create_and_verify<T, U>(
+        &mut self,
+        label: &str,
+        proof_instruction: ProofInstruction,
+        proof_data: &T,
+    ) -> Result<Pubkey>
+    where
+        T: Pod + ZkProofData<U>,
+        U: Pod,
+    {
+        match create_and_verify(self.config, label, proof_instruction, proof_data).await {
+            Ok(context_account) => {
+                self.accounts.push(context_account);
+                Ok(context_account)
+            }
+            Err(e) => Err(self.close_after_error(e).await),
+        }
+    }
+
+    pub async fn close_after_error(&self, error: anyhow::Error) -> anyhow::Error {
+        close_after_error(self.config, &self.accounts, error).await
+    }
+
+    pub fn into_accounts(self) -> Vec<Pubkey> {
+        self.accounts
+    }
+}
+
+/// Closes `context_accounts` after `error` stopped their consuming transaction, and
+/// returns `error`. Closing is safe even if that transaction's outcome is unknown: had it
+/// landed, it already closed them and this fails harmlessly; if not, it no longer can.
+pub async fn close_after_error(config: &AppConfig, context_accounts: &[Pubkey], error: anyhow::Error) -> anyhow::Error {
+    if let Err(close_error) = close(config, context_accounts).await {
+        say!("⚠️  {:#}", close_error);
+    }
+    error
+}
+
+fn context_instructions<T, U>(
+    payer: &Pubkey,
+    context_pubkey: &Pubkey,
+    rent: u64,
+    proof_instruction: ProofInstruction,
+    proof_data: &T,
+) -> (Instruction, Instruction)
+where
+    T: Pod + ZkProofData<U>,
+    U: Pod,
+{
+    let space = std::mem::size_of::<ProofContextState<U>>();
+    let create_ix = solana_system_interface::instruction::create_account(
+        payer,
+        context_pubkey,
+        rent,
+        space as u64,
+        &zk_elgamal_proof_program::id(),
+    );
+    let verify_ix = proof_instruction.encode_verify_proof(
+        Some(ContextStateInfo {
+            context_state_account: context_pubkey,
+            context_state_authority: payer,
+        }),
+        proof_data,
+    );
+    (create_ix, verify_ix)
+}
+
+/// The transactions `create_and_verify` would send for one proof, for `plan`.
+pub struct PlannedProof {
+    pub label: &'static str,
+    pub space: usize,
+    pub transactions: Vec<Vec<Instruction>>,
+}
+
+pub fn plan_context_account<T, U>(
+    payer: &Pubkey,
+    label: &'static str,
+    proof_instruction: ProofInstruction,
+    proof_data: &T,
+) -> Result<PlannedProof>
+where
+    T: Pod + ZkProofData<U>,
+    U: Pod,
+{
+    let (create_ix, verify_ix) = context_instructions(payer, &Pubkey::new_unique(), 0, proof_instruction, proof_data);
+    let combined = vec![create_ix.clone(), verify_ix.clone()];
+    let transactions = if transaction_fits(&combined, payer)? {
+        vec![combined]
+    } else {
+        vec![vec![create_ix], vec![verify_ix]]
+    };
+    Ok(PlannedProof {
+        label,
+        space: std::mem::size_of::<ProofContextState<U>>(),
+        transactions,
+    })
+}
+
+/// Prints and emits the cost of sending `inline_instructions` against verifying `proofs`
+/// into context accounts and then sending `context_instructions`, without sending anything.
+///
+/// Context accounts are placeholders here, so `context_instructions` should be built
+/// against any pubkeys and already include the close instructions.
+pub async fn plan(
+    config: &AppConfig,
+    command: &str,
+    inline_instructions: &[Instruction],
+    proofs: &[PlannedProof],
+    context_instructions: &[Instruction],
+) -> Result<()> {
+    let payer = config.payer.pubkey();
+    let recent_blockhash = config.rpc_client.get_latest_blockhash().await?;
+    let fee = |instructions: &[Instruction]| {
+        let message = Message::new_with_blockhash(instructions, Some(&payer), &recent_blockhash);
+        async move { config.rpc_client.get_fee_for_message(&message).await }
+    };
+
+    let inline_fits = transaction_fits(inline_instructions, &payer)?;
+    let inline_fee = match inline_fits {
+        true => Some(fee(inline_instructions).await?),
+        false => None,
+    };
+
+    let mut context_transactions = 0;
+    let mut context_fee = 0;
+    let mut context_rent = 0;
+    for proof in proofs {
+        for instructions in &proof.transactions {
+            context_transactions += 1;
+            context_fee += fee(instructions).await?;
+        }
+        context_rent += config.rpc_client.get_minimum_balance_for_rent_exemption(proof.space).await?;
+    }
+    if !transaction_fits(context_instructions, &payer)? {
+        anyhow::bail!("{} does not fit in one transaction even with context accounts", command);
+    }
+    context_transactions += 1;
+    context_fee += fee(context_instructions).await?;
+
+    let selected = match config::global_options().proof_strategy {
+        ProofStrategy::Inline => "inline",
+        ProofStrategy::Context => "context",
+        ProofStrategy::Auto if inline_fits => "inline",
+        ProofStrategy::Auto => "context",
+    };
+
+    say!("\n🧮 Proof Strategy Plan (nothing was sent):");
+    match inline_fee {
+        Some(inline_fee) => say!("  inline:  1 transaction, {} lamports in fees", inline_fee),
+        None => say!("  inline:  does not fit in one transaction ({} byte packet limit)", PACKET_DATA_SIZE),
+    }
+    say!("  context: {} transactions, {} lamports in fees", context_transactions, context_fee);
+    for proof in proofs {
+        say!("           • {} ({} bytes, {} transaction(s))", proof.label, proof.space, proof.transactions.len());
+    }
+    say!("           {} lamports of rent locked until the accounts are closed in the last transaction", context_rent);
+    say!("\n  --proof-strategy {:?} would use: {}", config::global_options().proof_strategy, selected);
+    say!("  Force one with --proof-strategy inline|context");
+
+    output::emit("proof-plan", json!({
+        "command": command,
+        "selected": selected,
+        "inline_fits": inline_fits,
+        "inline_transactions": inline_fits as u64,
+        "inline_fee_lamports": inline_fee,
+        "context_transactions": context_transactions,
+        "context_fee_lamports": context_fee,
+        "context_rent_lamports": context_rent,
+    }));
+
+    Ok(())
+}
+
+/// Closes context accounts whose consuming transaction won't be sent after all.
+pub async fn close(config: &AppConfig, context_accounts: &[Pubkey]) -> Result<()> {
+    if context_accounts.is_empty() {
+        return Ok(());
+    }
+    let mut transaction = Transaction::new_with_payer(
+        &close_instructions(config, context_accounts),
+        Some(&config.payer.pubkey()),
+    );
+    let recent_blockhash = config.rpc_client.get_latest_blockhash().await?;
+    config.sign(&mut transaction, recent_blockhash)?;
+    rpc::send_once(config, &transaction)
+        .await
+        .context("Failed to close the proof context accounts")?;
+    Ok(())
+}
+
+/// Closes context state accounts created by `create_and_verify`, returning their rent to the payer.
+pub fn close_instructions(config: &AppConfig, context_accounts: &[Pubkey]) -> Vec<Instruction> {
+    let payer = config.payer.pubkey();
+    context_accounts
+        .iter()
+        .map(|context_account| {
+            close_context_state(
+                ContextStateInfo {
+                    context_state_account: context_account,
+                    context_state_authority: &payer,
+                },
+                &payer,
+            )
+        })
+        .collect()
+}
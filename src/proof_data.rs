@@ -0,0 +1,144 @@
+use anyhow::{Context, Result};
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use bytemuck::Pod;
+use serde::{Deserialize, Serialize};
+use spl_token_2022::solana_zk_sdk::zk_elgamal_proof_program::proof_data::{
+    BatchedGroupedCiphertext2HandlesValidityProofData, BatchedGroupedCiphertext3HandlesValidityProofData,
+    BatchedRangeProofU128Data, BatchedRangeProofU256Data, BatchedRangeProofU64Data,
+    CiphertextCiphertextEqualityProofData, CiphertextCommitmentEqualityProofData,
+    GroupedCiphertext2HandlesValidityProofData, GroupedCiphertext3HandlesValidityProofData,
+    PercentageWithCapProofData, PubkeyValidityProofData, ZeroCiphertextProofData, ZkProofData,
+};
+use std::path::Path;
+
+/// Identifies exported proof data files.
+pub const PROOF_DATA_FORMAT: &str = "confidential-proof-data";
+
+/// Bumped whenever a field is removed, renamed or changes meaning.
+pub const PROOF_DATA_VERSION: u32 = 1;
+
+/// The ZK ElGamal proof program's proof types, named as on the command line.
+#[derive(clap::ValueEnum, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProofKind {
+    PubkeyValidity,
+    ZeroCiphertext,
+    CiphertextCommitmentEquality,
+    CiphertextCiphertextEquality,
+    #[value(name = "batched-range-u64")]
+    #[serde(rename = "batched-range-u64")]
+    BatchedRangeU64,
+    #[value(name = "batched-range-u128")]
+    #[serde(rename = "batched-range-u128")]
+    BatchedRangeU128,
+    #[value(name = "batched-range-u256")]
+    #[serde(rename = "batched-range-u256")]
+    BatchedRangeU256,
+    #[value(name = "grouped-ciphertext-2-handles-validity")]
+    #[serde(rename = "grouped-ciphertext-2-handles-validity")]
+    GroupedCiphertext2HandlesValidity,
+    #[value(name = "grouped-ciphertext-3-handles-validity")]
+    #[serde(rename = "grouped-ciphertext-3-handles-validity")]
+    GroupedCiphertext3HandlesValidity,
+    #[value(name = "batched-grouped-ciphertext-2-handles-validity")]
+    #[serde(rename = "batched-grouped-ciphertext-2-handles-validity")]
+    BatchedGroupedCiphertext2HandlesValidity,
+    #[value(name = "batched-grouped-ciphertext-3-handles-validity")]
+    #[serde(rename = "batched-grouped-ciphertext-3-handles-validity")]
+    BatchedGroupedCiphertext3HandlesValidity,
+    PercentageWithCap,
+}
+
+impl ProofKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            ProofKind::PubkeyValidity => "pubkey-validity",
+            ProofKind::ZeroCiphertext => "zero-ciphertext",
+            ProofKind::CiphertextCommitmentEquality => "ciphertext-commitment-equality",
+            ProofKind::CiphertextCiphertextEquality => "ciphertext-ciphertext-equality",
+            ProofKind::BatchedRangeU64 => "batched-range-u64",
+            ProofKind::BatchedRangeU128 => "batched-range-u128",
+            ProofKind::BatchedRangeU256 => "batched-range-u256",
+            ProofKind::GroupedCiphertext2HandlesValidity => "grouped-ciphertext-2-handles-validity",
+            ProofKind::GroupedCiphertext3HandlesValidity => "grouped-ciphertext-3-handles-validity",
+            ProofKind::BatchedGroupedCiphertext2HandlesValidity => "batched-grouped-ciphertext-2-handles-validity",
+            ProofKind::BatchedGroupedCiphertext3HandlesValidity => "batched-grouped-ciphertext-3-handles-validity",
+            ProofKind::PercentageWithCap => "percentage-with-cap",
+        }
+    }
+
+    /// Verifies `bytes`, the proof data as the proof program receives it after the
+    /// instruction byte, locally with the same checks the program runs.
+    pub fn verify(self, bytes: &[u8]) -> Result<()> {
+        match self {
+            ProofKind::PubkeyValidity => verify_as::<PubkeyValidityProofData, _>(self, bytes),
+            ProofKind::ZeroCiphertext => verify_as::<ZeroCiphertextProofData, _>(self, bytes),
+            ProofKind::CiphertextCommitmentEquality => verify_as::<CiphertextCommitmentEqualityProofData, _>(self, bytes),
+            ProofKind::CiphertextCiphertextEquality => verify_as::<CiphertextCiphertextEqualityProofData, _>(self, bytes),
+            ProofKind::BatchedRangeU64 => verify_as::<BatchedRangeProofU64Data, _>(self, bytes),
+            ProofKind::BatchedRangeU128 => verify_as::<BatchedRangeProofU128Data, _>(self, bytes),
+            ProofKind::BatchedRangeU256 => verify_as::<BatchedRangeProofU256Data, _>(self, bytes),
+            ProofKind::GroupedCiphertext2HandlesValidity => verify_as::<GroupedCiphertext2HandlesValidityProofData, _>(self, bytes),
+            ProofKind::GroupedCiphertext3HandlesValidity => verify_as::<GroupedCiphertext3HandlesValidityProofData, _>(self, bytes),
+            ProofKind::BatchedGroupedCiphertext2HandlesValidity => {
+                verify_as::<BatchedGroupedCiphertext2HandlesValidityProofData, _>(self, bytes)
+            }
+            ProofKind::BatchedGroupedCiphertext3HandlesValidity => {
+                verify_as::<BatchedGroupedCiphertext3HandlesValidityProofData, _>(self, bytes)
+            }
+            ProofKind::PercentageWithCap => verify_as::<PercentageWithCapProofData, _>(self, bytes),
+        }
+    }
+}
+
+fn verify_as<T, U>(kind: ProofKind, bytes: &[u8]) -> Result<()>
+where
+    T: Pod + ZkProofData<U>,
+    U: Pod,
+{
+    let proof = bytemuck::try_pod_read_unaligned::<T>(bytes).map_err(|_| anyhow::anyhow!(
+        "Proof data is {} bytes; {} proof data is {}",
+        bytes.len(),
+        kind.name(),
+        std::mem::size_of::<T>()
+    ))?;
+    proof.verify_proof()
+        .map_err(|e| anyhow::anyhow!("The {} proof does not verify: {}", kind.name(), e))
+}
+
+/// One proof's data, exported so a counterparty can check it without the chain.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProofDataFile {
+    pub format: String,
+    pub version: u32,
+    pub kind: ProofKind,
+    /// The proof data, base64
+    pub proof: String,
+}
+
+impl ProofDataFile {
+    pub fn verify(&self) -> Result<()> {
+        let bytes = BASE64.decode(self.proof.trim()).context("Proof is not base64")?;
+        self.kind.verify(&bytes)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read proof data {}", path.display()))?;
+        let proof: Self = serde_json::from_str(&contents)
+            .with_context(|| format!("Invalid proof data {}", path.display()))?;
+
+        if proof.format != PROOF_DATA_FORMAT {
+            anyhow::bail!("{} is not an exported proof data file", path.display());
+        }
+        if proof.version > PROOF_DATA_VERSION {
+            anyhow::bail!(
+                "Proof data {} uses format version {}, this CLI understands up to {}",
+                path.display(),
+                proof.version,
+                PROOF_DATA_VERSION
+            );
+        }
+        Ok(proof)
+    }
+}
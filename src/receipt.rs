@@ -0,0 +1,132 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use spl_token_2022::extension::ExtensionType;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::{output::say, utils};
+
+/// Identifies receipt files, like `intent::INTENT_FORMAT` does for intents.
+pub const RECEIPT_FORMAT: &str = "confidential-creation-receipt";
+
+/// Bumped whenever a field is removed, renamed or changes meaning. The first receipts
+/// had neither `format` nor `version` and load as version 0.
+pub const RECEIPT_VERSION: u32 = 1;
+
+/// What a `create-mint` or `create-account --receipt` run created and paid for.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CreationReceipt {
+    #[serde(default = "receipt_format")]
+    pub format: String,
+    #[serde(default)]
+    pub version: u32,
+    pub kind: String,
+    pub address: String,
+    pub mint: String,
+    /// Mint authority for mints, account owner for token accounts
+    pub owner: String,
+    pub payer: String,
+    pub elgamal_pubkey: Option<String>,
+    pub auditor_elgamal_pubkey: Option<String>,
+    pub extensions: Vec<String>,
+    pub space: usize,
+    pub funding: Funding,
+    pub signature: String,
+    pub signatures: Vec<String>,
+    pub created_at: u64,
+}
+
+/// Lamports the payer spent, split by purpose.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Funding {
+    pub rent_lamports: u64,
+    pub transaction_fee_lamports: u64,
+    pub total_lamports: u64,
+}
+
+impl CreationReceipt {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        kind: &str,
+        address: &Pubkey,
+        mint: &Pubkey,
+        owner: &Pubkey,
+        payer: &Pubkey,
+        extensions: &[ExtensionType],
+        space: usize,
+        rent_lamports: u64,
+        transaction_fee_lamports: u64,
+        signatures: &[Signature],
+    ) -> Self {
+        Self {
+            format: RECEIPT_FORMAT.to_string(),
+            version: RECEIPT_VERSION,
+            kind: kind.to_string(),
+            address: address.to_string(),
+            mint: mint.to_string(),
+            owner: owner.to_string(),
+            payer: payer.to_string(),
+            elgamal_pubkey: None,
+            auditor_elgamal_pubkey: None,
+            extensions: extensions.iter().map(|extension| format!("{:?}", extension)).collect(),
+            space,
+            funding: Funding {
+                rent_lamports,
+                transaction_fee_lamports,
+                total_lamports: rent_lamports + transaction_fee_lamports,
+            },
+            signature: signatures.last().map(|signature| signature.to_string()).unwrap_or_default(),
+            signatures: signatures.iter().map(|signature| signature.to_string()).collect(),
+            created_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_secs())
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn elgamal_pubkey(mut self, pubkey: impl ToString) -> Self {
+        self.elgamal_pubkey = Some(pubkey.to_string());
+        self
+    }
+
+    pub fn auditor_elgamal_pubkey(mut self, pubkey: Option<impl ToString>) -> Self {
+        self.auditor_elgamal_pubkey = pubkey.map(|pubkey| pubkey.to_string());
+        self
+    }
+
+    /// Reads a receipt, upgrading older versions in memory; `migrate` writes them back.
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read receipt {}", path.display()))?;
+        let mut receipt: Self = serde_json::from_str(&contents)
+            .with_context(|| format!("Invalid receipt file {}", path.display()))?;
+
+        if receipt.format != RECEIPT_FORMAT {
+            anyhow::bail!("{} is not a creation receipt", path.display());
+        }
+        if receipt.version > RECEIPT_VERSION {
+            anyhow::bail!(
+                "Receipt {} uses format version {}, this CLI understands up to {}",
+                path.display(),
+                receipt.version,
+                RECEIPT_VERSION
+            );
+        }
+
+        // Version 0 only lacked the format and version fields themselves
+        receipt.version = RECEIPT_VERSION;
+        Ok(receipt)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let contents = serde_json::to_string_pretty(self)?;
+        utils::write_atomic(path, contents)
+            .with_context(|| format!("Failed to write receipt {}", path.display()))?;
+        say!("🧾 Receipt written to {}", path.display());
+        Ok(())
+    }
+}
+
+fn receipt_format() -> String {
+    RECEIPT_FORMAT.to_string()
+}
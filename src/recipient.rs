@@ -0,0 +1,107 @@
+use anyhow::Result;
+use solana_sdk::pubkey::Pubkey;
+use spl_token_2022::{
+    extension::{confidential_transfer::ConfidentialTransferAccount, BaseStateWithExtensions, StateWithExtensions},
+    solana_zk_sdk::encryption::pod::elgamal::PodElGamalPubkey,
+};
+use crate::{config::AppConfig, elgamal_registry, output::say, rpc};
+
+/// Resolves a transfer `--to` that may be a wallet rather than a token account.
+///
+/// A token account is returned as is. For a wallet, its confidential accounts for `mint`
+/// are listed, narrowed to the ElGamal key it registered when it has one, and the
+/// account that can receive is returned: the associated token account when it is among
+/// them, otherwise the only one. Errors say what the recipient has to do.
+pub async fn resolve(config: &AppConfig, to: &Pubkey, mint: &Pubkey) -> Result<Pubkey> {
+    let accounts = config.rpc_client.get_multiple_accounts(&[*to, elgamal_registry::address(to)]).await?;
+    if accounts[0].as_ref().is_some_and(|account| account.owner == spl_token_2022::id()) {
+        return Ok(*to);
+    }
+    let registered = accounts[1].as_ref()
+        .and_then(elgamal_registry::from_account)
+        .map(|registry| registry.elgamal_pubkey);
+
+    let candidates = confidential_accounts(config, to, mint).await?;
+    let candidates: Vec<_> = match &registered {
+        Some(elgamal_pubkey) => candidates.into_iter().filter(|candidate| candidate.elgamal_pubkey == *elgamal_pubkey).collect(),
+        None => candidates,
+    };
+    if candidates.is_empty() {
+        match registered {
+            Some(elgamal_pubkey) => anyhow::bail!(
+                "{} registered ElGamal key {}, but has no confidential account for mint {} under it; \
+                 ask them to run 'create-account --mint {}'",
+                to, elgamal_pubkey, mint, mint
+            ),
+            None => anyhow::bail!(
+                "{} is neither a token account nor a wallet with a confidential account for mint {}; \
+                 ask the recipient to run 'create-account --mint {}', or for their account address",
+                to, mint, mint
+            ),
+        }
+    }
+
+    let associated = spl_associated_token_account_client::address::get_associated_token_address_with_program_id(
+        to,
+        mint,
+        &spl_token_2022::id(),
+    );
+    let candidate = match candidates.iter().find(|candidate| candidate.address == associated) {
+        Some(candidate) => candidate,
+        None if candidates.len() == 1 => &candidates[0],
+        None => anyhow::bail!(
+            "{} has {} confidential accounts for mint {}, so pick one for --to: {}",
+            to,
+            candidates.len(),
+            mint,
+            candidates.iter().map(|candidate| candidate.address.to_string()).collect::<Vec<_>>().join(", ")
+        ),
+    };
+    if !candidate.approved {
+        anyhow::bail!(
+            "{}'s account {} is not approved yet; the mint authority has to run 'approve-account --account {}'",
+            to, candidate.address, candidate.address
+        );
+    }
+    if !candidate.allows_confidential_credits {
+        anyhow::bail!(
+            "{}'s account {} does not accept confidential transfers; its owner can run 'enable-confidential-credits --account {}'",
+            to, candidate.address, candidate.address
+        );
+    }
+
+    say!("📇 {} resolved to its confidential account {}{}", to, candidate.address,
+        if registered.is_some() { " through the ElGamal registry" } else { "" });
+    Ok(candidate.address)
+}
+
+struct Candidate {
+    address: Pubkey,
+    elgamal_pubkey: PodElGamalPubkey,
+    approved: bool,
+    allows_confidential_credits: bool,
+}
+
+// The wallet's accounts for `mint` that are configured for confidential transfers
+async fn confidential_accounts(config: &AppConfig, wallet: &Pubkey, mint: &Pubkey) -> Result<Vec<Candidate>> {
+    let mut candidates = Vec::new();
+    for (address, account) in rpc::get_token_accounts_by_owner(&config.scan_client, wallet).await? {
+        let Ok(token_account) = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account.data) else {
+            continue;
+        };
+        if token_account.base.mint != *mint {
+            continue;
+        }
+        if let Ok(ct_account) = token_account.get_extension::<ConfidentialTransferAccount>() {
+            candidates.push(Candidate {
+                address,
+                elgamal_pubkey: ct_account.elgamal_pubkey,
+                approved: bool::from(ct_account.approved),
+                allows_confidential_credits: bool::from(ct_account.allow_confidential_credits),
+            });
+        }
+    }
+    // The listing comes back in no particular order
+    candidates.sort_by_key(|candidate| candidate.address);
+    Ok(candidates)
+}
@@ -0,0 +1,128 @@
+use anyhow::{Context, Result};
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use serde_json::{json, Value};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Signature, Signer},
+    signer::SignerError,
+};
+use std::{future::Future, str::FromStr, time::Duration};
+use crate::config;
+
+// Long enough for a service that waits on a human approval
+const SIGN_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// A signer whose key lives in an HTTP signing service; the CLI only sends it messages.
+///
+/// The service answers `GET <url>/pubkey` with `{"pubkey": "<base58>"}`, and
+/// `POST <url>/sign` with body `{"pubkey": "<base58>", "message": "<base64>"}` with
+/// `{"signature": "<base58>"}`. `CONFIDENTIAL_CLI_REMOTE_SIGNER_TOKEN`, when set, is sent
+/// as a bearer token. Only `https://` services are accepted, apart from `http://` on a
+/// loopback address with `--allow-insecure-signer`.
+pub struct RemoteSigner {
+    url: String,
+    pubkey: Pubkey,
+    client: reqwest::Client,
+}
+
+impl RemoteSigner {
+    pub fn connect(url: &str) -> Result<Self> {
+        check_transport(url)?;
+        let url = url.trim_end_matches('/').to_string();
+        let client = reqwest::Client::builder().timeout(SIGN_TIMEOUT).build()?;
+        let response: Value = block_on(request(client.get(format!("{}/pubkey", url))))
+            .with_context(|| format!("Failed to get the public key from remote signer {}", url))?;
+        let pubkey = response["pubkey"].as_str()
+            .and_then(|pubkey| Pubkey::from_str(pubkey).ok())
+            .ok_or_else(|| anyhow::anyhow!("Remote signer {} returned no valid \"pubkey\"", url))?;
+        Ok(Self { url, pubkey, client })
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Signature> {
+        let body = json!({
+            "pubkey": self.pubkey.to_string(),
+            "message": BASE64.encode(message),
+        });
+        let response = block_on(request(self.client.post(format!("{}/sign", self.url)).json(&body)))?;
+        let signature = response["signature"].as_str()
+            .and_then(|signature| Signature::from_str(signature).ok())
+            .ok_or_else(|| anyhow::anyhow!("the response has no valid \"signature\""))?;
+        // A service signing with another key would only fail later, on chain
+        if !signature.verify(self.pubkey.as_ref(), message) {
+            anyhow::bail!("the signature does not verify against {}", self.pubkey);
+        }
+        Ok(signature)
+    }
+}
+
+impl Signer for RemoteSigner {
+    fn try_pubkey(&self) -> Result<Pubkey, SignerError> {
+        Ok(self.pubkey)
+    }
+
+    fn try_sign_message(&self, message: &[u8]) -> Result<Signature, SignerError> {
+        self.sign(message)
+            .map_err(|e| SignerError::Custom(format!("Remote signer {}: {:#}", self.url, e)))
+    }
+
+    fn is_interactive(&self) -> bool {
+        false
+    }
+}
+
+// Plain http would carry the bearer token and every message in cleartext, and let anyone
+// on the path answer /pubkey with their own key
+fn check_transport(url: &str) -> Result<()> {
+    let parsed = reqwest::Url::parse(url).with_context(|| format!("Invalid remote signer URL {}", url))?;
+    match parsed.scheme() {
+        "https" => Ok(()),
+        "http" => {
+            let host = parsed.host_str().unwrap_or_default().trim_start_matches('[').trim_end_matches(']');
+            let loopback = host == "localhost"
+                || host.parse::<std::net::IpAddr>().is_ok_and(|ip| ip.is_loopback());
+            if !loopback {
+                anyhow::bail!("Remote signer {} must use https://", url);
+            }
+            if !config::global_options().allow_insecure_signer {
+                anyhow::bail!("Remote signer {} is plain http; pass --allow-insecure-signer to use it for local testing", url);
+            }
+            Ok(())
+        }
+        scheme => anyhow::bail!("Unsupported remote signer scheme {}://", scheme),
+    }
+}
+
+async fn request(request: reqwest::RequestBuilder) -> Result<Value> {
+    let request = match std::env::var("CONFIDENTIAL_CLI_REMOTE_SIGNER_TOKEN") {
+        Ok(token) => request.bearer_auth(token),
+        Err(_) => request,
+    };
+    request.send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .context("Request failed")?
+        .json()
+        .await
+        .context("Response is not JSON")
+}
+
+/// `Signer` is synchronous, but is only ever called from within the runtime.
+fn block_on<T>(future: impl Future<Output = Result<T>>) -> Result<T> {
+    let handle = tokio::runtime::Handle::try_current()
+        .context("Remote signers can only be used from within the runtime")?;
+    tokio::task::block_in_place(|| handle.block_on(future))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_https_is_accepted_by_default() {
+        assert!(check_transport("https://signer.example.com").is_ok());
+        assert!(check_transport("http://signer.example.com").is_err());
+        assert!(check_transport("http://localhost:8080").is_err());
+        assert!(check_transport("http://127.0.0.1:8080").is_err());
+        assert!(check_transport("ftp://signer.example.com").is_err());
+    }
+}
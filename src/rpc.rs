@@ -0,0 +1,311 @@
+use anyhow::{Context, Result};
+use solana_account_decoder_client_types::UiAccountEncoding;
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind},
+    nonblocking::rpc_client::RpcClient,
+    rpc_config::RpcAccountInfoConfig,
+    rpc_request::{RpcError, RpcRequest},
+    rpc_response::{Response, RpcKeyedAccount},
+};
+use serde_json::json;
+use solana_sdk::{account::Account, commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature, transaction::Transaction};
+use spl_token_2022::{
+    extension::{confidential_transfer::ConfidentialTransferAccount, BaseStateWithExtensions, StateWithExtensions},
+    solana_zk_sdk::encryption::pod::elgamal::PodElGamalCiphertext,
+};
+use std::{fmt, future::Future, str::FromStr, time::{Duration, Instant}};
+use crate::{config::{self, AppConfig}, output::say, trace};
+
+const IDEMPOTENT_ATTEMPTS: u32 = 3;
+const STALE_BALANCE_ATTEMPTS: u32 = 3;
+
+// About eight seconds of slots; a read endpoint further behind is likely a stale cache
+const MAX_READ_LAG_SLOTS: u64 = 20;
+const SEND_SYNC_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Runs a step that can safely run twice, retrying transient failures.
+///
+/// Only reads and proofs verified into a fresh context account qualify. Anything that
+/// moves balances (deposit, transfer, apply, withdraw) goes through `send_once` instead,
+/// because a retry after a send that actually landed would apply it twice.
+pub async fn retry_idempotent<T, F, Fut>(label: &str, mut step: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut span = trace::span(label);
+    let mut attempt = 1;
+    loop {
+        trace::attr(&mut span, "attempt", attempt);
+        match step().await {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt < IDEMPOTENT_ATTEMPTS => {
+                say!("  ⚠️  {} failed (attempt {}/{}): {:#}", label, attempt, IDEMPOTENT_ATTEMPTS, e);
+                tokio::time::sleep(Duration::from_millis(500 << attempt)).await;
+                attempt += 1;
+            }
+            Err(e) => return trace::record(&mut span, Err(e)),
+        }
+    }
+}
+
+/// Returned when a non-idempotent transaction was sent but its outcome is unknown.
+#[derive(Debug)]
+pub struct AmbiguousSend {
+    pub signature: Signature,
+    pub reason: String,
+}
+
+impl fmt::Display for AmbiguousSend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Transaction {} may or may not have landed: {}", self.signature, self.reason)?;
+        writeln!(f, "  It was not resent, since sending it again could apply it twice")?;
+        write!(f, "  Check it with 'solana confirm {}' before retrying the command", self.signature)
+    }
+}
+
+impl std::error::Error for AmbiguousSend {}
+
+/// Returned, before sending, when an account's available balance changed after its proofs
+/// were built, e.g. because another operator spent from it; the proofs would not verify.
+#[derive(Debug)]
+pub struct StaleBalance {
+    pub account: Pubkey,
+}
+
+impl fmt::Display for StaleBalance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "The available balance of {} changed after the proofs were built", self.account)?;
+        write!(f, " (is another machine using this account?)")
+    }
+}
+
+impl std::error::Error for StaleBalance {}
+
+/// Re-reads `account` and fails with `StaleBalance` unless its available balance is still
+/// `proven`, the ciphertext the proofs were built against.
+pub async fn ensure_balance_unchanged(config: &AppConfig, account: &Pubkey, proven: &PodElGamalCiphertext) -> Result<()> {
+    let account_data = config.rpc_client.get_account(account).await?;
+    let token_account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data.data)?;
+    let ct_account = token_account.get_extension::<ConfidentialTransferAccount>()?;
+    if ct_account.available_balance != *proven {
+        return Err(StaleBalance { account: *account }.into());
+    }
+    Ok(())
+}
+
+/// Runs `attempt` again when it stopped on a `StaleBalance`. Nothing was sent by then, so
+/// reading the account afresh and rebuilding the proofs is always safe.
+pub async fn rebuild_on_stale_balance<T, F, Fut>(mut attempt: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut attempts = 1;
+    loop {
+        match attempt().await {
+            Err(e) if attempts < STALE_BALANCE_ATTEMPTS && e.downcast_ref::<StaleBalance>().is_some() => {
+                attempts += 1;
+                say!("\n♻️  {:#}", e);
+                say!("   Rebuilding the proofs against the new balance (attempt {}/{})\n", attempts, STALE_BALANCE_ATTEMPTS);
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Sends a transaction that must not land twice, exactly once.
+///
+/// A confirmation failure is resolved through the signature status: a transaction that
+/// landed is returned as sent, one that was rejected (including by preflight) is an
+/// error, and one whose status is unknown becomes an `AmbiguousSend`.
+pub async fn send_once(config: &AppConfig, transaction: &Transaction) -> Result<Signature> {
+    sync_endpoints(config).await?;
+    dump_instructions(transaction);
+    let signature = transaction.signatures[0];
+    let mut span = trace::span("send_and_confirm");
+    trace::attr(&mut span, "signature", signature);
+    let result = send_and_resolve(&config.send_client, transaction, signature).await;
+    trace::record(&mut span, result)
+}
+
+/// Checks, before a send with `--send-url`, that the two endpoints agree closely enough
+/// for the transaction to execute against the state its proofs were built from.
+///
+/// The read endpoint may trail the send endpoint by at most `MAX_READ_LAG_SLOTS`, or the
+/// balances just read could already be stale; a send endpoint that trails the read one
+/// is given `SEND_SYNC_TIMEOUT` to process the read slot, which then acts as the send's
+/// minimum context slot. Without `--send-url` both are the same node and this is a no-op.
+pub async fn sync_endpoints(config: &AppConfig) -> Result<()> {
+    if config::global_options().send_url.is_none() {
+        return Ok(());
+    }
+    let processed = CommitmentConfig::processed();
+    let read_slot = config.rpc_client.get_slot_with_commitment(processed).await
+        .context("Failed to get the read endpoint's slot")?;
+    let mut send_slot = config.send_client.get_slot_with_commitment(processed).await
+        .context("Failed to get the send endpoint's slot")?;
+
+    if read_slot + MAX_READ_LAG_SLOTS < send_slot {
+        anyhow::bail!(
+            "The read endpoint is {} slots behind the send endpoint, so the balances proofs were built from may be stale; \
+             use a fresher read endpoint or drop --send-url",
+            send_slot - read_slot
+        );
+    }
+
+    let started = Instant::now();
+    if send_slot < read_slot {
+        say!("  ⏳ Waiting for the send endpoint to reach slot {} (at {})", read_slot, send_slot);
+    }
+    while send_slot < read_slot {
+        if started.elapsed() > SEND_SYNC_TIMEOUT {
+            anyhow::bail!(
+                "The send endpoint is still {} slots behind the read endpoint after {}s; \
+                 it would execute the transaction against older state than was read",
+                read_slot - send_slot,
+                SEND_SYNC_TIMEOUT.as_secs()
+            );
+        }
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        send_slot = config.send_client.get_slot_with_commitment(processed).await?;
+    }
+    Ok(())
+}
+
+/// Prints every instruction of `transaction` as sent on the wire when `--dump-instructions`
+/// is set, so other implementations can be byte-compared against it.
+pub fn dump_instructions(transaction: &Transaction) {
+    if !config::global_options().dump_instructions {
+        return;
+    }
+    let message = &transaction.message;
+    for (index, instruction) in message.instructions.iter().enumerate() {
+        say!("\n🔬 Instruction {}/{}", index + 1, message.instructions.len());
+        say!("  Program: {}", message.account_keys[instruction.program_id_index as usize]);
+        say!("  Accounts:");
+        for (position, account_index) in instruction.accounts.iter().enumerate() {
+            let account_index = *account_index as usize;
+            let flags = match (message.is_signer(account_index), message.is_maybe_writable(account_index, None)) {
+                (true, true) => "signer, writable",
+                (true, false) => "signer",
+                (false, true) => "writable",
+                (false, false) => "readonly",
+            };
+            say!("    {:>2}. {} [{}]", position, message.account_keys[account_index], flags);
+        }
+        let data: String = instruction.data.iter().map(|byte| format!("{:02x}", byte)).collect();
+        say!("  Data ({} bytes): {}", instruction.data.len(), data);
+    }
+}
+
+async fn send_and_resolve(rpc_client: &RpcClient, transaction: &Transaction, signature: Signature) -> Result<Signature> {
+    let error = match rpc_client.send_and_confirm_transaction(transaction).await {
+        Ok(signature) => return Ok(signature),
+        Err(e) => e,
+    };
+    if error.get_transaction_error().is_some() {
+        return Err(error.into());
+    }
+
+    match rpc_client.get_signature_status(&signature).await {
+        Ok(Some(Ok(()))) => Ok(signature),
+        Ok(Some(Err(transaction_error))) => {
+            Err(anyhow::anyhow!("Transaction {} failed: {}", signature, transaction_error))
+        }
+        Ok(None) => Err(AmbiguousSend { signature, reason: error.to_string() }.into()),
+        Err(status_error) => Err(AmbiguousSend {
+            signature,
+            reason: format!("{} (status lookup failed: {})", error, status_error),
+        }.into()),
+    }
+}
+
+/// True when the endpoint refused the method itself, as providers that disable
+/// scanning methods do, rather than failing the particular request.
+fn method_unavailable(error: &ClientError) -> bool {
+    match error.kind() {
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. }) => *code == -32601,
+        ClientErrorKind::Reqwest(e) => e.status().is_some_and(|status| status.as_u16() == 403 || status.as_u16() == 405),
+        _ => false,
+    }
+}
+
+/// Points at `--scan-url` when a scan or history lookup fails because the endpoint
+/// does not serve `method`.
+pub fn with_scan_hint<T>(result: Result<T>, method: &str) -> Result<T> {
+    result.map_err(|e| {
+        let unavailable = e.chain()
+            .filter_map(|cause| cause.downcast_ref::<ClientError>())
+            .any(method_unavailable);
+        if unavailable {
+            e.context(format!("The RPC endpoint does not serve {}; pass --scan-url with one that does", method))
+        } else {
+            e
+        }
+    })
+}
+
+/// Fetches an account together with the slot the RPC node served it at.
+///
+/// RPC nodes only serve the latest state, so there is no way to read an account as it
+/// was at an earlier slot; `balance --snapshot` records one for later comparison.
+pub async fn get_account_with_slot(rpc_client: &RpcClient, pubkey: &Pubkey) -> Result<(Account, u64)> {
+    let config = RpcAccountInfoConfig {
+        encoding: Some(UiAccountEncoding::Base64),
+        commitment: Some(rpc_client.commitment()),
+        min_context_slot: None,
+        data_slice: None,
+    };
+
+    let response = retry_idempotent("Account fetch", || async {
+        rpc_client
+            .get_account_with_config(pubkey, config.clone())
+            .await
+            .with_context(|| format!("Failed to fetch account {}", pubkey))
+    }).await?;
+
+    let account = response
+        .value
+        .ok_or_else(|| anyhow::anyhow!("Account {} not found", pubkey))?;
+
+    Ok((account, response.context.slot))
+}
+
+/// Lists every Token-2022 account owned by `owner`, with raw account data.
+///
+/// `RpcClient::get_token_accounts_by_owner` requests `jsonParsed`, which cannot be
+/// unpacked into extension state, so this issues the request with base64 encoding.
+pub async fn get_token_accounts_by_owner(
+    rpc_client: &RpcClient,
+    owner: &Pubkey,
+) -> Result<Vec<(Pubkey, Account)>> {
+    let config = RpcAccountInfoConfig {
+        encoding: Some(UiAccountEncoding::Base64),
+        commitment: Some(rpc_client.commitment()),
+        min_context_slot: None,
+        data_slice: None,
+    };
+    let filter = json!({ "programId": spl_token_2022::id().to_string() });
+
+    let params = json!([owner.to_string(), filter, config]);
+    let response: Response<Vec<RpcKeyedAccount>> = with_scan_hint(retry_idempotent("Token account listing", || async {
+        rpc_client
+            .send(RpcRequest::GetTokenAccountsByOwner, params.clone())
+            .await
+            .with_context(|| format!("Failed to list token accounts owned by {}", owner))
+    }).await, "getTokenAccountsByOwner")?;
+
+    response
+        .value
+        .into_iter()
+        .map(|keyed| {
+            let pubkey = Pubkey::from_str(&keyed.pubkey)?;
+            let account = keyed
+                .account
+                .decode::<Account>()
+                .ok_or_else(|| anyhow::anyhow!("Failed to decode account {}", pubkey))?;
+            Ok((pubkey, account))
+        })
+        .collect()
+}
@@ -0,0 +1,127 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use crate::utils;
+
+/// Identifies schedule files, like `intent::INTENT_FORMAT` does for intents.
+pub const SCHEDULE_FORMAT: &str = "confidential-transfer-schedule";
+
+/// Bumped whenever a field is removed, renamed or changes meaning.
+pub const SCHEDULE_VERSION: u32 = 1;
+
+/// Transfers queued with `--execute-at`, as sent by `run-due`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TransferSchedule {
+    pub format: String,
+    pub version: u32,
+    pub transfers: Vec<ScheduledTransfer>,
+}
+
+/// Which command sends a scheduled transfer.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum TransferKind {
+    Transfer,
+    ConfidentialTransfer,
+}
+
+impl TransferKind {
+    pub fn command(self) -> &'static str {
+        match self {
+            TransferKind::Transfer => "transfer",
+            TransferKind::ConfidentialTransfer => "confidential-transfer",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Status {
+    Scheduled,
+    /// Saved just before sending, so a crash mid-send is never sent twice
+    Executing,
+    Executed,
+    Failed,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ScheduledTransfer {
+    pub id: u64,
+    pub kind: TransferKind,
+    pub from: String,
+    pub to: String,
+    pub mint: String,
+    /// In base units; `decimals` turns it back into the amount the command takes
+    pub amount: u64,
+    pub decimals: u8,
+    pub auto_split: bool,
+    /// `--owner` as given (a keypair path or URL, never the key itself)
+    #[serde(default)]
+    pub owner: Option<String>,
+    /// RFC3339, in UTC
+    pub execute_at: String,
+    pub created_at: String,
+    pub status: Status,
+    pub executed_at: Option<String>,
+    pub signatures: Vec<String>,
+    pub error: Option<String>,
+}
+
+impl ScheduledTransfer {
+    pub fn execute_at(&self) -> Result<DateTime<Utc>> {
+        DateTime::parse_from_rfc3339(&self.execute_at)
+            .map(|time| time.with_timezone(&Utc))
+            .with_context(|| format!("Scheduled transfer {} has an invalid time: {}", self.id, self.execute_at))
+    }
+
+    pub fn ui_amount(&self) -> String {
+        utils::format_amount(self.amount, self.decimals)
+    }
+}
+
+impl Default for TransferSchedule {
+    fn default() -> Self {
+        Self {
+            format: SCHEDULE_FORMAT.to_string(),
+            version: SCHEDULE_VERSION,
+            transfers: Vec::new(),
+        }
+    }
+}
+
+impl TransferSchedule {
+    /// Reads the schedule at `path`; a missing file is an empty schedule.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read schedule {}", path.display()))?;
+        let schedule: Self = serde_json::from_str(&contents)
+            .with_context(|| format!("Invalid schedule file {}", path.display()))?;
+
+        if schedule.format != SCHEDULE_FORMAT {
+            anyhow::bail!("{} is not a transfer schedule", path.display());
+        }
+        if schedule.version > SCHEDULE_VERSION {
+            anyhow::bail!(
+                "Schedule {} uses format version {}, this CLI understands up to {}",
+                path.display(),
+                schedule.version,
+                SCHEDULE_VERSION
+            );
+        }
+        Ok(schedule)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let contents = serde_json::to_string_pretty(self)?;
+        utils::write_atomic(path, contents)
+            .with_context(|| format!("Failed to write schedule {}", path.display()))
+    }
+
+    pub fn next_id(&self) -> u64 {
+        self.transfers.iter().map(|transfer| transfer.id).max().unwrap_or(0) + 1
+    }
+}
@@ -0,0 +1,31 @@
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use spl_token_2022::solana_zk_sdk::encryption::{auth_encryption::AeKey, elgamal::ElGamalKeypair};
+use zeroize::Zeroizing;
+
+// `ElGamalSecretKey` and `Keypair` wipe themselves when dropped. Everything else that
+// holds key material, including the copies this CLI makes of those two, goes through
+// the types here so it is wiped too.
+
+/// An AE key that is wiped when dropped, which `AeKey` alone is not.
+pub type AesKey = Zeroizing<AeKey>;
+
+/// Raw key bytes, e.g. read from a file or decrypted.
+pub type SecretBytes = Zeroizing<Vec<u8>>;
+
+/// Key material in text form: base64, JSON byte arrays, passphrases.
+pub type SecretString = Zeroizing<String>;
+
+pub fn encode_elgamal_keypair(keypair: &ElGamalKeypair) -> SecretString {
+    let bytes = Zeroizing::new(<[u8; 64]>::from(keypair));
+    Zeroizing::new(BASE64.encode(bytes.as_slice()))
+}
+
+pub fn encode_aes_key(key: &AeKey) -> SecretString {
+    let bytes = Zeroizing::new(<[u8; 16]>::from(key.clone()));
+    Zeroizing::new(BASE64.encode(bytes.as_slice()))
+}
+
+/// Decodes base64 key material without leaving a plain copy behind.
+pub fn decode_base64(encoded: &str) -> Result<SecretBytes, base64::DecodeError> {
+    BASE64.decode(encoded).map(Zeroizing::new)
+}
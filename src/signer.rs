@@ -0,0 +1,188 @@
+use anyhow::{Context, Result};
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use solana_clap_utils::keypair::{keypair_from_seed_phrase, ASK_KEYWORD};
+use solana_derivation_path::DerivationPath;
+#[cfg(feature = "ledger")]
+use solana_remote_wallet::{
+    locator::Locator,
+    remote_keypair::generate_remote_keypair,
+    remote_wallet::maybe_wallet_manager,
+};
+use solana_sdk::signature::{Keypair, Signer, read_keypair};
+use std::path::Path;
+use crate::{config, keypair_file, secrets::{SecretBytes, SecretString}, utils};
+#[cfg(feature = "kms")]
+use crate::remote_signer::RemoteSigner;
+
+/// Resolves a signer from any supported source, for any role (`--keypair`, `--owner`, ...):
+///
+/// - `path/to/keypair.json` or `file://path/to/keypair.json`, plain or passphrase-encrypted
+///   by `keygen` (the passphrase is prompted for, or read from `CONFIDENTIAL_CLI_KEYPAIR_PASSPHRASE`)
+/// - `usb://ledger[/<PUBKEY>][?key=<ACCOUNT>[/<CHANGE>]]`
+/// - `prompt://[?key=<ACCOUNT>[/<CHANGE>] | ?full-path=<PATH>]` (BIP39 seed phrase
+///   and optional passphrase)
+/// - `ASK` (seed phrase turned into a keypair without a derivation path, as older
+///   Solana CLIs did)
+/// - `stdin://` or `-` (JSON keypair bytes)
+/// - `[1,2,...]` (the JSON keypair bytes themselves, e.g. held in `CONFIDENTIAL_CLI_KEYPAIR`)
+/// - `env://<VAR>` (JSON keypair bytes, or the secret key in base58 or base64)
+/// - `https://<SIGNING SERVICE>` (a remote signer holding the key; see `RemoteSigner`)
+///
+/// `prompt://` and `usb://` without a query use `--derivation-path` when it is given.
+///
+/// `role` is the flag name without dashes; it is only used in prompts and error messages.
+pub fn resolve_signer(source: &str, role: &str) -> Result<Box<dyn Signer>> {
+    if source == ASK_KEYWORD {
+        if config::global_options().derivation_path.is_some() {
+            anyhow::bail!("--{} ASK derives without a path; use prompt:// with --derivation-path", role);
+        }
+        return Ok(Box::new(
+            keypair_from_seed_phrase(role, false, false, None, true)
+                .map_err(|e| anyhow::anyhow!("Failed to derive --{} from seed phrase: {}", role, e))?,
+        ));
+    }
+    if source == "-" {
+        return resolve_signer("stdin://", role);
+    }
+    if source.trim_start().starts_with('[') {
+        let bytes = SecretBytes::new(serde_json::from_str(source)
+            .with_context(|| format!("--{} is not a JSON keypair", role))?);
+        return Ok(Box::new(Keypair::try_from(bytes.as_slice())
+            .map_err(|e| anyhow::anyhow!("Invalid keypair bytes for --{}: {}", role, e))?));
+    }
+    let (scheme, rest) = source.split_once("://").unwrap_or(("file", source));
+
+    match scheme {
+        "file" => {
+            let contents = SecretString::new(std::fs::read_to_string(rest)
+                .with_context(|| format!("Failed to read --{} from {}", role, rest))?);
+            if keypair_file::is_encrypted(&contents) {
+                return Ok(Box::new(keypair_file::read(Path::new(rest), &contents, role)?));
+            }
+            Ok(Box::new(read_keypair(&mut contents.as_bytes()).map_err(|e| {
+                anyhow::anyhow!("Failed to read --{} from {}: {}", role, rest, e)
+            })?))
+        }
+        "stdin" => {
+            let contents = SecretString::new(utils::read_stdin(&format!("--{}", role))?);
+            Ok(Box::new(read_keypair(&mut contents.as_bytes()).map_err(|e| {
+                anyhow::anyhow!("Failed to read --{} from stdin: {}", role, e)
+            })?))
+        }
+        "env" => Ok(Box::new(keypair_from_env(rest, role)?)),
+        "prompt" => {
+            let derivation_path = parse_derivation_path(rest)?;
+            Ok(Box::new(
+                keypair_from_seed_phrase(role, false, false, derivation_path, false)
+                    .map_err(|e| anyhow::anyhow!("Failed to derive --{} from seed phrase: {}", role, e))?,
+            ))
+        }
+        #[cfg(feature = "kms")]
+        "http" | "https" => Ok(Box::new(RemoteSigner::connect(source)
+            .with_context(|| format!("Failed to load --{} signer", role))?)),
+        #[cfg(not(feature = "kms"))]
+        "http" | "https" => anyhow::bail!("--{} {} needs a build with the `kms` feature", role, source),
+        #[cfg(feature = "ledger")]
+        "usb" => {
+            let locator = Locator::new_from_path(source)
+                .map_err(|e| anyhow::anyhow!("Invalid hardware wallet URL {}: {}", source, e))?;
+            let derivation_path = parse_derivation_path(rest)?.unwrap_or_default();
+            let wallet_manager = maybe_wallet_manager()
+                .map_err(|e| anyhow::anyhow!("Failed to connect to hardware wallet: {}", e))?
+                .ok_or_else(|| anyhow::anyhow!("No hardware wallet found for {}", source))?;
+            Ok(Box::new(
+                generate_remote_keypair(locator, derivation_path, &wallet_manager, false, role)
+                    .map_err(|e| anyhow::anyhow!("Failed to load --{} signer from {}: {}", role, source, e))?,
+            ))
+        }
+        #[cfg(not(feature = "ledger"))]
+        "usb" => anyhow::bail!("--{} {} needs a build with the `ledger` feature", role, source),
+        _ => anyhow::bail!(
+            "Unsupported signer source '{}' for --{} (expected a file path, usb://, prompt://, ASK, stdin://, -, env:// or https://)",
+            source,
+            role
+        ),
+    }
+}
+
+fn keypair_from_env(var: &str, role: &str) -> Result<Keypair> {
+    let value = SecretString::new(std::env::var(var)
+        .with_context(|| format!("Environment variable {} for --{} is not set", var, role))?);
+    let value = value.trim();
+
+    if value.starts_with('[') {
+        let bytes = SecretBytes::new(serde_json::from_str(value)
+            .with_context(|| format!("{} does not contain a JSON keypair", var))?);
+        Keypair::try_from(bytes.as_slice())
+            .map_err(|e| anyhow::anyhow!("Invalid keypair bytes in {}: {}", var, e))
+    } else {
+        // A base64 secret key can happen to be valid base58 too, so take whichever yields a keypair
+        let base58 = bs58::decode(value).into_vec().ok();
+        let base64 = BASE64.decode(value).ok();
+        if base58.is_none() && base64.is_none() {
+            anyhow::bail!("{} is neither a JSON keypair nor a base58 or base64 secret key", var);
+        }
+        base58.into_iter()
+            .chain(base64)
+            .find_map(|bytes| Keypair::try_from(bytes.as_slice()).ok())
+            .ok_or_else(|| anyhow::anyhow!("Invalid keypair bytes in {}", var))
+    }
+}
+
+/// Reads raw key bytes for `--<role>` from `env://<VAR>` or a file, never writing them anywhere.
+///
+/// The value may be a JSON byte array (as written by `solana-zk-keygen`) or base64.
+pub fn read_key_bytes(source: &str, role: &str) -> Result<SecretBytes> {
+    let value = SecretString::new(match source.strip_prefix("env://") {
+        Some(var) => std::env::var(var)
+            .with_context(|| format!("Environment variable {} for --{} is not set", var, role))?,
+        None => std::fs::read_to_string(source.strip_prefix("file://").unwrap_or(source))
+            .with_context(|| format!("Failed to read --{} from {}", role, source))?,
+    });
+    let value = value.trim();
+
+    if value.starts_with('[') {
+        serde_json::from_str(value)
+            .map(SecretBytes::new)
+            .with_context(|| format!("--{} does not contain a JSON byte array", role))
+    } else {
+        BASE64.decode(value)
+            .map(SecretBytes::new)
+            .with_context(|| format!("--{} is neither a JSON byte array nor base64", role))
+    }
+}
+
+// Accepts the `?key=<ACCOUNT>/<CHANGE>` and `?full-path=<PATH>` queries used by the Solana CLI,
+// falling back to `--derivation-path`
+fn parse_derivation_path(uri_rest: &str) -> Result<Option<DerivationPath>> {
+    let Some((_, query)) = uri_rest.split_once('?') else {
+        return config::global_options().derivation_path
+            .map(|path| {
+                let parsed = if path.starts_with('m') {
+                    DerivationPath::from_absolute_path_str(&path)
+                } else {
+                    DerivationPath::from_key_str(&path)
+                };
+                parsed.map_err(|e| anyhow::anyhow!("Invalid --derivation-path {}: {}", path, e))
+            })
+            .transpose();
+    };
+
+    for pair in query.split('&') {
+        match pair.split_once('=') {
+            Some(("key", value)) => {
+                return DerivationPath::from_key_str(value)
+                    .map(Some)
+                    .map_err(|e| anyhow::anyhow!("Invalid derivation key {}: {}", value, e));
+            }
+            Some(("full-path", value)) => {
+                return DerivationPath::from_absolute_path_str(value)
+                    .map(Some)
+                    .map_err(|e| anyhow::anyhow!("Invalid derivation path {}: {}", value, e));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(None)
+}
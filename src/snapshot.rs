@@ -0,0 +1,155 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use spl_token_2022::{
+    extension::confidential_transfer::ConfidentialTransferAccount,
+    solana_zk_sdk::encryption::{
+        auth_encryption::AeKey,
+        elgamal::ElGamalKeypair,
+        pod::elgamal::PodElGamalCiphertext,
+    },
+};
+use bytemuck::Zeroable;
+use std::path::Path;
+use crate::{crypto, utils};
+
+/// Identifies snapshot files, like `intent::INTENT_FORMAT` does for intents.
+pub const SNAPSHOT_FORMAT: &str = "confidential-account-snapshot";
+
+/// Bumped whenever a field is removed, renamed or changes meaning. Snapshots written
+/// before versioning have neither `format` nor `version` and load as version 0.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// Point-in-time view of a confidential token account, as written by `balance --snapshot`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AccountSnapshot {
+    #[serde(default = "snapshot_format")]
+    pub format: String,
+    #[serde(default)]
+    pub version: u32,
+    pub account: String,
+    pub slot: u64,
+    pub mint: String,
+    pub owner: String,
+    pub decimals: u8,
+    pub public_balance: u64,
+    pub approved: bool,
+    pub elgamal_pubkey: String,
+    pub pending_balance_lo: String,
+    pub pending_balance_hi: String,
+    pub available_balance: String,
+    pub decryptable_available_balance: String,
+    pub allow_confidential_credits: bool,
+    pub allow_non_confidential_credits: bool,
+    pub pending_balance_credit_counter: u64,
+    pub maximum_pending_balance_credit_counter: u64,
+    pub expected_pending_balance_credit_counter: u64,
+    pub actual_pending_balance_credit_counter: u64,
+    pub decrypted_available_balance: Option<u64>,
+    pub decrypted_pending_balance: Option<u64>,
+}
+
+impl AccountSnapshot {
+    #[allow(clippy::too_many_arguments)]
+    pub fn capture(
+        account: &Pubkey,
+        slot: u64,
+        base: &spl_token_2022::state::Account,
+        ct_account: &ConfidentialTransferAccount,
+        decimals: u8,
+        elgamal_keypair: &ElGamalKeypair,
+        aes_key: &AeKey,
+    ) -> Self {
+        // Decryption failures are recorded as `None` so foreign accounts can still be snapshotted
+        let decrypted_available_balance = if ct_account.available_balance == PodElGamalCiphertext::zeroed() {
+            Some(0)
+        } else {
+            ct_account.decryptable_available_balance.try_into().ok()
+                .and_then(|ciphertext| aes_key.decrypt(&ciphertext))
+        };
+
+        let decrypted_pending_balance = crypto::decrypt_pending_balance(
+            elgamal_keypair,
+            &ct_account.pending_balance_lo,
+            &ct_account.pending_balance_hi,
+        ).ok();
+
+        Self {
+            format: SNAPSHOT_FORMAT.to_string(),
+            version: SNAPSHOT_VERSION,
+            account: account.to_string(),
+            slot,
+            mint: base.mint.to_string(),
+            owner: base.owner.to_string(),
+            decimals,
+            public_balance: base.amount,
+            approved: ct_account.approved.into(),
+            elgamal_pubkey: ct_account.elgamal_pubkey.to_string(),
+            pending_balance_lo: ct_account.pending_balance_lo.to_string(),
+            pending_balance_hi: ct_account.pending_balance_hi.to_string(),
+            available_balance: ct_account.available_balance.to_string(),
+            decryptable_available_balance: ct_account.decryptable_available_balance.to_string(),
+            allow_confidential_credits: ct_account.allow_confidential_credits.into(),
+            allow_non_confidential_credits: ct_account.allow_non_confidential_credits.into(),
+            pending_balance_credit_counter: ct_account.pending_balance_credit_counter.into(),
+            maximum_pending_balance_credit_counter: ct_account.maximum_pending_balance_credit_counter.into(),
+            expected_pending_balance_credit_counter: ct_account.expected_pending_balance_credit_counter.into(),
+            actual_pending_balance_credit_counter: ct_account.actual_pending_balance_credit_counter.into(),
+            decrypted_available_balance,
+            decrypted_pending_balance,
+        }
+    }
+
+    /// Reads a snapshot, upgrading older versions in memory; `migrate` writes them back.
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read snapshot {}", path.display()))?;
+        let mut snapshot: Self = serde_json::from_str(&contents)
+            .with_context(|| format!("Invalid snapshot file {}", path.display()))?;
+
+        if snapshot.format != SNAPSHOT_FORMAT {
+            anyhow::bail!("{} is not an account snapshot", path.display());
+        }
+        if snapshot.version > SNAPSHOT_VERSION {
+            anyhow::bail!(
+                "Snapshot {} uses format version {}, this CLI understands up to {}",
+                path.display(),
+                snapshot.version,
+                SNAPSHOT_VERSION
+            );
+        }
+
+        // Version 0 only lacked the format and version fields themselves
+        snapshot.version = SNAPSHOT_VERSION;
+        Ok(snapshot)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let contents = serde_json::to_string_pretty(self)?;
+        utils::write_atomic(path, contents)
+            .with_context(|| format!("Failed to write snapshot {}", path.display()))
+    }
+
+    /// Lists `(field, before, after)` for every field that differs from `other`.
+    pub fn diff(&self, other: &Self) -> Result<Vec<(String, String, String)>> {
+        let before = serde_json::to_value(self)?;
+        let after = serde_json::to_value(other)?;
+        let (before, after) = match (before, after) {
+            (serde_json::Value::Object(before), serde_json::Value::Object(after)) => (before, after),
+            _ => unreachable!("snapshots serialize to objects"),
+        };
+
+        Ok(before
+            .iter()
+            .filter(|(field, value)| after.get(*field) != Some(*value))
+            .map(|(field, value)| {
+                let new_value = after.get(field).cloned().unwrap_or(serde_json::Value::Null);
+                (field.clone(), value.to_string(), new_value.to_string())
+            })
+            .collect())
+    }
+}
+
+fn snapshot_format() -> String {
+    SNAPSHOT_FORMAT.to_string()
+}
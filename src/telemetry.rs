@@ -0,0 +1,50 @@
+use serde::Serialize;
+use std::time::Duration;
+
+/// The only data ever reported: no addresses, amounts, keys or error messages.
+#[derive(Serialize, Debug)]
+pub struct CommandEvent {
+    pub cli_version: &'static str,
+    pub command: String,
+    pub duration_ms: u128,
+    pub error_class: Option<&'static str>,
+}
+
+impl CommandEvent {
+    pub fn new(command: String, duration: Duration, result: &anyhow::Result<()>) -> Self {
+        Self {
+            cli_version: env!("CARGO_PKG_VERSION"),
+            command,
+            duration_ms: duration.as_millis(),
+            error_class: result.as_ref().err().map(classify_error),
+        }
+    }
+}
+
+/// Maps an error to a coarse class without leaking its message.
+pub fn classify_error(error: &anyhow::Error) -> &'static str {
+    for cause in error.chain() {
+        if cause.is::<solana_client::client_error::ClientError>() {
+            return "rpc";
+        }
+        if cause.is::<std::io::Error>() {
+            return "io";
+        }
+        if cause.is::<serde_json::Error>() {
+            return "serialization";
+        }
+        if cause.is::<solana_sdk::program_error::ProgramError>() {
+            return "account_state";
+        }
+    }
+    "other"
+}
+
+/// Best-effort delivery: telemetry must never fail or noticeably slow a command.
+pub async fn report(endpoint: &str, event: &CommandEvent) {
+    let client = match reqwest::Client::builder().timeout(Duration::from_secs(2)).build() {
+        Ok(client) => client,
+        Err(_) => return,
+    };
+    let _ = client.post(endpoint).json(event).send().await;
+}
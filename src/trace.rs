@@ -0,0 +1,171 @@
+use serde_json::{json, Value};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Opt-in span recording, exported to an OTLP/HTTP collector when the command finishes.
+///
+/// Commands run their steps one after the other, so the innermost open span is the
+/// parent of the next one. Nothing is recorded unless `init` was given an endpoint.
+struct Tracer {
+    endpoint: String,
+    trace_id: [u8; 16],
+    root_parent: Option<[u8; 8]>,
+    open: Mutex<Vec<[u8; 8]>>,
+    finished: Mutex<Vec<Value>>,
+}
+
+static TRACER: OnceLock<Tracer> = OnceLock::new();
+
+/// Starts recording. `traceparent` is a W3C trace context (`00-<trace id>-<span id>-<flags>`),
+/// so spans join the caller's distributed trace instead of starting a new one.
+pub fn init(endpoint: Option<String>, traceparent: Option<&str>) {
+    let Some(endpoint) = endpoint else {
+        return;
+    };
+    let parent = traceparent.and_then(parse_traceparent);
+    let _ = TRACER.set(Tracer {
+        endpoint,
+        trace_id: parent.map(|(trace_id, _)| trace_id).unwrap_or_else(rand::random),
+        root_parent: parent.map(|(_, span_id)| span_id),
+        open: Mutex::new(Vec::new()),
+        finished: Mutex::new(Vec::new()),
+    });
+}
+
+/// An open span; it ends when dropped.
+pub struct Span {
+    span_id: [u8; 8],
+    parent_id: Option<[u8; 8]>,
+    name: String,
+    start: SystemTime,
+    attributes: Vec<(String, String)>,
+    error: Option<String>,
+}
+
+pub fn span(name: &str) -> Option<Span> {
+    let tracer = TRACER.get()?;
+    let span_id = rand::random();
+    let mut open = tracer.open.lock().ok()?;
+    let parent_id = open.last().copied().or(tracer.root_parent);
+    open.push(span_id);
+    Some(Span {
+        span_id,
+        parent_id,
+        name: name.to_string(),
+        start: SystemTime::now(),
+        attributes: Vec::new(),
+        error: None,
+    })
+}
+
+/// Adds an attribute to an open span; a no-op when tracing is off.
+pub fn attr(span: &mut Option<Span>, key: &str, value: impl ToString) {
+    if let Some(span) = span {
+        span.attributes.push((key.to_string(), value.to_string()));
+    }
+}
+
+/// Marks an open span as failed when `result` is an error, and passes `result` through.
+pub fn record<T>(span: &mut Option<Span>, result: anyhow::Result<T>) -> anyhow::Result<T> {
+    if let (Some(span), Err(e)) = (span.as_mut(), &result) {
+        span.error = Some(crate::telemetry::classify_error(e).to_string());
+    }
+    result
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        let Some(tracer) = TRACER.get() else {
+            return;
+        };
+        if let Ok(mut open) = tracer.open.lock() {
+            open.retain(|id| *id != self.span_id);
+        }
+
+        let attributes: Vec<Value> = self.attributes
+            .iter()
+            .map(|(key, value)| json!({ "key": key, "value": { "stringValue": value } }))
+            .collect();
+        let status = match &self.error {
+            Some(class) => json!({ "code": 2, "message": class }),
+            None => json!({ "code": 1 }),
+        };
+        let span = json!({
+            "traceId": hex(&tracer.trace_id),
+            "spanId": hex(&self.span_id),
+            "parentSpanId": self.parent_id.map(|id| hex(&id)).unwrap_or_default(),
+            "name": self.name,
+            "kind": 1,
+            "startTimeUnixNano": unix_nanos(self.start),
+            "endTimeUnixNano": unix_nanos(SystemTime::now()),
+            "attributes": attributes,
+            "status": status,
+        });
+        if let Ok(mut finished) = tracer.finished.lock() {
+            finished.push(span);
+        }
+    }
+}
+
+/// Sends every finished span to `<endpoint>/v1/traces`. Best-effort, like telemetry.
+pub async fn export() {
+    let Some(tracer) = TRACER.get() else {
+        return;
+    };
+    let spans = match tracer.finished.lock() {
+        Ok(mut finished) => std::mem::take(&mut *finished),
+        Err(_) => return,
+    };
+    if spans.is_empty() {
+        return;
+    }
+
+    let body = json!({
+        "resourceSpans": [{
+            "resource": {
+                "attributes": [
+                    { "key": "service.name", "value": { "stringValue": "confidential-cli" } },
+                    { "key": "service.version", "value": { "stringValue": env!("CARGO_PKG_VERSION") } },
+                ],
+            },
+            "scopeSpans": [{
+                "scope": { "name": "confidential-cli" },
+                "spans": spans,
+            }],
+        }],
+    });
+
+    let client = match reqwest::Client::builder().timeout(Duration::from_secs(2)).build() {
+        Ok(client) => client,
+        Err(_) => return,
+    };
+    let url = format!("{}/v1/traces", tracer.endpoint.trim_end_matches('/'));
+    let _ = client.post(url).json(&body).send().await;
+}
+
+fn parse_traceparent(traceparent: &str) -> Option<([u8; 16], [u8; 8])> {
+    let mut parts = traceparent.trim().split('-');
+    let _version = parts.next()?;
+    let trace_id = unhex(parts.next()?)?.try_into().ok()?;
+    let span_id = unhex(parts.next()?)?.try_into().ok()?;
+    Some((trace_id, span_id))
+}
+
+fn unix_nanos(time: SystemTime) -> String {
+    // OTLP/JSON encodes 64-bit integers as strings
+    time.duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos().to_string()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn unhex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(text.get(index..index + 2)?, 16).ok())
+        .collect()
+}
@@ -1,14 +1,315 @@
 use anyhow::{Result, Context};
 use solana_sdk::pubkey::Pubkey;
+use std::fs::{File, TryLockError};
+use std::io::Read;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 pub fn parse_pubkey(s: &str) -> Result<Pubkey> {
     Pubkey::from_str(s).context("Invalid public key format")
 }
 
-pub fn format_amount(amount: u64, decimals: u8) -> String {
-    let divisor = 10u64.pow(decimals as u32);
+/// Formats base units for display. Takes u128 as well, so totals summed across accounts
+/// or history can be shown without first squeezing them back into a u64.
+pub fn format_amount(amount: impl Into<u128>, decimals: u8) -> String {
+    let amount = amount.into();
+    if decimals == 0 {
+        return amount.to_string();
+    }
+    let divisor = 10u128.pow(decimals as u32);
     let whole = amount / divisor;
     let fraction = amount % divisor;
     format!("{}.{:0width$}", whole, fraction, width = decimals as usize)
-}
\ No newline at end of file
+}
+
+/// Narrows an aggregate back to u64 where a report or instruction needs one, failing
+/// rather than wrapping when it doesn't fit.
+pub fn checked_u64(total: u128, what: &str) -> Result<u64> {
+    u64::try_from(total).map_err(|_| anyhow::anyhow!("{} ({}) exceeds the u64 range", what, total))
+}
+
+/// Converts a UI amount such as "1.5" into base units for a mint with `decimals`.
+///
+/// Surrounding whitespace and `_` digit separators are stripped before conversion. Amounts
+/// with more fractional digits than the mint supports are rejected rather than
+/// silently truncated.
+pub fn parse_amount(input: &str, decimals: u8) -> Result<u64> {
+    let normalized: String = input.trim().chars().filter(|c| *c != '_').collect();
+
+    if normalized.is_empty() {
+        anyhow::bail!("Amount must not be empty");
+    }
+    if normalized.starts_with('-') {
+        anyhow::bail!("Amount must not be negative: {}", input.trim());
+    }
+
+    let (whole, fraction) = match normalized.split_once('.') {
+        Some((whole, fraction)) => (whole, fraction),
+        None => (normalized.as_str(), ""),
+    };
+
+    if whole.is_empty() && fraction.is_empty() {
+        anyhow::bail!("Invalid amount: {}", input.trim());
+    }
+    if !whole.chars().all(|c| c.is_ascii_digit()) || !fraction.chars().all(|c| c.is_ascii_digit()) {
+        anyhow::bail!("Invalid amount: {} (expected a decimal number like 1.5)", input.trim());
+    }
+
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.len() > decimals as usize {
+        anyhow::bail!(
+            "Amount {} has {} decimal places but the mint only supports {}",
+            input.trim(),
+            fraction.len(),
+            decimals
+        );
+    }
+
+    let too_large = || anyhow::anyhow!("Amount {} is too large for a {}-decimal mint", input.trim(), decimals);
+    let multiplier = 10u64.checked_pow(decimals as u32).ok_or_else(too_large)?;
+    let whole_units = if whole.is_empty() { 0 } else { whole.parse::<u64>().map_err(|_| too_large())? };
+    let fraction_units = if fraction.is_empty() {
+        0
+    } else {
+        fraction.parse::<u64>()? * (multiplier / 10u64.pow(fraction.len() as u32))
+    };
+
+    whole_units
+        .checked_mul(multiplier)
+        .and_then(|units| units.checked_add(fraction_units))
+        .ok_or_else(too_large)
+}
+
+static STDIN_CONSUMED: AtomicBool = AtomicBool::new(false);
+
+/// Reads all of stdin for `arg_name`. Stdin can only be read once, so a second argument
+/// given as `-` is an error instead of an empty read.
+pub fn read_stdin(arg_name: &str) -> Result<String> {
+    if STDIN_CONSUMED.swap(true, Ordering::SeqCst) {
+        anyhow::bail!("Only one argument can be read from stdin, but {} is also '-'", arg_name);
+    }
+    let mut input = String::new();
+    std::io::stdin()
+        .read_to_string(&mut input)
+        .with_context(|| format!("Failed to read {} from stdin", arg_name))?;
+    Ok(input)
+}
+
+/// Returns `value`, or the trimmed contents of stdin when `value` is `-`.
+pub fn stdin_or(value: String, arg_name: &str) -> Result<String> {
+    if value != "-" {
+        return Ok(value);
+    }
+    let input = read_stdin(arg_name)?;
+    let input = input.trim();
+    if input.is_empty() {
+        anyhow::bail!("Expected {} on stdin, got nothing", arg_name);
+    }
+    Ok(input.to_string())
+}
+
+/// Expands a list argument given as `-` into the items read from stdin.
+///
+/// Accepts whitespace/comma separated values, a JSON array of strings, or a JSON
+/// array of objects with an `account` field (as printed by `--output json | jq`).
+pub fn stdin_list_or(values: Vec<String>, arg_name: &str) -> Result<Vec<String>> {
+    if values.len() != 1 || values[0] != "-" {
+        return Ok(values);
+    }
+    let input = read_stdin(arg_name)?;
+    let input = input.trim();
+
+    let items: Vec<String> = if input.starts_with('[') {
+        let parsed: Vec<serde_json::Value> = serde_json::from_str(input)
+            .with_context(|| format!("Invalid JSON list for {} on stdin", arg_name))?;
+        parsed
+            .into_iter()
+            .map(|item| match item {
+                serde_json::Value::String(value) => Ok(value),
+                serde_json::Value::Object(object) => object
+                    .get("account")
+                    .and_then(|value| value.as_str())
+                    .map(str::to_string)
+                    .ok_or_else(|| anyhow::anyhow!("JSON object on stdin has no \"account\" field")),
+                other => Err(anyhow::anyhow!("Unexpected JSON value on stdin: {}", other)),
+            })
+            .collect::<Result<_>>()?
+    } else {
+        input
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|item| !item.is_empty())
+            .map(str::to_string)
+            .collect()
+    };
+
+    if items.is_empty() {
+        anyhow::bail!("Expected {} on stdin, got nothing", arg_name);
+    }
+    Ok(items)
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(suffix);
+    PathBuf::from(path)
+}
+
+/// Writes `contents` to a temporary file next to `path` and renames it into place,
+/// so a crash or a concurrent reader never sees a half-written file.
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> std::io::Result<()> {
+    let tmp = with_suffix(path, &format!(".tmp.{}", std::process::id()));
+    let result = std::fs::write(&tmp, contents).and_then(|()| std::fs::rename(&tmp, path));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp);
+    }
+    result
+}
+
+/// Like `write_atomic`, for secrets: the file is readable by its owner only (0600 on
+/// unix) from the moment it is created. Without `overwrite`, an existing `path` is left
+/// alone and the write fails with `AlreadyExists`.
+pub fn write_private(path: &Path, contents: impl AsRef<[u8]>, overwrite: bool) -> std::io::Result<()> {
+    let tmp = with_suffix(path, &format!(".tmp.{}", std::process::id()));
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    let result = options.open(&tmp)
+        .and_then(|mut file| std::io::Write::write_all(&mut file, contents.as_ref()).and_then(|()| file.sync_all()))
+        .and_then(|()| if overwrite {
+            std::fs::rename(&tmp, path)
+        } else {
+            // Unlike rename, a hard link never replaces the target
+            std::fs::hard_link(&tmp, path)
+        });
+    let _ = std::fs::remove_file(&tmp);
+    result
+}
+
+/// Takes an advisory lock on `<path>.lock`, held until the returned file is dropped.
+///
+/// Fails straight away rather than waiting if another invocation holds it.
+pub fn lock_exclusive(path: &Path) -> Result<File> {
+    let lock_path = with_suffix(path, ".lock");
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .with_context(|| format!("Failed to open lock file {}", lock_path.display()))?;
+    match file.try_lock() {
+        Ok(()) => Ok(file),
+        Err(TryLockError::WouldBlock) => anyhow::bail!(
+            "Another confidential-cli instance is running against {} (lock held on {}); wait for it to finish",
+            path.display(), lock_path.display()),
+        Err(TryLockError::Error(err)) => Err(err)
+            .with_context(|| format!("Failed to lock {}", lock_path.display())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_amount_scales_by_decimals() {
+        assert_eq!(parse_amount("1.5", 9).unwrap(), 1_500_000_000);
+        assert_eq!(parse_amount("0.000000001", 9).unwrap(), 1);
+        assert_eq!(parse_amount(".5", 2).unwrap(), 50);
+        assert_eq!(parse_amount("1_000", 0).unwrap(), 1000);
+    }
+
+    #[test]
+    fn parse_amount_accepts_a_trailing_point() {
+        assert_eq!(parse_amount("1.", 6).unwrap(), 1_000_000);
+        assert_eq!(parse_amount("1.", 0).unwrap(), 1);
+    }
+
+    #[test]
+    fn parse_amount_rejects_too_many_decimal_places() {
+        let error = parse_amount("1.0000000001", 9).unwrap_err();
+        assert_eq!(error.to_string(), "Amount 1.0000000001 has 10 decimal places but the mint only supports 9");
+        assert!(parse_amount("0.5", 0).is_err());
+        // Trailing zeros add no precision
+        assert_eq!(parse_amount("1.50", 1).unwrap(), 15);
+    }
+
+    #[test]
+    fn parse_amount_rejects_overflow() {
+        assert_eq!(parse_amount("18446744073709551615", 0).unwrap(), u64::MAX);
+        assert!(parse_amount("18446744073709551616", 0).is_err());
+        assert_eq!(parse_amount("18446744073.709551615", 9).unwrap(), u64::MAX);
+        assert!(parse_amount("18446744073.709551616", 9).is_err());
+        assert!(parse_amount("18446744074", 9).is_err());
+        assert!(parse_amount("1", 20).is_err());
+    }
+
+    #[test]
+    fn parse_amount_rejects_empty_and_bare_points() {
+        assert_eq!(parse_amount("", 9).unwrap_err().to_string(), "Amount must not be empty");
+        assert_eq!(parse_amount("   ", 9).unwrap_err().to_string(), "Amount must not be empty");
+        assert_eq!(parse_amount(".", 9).unwrap_err().to_string(), "Invalid amount: .");
+    }
+
+    #[test]
+    fn parse_amount_rejects_signs() {
+        assert!(parse_amount("+1", 9).is_err());
+        assert!(parse_amount("-1", 9).is_err());
+        assert!(parse_amount("1.+5", 9).is_err());
+    }
+
+    #[test]
+    fn parse_amount_trims_surrounding_whitespace_only() {
+        assert_eq!(parse_amount("  2.5\n", 1).unwrap(), 25);
+        assert!(parse_amount("1 000", 0).is_err());
+        assert!(parse_amount("1. 5", 1).is_err());
+    }
+
+    #[test]
+    fn format_amount_at_u64_max() {
+        assert_eq!(format_amount(u64::MAX, 0), "18446744073709551615");
+        assert_eq!(format_amount(u64::MAX, 9), "18446744073.709551615");
+    }
+
+    #[test]
+    fn format_amount_beyond_u64() {
+        let doubled = u128::from(u64::MAX) * 2;
+        assert_eq!(format_amount(doubled, 0), "36893488147419103230");
+        assert_eq!(format_amount(doubled, 9), "36893488147.419103230");
+        assert_eq!(format_amount(u128::MAX, 9), "340282366920938463463374607431.768211455");
+    }
+
+    #[test]
+    fn checked_u64_at_the_boundary() {
+        assert_eq!(checked_u64(u128::from(u64::MAX), "Total").unwrap(), u64::MAX);
+        assert_eq!(checked_u64(0, "Total").unwrap(), 0);
+    }
+
+    #[test]
+    fn checked_u64_fails_past_the_boundary() {
+        let error = checked_u64(u128::from(u64::MAX) + 1, "Total").unwrap_err();
+        assert_eq!(error.to_string(), "Total (18446744073709551616) exceeds the u64 range");
+        assert!(checked_u64(u128::MAX, "Total").is_err());
+    }
+
+    #[test]
+    fn write_private_never_overwrites_unless_asked() {
+        let path = std::env::temp_dir().join(format!("confidential-cli-test-private-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        write_private(&path, "first", false).unwrap();
+        assert_eq!(write_private(&path, "second", false).unwrap_err().kind(), std::io::ErrorKind::AlreadyExists);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "first");
+        write_private(&path, "third", true).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "third");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
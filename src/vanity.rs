@@ -0,0 +1,62 @@
+use anyhow::Result;
+use solana_sdk::signature::{Keypair, Signer};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Instant;
+use crate::output::say;
+
+const BASE58_ALPHABET: &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+// Beyond this a grind takes days on a typical machine
+const MAX_PREFIX_LEN: usize = 6;
+
+/// A new keypair, or, with `prefix`, one ground until its base58 address starts with it,
+/// like `solana-keygen grind --starts-with`. Every core searches; the first match wins.
+pub fn keypair(prefix: Option<&str>) -> Result<Keypair> {
+    let Some(prefix) = prefix else {
+        return Ok(Keypair::new());
+    };
+    if prefix.is_empty() {
+        anyhow::bail!("--starts-with must not be empty");
+    }
+    if let Some(invalid) = prefix.chars().find(|c| !BASE58_ALPHABET.contains(*c)) {
+        anyhow::bail!("--starts-with '{}' can never match: '{}' is not a base58 character (0, O, I and l aren't)", prefix, invalid);
+    }
+    if prefix.len() > MAX_PREFIX_LEN {
+        anyhow::bail!("--starts-with '{}' is too long to grind; keep it to {} characters", prefix, MAX_PREFIX_LEN);
+    }
+
+    let threads = std::thread::available_parallelism().map_or(1, |threads| threads.get());
+    say!("⛏️  Grinding for an address starting with '{}' on {} thread(s)", prefix, threads);
+    say!("   Expect around {} attempts", 58u64.pow(prefix.len() as u32));
+
+    let started = Instant::now();
+    let found = AtomicBool::new(false);
+    let attempts = AtomicU64::new(0);
+    let keypair = std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|_| scope.spawn(|| {
+                let mut tried = 0;
+                let result = loop {
+                    if found.load(Ordering::Relaxed) {
+                        break None;
+                    }
+                    let keypair = Keypair::new();
+                    tried += 1;
+                    if keypair.pubkey().to_string().starts_with(prefix) {
+                        found.store(true, Ordering::Relaxed);
+                        break Some(keypair);
+                    }
+                };
+                attempts.fetch_add(tried, Ordering::Relaxed);
+                result
+            }))
+            .collect();
+        workers.into_iter()
+            .filter_map(|worker| worker.join().expect("grind thread panicked"))
+            .next()
+            .expect("a worker only stops once a match is found")
+    });
+
+    say!("   Found {} after {} attempts in {:.1}s\n", keypair.pubkey(), attempts.load(Ordering::Relaxed), started.elapsed().as_secs_f64());
+    Ok(keypair)
+}